@@ -0,0 +1,38 @@
+fn main() {
+    #[cfg(feature = "grpc")]
+    grpc::generate();
+}
+
+// 根据 proto/bridge.proto 中的 ProtocolService 生成 tonic 服务代码。
+// 消息类型由 src/defi/proto.rs 用 prost 派生手写，这里只生成服务部分，因此不依赖 protoc。
+#[cfg(feature = "grpc")]
+mod grpc {
+    use tonic_build::manual::{Builder, Method, Service};
+
+    fn method(name: &str, route: &str, input: &str, output: &str) -> Method {
+        Method::builder()
+            .name(name)
+            .route_name(route)
+            .input_type(format!("crate::defi::proto::{}", input))
+            .output_type(format!("crate::defi::proto::{}", output))
+            .codec_path("tonic_prost::ProstCodec")
+            .build()
+    }
+
+    pub fn generate() {
+        println!("cargo:rerun-if-changed=build.rs");
+        let service = Service::builder()
+            .name("ProtocolService")
+            .package("protocol_core.bridge")
+            .method(method("decode", "Decode", "JniRequest", "JniResponse"))
+            .method(method("encode", "Encode", "JniRequest", "JniResponse"))
+            .method(method(
+                "batch_decode",
+                "BatchDecode",
+                "BatchDecodeRequest",
+                "BatchDecodeResponse",
+            ))
+            .build();
+        Builder::new().compile(&[service]);
+    }
+}
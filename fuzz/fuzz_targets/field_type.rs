@@ -0,0 +1,46 @@
+//! 字段定义：任意字段类型解码任意字节、编码任意输入，不应 panic；
+//! 解码成功的数值再编码回去应得到相同的字节
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use protocol_core::{FieldType, math_util::DecimalRoundingMode};
+
+#[derive(Debug, arbitrary::Arbitrary)]
+struct Input {
+    field_type: FieldType,
+    bytes: Vec<u8>,
+    input: String,
+    precision: u8,
+    rounding_mode: DecimalRoundingMode,
+}
+
+fuzz_target!(|input: Input| {
+    let Input {
+        field_type,
+        bytes,
+        input,
+        precision,
+        rounding_mode,
+    } = input;
+    let _ = field_type.encode_with(&input, rounding_mode);
+    let Ok(decoded) = field_type.decode_with(&bytes, u32::from(precision % 29), rounding_mode)
+    else {
+        return;
+    };
+    // 不缩放的整数解码结果是精确的，可以编码回原始字节
+    let unscaled = matches!(
+        field_type,
+        FieldType::UnsignedU8(1.0)
+            | FieldType::UnsignedU16(1.0)
+            | FieldType::UnsignedU32(1.0)
+            | FieldType::UnsignedU64(1.0)
+            | FieldType::SignedI8(1.0)
+            | FieldType::SignedI16(1.0)
+            | FieldType::SignedI32(1.0)
+            | FieldType::SignedI64(1.0)
+    ) || matches!(field_type, FieldType::StringOrBCD);
+    if unscaled {
+        assert_eq!(field_type.encode(&decoded).unwrap(), bytes);
+    }
+});
@@ -0,0 +1,37 @@
+//! 帧外壳：任意字节流按任意帧外壳分帧 (`scan_frame`、`ProtocolCodec`) 与校验，不应 panic
+
+#![no_main]
+
+use bytes::BytesMut;
+use libfuzzer_sys::fuzz_target;
+use protocol_core::{EnvelopeSchema, FrameScan, ProtocolCodec, ProtocolConfig, scan_frame};
+use tokio_util::codec::Decoder;
+
+#[derive(Debug, arbitrary::Arbitrary)]
+struct Input {
+    envelope: EnvelopeSchema,
+    // 按块到达的字节流
+    chunks: Vec<Vec<u8>>,
+}
+
+fuzz_target!(|input: Input| {
+    let Input { envelope, chunks } = input;
+    for chunk in &chunks {
+        let _ = envelope.validate_envelope(chunk);
+        match scan_frame(&envelope, chunk) {
+            Ok(FrameScan::Frame { skip, len }) => assert!(skip + len <= chunk.len()),
+            Ok(FrameScan::Partial { skip }) => assert!(skip <= chunk.len()),
+            Err(_) => {}
+        }
+    }
+
+    let mut codec = ProtocolCodec::new(envelope);
+    let mut buffer = BytesMut::new();
+    for chunk in chunks {
+        buffer.extend_from_slice(&chunk);
+        while let Ok(Some(frame)) = codec.decode(&mut buffer) {
+            assert!(!frame.is_empty());
+        }
+    }
+    let _ = codec.decode_eof(&mut buffer);
+});
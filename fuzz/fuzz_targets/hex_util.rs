@@ -0,0 +1,58 @@
+//! hex 工具：奇数长度、非法字符、越界下标等输入只应返回错误，不应 panic
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use protocol_core::hex_util;
+
+#[derive(Debug, arbitrary::Arbitrary)]
+struct Input {
+    text: String,
+    bytes: Vec<u8>,
+    start: i64,
+    end: i64,
+    length: u16,
+    padding: Option<u8>,
+    append_on_tail: bool,
+}
+
+fuzz_target!(|input: Input| {
+    let Input {
+        text,
+        bytes,
+        start,
+        end,
+        length,
+        padding,
+        append_on_tail,
+    } = input;
+    let length = usize::from(length);
+
+    if let Ok(decoded) = hex_util::hex_to_bytes(&text) {
+        let _ = hex_util::hex_to_bytes_swap(&text);
+        let _ = hex_util::bytes_to_hex(&decoded);
+    }
+    let _ = hex_util::swap(&text);
+    let _ = hex_util::cut_hex(&text, start, end);
+    let _ = hex_util::hex_to_u64(&text);
+    let _ = hex_util::hex_to_i16(&text);
+    let _ = hex_util::hex_to_f32_or_f64(&text);
+    let _ = hex_util::binary_str_to_u32(&text);
+    let _ = hex_util::binary_str_to_bits(&text);
+    let _ = hex_util::ascii_to_string(&text);
+    let _ = hex_util::string_to_ascii(&text);
+    let _ = hex_util::ensure_is_machine_code(&text);
+    let padding_hex = padding.map(|byte| format!("{:02X}", byte));
+    let _ = hex_util::pad_hex_to_length(&text, length, append_on_tail, padding_hex.as_deref());
+    let _ = hex_util::pad_hex_to_block_size(&text, length, padding_hex.as_deref());
+
+    let _ = hex_util::bytes_to_hex_swap(&bytes);
+    let _ = hex_util::bytes_to_f32_or_f64(&bytes);
+    let _ = hex_util::bytes_to_i64(&bytes);
+    let _ = hex_util::cut_bytes(&bytes, start, end);
+    let _ = hex_util::replace_bytes(&bytes, start, end, &bytes);
+    let _ = hex_util::pad_bytes_to_length(&bytes, length, append_on_tail, padding);
+    let _ = hex_util::pad_bytes_to_block_size(&bytes, length, padding);
+    let _ = hex_util::u64_to_hex(length as u64, usize::from(bytes.len() as u8));
+    let _ = hex_util::i32_to_binary_str(start as i32, length);
+});
@@ -0,0 +1,28 @@
+//! PKCS#7：任意数据去除填充不应 panic；填充后再去除应得到原数据。
+//! 同时用固定密钥解密任意密文，覆盖解密后的去填充
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use protocol_core::aes_digester::{AesCipher, AesMode, pkcs7_pad, pkcs7_unpad};
+
+const KEY: [u8; 16] = *b"0123456789ABCDEF";
+
+#[derive(Debug, arbitrary::Arbitrary)]
+struct Input {
+    data: Vec<u8>,
+    iv: Vec<u8>,
+    cbc: bool,
+}
+
+fuzz_target!(|input: Input| {
+    let Input { data, iv, cbc } = input;
+    if let Ok(unpadded) = pkcs7_unpad(&data) {
+        assert!(unpadded.len() < data.len() || data.is_empty());
+    }
+    assert_eq!(pkcs7_unpad(&pkcs7_pad(&data)).unwrap(), data);
+
+    let mode = if cbc { AesMode::CBC } else { AesMode::ECB };
+    let cipher = AesCipher::new(&KEY, mode).unwrap();
+    let _ = cipher.decrypt(&data, &iv);
+});
@@ -0,0 +1,74 @@
+#ifndef PROTOCOL_CORE_H
+#define PROTOCOL_CORE_H
+
+/* 由 cbindgen 生成，请勿手动修改 */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * 成功
+ */
+#define PROTOCOL_OK 0
+
+/**
+ * 入参为空指针
+ */
+#define PROTOCOL_ERR_NULL_POINTER -1
+
+/**
+ * 请求不是合法的 JniRequest JSON
+ */
+#define PROTOCOL_ERR_INVALID_REQUEST -2
+
+/**
+ * 没有注册处理函数
+ */
+#define PROTOCOL_ERR_NO_HANDLER -3
+
+/**
+ * 处理过程中发生 panic
+ */
+#define PROTOCOL_ERR_PANIC -4
+
+/**
+ * 返回的 JniResponse 序列化失败
+ */
+#define PROTOCOL_ERR_SERIALIZE -5
+
+/**
+ * 由本库分配、交给调用方的字节缓冲区
+ */
+typedef struct ProtocolBuffer {
+  uint8_t *data;
+  size_t len;
+} ProtocolBuffer;
+
+/**
+ * 上行解码。`request` 为 JniRequest JSON，结果 (JniResponse JSON) 写入 `out`。
+ *
+ * # Safety
+ * `request` 必须指向 `request_len` 个可读字节；`out` 必须指向可写的 `ProtocolBuffer`。
+ */
+int32_t protocol_decode(const uint8_t *request, size_t request_len, struct ProtocolBuffer *out);
+
+/**
+ * 下行编码。参数与返回值同 `protocol_decode`。
+ *
+ * # Safety
+ * 同 `protocol_decode`。
+ */
+int32_t protocol_encode(const uint8_t *request, size_t request_len, struct ProtocolBuffer *out);
+
+/**
+ * 释放本库返回的缓冲区。传入空缓冲区是安全的。
+ *
+ * # Safety
+ * `buffer` 必须来自 `protocol_decode` / `protocol_encode`，且未被释放过。
+ */
+void protocol_buffer_free(struct ProtocolBuffer buffer);
+
+#endif  /* PROTOCOL_CORE_H */
@@ -0,0 +1,380 @@
+//! protocol-core 的派生宏，通过 `protocol-core` 的 `derive` feature 使用。
+//!
+//! ```ignore
+//! #[derive(Clone, AutoEncodingParam, AutoEncoding)]
+//! #[ep(cmd_code = "A1")]
+//! enum PriceParams {
+//!     #[ep(code = "price", title = "单价", byte_length = 4, field_type = "UnsignedU32(100)", swap)]
+//!     Price,
+//!     #[ep(code = "remark", byte_length = 8, field_type = "Ascii", optional)]
+//!     Remark,
+//! }
+//! ```
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    Attribute, Data, DeriveInput, Expr, Fields, Ident, Lit, LitStr, Result, Variant,
+    parse_macro_input, spanned::Spanned,
+};
+
+/// 为只含单元变体的枚举实现 `AutoEncodingParam`，每个变体通过 `#[ep(...)]` 描述一个帧字段。
+///
+/// 变体属性：
+/// - `code` 唯一标识 (默认为变体名)，`title` 字段名称 (默认与 code 相同)；
+/// - `byte_length` 字节长度 (默认 0 即变长)，`field_type` 字段类型，如 `"UnsignedU32(100)"`、`"Ascii"`；
+/// - `default_value` / `default_hex` 默认值，`cmd_code` 命令码 (也可以写在枚举上)；
+/// - `order` 写入顺序，`group` 所属分组；
+/// - `min` / `max` 数值范围，`pattern` 正则，`one_of = "1,2,3"` 允许的取值；
+/// - `rounding = "HalfEven"` 整数字段反缩放后的舍入模式 (默认 HalfUp)；
+/// - `pad_byte = 0x20` 补齐字节，`pad_side` / `truncate_side = "Right"` 补齐/截断的一侧 (默认 Left)；
+/// - `swap` 小端，`optional` 非必填。
+#[proc_macro_derive(AutoEncodingParam, attributes(ep))]
+pub fn derive_auto_encoding_param(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_encoding_param(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// 为枚举实现 `AutoEncoding<Self>`：`variants()` 按声明顺序返回所有变体，`variants_map()` 以 code 为 key。
+/// 需要同时实现 (或派生) `AutoEncodingParam`。
+#[proc_macro_derive(AutoEncoding)]
+pub fn derive_auto_encoding(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_encoding(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+// 单个变体上 #[ep(...)] 的内容
+#[derive(Default)]
+struct FieldAttrs {
+    code: Option<String>,
+    title: Option<String>,
+    byte_length: Option<usize>,
+    field_type: Option<LitStr>,
+    cmd_code: Option<String>,
+    default_value: Option<String>,
+    default_hex: Option<String>,
+    order: Option<u32>,
+    group: Option<String>,
+    rules: Vec<TokenStream2>,
+    rounding: Option<Ident>,
+    pad_byte: Option<u8>,
+    pad_side: Option<Ident>,
+    truncate_side: Option<Ident>,
+    swap: bool,
+    optional: bool,
+}
+
+fn parse_attrs(attrs: &[Attribute]) -> Result<FieldAttrs> {
+    let mut parsed = FieldAttrs::default();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("ep")) {
+        attr.parse_nested_meta(|meta| {
+            let key = meta
+                .path
+                .get_ident()
+                .map(Ident::to_string)
+                .unwrap_or_default();
+            match key.as_str() {
+                "code" => parsed.code = Some(meta.value()?.parse::<LitStr>()?.value()),
+                "title" => parsed.title = Some(meta.value()?.parse::<LitStr>()?.value()),
+                "byte_length" => {
+                    let lit: syn::LitInt = meta.value()?.parse()?;
+                    parsed.byte_length = Some(lit.base10_parse()?);
+                }
+                "field_type" => parsed.field_type = Some(meta.value()?.parse()?),
+                "cmd_code" => parsed.cmd_code = Some(meta.value()?.parse::<LitStr>()?.value()),
+                "default_value" => {
+                    parsed.default_value = Some(meta.value()?.parse::<LitStr>()?.value())
+                }
+                "default_hex" => {
+                    parsed.default_hex = Some(meta.value()?.parse::<LitStr>()?.value())
+                }
+                "order" => {
+                    let lit: syn::LitInt = meta.value()?.parse()?;
+                    parsed.order = Some(lit.base10_parse()?);
+                }
+                "group" => parsed.group = Some(meta.value()?.parse::<LitStr>()?.value()),
+                "min" | "max" => {
+                    let value = parse_number(&meta.value()?.parse()?)?;
+                    parsed.rules.push(if key == "min" {
+                        quote!(::protocol_core::ValidationRule::Min(#value))
+                    } else {
+                        quote!(::protocol_core::ValidationRule::Max(#value))
+                    });
+                }
+                "pattern" => {
+                    let pattern = meta.value()?.parse::<LitStr>()?.value();
+                    parsed
+                        .rules
+                        .push(quote!(::protocol_core::ValidationRule::Pattern(
+                            ::protocol_core::__private::String::from(#pattern)
+                        )));
+                }
+                "one_of" => {
+                    let values = meta.value()?.parse::<LitStr>()?.value();
+                    let values = values.split(',').map(str::trim);
+                    parsed
+                        .rules
+                        .push(quote!(::protocol_core::ValidationRule::OneOf(
+                            ::protocol_core::__private::vec![
+                                #(::protocol_core::__private::String::from(#values)),*
+                            ]
+                        )));
+                }
+                "rounding" => parsed.rounding = Some(meta.value()?.parse::<LitStr>()?.parse()?),
+                "pad_byte" => {
+                    let lit: syn::LitInt = meta.value()?.parse()?;
+                    parsed.pad_byte = Some(lit.base10_parse()?);
+                }
+                "pad_side" => parsed.pad_side = Some(meta.value()?.parse::<LitStr>()?.parse()?),
+                "truncate_side" => {
+                    parsed.truncate_side = Some(meta.value()?.parse::<LitStr>()?.parse()?)
+                }
+                "swap" => parsed.swap = true,
+                "optional" => parsed.optional = true,
+                _ => return Err(meta.error(format!("unknown ep attribute `{}`", key))),
+            }
+            Ok(())
+        })?;
+    }
+    Ok(parsed)
+}
+
+// 数字字面量 (可以带负号) 转为 f64
+fn parse_number(expr: &Expr) -> Result<f64> {
+    match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Int(int) => int.base10_parse::<f64>(),
+            Lit::Float(float) => float.base10_parse::<f64>(),
+            other => Err(syn::Error::new(other.span(), "expected a number")),
+        },
+        Expr::Unary(unary) if matches!(unary.op, syn::UnOp::Neg(_)) => {
+            parse_number(&unary.expr).map(|value| -value)
+        }
+        other => Err(syn::Error::new(other.span(), "expected a number")),
+    }
+}
+
+// "UnsignedU32(100)" -> FieldType::UnsignedU32(100f64)；缩放倍数统一转为 f64
+fn field_type_tokens(lit: &LitStr) -> Result<TokenStream2> {
+    let expr: Expr = lit.parse()?;
+    match expr {
+        Expr::Path(path) => Ok(quote!(::protocol_core::FieldType::#path)),
+        Expr::Call(call) if call.args.len() == 1 => {
+            let func = &call.func;
+            let scale = parse_number(&call.args[0])?;
+            Ok(quote!(::protocol_core::FieldType::#func(#scale)))
+        }
+        other => Err(syn::Error::new(
+            lit.span(),
+            format!("invalid field_type `{}`", quote!(#other)),
+        )),
+    }
+}
+
+fn unit_variants(input: &DeriveInput) -> Result<Vec<&Variant>> {
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "only enums with unit variants are supported",
+        ));
+    };
+    data.variants
+        .iter()
+        .map(|variant| match variant.fields {
+            Fields::Unit => Ok(variant),
+            _ => Err(syn::Error::new(
+                variant.span(),
+                "only unit variants are supported",
+            )),
+        })
+        .collect()
+}
+
+fn expand_encoding_param(input: &DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let enum_attrs = parse_attrs(&input.attrs)?;
+    let mut codes = Vec::new();
+    let mut titles = Vec::new();
+    let mut lengths = Vec::new();
+    let mut field_types = Vec::new();
+    let mut cmd_codes = Vec::new();
+    let mut default_values = Vec::new();
+    let mut default_hexes = Vec::new();
+    let mut orders = Vec::new();
+    let mut groups = Vec::new();
+    let mut rules = Vec::new();
+    let mut roundings = Vec::new();
+    let mut pad_bytes = Vec::new();
+    let mut pad_sides = Vec::new();
+    let mut truncate_sides = Vec::new();
+    let mut swaps = Vec::new();
+    let mut requireds = Vec::new();
+    let mut idents = Vec::new();
+
+    for variant in unit_variants(input)? {
+        let attrs = parse_attrs(&variant.attrs)?;
+        let field_type = attrs.field_type.as_ref().ok_or_else(|| {
+            syn::Error::new(variant.span(), "missing #[ep(field_type = \"...\")]")
+        })?;
+        let code = attrs.code.unwrap_or_else(|| variant.ident.to_string());
+        titles.push(attrs.title.unwrap_or_else(|| code.clone()));
+        codes.push(code);
+        lengths.push(attrs.byte_length.unwrap_or(0));
+        field_types.push(field_type_tokens(field_type)?);
+        cmd_codes.push(
+            attrs
+                .cmd_code
+                .or_else(|| enum_attrs.cmd_code.clone())
+                .unwrap_or_default(),
+        );
+        default_values.push(attrs.default_value.unwrap_or_default());
+        default_hexes.push(attrs.default_hex.unwrap_or_default());
+        orders.push(match attrs.order {
+            Some(order) => quote!(Some(#order)),
+            None => quote!(None),
+        });
+        groups.push(match attrs.group {
+            Some(group) => quote!(Some(::protocol_core::__private::String::from(#group))),
+            None => quote!(None),
+        });
+        let variant_rules = &attrs.rules;
+        rules.push(quote!(
+            ::protocol_core::__private::vec![#(#variant_rules),*]
+        ));
+        let rounding = attrs.rounding.unwrap_or_else(|| format_ident!("HalfUp"));
+        roundings.push(quote!(::protocol_core::math_util::DecimalRoundingMode::#rounding));
+        pad_bytes.push(attrs.pad_byte.unwrap_or(0));
+        let side = |side: Option<Ident>| {
+            let side = side.unwrap_or_else(|| format_ident!("Left"));
+            quote!(::protocol_core::PadSide::#side)
+        };
+        pad_sides.push(side(attrs.pad_side));
+        truncate_sides.push(side(attrs.truncate_side));
+        swaps.push(attrs.swap);
+        requireds.push(!attrs.optional);
+        idents.push(&variant.ident);
+    }
+
+    let string_fn = |fn_name: &str, values: &[String]| {
+        let fn_name = format_ident!("{}", fn_name);
+        quote! {
+            fn #fn_name(&self) -> ::protocol_core::__private::String {
+                match self {
+                    #(Self::#idents => ::protocol_core::__private::String::from(#values),)*
+                }
+            }
+        }
+    };
+    let code_fn = string_fn("code", &codes);
+    let title_fn = string_fn("title", &titles);
+    let cmd_code_fn = string_fn("cmd_code", &cmd_codes);
+    let default_value_fn = string_fn("default_value", &default_values);
+    let default_hex_fn = string_fn("default_hex", &default_hexes);
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::protocol_core::AutoEncodingParam for #name #ty_generics #where_clause {
+            #code_fn
+            #title_fn
+            #cmd_code_fn
+            #default_value_fn
+            #default_hex_fn
+
+            fn byte_length(&self) -> usize {
+                match self {
+                    #(Self::#idents => #lengths,)*
+                }
+            }
+
+            fn field_type(&self) -> ::protocol_core::FieldType {
+                match self {
+                    #(Self::#idents => #field_types,)*
+                }
+            }
+
+            fn swap(&self) -> bool {
+                match self {
+                    #(Self::#idents => #swaps,)*
+                }
+            }
+
+            fn rules(&self) -> ::protocol_core::__private::Vec<::protocol_core::ValidationRule> {
+                match self {
+                    #(Self::#idents => #rules,)*
+                }
+            }
+
+            fn pad_byte(&self) -> u8 {
+                match self {
+                    #(Self::#idents => #pad_bytes,)*
+                }
+            }
+
+            fn pad_side(&self) -> ::protocol_core::PadSide {
+                match self {
+                    #(Self::#idents => #pad_sides,)*
+                }
+            }
+
+            fn truncate_side(&self) -> ::protocol_core::PadSide {
+                match self {
+                    #(Self::#idents => #truncate_sides,)*
+                }
+            }
+
+            fn rounding_mode(&self) -> ::protocol_core::math_util::DecimalRoundingMode {
+                match self {
+                    #(Self::#idents => #roundings,)*
+                }
+            }
+
+            fn order(&self) -> Option<u32> {
+                match self {
+                    #(Self::#idents => #orders,)*
+                }
+            }
+
+            fn group(&self) -> Option<::protocol_core::__private::String> {
+                match self {
+                    #(Self::#idents => #groups,)*
+                }
+            }
+
+            fn required(&self) -> bool {
+                match self {
+                    #(Self::#idents => #requireds,)*
+                }
+            }
+        }
+    })
+}
+
+fn expand_encoding(input: &DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let idents: Vec<&Ident> = unit_variants(input)?
+        .into_iter()
+        .map(|variant| &variant.ident)
+        .collect();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::protocol_core::AutoEncoding<Self> for #name #ty_generics #where_clause {
+            fn variants(&self) -> ::protocol_core::__private::Vec<Self> {
+                ::protocol_core::__private::vec![#(Self::#idents),*]
+            }
+
+            fn variants_map(
+                &self,
+            ) -> ::protocol_core::__private::HashMap<::protocol_core::__private::String, Self> {
+                self.variants()
+                    .into_iter()
+                    .map(|variant| (::protocol_core::AutoEncodingParam::code(&variant), variant))
+                    .collect()
+            }
+        }
+    })
+}
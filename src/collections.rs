@@ -0,0 +1,7 @@
+//! 内部使用的集合类型：std 下为标准库的 `HashMap`，no_std 下使用 hashbrown。
+
+#[cfg(feature = "std")]
+pub use std::collections::HashMap;
+
+#[cfg(not(feature = "std"))]
+pub use hashbrown::HashMap;
@@ -0,0 +1,198 @@
+use moka::future::Cache;
+use std::{future::Future, sync::Arc};
+
+use crate::core::cache::{DeviceCacheBuilder, load_error, namespaced_key, stash_load_error};
+use crate::core::parts::{traits::Transport, transport_carrier::TransportCarrier};
+use crate::defi::ProtocolResult;
+
+impl DeviceCacheBuilder {
+    /// 构建异步 (moka::future) 版本的设备缓存
+    pub fn build_async(self) -> AsyncDeviceCache {
+        self.build_async_for()
+    }
+
+    /// 构建缓存自定义 `Transport` 类型的异步设备缓存
+    pub fn build_async_for<T: Transport>(self) -> AsyncDeviceCache<T> {
+        let mut builder = Cache::builder()
+            .max_capacity(self.max_capacity)
+            .eviction_policy(self.eviction_policy.to_moka());
+        if let Some(ttl) = self.time_to_live {
+            builder = builder.time_to_live(ttl);
+        }
+        if let Some(tti) = self.time_to_idle {
+            builder = builder.time_to_idle(tti);
+        }
+        AsyncDeviceCache {
+            inner: builder.build(),
+            namespace: None,
+        }
+    }
+}
+
+/// 异步设备状态缓存 (moka::future)，供 tokio 等异步网关使用。
+///
+/// 只提供读写、加载与命名空间视图。计数自增、在线状态 (`touch` / `is_online` / `stale_devices`)、
+/// 命中率指标、快照恢复与预热目前只有同步的 `DeviceCache` 支持。
+pub struct AsyncDeviceCache<T = TransportCarrier> {
+    inner: Cache<String, Arc<T>>,
+    namespace: Option<String>,
+}
+
+impl<T> Clone for AsyncDeviceCache<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            namespace: self.namespace.clone(),
+        }
+    }
+}
+
+impl<T: Transport> Default for AsyncDeviceCache<T> {
+    fn default() -> Self {
+        DeviceCacheBuilder::default().build_async_for()
+    }
+}
+
+impl AsyncDeviceCache {
+    pub fn builder() -> DeviceCacheBuilder {
+        DeviceCacheBuilder::new()
+    }
+
+    // 从缓存里获取，如果空，则根据unique&upstream_count_hex创建一个新的。
+    pub async fn read_or_default(
+        &self,
+        unique: &str,
+        upstream_count_hex: &str,
+    ) -> Arc<TransportCarrier> {
+        if let Some(state) = self.read(unique).await {
+            return state;
+        }
+        let tp =
+            TransportCarrier::new_with_device_no_and_upstream_count_hex(unique, upstream_count_hex);
+        let arc_tp = Arc::new(tp);
+        self.store(unique, Arc::clone(&arc_tp)).await;
+        arc_tp
+    }
+}
+
+impl<T: Transport> AsyncDeviceCache<T> {
+    /// 返回一个指定命名空间的视图 (与原缓存共享存储)
+    pub fn namespace(&self, namespace: &str) -> AsyncDeviceCache<T> {
+        AsyncDeviceCache {
+            inner: self.inner.clone(),
+            namespace: Some(namespace.to_string()),
+        }
+    }
+
+    /// 当前视图的命名空间 (None 表示默认命名空间)
+    pub fn namespace_name(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+
+    fn key(&self, unique: &str) -> String {
+        namespaced_key(self.namespace.as_deref(), unique)
+    }
+
+    /// 根据设备号获取设备状态的共享引用 (Arc)。
+    /// 如果缓存中不存在或已过期，则返回 None。
+    pub async fn read(&self, unique: &str) -> Option<Arc<T>> {
+        self.inner.get(&self.key(unique)).await
+    }
+
+    /// 从缓存里获取，如果空，则等待 loader 加载 (例如从数据库) 并写入缓存。
+    /// 同一个 key 并发调用时 loader 只会执行一次。
+    pub async fn read_or_load<F, Fut>(&self, unique: &str, loader: F) -> ProtocolResult<Arc<T>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = ProtocolResult<T>>,
+    {
+        let mut failure = None;
+        let slot = &mut failure;
+        self.inner
+            .try_get_with(self.key(unique), async move {
+                loader()
+                    .await
+                    .map(Arc::new)
+                    .map_err(|e| stash_load_error(slot, e))
+            })
+            .await
+            .map_err(|e| load_error(failure.take(), e))
+    }
+
+    /// 插入或更新设备状态到缓存中。
+    pub async fn store(&self, unique: &str, state: Arc<T>) {
+        self.inner.insert(self.key(unique), state).await;
+    }
+
+    /// 从缓存中移除设备状态。
+    pub async fn remove(&self, unique: &str) {
+        self.inner.invalidate(&self.key(unique)).await;
+    }
+
+    /// 获取缓存中当前的设备数量 (近似值，包含所有命名空间)。
+    pub fn read_size(&self) -> u64 {
+        self.inner.entry_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::defi::error::ProtocolError;
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_store_read_remove() {
+        let cache = AsyncDeviceCache::builder().max_capacity(16).build_async();
+        assert!(cache.read("0001").await.is_none());
+        let state = cache.read_or_default("0001", "01").await;
+        assert_eq!(state.upstream_count_clone().unwrap().hex(), "01");
+        // 已存在时不会被覆盖
+        let again = cache.read_or_default("0001", "02").await;
+        assert!(Arc::ptr_eq(&state, &again));
+
+        let replaced = Arc::new(TransportCarrier::new_with_device_no_and_upstream_count_hex(
+            "0001", "05",
+        ));
+        cache.store("0001", Arc::clone(&replaced)).await;
+        assert!(Arc::ptr_eq(&cache.read("0001").await.unwrap(), &replaced));
+        cache.remove("0001").await;
+        assert!(cache.read("0001").await.is_none());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_namespaces_and_load() {
+        let cache = AsyncDeviceCache::builder().max_capacity(16).build_async();
+        let vendor_a = cache.namespace("vendorA");
+        let vendor_b = cache.namespace("vendorB");
+        assert_eq!(vendor_a.namespace_name(), Some("vendorA"));
+        vendor_a.read_or_default("0001", "01").await;
+        vendor_b.read_or_default("0001", "02").await;
+        let b = vendor_b.read("0001").await.unwrap();
+        assert_eq!(b.upstream_count_clone().unwrap().hex(), "02");
+        assert!(cache.read("0001").await.is_none());
+
+        // 移除只影响当前命名空间
+        vendor_a.remove("0001").await;
+        assert!(vendor_a.read("0001").await.is_none());
+        assert!(vendor_b.read("0001").await.is_some());
+        cache.inner.run_pending_tasks().await;
+        assert_eq!(cache.read_size(), 1);
+
+        let loaded = cache
+            .read_or_load("0002", || async {
+                Ok(TransportCarrier::new_with_device_no_and_upstream_count_hex(
+                    "0002", "0A",
+                ))
+            })
+            .await
+            .unwrap();
+        assert_eq!(loaded.upstream_count_clone().unwrap().hex(), "0A");
+        let failed = cache
+            .read_or_load("0003", || async {
+                Err(ProtocolError::CommonError("db down".into()))
+            })
+            .await;
+        assert!(matches!(failed, Err(ProtocolError::CommonError(msg)) if msg == "db down"));
+        assert!(cache.read("0003").await.is_none());
+    }
+}
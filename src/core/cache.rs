@@ -1,61 +1,821 @@
-use moka::sync::Cache;
-use once_cell::sync::Lazy;
-use std::{sync::Arc, time::Duration};
+use moka::{policy::EvictionPolicy, sync::Cache};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
 
-use crate::core::parts::transport_carrier::TransportCarrier;
+use crate::core::parts::{
+    traits::Transport, transport_carrier::TransportCarrier, transport_pair::TransportPair,
+};
+use crate::defi::{ProtocolResult, error::ProtocolError};
+
+// --- 缓存配置 ---
+
+/// 默认最大缓存设备数
+pub const DEFAULT_MAX_CAPACITY: u64 = 100_000;
+/// 默认 TTL (1 小时)
+pub const DEFAULT_TIME_TO_LIVE: Duration = Duration::from_secs(60 * 60);
+
+/// 缓存淘汰策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CacheEvictionPolicy {
+    /// TinyLFU：综合访问频率与时间，适合大多数场景 (moka 默认)
+    #[default]
+    TinyLfu,
+    /// LRU：仅按最近访问时间淘汰
+    Lru,
+}
+
+impl CacheEvictionPolicy {
+    pub(crate) fn to_moka(self) -> EvictionPolicy {
+        match self {
+            CacheEvictionPolicy::TinyLfu => EvictionPolicy::tiny_lfu(),
+            CacheEvictionPolicy::Lru => EvictionPolicy::lru(),
+        }
+    }
+}
+
+/// 设备缓存构建器
+#[derive(Debug, Clone)]
+pub struct DeviceCacheBuilder {
+    pub(crate) max_capacity: u64,
+    pub(crate) time_to_live: Option<Duration>,
+    pub(crate) time_to_idle: Option<Duration>,
+    pub(crate) eviction_policy: CacheEvictionPolicy,
+}
+
+impl Default for DeviceCacheBuilder {
+    fn default() -> Self {
+        Self {
+            max_capacity: DEFAULT_MAX_CAPACITY,
+            time_to_live: Some(DEFAULT_TIME_TO_LIVE),
+            time_to_idle: None,
+            eviction_policy: CacheEvictionPolicy::TinyLfu,
+        }
+    }
+}
+
+impl DeviceCacheBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 最大缓存设备数
+    pub fn max_capacity(mut self, max_capacity: u64) -> Self {
+        self.max_capacity = max_capacity;
+        self
+    }
+
+    /// 存活时间 (TTL)，写入后超过该时间即过期。None 表示不限制
+    pub fn time_to_live(mut self, ttl: Option<Duration>) -> Self {
+        self.time_to_live = ttl;
+        self
+    }
+
+    /// 空闲时间 (TTI)，超过该时间未访问即过期。None 表示不限制
+    pub fn time_to_idle(mut self, tti: Option<Duration>) -> Self {
+        self.time_to_idle = tti;
+        self
+    }
+
+    /// 淘汰策略
+    pub fn eviction_policy(mut self, policy: CacheEvictionPolicy) -> Self {
+        self.eviction_policy = policy;
+        self
+    }
+
+    /// 构建缓存 `TransportCarrier` 的设备缓存
+    pub fn build(self) -> DeviceCache {
+        self.build_for()
+    }
+
+    /// 构建缓存自定义 `Transport` 类型的设备缓存，例如 `builder.build_for::<MyCarrier>()`
+    pub fn build_for<T: Transport>(self) -> DeviceCache<T> {
+        let mut builder = Cache::builder()
+            .max_capacity(self.max_capacity)
+            .eviction_policy(self.eviction_policy.to_moka());
+        if let Some(ttl) = self.time_to_live {
+            builder = builder.time_to_live(ttl);
+        }
+        if let Some(tti) = self.time_to_idle {
+            builder = builder.time_to_idle(tti);
+        }
+        let counters = Arc::new(CacheCounters::default());
+        let listener_counters = Arc::clone(&counters);
+        builder = builder.eviction_listener(move |_key, _value, cause| {
+            if cause.was_evicted() {
+                listener_counters.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+        DeviceCache {
+            inner: builder.build(),
+            namespace: None,
+            counters,
+            last_seen: Cache::new(self.max_capacity),
+        }
+    }
+}
+
+// --- 缓存指标 ---
+
+/// 缓存指标快照，可用于监控设备状态的抖动是否超出容量
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheMetrics {
+    /// 命中次数
+    pub hits: u64,
+    /// 未命中次数
+    pub misses: u64,
+    /// 写入次数
+    pub insertions: u64,
+    /// 因容量或过期被淘汰的次数 (不含主动 remove)
+    pub evictions: u64,
+    /// 通过 `read_or_load` 加载的次数
+    pub loads: u64,
+    /// 加载累计耗时 (纳秒)
+    pub total_load_nanos: u64,
+}
+
+impl CacheMetrics {
+    /// 命中率 (0.0 ~ 1.0)，没有任何读取时返回 0
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+
+    /// 平均加载耗时
+    pub fn average_load_time(&self) -> Duration {
+        self.total_load_nanos
+            .checked_div(self.loads)
+            .map(Duration::from_nanos)
+            .unwrap_or(Duration::ZERO)
+    }
+}
+
+// (内部) 原子计数器，同一缓存的所有命名空间视图共享
+#[derive(Debug, Default)]
+struct CacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    insertions: AtomicU64,
+    evictions: AtomicU64,
+    loads: AtomicU64,
+    total_load_nanos: AtomicU64,
+}
+
+impl CacheCounters {
+    fn snapshot(&self) -> CacheMetrics {
+        CacheMetrics {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            insertions: self.insertions.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            loads: self.loads.load(Ordering::Relaxed),
+            total_load_nanos: self.total_load_nanos.load(Ordering::Relaxed),
+        }
+    }
+
+    fn reset(&self) {
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+        self.insertions.store(0, Ordering::Relaxed);
+        self.evictions.store(0, Ordering::Relaxed);
+        self.loads.store(0, Ordering::Relaxed);
+        self.total_load_nanos.store(0, Ordering::Relaxed);
+    }
+}
+
+// --- 缓存快照 ---
+
+/// 缓存快照中的单条记录，用于网关热重启时保存/恢复设备状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheSnapshotEntry<T = TransportCarrier> {
+    pub unique: String,
+    pub state: T,
+}
+
+// --- 设备缓存实例 ---
+
+/// 设备状态缓存。
+/// 内部的 moka 缓存本身是线程安全且可廉价克隆的 (克隆后共享同一份数据)。
+/// 默认缓存 `TransportCarrier`，下游协议也可以缓存自己实现了 `Transport` 的类型。
+pub struct DeviceCache<T = TransportCarrier> {
+    // 使用 Arc 可以在多个地方共享同一个设备状态实例，减少克隆开销。
+    inner: Cache<String, Arc<T>>,
+    // 命名空间。不同厂商/协议的设备号可能冲突，命名空间会作为 key 的前缀隔离它们
+    namespace: Option<String>,
+    // 命中/未命中等指标
+    counters: Arc<CacheCounters>,
+    // 设备最近一次上报的时间，用于在线/离线判断。不受设备状态的 TTL 影响
+    last_seen: Cache<String, Instant>,
+}
+
+/// 预热缓存时每写入多少条回调一次进度
+pub const WARM_UP_PROGRESS_STEP: usize = 1_000;
+
+// 命名空间与设备号之间的分隔符
+const NAMESPACE_SEPARATOR: &str = "::";
+
+// 组合命名空间与设备号
+pub(crate) fn namespaced_key(namespace: Option<&str>, unique: &str) -> String {
+    match namespace {
+        Some(ns) => format!("{}{}{}", ns, NAMESPACE_SEPARATOR, unique),
+        None => unique.to_string(),
+    }
+}
+
+// loader 的错误原样留给执行 loader 的调用方，交给 moka 的是同样信息的副本 (由并发等待者共享)
+pub(crate) fn stash_load_error(
+    slot: &mut Option<ProtocolError>,
+    error: ProtocolError,
+) -> ProtocolError {
+    let shared = ProtocolError::CommonError(error.to_string());
+    *slot = Some(error);
+    shared
+}
+
+// 执行了 loader 的调用方取回原错误；并发等待者拿到包装后的共享副本，可通过 source() 取到
+pub(crate) fn load_error(
+    failure: Option<ProtocolError>,
+    shared: Arc<ProtocolError>,
+) -> ProtocolError {
+    failure.unwrap_or_else(|| ProtocolError::external(shared))
+}
+
+// 手动实现，避免 derive 要求 T: Clone
+impl<T> Clone for DeviceCache<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            namespace: self.namespace.clone(),
+            counters: Arc::clone(&self.counters),
+            last_seen: self.last_seen.clone(),
+        }
+    }
+}
+
+impl<T: Transport> Default for DeviceCache<T> {
+    fn default() -> Self {
+        DeviceCacheBuilder::default().build_for()
+    }
+}
+
+impl DeviceCache {
+    pub fn builder() -> DeviceCacheBuilder {
+        DeviceCacheBuilder::new()
+    }
+
+    // 从缓存里获取，如果空，则根据unique&upstream_count_hex创建一个新的。
+    // 不刷新最近上报时间，收到上行帧时由调用方 `touch`。
+    pub fn read_or_default(&self, unique: &str, upstream_count_hex: &str) -> Arc<TransportCarrier> {
+        self.read(unique).unwrap_or_else(|| {
+            let tp = TransportCarrier::new_with_device_no_and_upstream_count_hex(
+                unique,
+                upstream_count_hex,
+            );
+            let arc_tp = Arc::new(tp);
+            self.store(unique, Arc::clone(&arc_tp));
+            arc_tp
+        })
+    }
+}
+
+impl<T: Transport> DeviceCache<T> {
+    /// 返回一个指定命名空间的视图。
+    /// 视图与原缓存共享同一份存储 (容量、TTL 共用)，但 key 互不干扰。
+    /// 例如 `cache.namespace("vendorA")` 与 `cache.namespace("vendorB")` 中相同设备号的状态不会互相覆盖。
+    pub fn namespace(&self, namespace: &str) -> DeviceCache<T> {
+        DeviceCache {
+            inner: self.inner.clone(),
+            namespace: Some(namespace.to_string()),
+            counters: Arc::clone(&self.counters),
+            last_seen: self.last_seen.clone(),
+        }
+    }
+
+    /// 当前视图的命名空间 (None 表示默认命名空间)
+    pub fn namespace_name(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+
+    // 组合出实际存储使用的 key
+    fn key(&self, unique: &str) -> String {
+        namespaced_key(self.namespace.as_deref(), unique)
+    }
+
+    /// 根据设备号获取设备状态的共享引用 (Arc)。
+    /// 如果缓存中不存在或已过期，则返回 None。
+    pub fn read(&self, unique: &str) -> Option<Arc<T>> {
+        // 注意：moka v0.12+ get() 直接返回 Option<V> (如果是 Arc，则 Arc 被 clone)
+        let state = self.inner.get(&self.key(unique));
+        let counter = if state.is_some() {
+            &self.counters.hits
+        } else {
+            &self.counters.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        state
+    }
+
+    /// 从缓存里获取，如果空，则调用 loader 加载 (例如从数据库) 并写入缓存。
+    /// 同一个 key 并发调用时 loader 只会执行一次。
+    pub fn read_or_load<F>(&self, unique: &str, loader: F) -> ProtocolResult<Arc<T>>
+    where
+        F: FnOnce() -> ProtocolResult<T>,
+    {
+        if let Some(state) = self.read(unique) {
+            return Ok(state);
+        }
+        let mut failure = None;
+        self.inner
+            .try_get_with(self.key(unique), || {
+                let started = Instant::now();
+                let loaded = loader()
+                    .map(Arc::new)
+                    .map_err(|e| stash_load_error(&mut failure, e));
+                self.counters.loads.fetch_add(1, Ordering::Relaxed);
+                self.counters.total_load_nanos.fetch_add(
+                    started.elapsed().as_nanos().min(u64::MAX as u128) as u64,
+                    Ordering::Relaxed,
+                );
+                if loaded.is_ok() {
+                    self.counters.insertions.fetch_add(1, Ordering::Relaxed);
+                }
+                loaded
+            })
+            .map_err(|e| load_error(failure.take(), e))
+    }
+
+    /// 原子地将缓存中设备的上行消息序号 +1，并返回新的序号。
+    /// 直接更新缓存中共享的设备状态 (`Transport::upstream_counter`)，持有旧 `Arc` 的调用方也能看到新值。
+    /// 设备不在缓存中、或状态类型不支持递增时返回错误；序号未设置时从 2 字节的 0 开始计数。
+    pub fn increment_upstream(&self, unique: &str) -> ProtocolResult<TransportPair> {
+        self._cached_state(unique)?.increment_upstream()
+    }
+
+    /// 原子地将缓存中设备的下行消息序号 +1，并返回新的序号。
+    pub fn increment_downstream(&self, unique: &str) -> ProtocolResult<TransportPair> {
+        self._cached_state(unique)?.increment_downstream()
+    }
+
+    // 不计入命中率指标
+    fn _cached_state(&self, unique: &str) -> ProtocolResult<Arc<T>> {
+        self.inner.get(&self.key(unique)).ok_or_else(|| {
+            ProtocolError::CommonError(format!("Device '{}' not found in cache", unique))
+        })
+    }
+
+    /// 插入或更新设备状态到缓存中。只写缓存，不改变设备的在线状态
+    pub fn store(&self, unique: &str, state: Arc<T>) {
+        self.inner.insert(self.key(unique), state);
+        self.counters.insertions.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 从缓存中移除设备状态 (连同最近上报时间)。
+    pub fn remove(&self, unique: &str) {
+        let key = self.key(unique);
+        self.inner.invalidate(&key);
+        self.last_seen.invalidate(&key);
+    }
+
+    /// 记录设备在此刻上报过 (收到并解码了该设备的帧)
+    pub fn touch(&self, unique: &str) {
+        self.last_seen.insert(self.key(unique), Instant::now());
+    }
+
+    /// 设备最近一次上报距今的时长，从未上报过返回 None
+    pub fn last_seen(&self, unique: &str) -> Option<Duration> {
+        self.last_seen
+            .get(&self.key(unique))
+            .map(|seen| seen.elapsed())
+    }
+
+    /// 设备在 `window` 时间窗口内是否上报过
+    pub fn is_online(&self, unique: &str, window: Duration) -> bool {
+        self.last_seen(unique)
+            .is_some_and(|elapsed| elapsed <= window)
+    }
+
+    /// 当前视图下超过 `window` 未上报的设备号 (规则同 `entries`)
+    pub fn stale_devices(&self, window: Duration) -> impl Iterator<Item = String> + '_ {
+        let prefix = self.view_prefix();
+        self.last_seen
+            .iter()
+            .filter(move |(_, seen)| seen.elapsed() > window)
+            .filter_map(move |(key, _)| match &prefix {
+                Some(p) => key.strip_prefix(p.as_str()).map(str::to_string),
+                None => Some(key.to_string()),
+            })
+    }
+
+    // 命名空间视图的 key 前缀
+    fn view_prefix(&self) -> Option<String> {
+        self.namespace
+            .as_deref()
+            .map(|ns| format!("{}{}", ns, NAMESPACE_SEPARATOR))
+    }
+
+    /// 获取缓存中当前的设备数量 (近似值，包含所有命名空间)。
+    pub fn read_size(&self) -> u64 {
+        self.inner.entry_count()
+    }
+
+    /// 获取缓存指标快照 (所有命名空间合计)
+    pub fn metrics(&self) -> CacheMetrics {
+        // 让 moka 处理挂起的淘汰任务，保证淘汰计数及时
+        self.inner.run_pending_tasks();
+        self.counters.snapshot()
+    }
+
+    /// 清零缓存指标
+    pub fn reset_metrics(&self) {
+        self.counters.reset();
+    }
+
+    /// 获取当前视图下所有的 (设备号, 设备状态)。
+    /// 命名空间视图只返回本命名空间的记录 (设备号不含前缀)；默认视图返回全部记录 (key 原样返回)。
+    pub fn entries(&self) -> Vec<(String, Arc<T>)> {
+        let prefix = self.view_prefix();
+        self.inner
+            .iter()
+            .filter_map(|(key, state)| match &prefix {
+                Some(p) => key.strip_prefix(p.as_str()).map(|u| (u.to_string(), state)),
+                None => Some((key.to_string(), state)),
+            })
+            .collect()
+    }
+
+    /// 将当前视图下的所有设备状态序列化为 JSON 字节 (用于热重启)
+    pub fn snapshot(&self) -> ProtocolResult<Vec<u8>>
+    where
+        T: Clone + Serialize,
+    {
+        let entries: Vec<CacheSnapshotEntry<T>> = self
+            .entries()
+            .into_iter()
+            .map(|(unique, state)| CacheSnapshotEntry {
+                unique,
+                state: state.as_ref().clone(),
+            })
+            .collect();
+        serde_json::to_vec(&entries).map_err(ProtocolError::external)
+    }
+
+    /// 从 `snapshot` 生成的 JSON 字节恢复设备状态，返回恢复的条数。
+    /// 已存在的同名记录会被覆盖，TTL 从恢复时重新计算。恢复的设备不会被视为在线。
+    pub fn restore(&self, data: &[u8]) -> ProtocolResult<usize>
+    where
+        T: DeserializeOwned,
+    {
+        let entries: Vec<CacheSnapshotEntry<T>> =
+            serde_json::from_slice(data).map_err(ProtocolError::external)?;
+        Ok(self.warm_up(
+            entries.into_iter().map(|entry| (entry.unique, entry.state)),
+            |_, _| {},
+        ))
+    }
+
+    /// 启动时批量预热缓存 (例如从数据库加载设备状态)，返回写入的条数。
+    /// 每写入 `WARM_UP_PROGRESS_STEP` 条以及结束时调用一次 `progress(已写入条数, 预估总数)`，
+    /// 预估总数取自迭代器的 size_hint，无法确定时为 None。
+    /// 预热的设备不会被视为在线。
+    pub fn warm_up<I, S, F>(&self, entries: I, mut progress: F) -> usize
+    where
+        I: IntoIterator<Item = (S, T)>,
+        S: AsRef<str>,
+        F: FnMut(usize, Option<usize>),
+    {
+        let iter = entries.into_iter();
+        let total = iter.size_hint().1;
+        let mut loaded = 0;
+        for (unique, state) in iter {
+            self.inner
+                .insert(self.key(unique.as_ref()), Arc::new(state));
+            self.counters.insertions.fetch_add(1, Ordering::Relaxed);
+            loaded += 1;
+            if loaded % WARM_UP_PROGRESS_STEP == 0 {
+                progress(loaded, total);
+            }
+        }
+        if loaded % WARM_UP_PROGRESS_STEP != 0 || loaded == 0 {
+            progress(loaded, total);
+        }
+        loaded
+    }
+}
 
 // --- 全局缓存定义 ---
 
-// 定义缓存的值类型为一个 Arc<DeviceState>。
-// 使用 Arc 可以在多个地方共享同一个设备状态实例，减少克隆开销。
-// Cache<String, Arc<DeviceState>> 是线程安全的。
-static DEVICE_CACHE: Lazy<Cache<String, Arc<TransportCarrier>>> = Lazy::new(|| {
-    Cache::builder()
-        .max_capacity(100_000) // 例如，最大缓存10万个设备
-        .time_to_live(Duration::from_secs(60 * 60)) // 例如，TTL 设置为 1 小时
-        // .time_to_idle(Duration::from_secs(1 * 60 * 60)) // 也可以设置空闲过期时间 (TTI)
-        .build()
-});
+// 全局默认缓存。首次使用时按默认配置创建，也可以在启动时通过 `ProtocolCache::init_global` 指定。
+static DEVICE_CACHE: OnceCell<DeviceCache> = OnceCell::new();
 
 pub struct ProtocolCache {}
 
 impl ProtocolCache {
+    /// 创建一个独立的设备缓存构建器
+    pub fn builder() -> DeviceCacheBuilder {
+        DeviceCacheBuilder::new()
+    }
+
+    /// 使用自定义配置初始化全局缓存。必须在首次访问全局缓存之前调用。
+    pub fn init_global(cache: DeviceCache) -> ProtocolResult<()> {
+        DEVICE_CACHE.set(cache).map_err(|_| {
+            ProtocolError::CommonError("ProtocolCache global cache already initialized".into())
+        })
+    }
+
+    /// 获取全局缓存实例
+    pub fn global() -> &'static DeviceCache {
+        DEVICE_CACHE.get_or_init(DeviceCache::default)
+    }
+
+    /// 获取全局缓存的命名空间视图，例如 `ProtocolCache::namespace("vendorA").read(device_no)`
+    pub fn namespace(namespace: &str) -> DeviceCache {
+        Self::global().namespace(namespace)
+    }
+
     // --- 公共访问函数 ---
 
     /// 根据设备号获取设备状态的共享引用 (Arc)。
     /// 如果缓存中不存在或已过期，则返回 None。
     pub fn read(unique: &str) -> Option<Arc<TransportCarrier>> {
-        DEVICE_CACHE.get(unique)
-        // .cloned() // moka v0.10+ 返回 Option<&V>, 需要 clone() 或 cloned()
-        // 注意：moka v0.12+ get() 直接返回 Option<V> (如果是 Arc，则 Arc 被 clone)
+        Self::global().read(unique)
     }
 
     // 从缓存里获取，如果空，则根据unique&upstream_count_hex创建一个新的。upstream_count_hex是上行序列号，通常来说，协议都需要。如果不需要传个随便什么就行。
     pub fn read_or_default(unique: &str, upstream_count_hex: &str) -> Arc<TransportCarrier> {
-        Self::read(unique).unwrap_or_else(|| {
-            let tp = TransportCarrier::new_with_device_no_and_upstream_count_hex(
-                unique,
-                upstream_count_hex,
-            );
-            let arc_tp = Arc::new(tp);
-            Self::store(unique, Arc::clone(&arc_tp));
-            arc_tp
-        })
+        Self::global().read_or_default(unique, upstream_count_hex)
     }
 
     /// 插入或更新设备状态到缓存中。
     /// `state` 应该是 `Arc<DeviceState>` 类型。
     pub fn store(unique: &str, state: Arc<TransportCarrier>) {
-        DEVICE_CACHE.insert(unique.into(), state);
+        Self::global().store(unique, state)
     }
     /// 从缓存中移除设备状态。
     pub fn remove(device_no: &str) {
-        DEVICE_CACHE.invalidate(device_no);
+        Self::global().remove(device_no)
     }
 
     /// 获取缓存中当前的设备数量 (近似值)。
     pub fn read_size() -> u64 {
-        DEVICE_CACHE.entry_count()
+        Self::global().read_size()
+    }
+
+    /// 原子地将设备的上行消息序号 +1，并返回新的序号
+    pub fn increment_upstream(unique: &str) -> ProtocolResult<TransportPair> {
+        Self::global().increment_upstream(unique)
+    }
+
+    /// 原子地将设备的下行消息序号 +1，并返回新的序号
+    pub fn increment_downstream(unique: &str) -> ProtocolResult<TransportPair> {
+        Self::global().increment_downstream(unique)
+    }
+
+    /// 记录设备在此刻上报过
+    pub fn touch(unique: &str) {
+        Self::global().touch(unique)
+    }
+
+    /// 设备在 `window` 时间窗口内是否上报过
+    pub fn is_online(unique: &str, window: Duration) -> bool {
+        Self::global().is_online(unique, window)
+    }
+
+    /// 超过 `window` 未上报的设备号 (包含所有命名空间，key 原样返回)
+    pub fn stale_devices(window: Duration) -> Vec<String> {
+        Self::global().stale_devices(window).collect()
+    }
+
+    /// 获取全局缓存的指标快照
+    pub fn metrics() -> CacheMetrics {
+        Self::global().metrics()
+    }
+
+    /// 将全局缓存序列化为 JSON 字节 (包含所有命名空间)
+    pub fn snapshot() -> ProtocolResult<Vec<u8>> {
+        Self::global().snapshot()
+    }
+
+    /// 从快照恢复全局缓存，返回恢复的条数
+    pub fn restore(data: &[u8]) -> ProtocolResult<usize> {
+        Self::global().restore(data)
+    }
+
+    /// 启动时批量预热全局缓存，返回写入的条数。`progress(已写入条数, 预估总数)`
+    pub fn warm_up<I, S, F>(entries: I, progress: F) -> usize
+    where
+        I: IntoIterator<Item = (S, TransportCarrier)>,
+        S: AsRef<str>,
+        F: FnMut(usize, Option<usize>),
+    {
+        Self::global().warm_up(entries, progress)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HexDigestError;
+
+    #[test]
+    fn test_namespaces_do_not_collide() {
+        let cache = DeviceCache::builder().max_capacity(16).build();
+        let vendor_a = cache.namespace("vendorA");
+        let vendor_b = cache.namespace("vendorB");
+        vendor_a.read_or_default("0001", "01");
+        vendor_b.read_or_default("0001", "02");
+
+        let a = vendor_a.read("0001").unwrap();
+        let b = vendor_b.read("0001").unwrap();
+        assert_eq!(a.upstream_count_clone().unwrap().hex(), "01");
+        assert_eq!(b.upstream_count_clone().unwrap().hex(), "02");
+        assert!(cache.read("0001").is_none());
+    }
+
+    // 下游厂商自定义的设备状态，带有额外字段
+    struct VendorCarrier {
+        device_no: TransportPair,
+        signal: i32,
+    }
+
+    impl Transport for VendorCarrier {
+        fn device_no(&self) -> Option<TransportPair> {
+            Some(self.device_no.clone())
+        }
+        fn device_no_length(&self) -> Option<TransportPair> {
+            None
+        }
+        fn report_type(&self) -> Option<TransportPair> {
+            None
+        }
+        fn control_field(&self) -> Option<TransportPair> {
+            None
+        }
+        fn protocol_version(&self) -> Option<TransportPair> {
+            None
+        }
+        fn device_type(&self) -> Option<TransportPair> {
+            None
+        }
+        fn factory_code(&self) -> Option<TransportPair> {
+            None
+        }
+        fn upstream_count(&self) -> Option<TransportPair> {
+            None
+        }
+        fn downstream_count(&self) -> Option<TransportPair> {
+            None
+        }
+    }
+
+    #[test]
+    fn test_custom_transport() {
+        let cache = DeviceCache::builder()
+            .max_capacity(16)
+            .build_for::<VendorCarrier>();
+        let loaded = cache
+            .read_or_load("0001", || {
+                Ok(VendorCarrier {
+                    device_no: TransportPair::new("0001".into(), vec![0x00, 0x01]),
+                    signal: -73,
+                })
+            })
+            .unwrap();
+        assert_eq!(loaded.signal, -73);
+        assert_eq!(
+            cache.read("0001").unwrap().device_no().unwrap().hex(),
+            "0001"
+        );
+    }
+
+    #[test]
+    fn test_warm_up() {
+        let cache = DeviceCache::builder().max_capacity(4096).build();
+        let rows = (0..2500).map(|i| {
+            let unique = format!("{:04}", i);
+            let state = TransportCarrier::new_with_device_no_and_upstream_count_hex(&unique, "01");
+            (unique, state)
+        });
+        let mut reports = Vec::new();
+        let loaded = cache.warm_up(rows, |done, total| reports.push((done, total)));
+        assert_eq!(loaded, 2500);
+        assert_eq!(
+            reports,
+            vec![(1000, Some(2500)), (2000, Some(2500)), (2500, Some(2500))]
+        );
+        assert!(cache.read("2499").is_some());
+        assert!(!cache.is_online("2499", Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_online_tracking() {
+        let cache = DeviceCache::builder().max_capacity(16).build();
+        assert!(!cache.is_online("0001", Duration::from_secs(60)));
+        // 写缓存 (预热、恢复、下行命令) 不代表设备上报过
+        cache.read_or_default("0001", "01");
+        cache.store("0004", Arc::new(TransportCarrier::default()));
+        assert!(!cache.is_online("0001", Duration::from_secs(60)));
+        assert!(!cache.is_online("0004", Duration::from_secs(60)));
+        cache.touch("0001");
+        cache.namespace("vendorA").touch("0002");
+        assert!(cache.is_online("0001", Duration::from_secs(60)));
+
+        std::thread::sleep(Duration::from_millis(20));
+        cache.touch("0003");
+        let mut stale: Vec<String> = cache.stale_devices(Duration::from_millis(10)).collect();
+        stale.sort();
+        assert_eq!(stale, vec!["0001", "vendorA::0002"]);
+        let stale_a: Vec<String> = cache
+            .namespace("vendorA")
+            .stale_devices(Duration::from_millis(10))
+            .collect();
+        assert_eq!(stale_a, vec!["0002"]);
+
+        cache.remove("0001");
+        assert!(cache.last_seen("0001").is_none());
+    }
+
+    #[test]
+    fn test_increment_counters() {
+        let cache = DeviceCache::builder().max_capacity(16).build();
+        assert!(cache.increment_upstream("0001").is_err());
+        let held = cache.read_or_default("0001", "00FF");
+        assert_eq!(cache.increment_upstream("0001").unwrap().hex(), "0100");
+        // 原地递增，之前取出的 Arc 也能看到新值
+        assert!(Arc::ptr_eq(&held, &cache.read("0001").unwrap()));
+        assert_eq!(held.upstream_count_clone().unwrap().hex(), "0100");
+        assert_eq!(cache.increment_downstream("0001").unwrap().hex(), "0001");
+        let state = cache.read("0001").unwrap();
+        assert_eq!(state.upstream_count_clone().unwrap().bytes(), &[0x01, 0x00]);
+        assert_eq!(
+            TransportPair::new("FF".into(), vec![0xFF])
+                .incremented()
+                .hex(),
+            "00"
+        );
+    }
+
+    #[test]
+    fn test_metrics() {
+        let cache = DeviceCache::builder().max_capacity(16).build();
+        assert!(cache.read("0001").is_none());
+        cache.read_or_default("0001", "01");
+        cache.read("0001");
+        let loaded = cache
+            .read_or_load("0002", || {
+                Ok(TransportCarrier::new_with_device_no_and_upstream_count_hex(
+                    "0002", "01",
+                ))
+            })
+            .unwrap();
+        assert_eq!(loaded.device_no().unwrap().hex(), "0002");
+
+        let metrics = cache.metrics();
+        assert_eq!(metrics.hits, 1);
+        assert_eq!(metrics.misses, 3);
+        assert_eq!(metrics.insertions, 2);
+        assert_eq!(metrics.loads, 1);
+        cache.reset_metrics();
+        assert_eq!(cache.metrics().hits, 0);
+
+        // loader 的错误原样返回
+        let failed = cache.read_or_load("0003", || {
+            Err(HexDigestError::UnknownCmdCode {
+                code: "B9".into(),
+                nearest: None,
+            }
+            .into())
+        });
+        assert!(matches!(
+            failed,
+            Err(ProtocolError::HexDigestError(
+                HexDigestError::UnknownCmdCode { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let cache = DeviceCache::builder().max_capacity(16).build();
+        cache.read_or_default("0001", "0A");
+        cache.namespace("vendorA").read_or_default("0002", "0B");
+        let data = cache.snapshot().unwrap();
+
+        let restored = DeviceCache::builder().max_capacity(16).build();
+        assert_eq!(restored.restore(&data).unwrap(), 2);
+        let state = restored.namespace("vendorA").read("0002").unwrap();
+        assert_eq!(state.upstream_count_clone().unwrap().hex(), "0B");
+        assert!(restored.read("0001").is_some());
     }
 }
 
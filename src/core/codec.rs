@@ -0,0 +1,123 @@
+//! tokio_util 的 `Decoder` / `Encoder`，异步 TCP 服务可以直接使用 `Framed<TcpStream, ProtocolCodec<_>>`。
+//!
+//! 分帧规则见 `framing::scan_frame`：解码得到的是一帧完整的原始报文 (`Vec<u8>`)，
+//! 之后仍然交给 `decode_upstream` 等解码流程。编码直接写出报文字节，`RawCapsule` 也可以直接发送。
+
+use bytes::{BufMut, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::core::framing::{FrameScan, scan_frame};
+use crate::utils::buffer_pool;
+use crate::{Cmd, ProtocolConfig, ProtocolError, RawCapsule};
+
+/// 按 `ProtocolConfig` 分帧的编解码器
+#[derive(Debug, Clone)]
+pub struct ProtocolCodec<C: ProtocolConfig> {
+    pub(crate) config: C,
+}
+
+impl<C: ProtocolConfig> ProtocolCodec<C> {
+    pub fn new(config: C) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &C {
+        &self.config
+    }
+}
+
+impl<C: ProtocolConfig> Decoder for ProtocolCodec<C> {
+    type Item = Vec<u8>;
+    type Error = ProtocolError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match scan_frame(&self.config, src)? {
+            FrameScan::Frame { skip, len } => {
+                let _ = src.split_to(skip);
+                Ok(Some(buffer_pool::copy(&src.split_to(len))))
+            }
+            FrameScan::Partial { skip } => {
+                let _ = src.split_to(skip);
+                Ok(None)
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        // 连接关闭时剩下的半帧无法再补全，直接丢弃
+        let frame = self.decode(src)?;
+        if frame.is_none() {
+            src.clear();
+        }
+        Ok(frame)
+    }
+}
+
+impl<C: ProtocolConfig> Encoder<Vec<u8>> for ProtocolCodec<C> {
+    type Error = ProtocolError;
+
+    fn encode(&mut self, item: Vec<u8>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.put_slice(&item);
+        Ok(())
+    }
+}
+
+impl<C: ProtocolConfig, T: Cmd + 'static> Encoder<RawCapsule<T>> for ProtocolCodec<C> {
+    type Error = ProtocolError;
+
+    fn encode(&mut self, item: RawCapsule<T>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.put_slice(item.bytes());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CrcType;
+
+    struct Meter;
+
+    impl ProtocolConfig for Meter {
+        fn head_tag(&self) -> String {
+            "68".into()
+        }
+        fn tail_tag(&self) -> String {
+            "16".into()
+        }
+        fn crc_mode(&self) -> CrcType {
+            CrcType::Crc16Modbus
+        }
+        fn crc_index(&self) -> (u8, u8) {
+            (0, 0)
+        }
+        fn length_index(&self) -> (u8, u8) {
+            (1, 2)
+        }
+    }
+
+    #[test]
+    fn test_codec_split_frames() {
+        let mut codec = ProtocolCodec::new(Meter);
+        // 噪声 + 两帧 + 半帧，分两次到达
+        let mut buffer = BytesMut::from(&[0x00, 0x68, 0x05, 0x01][..]);
+        assert_eq!(codec.decode(&mut buffer).unwrap(), None);
+        buffer.extend_from_slice(&[0x02, 0x16, 0x68, 0x04, 0x03, 0x16, 0x68]);
+        assert_eq!(
+            codec.decode(&mut buffer).unwrap(),
+            Some(vec![0x68, 0x05, 0x01, 0x02, 0x16])
+        );
+        assert_eq!(
+            codec.decode(&mut buffer).unwrap(),
+            Some(vec![0x68, 0x04, 0x03, 0x16])
+        );
+        assert_eq!(codec.decode_eof(&mut buffer).unwrap(), None);
+        assert!(buffer.is_empty());
+
+        let mut out = BytesMut::new();
+        codec
+            .encode(vec![0x68, 0x04, 0x03, 0x16], &mut out)
+            .unwrap();
+        assert_eq!(&out[..], &[0x68, 0x04, 0x03, 0x16]);
+    }
+}
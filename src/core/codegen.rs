@@ -0,0 +1,671 @@
+//! 从协议定义 (`ProtocolSchema`) 生成 Rust 代码：与运行时加载使用同一个定义文件，
+//! 长期维护的协议可以在编译期得到类型检查。生成的内容：
+//! - `Envelope`：实现 `ProtocolConfig` 的帧外壳；
+//! - `Cmd`：命令枚举，实现 `protocol_core::Cmd`；
+//! - 每个命令一个字段枚举 (例如 `ShuJuShangBaoField`)，上行命令实现 `AutoDecodingParam` / `AutoDecoding`，
+//!   下行命令实现 `AutoEncodingParam` / `AutoEncoding`，命令码作为第一个字段 (`CmdCode`)；
+//! - `decode`：按命令码选择字段定义做上行解码；`encode`：按 capsule 中的命令做下行编码。
+//!
+//! 枚举变体名取 schema 中的 `ident`，省略时由 title 的拼音生成 (例如 "数据上报" -> `ShuJuShangBao`)。
+//!
+//! 在 build.rs 中生成，再用 `include!` 引入：
+//!
+//! ```ignore
+//! // build.rs
+//! fn main() {
+//!     protocol_core::codegen::generate_file("schema/vendor_a.toml", "vendor_a.rs").unwrap();
+//! }
+//!
+//! // src/lib.rs
+//! mod vendor_a {
+//!     include!(concat!(env!("OUT_DIR"), "/vendor_a.rs"));
+//! }
+//! // 使用：let capsule = vendor_a::decode(&bytes)?;
+//! ```
+
+use std::{
+    collections::HashSet,
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    FieldType, ProtocolError, ProtocolResult, ValidationRule,
+    core::protocol_schema::{CmdSchema, FieldKind, FieldSchema, ProtocolSchema},
+    to_pinyin,
+};
+
+/// 生成协议定义对应的 Rust 代码
+pub fn generate(schema: &ProtocolSchema) -> ProtocolResult<String> {
+    if schema.commands.is_empty() {
+        return Err(ProtocolError::ValidationFailed(
+            "codegen: schema has no commands".into(),
+        ));
+    }
+    let envelope = &schema.envelope;
+    let mut out = String::new();
+    let version = if schema.version.is_empty() {
+        String::new()
+    } else {
+        format!(" {}", schema.version)
+    };
+    let _ = writeln!(
+        out,
+        "// @generated by protocol_core::codegen from schema {:?}{}, do not edit.\n",
+        schema.name, version
+    );
+
+    out.push_str(&_envelope(schema));
+
+    let mut cmd_idents = HashSet::new();
+    let cmds: Vec<(String, &CmdSchema)> = schema
+        .commands
+        .iter()
+        .map(|cmd| {
+            let ident = _unique_ident(&mut cmd_idents, cmd.ident.as_deref(), &cmd.title, "Cmd");
+            (ident, cmd)
+        })
+        .collect();
+    out.push_str(&_cmd_enum(&cmds));
+
+    for (ident, cmd) in &cmds {
+        let fields = _fields_with_code(cmd, envelope.cmd_length);
+        if fields.is_empty() {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "codegen: cmd '{}' has neither a cmd code nor fields",
+                cmd.title
+            )));
+        }
+        out.push_str(&_field_enum(&format!("{}Field", ident), cmd, &fields));
+    }
+
+    out.push_str(&_decode_fn(schema, &cmds));
+    out.push_str(&_encode_fn(&cmds, envelope.cmd_length));
+    Ok(out)
+}
+
+/// 供 build.rs 使用：加载 `schema_path`，生成代码写入 `$OUT_DIR/out_file`，返回写入的路径。
+/// 同时输出 `cargo:rerun-if-changed`，定义文件修改后重新生成
+pub fn generate_file(schema_path: impl AsRef<Path>, out_file: &str) -> ProtocolResult<PathBuf> {
+    let schema_path = schema_path.as_ref();
+    let out_dir = std::env::var("OUT_DIR").map_err(ProtocolError::external)?;
+    let code = generate(&ProtocolSchema::from_file(schema_path)?)?;
+    let out_path = Path::new(&out_dir).join(out_file);
+    std::fs::write(&out_path, code).map_err(ProtocolError::external)?;
+    println!("cargo:rerun-if-changed={}", schema_path.display());
+    Ok(out_path)
+}
+
+fn _envelope(schema: &ProtocolSchema) -> String {
+    use crate::ProtocolConfig;
+
+    let envelope = &schema.envelope;
+    let mut out = String::new();
+    let _ = writeln!(out, "/// {} 的帧外壳", schema.name);
+    out.push_str("#[derive(Debug, Clone, Copy, Default)]\npub struct Envelope;\n\n");
+    out.push_str("impl ::protocol_core::ProtocolConfig for Envelope {\n");
+    let _ = writeln!(
+        out,
+        "    fn head_tag(&self) -> String {{\n        {:?}.to_string()\n    }}\n",
+        envelope.head
+    );
+    let _ = writeln!(
+        out,
+        "    fn tail_tag(&self) -> String {{\n        {:?}.to_string()\n    }}\n",
+        envelope.tail
+    );
+    let _ = writeln!(
+        out,
+        "    fn crc_mode(&self) -> ::protocol_core::CrcType {{\n        ::protocol_core::CrcType::{:?}\n    }}\n",
+        envelope.crc_mode()
+    );
+    let _ = writeln!(
+        out,
+        "    fn crc_index(&self) -> (u8, u8) {{\n        {:?}\n    }}\n",
+        envelope.crc_index()
+    );
+    let _ = writeln!(
+        out,
+        "    fn length_index(&self) -> (u8, u8) {{\n        {:?}\n    }}\n",
+        envelope.length_index
+    );
+    let _ = writeln!(
+        out,
+        "    fn max_frame_len(&self) -> usize {{\n        {}\n    }}\n",
+        envelope.max_frame_len
+    );
+    let _ = writeln!(
+        out,
+        "    fn crc_little_endian(&self) -> bool {{\n        {}\n    }}",
+        envelope.crc_little_endian
+    );
+    out.push_str("}\n\n");
+    out
+}
+
+fn _cmd_enum(cmds: &[(String, &CmdSchema)]) -> String {
+    let mut out = String::new();
+    out.push_str("/// 协议中的所有命令\n");
+    out.push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\npub enum Cmd {\n");
+    for (ident, cmd) in cmds {
+        let _ = writeln!(out, "    /// {} ({})\n    {},", cmd.title, cmd.code, ident);
+    }
+    out.push_str("}\n\n");
+
+    out.push_str("impl Cmd {\n");
+    let _ = writeln!(
+        out,
+        "    pub const ALL: [Cmd; {}] = [{}];\n",
+        cmds.len(),
+        cmds.iter()
+            .map(|(ident, _)| format!("Cmd::{}", ident))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    out.push_str(
+        "    /// 按命令码与方向查找，`DirectionEnum::Both` 表示不限方向\n    pub fn from_code(code: &str, direction: ::protocol_core::DirectionEnum) -> Option<Self> {\n        Self::ALL.into_iter().find(|cmd| {\n            let d = ::protocol_core::Cmd::direction(cmd);\n            ::protocol_core::Cmd::code(cmd).eq_ignore_ascii_case(code)\n                && match direction {\n                    ::protocol_core::DirectionEnum::Upstream => d.is_upstream(),\n                    ::protocol_core::DirectionEnum::Downstream => d.is_downstream(),\n                    ::protocol_core::DirectionEnum::Both => true,\n                }\n        })\n    }\n}\n\n",
+    );
+
+    let arms = |f: &dyn Fn(&CmdSchema) -> String| {
+        cmds.iter()
+            .map(|(ident, cmd)| (format!("Cmd::{}", ident), f(cmd)))
+            .collect::<Vec<_>>()
+    };
+    out.push_str("impl ::protocol_core::Cmd for Cmd {\n");
+    out.push_str(&_method(
+        "fn code(&self) -> String",
+        &arms(&|cmd| format!("{:?}.to_string()", cmd.code)),
+    ));
+    out.push_str(&_method(
+        "fn title(&self) -> String",
+        &arms(&|cmd| format!("{:?}.to_string()", cmd.title)),
+    ));
+    out.push_str(&_method(
+        "fn direction(&self) -> ::protocol_core::DirectionEnum",
+        &arms(&|cmd| format!("::protocol_core::DirectionEnum::{:?}", cmd.direction)),
+    ));
+    out.push_str(&_method(
+        "fn rw(&self) -> Option<::protocol_core::RW>",
+        &arms(&|cmd| match cmd.rw {
+            Some(rw) => format!("Some(::protocol_core::RW::{:?})", rw),
+            None => "None".into(),
+        }),
+    ));
+    out.push_str(&_method(
+        "fn msg_type(&self) -> Option<::protocol_core::MsgTypeEnum>",
+        &arms(&|cmd| match &cmd.msg_type {
+            Some(msg_type) => format!(
+                "::protocol_core::MsgTypeEnum::code_of({:?}).ok()",
+                msg_type.code()
+            ),
+            None => "None".into(),
+        }),
+    ));
+    out.push_str("}\n\n");
+    out
+}
+
+// 命令码作为第一个字段 (解码时比较，编码时写入固定内容)
+fn _fields_with_code(cmd: &CmdSchema, cmd_length: usize) -> Vec<FieldSchema> {
+    let mut fields = Vec::new();
+    if cmd_length > 0 {
+        fields.push(FieldSchema {
+            code: "cmd_code".into(),
+            ident: Some("CmdCode".into()),
+            title: "命令码".into(),
+            kind: FieldKind::Bcd,
+            length: cmd_length,
+            scale: 1.0,
+            swap: false,
+            unit: None,
+            precision: None,
+            enum_map: Default::default(),
+            compare: Some(cmd.code.clone()),
+            default: None,
+            required: true,
+            rules: Vec::new(),
+            condition: None,
+            cmd_code: cmd.code.clone(),
+        });
+    }
+    fields.extend(cmd.fields.iter().cloned());
+    fields
+}
+
+fn _field_enum(name: &str, cmd: &CmdSchema, fields: &[FieldSchema]) -> String {
+    use crate::{AutoDecodingParam, AutoEncodingParam};
+
+    let mut idents = HashSet::new();
+    let variants: Vec<(String, &FieldSchema)> = fields
+        .iter()
+        .map(|field| {
+            let ident = _unique_ident(&mut idents, field.ident.as_deref(), &field.title, "Field");
+            (format!("{}::{}", name, ident), field)
+        })
+        .collect();
+    let arms = |f: &dyn Fn(&FieldSchema) -> String| {
+        variants
+            .iter()
+            .map(|(path, field)| (path.clone(), f(field)))
+            .collect::<Vec<_>>()
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(out, "/// {} ({}) 的字段", cmd.title, cmd.code);
+    let _ = writeln!(
+        out,
+        "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\npub enum {} {{",
+        name
+    );
+    for (path, field) in &variants {
+        let ident = path.rsplit("::").next().unwrap_or_default();
+        let _ = writeln!(out, "    /// {}\n    {},", field.title, ident);
+    }
+    out.push_str("}\n\n");
+    let _ = writeln!(
+        out,
+        "impl {} {{\n    pub const ALL: [{}; {}] = [{}];\n}}\n",
+        name,
+        name,
+        variants.len(),
+        variants
+            .iter()
+            .map(|(path, _)| path.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    if cmd.direction.is_upstream() {
+        let _ = writeln!(
+            out,
+            "impl ::protocol_core::AutoDecodingParam<String> for {} {{",
+            name
+        );
+        out.push_str(&_method(
+            "fn byte_length(&self) -> usize",
+            &arms(&|field| field.length.to_string()),
+        ));
+        out.push_str(&_method(
+            "fn title(&self) -> String",
+            &arms(&|field| format!("{:?}.to_string()", field.title)),
+        ));
+        out.push_str(&_method(
+            "fn swap(&self) -> bool",
+            &arms(&|field| field.swap.to_string()),
+        ));
+        out.push_str(&_method(
+            "fn cmd_code(&self) -> String",
+            &arms(&|field| format!("{:?}.to_string()", field.cmd_code)),
+        ));
+        out.push_str(&_method(
+            "fn symbol(&self) -> Option<::protocol_core::Symbol>",
+            &arms(&|field| match &field.unit {
+                Some(unit) => format!("Some(::protocol_core::Symbol::from_tag({:?}))", unit.tag()),
+                None => "None".into(),
+            }),
+        ));
+        out.push_str(&_method(
+            "fn precision(&self) -> u32",
+            &arms(&|field| AutoDecodingParam::precision(field).to_string()),
+        ));
+        out.push_str(&_method(
+            "fn field_type(&self) -> ::protocol_core::FieldType",
+            &arms(&|field| _field_type(&AutoDecodingParam::field_type(field))),
+        ));
+        out.push_str(&_method(
+            "fn compare_target(&self) -> Vec<u8>",
+            &arms(&|field| _bytes(&field.compare_target())),
+        ));
+        out.push_str(&_method(
+            "fn enum_values(&self) -> Vec<(String, String)>",
+            &arms(&|field| {
+                let values: Vec<String> = field
+                    .enum_map
+                    .iter()
+                    .map(|(key, label)| format!("({:?}.to_string(), {:?}.to_string())", key, label))
+                    .collect();
+                format!("vec![{}]", values.join(", "))
+            }),
+        ));
+        out.push_str(&_method(
+            "fn condition(&self) -> Option<::protocol_core::FieldCondition>",
+            &arms(&_condition),
+        ));
+        out.push_str("}\n\n");
+        let _ = writeln!(
+            out,
+            "impl ::protocol_core::AutoDecoding<{0}, String> for {0} {{\n    fn variants(&self) -> Vec<{0}> {{\n        {0}::ALL.to_vec()\n    }}\n}}\n",
+            name
+        );
+    }
+
+    if cmd.direction.is_downstream() {
+        let _ = writeln!(
+            out,
+            "impl ::protocol_core::AutoEncodingParam for {} {{",
+            name
+        );
+        out.push_str(&_method(
+            "fn code(&self) -> String",
+            &arms(&|field| format!("{:?}.to_string()", field.code)),
+        ));
+        out.push_str(&_method(
+            "fn title(&self) -> String",
+            &arms(&|field| format!("{:?}.to_string()", field.title)),
+        ));
+        out.push_str(&_method(
+            "fn byte_length(&self) -> usize",
+            &arms(&|field| field.length.to_string()),
+        ));
+        out.push_str(&_method(
+            "fn cmd_code(&self) -> String",
+            &arms(&|field| format!("{:?}.to_string()", field.cmd_code)),
+        ));
+        out.push_str(&_method(
+            "fn field_type(&self) -> ::protocol_core::FieldType",
+            &arms(&|field| _field_type(&AutoEncodingParam::field_type(field))),
+        ));
+        out.push_str(&_method(
+            "fn default_value(&self) -> String",
+            &arms(&|field| format!("{:?}.to_string()", field.default_value())),
+        ));
+        out.push_str(&_method(
+            "fn default_hex(&self) -> String",
+            &arms(&|field| format!("{:?}.to_string()", field.default_hex())),
+        ));
+        out.push_str(&_method(
+            "fn swap(&self) -> bool",
+            &arms(&|field| field.swap.to_string()),
+        ));
+        out.push_str(&_method(
+            "fn required(&self) -> bool",
+            &arms(&|field| field.required.to_string()),
+        ));
+        out.push_str(&_method(
+            "fn condition(&self) -> Option<::protocol_core::FieldCondition>",
+            &arms(&_condition),
+        ));
+        out.push_str(&_method(
+            "fn rules(&self) -> Vec<::protocol_core::ValidationRule>",
+            &arms(&|field| {
+                let rules: Vec<String> = field.rules.iter().map(_rule).collect();
+                format!("vec![{}]", rules.join(", "))
+            }),
+        ));
+        out.push_str("}\n\n");
+        let _ = writeln!(
+            out,
+            "impl ::protocol_core::AutoEncoding<{0}> for {0} {{\n    fn variants(&self) -> Vec<{0}> {{\n        {0}::ALL.to_vec()\n    }}\n}}\n",
+            name
+        );
+    }
+    out
+}
+
+fn _decode_fn(schema: &ProtocolSchema, cmds: &[(String, &CmdSchema)]) -> String {
+    let upstream: Vec<&(String, &CmdSchema)> = cmds
+        .iter()
+        .filter(|(_, cmd)| cmd.direction.is_upstream())
+        .collect();
+    if upstream.is_empty() {
+        return String::new();
+    }
+    let envelope = &schema.envelope;
+    let decode = |ident: &str| {
+        format!(
+            "::protocol_core::decode_upstream::<Cmd, {0}Field, {0}Field, String>(&Envelope, &{0}Field::ALL[0], bytes)?",
+            ident
+        )
+    };
+
+    let mut out = String::new();
+    out.push_str("/// 上行解码：按帧中的命令码选择字段定义\n");
+    out.push_str("pub fn decode(\n    bytes: &[u8],\n) -> ::protocol_core::ProtocolResult<::protocol_core::RawCapsule<Cmd>> {\n");
+    if envelope.cmd_length == 0 {
+        let (ident, _) = upstream[0];
+        let _ = writeln!(out, "    let mut capsule = {};", decode(ident));
+        let _ = writeln!(out, "    capsule.set_cmd(Cmd::{});", ident);
+    } else {
+        let start = envelope.cmd_offset();
+        let end = start + envelope.cmd_length;
+        out.push_str(
+            "    ::protocol_core::ProtocolConfig::validate_envelope(&Envelope, bytes)?;\n",
+        );
+        let _ = writeln!(
+            out,
+            "    let raw = bytes.get({}..{}).ok_or(::protocol_core::ProtocolError::InputTooShort {{\n        needed: {},\n        available: bytes.len(),\n    }})?;",
+            start, end, end
+        );
+        out.push_str("    let code = ::protocol_core::hex_util::bytes_to_hex(raw)?;\n");
+        out.push_str("    let (cmd, mut capsule) = match code.as_str() {\n");
+        for (ident, cmd) in &upstream {
+            let _ = writeln!(
+                out,
+                "        {:?} => (Cmd::{}, {}),",
+                cmd.code,
+                ident,
+                decode(ident)
+            );
+        }
+        out.push_str("        _ => {\n            return Err(::protocol_core::HexDigestError::UnknownCmdCode {\n                code,\n                nearest: None,\n            }\n            .into());\n        }\n    };\n");
+        out.push_str("    capsule.set_cmd(cmd);\n");
+    }
+    out.push_str("    Ok(capsule)\n}\n\n");
+    out
+}
+
+fn _encode_fn(cmds: &[(String, &CmdSchema)], cmd_length: usize) -> String {
+    let downstream: Vec<&(String, &CmdSchema)> = cmds
+        .iter()
+        .filter(|(_, cmd)| cmd.direction.is_downstream())
+        .collect();
+    if downstream.is_empty() {
+        return String::new();
+    }
+
+    let mut out = String::new();
+    out.push_str("/// 下行编码：参数名为字段的 code，取值表中的文字会换成对应的值，固定内容与默认值可以省略\n");
+    out.push_str("pub fn encode(\n    params: &::std::collections::HashMap<String, String>,\n    capsule: &mut ::protocol_core::RawCapsule<Cmd>,\n) -> ::protocol_core::ProtocolResult<usize> {\n");
+    out.push_str("    let cmd = capsule.cmd().copied().ok_or_else(|| {\n        ::protocol_core::ProtocolError::ValidationFailed(\"capsule has no cmd to encode\".into())\n    })?;\n");
+    out.push_str("    let mut params = params.clone();\n    match cmd {\n");
+    for (ident, cmd) in &downstream {
+        let fields = _fields_with_code(cmd, cmd_length);
+        let constants: Vec<String> = fields
+            .iter()
+            .filter(|field| field.compare.is_some() || field.default.is_some())
+            .map(|field| format!("{:?}", field.code))
+            .collect();
+        let labels: Vec<String> = fields
+            .iter()
+            .flat_map(|field| {
+                field.enum_map.values().map(|label| {
+                    format!(
+                        "({:?}, {:?}, {:?})",
+                        field.code,
+                        label,
+                        field.resolve_label(label.clone())
+                    )
+                })
+            })
+            .collect();
+        let _ = writeln!(
+            out,
+            "        Cmd::{0} => {{\n            _prepare_params(&mut params, &[{1}], &[{2}]);\n            ::protocol_core::encode_downstream(&Envelope, &{0}Field::ALL[0], &params, capsule)\n        }}",
+            ident,
+            constants.join(", "),
+            labels.join(", ")
+        );
+    }
+    if downstream.len() < cmds.len() {
+        out.push_str("        _ => Err(::protocol_core::ProtocolError::ValidationFailed(format!(\n            \"cmd {:?} is not a downstream cmd\",\n            cmd\n        ))),\n");
+    }
+    out.push_str("    }\n}\n\n");
+    out.push_str("// 固定内容与默认值放入空输入，取值表中的文字换成值\n");
+    out.push_str("fn _prepare_params(\n    params: &mut ::std::collections::HashMap<String, String>,\n    constants: &[&str],\n    labels: &[(&str, &str, &str)],\n) {\n    for code in constants {\n        params.entry(code.to_string()).or_default();\n    }\n    for (code, label, value) in labels {\n        if params.get(*code).map(String::as_str) == Some(*label) {\n            params.insert(code.to_string(), value.to_string());\n        }\n    }\n}\n");
+    out
+}
+
+// 按变体 match 的方法体；所有变体取值相同时直接返回
+fn _method(signature: &str, arms: &[(String, String)]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "    {} {{", signature);
+    let first = &arms[0].1;
+    if arms.iter().all(|(_, value)| value == first) {
+        let _ = writeln!(out, "        {}", first);
+    } else {
+        out.push_str("        match self {\n");
+        for (path, value) in arms {
+            let _ = writeln!(out, "            {} => {},", path, value);
+        }
+        out.push_str("        }\n");
+    }
+    out.push_str("    }\n\n");
+    out
+}
+
+fn _field_type(field_type: &FieldType) -> String {
+    let scaled =
+        |name: &str, scale: &f64| format!("::protocol_core::FieldType::{}({:?})", name, scale);
+    match field_type {
+        FieldType::UnsignedU8(scale) => scaled("UnsignedU8", scale),
+        FieldType::UnsignedU16(scale) => scaled("UnsignedU16", scale),
+        FieldType::UnsignedU32(scale) => scaled("UnsignedU32", scale),
+        FieldType::UnsignedU64(scale) => scaled("UnsignedU64", scale),
+        FieldType::SignedI8(scale) => scaled("SignedI8", scale),
+        FieldType::SignedI16(scale) => scaled("SignedI16", scale),
+        FieldType::SignedI32(scale) => scaled("SignedI32", scale),
+        FieldType::SignedI64(scale) => scaled("SignedI64", scale),
+        other => format!("::protocol_core::FieldType::{:?}", other),
+    }
+}
+
+fn _bytes(bytes: &[u8]) -> String {
+    let items: Vec<String> = bytes.iter().map(|b| format!("0x{:02X}", b)).collect();
+    format!("vec![{}]", items.join(", "))
+}
+
+fn _condition(field: &FieldSchema) -> String {
+    match &field.condition {
+        Some(condition) => format!(
+            "Some(::protocol_core::FieldCondition::new({:?}, &{:?}))",
+            condition.field, condition.values
+        ),
+        None => "None".into(),
+    }
+}
+
+fn _rule(rule: &ValidationRule) -> String {
+    match rule {
+        ValidationRule::Min(min) => format!("::protocol_core::ValidationRule::Min({:?})", min),
+        ValidationRule::Max(max) => format!("::protocol_core::ValidationRule::Max({:?})", max),
+        ValidationRule::Pattern(pattern) => format!(
+            "::protocol_core::ValidationRule::Pattern({:?}.to_string())",
+            pattern
+        ),
+        ValidationRule::OneOf(values) => format!(
+            "::protocol_core::ValidationRule::OneOf(vec![{}])",
+            values
+                .iter()
+                .map(|value| format!("{:?}.to_string()", value))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+// UpperCamelCase 标识符：优先使用 ident，否则取 title 的拼音；重复时追加序号
+fn _unique_ident(
+    used: &mut HashSet<String>,
+    ident: Option<&str>,
+    title: &str,
+    fallback: &str,
+) -> String {
+    let source = ident.map_or_else(|| to_pinyin(title), str::to_string);
+    let mut base: String = source
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+    if base.is_empty() {
+        base = fallback.to_string();
+    } else if base.starts_with(|c: char| c.is_ascii_digit()) {
+        base = format!("{}{}", fallback, base);
+    }
+    let mut candidate = base.clone();
+    let mut index = 2;
+    while !used.insert(candidate.clone()) {
+        candidate = format!("{}{}", base, index);
+        index += 1;
+    }
+    candidate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"{
+        "name": "vendor-a",
+        "envelope": { "head": "68", "tail": "16", "lengthIndex": [1, 2] },
+        "commands": [
+            { "code": "01", "title": "数据上报", "direction": "upstream",
+              "fields": [{ "title": "电压", "type": "u16", "scale": 0.1, "unit": "V" }] },
+            { "code": "A1", "title": "阀门控制", "ident": "valve_control", "direction": "downstream",
+              "fields": [{ "code": "valve", "title": "阀门", "type": "u8", "enum": { "1": "开" } }] }
+        ]
+    }"#;
+
+    #[test]
+    fn test_generate() {
+        let schema = ProtocolSchema::from_json(SCHEMA).unwrap();
+        let code = generate(&schema).unwrap();
+        assert!(code.contains("pub enum Cmd {"));
+        assert!(code.contains("    ShuJuShangBao,"));
+        assert!(code.contains("pub enum ValveControlField {"));
+        assert!(code.contains("::protocol_core::FieldType::UnsignedU16(0.1)"));
+        assert!(code.contains(r#""01" => (Cmd::ShuJuShangBao, "#));
+        assert!(code.contains(r#"("valve", "开", "1")"#));
+        assert_eq!(
+            _unique_ident(&mut HashSet::new(), None, "3相电压", "Field"),
+            "Field3XiangDianYa"
+        );
+    }
+
+    // SCHEMA 生成的代码，随本测试一起编译，确保生成的代码能通过类型检查
+    #[allow(dead_code)]
+    mod vendor_a {
+        include!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/codegen_vendor_a.rs"
+        ));
+    }
+
+    #[test]
+    fn test_generated_code_compiles() {
+        let code = generate(&ProtocolSchema::from_json(SCHEMA).unwrap()).unwrap();
+        assert_eq!(
+            code,
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/fixtures/codegen_vendor_a.rs"
+            )),
+            "生成的代码有变化，请重新生成 tests/fixtures/codegen_vendor_a.rs"
+        );
+
+        let capsule = vendor_a::decode(&[0x68, 0x06, 0x01, 0x08, 0xFC, 0x16]).unwrap();
+        assert_eq!(capsule.cmd(), Some(&vendor_a::Cmd::ShuJuShangBao));
+        assert_eq!(capsule.get_field("dian_ya").unwrap().value, "230 V");
+
+        let mut capsule =
+            crate::RawCapsule::new_downstream(vendor_a::Cmd::ValveControl, "0001", "");
+        let params = std::collections::HashMap::from([("valve".to_string(), "开".to_string())]);
+        assert_eq!(vendor_a::encode(&params, &mut capsule).unwrap(), 5);
+        assert_eq!(capsule.bytes(), [0x68, 0x05, 0xA1, 0x01, 0x16]);
+    }
+}
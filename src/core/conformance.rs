@@ -0,0 +1,426 @@
+//! 协议一致性测试：按厂家文档中的示例报文编写测试向量 (JSON / TOML / YAML)，
+//! 对协议实现逐条运行，输出字段级的差异，用于按文档认证每个厂家协议。
+//!
+//! 每条向量是下面两种之一：
+//! - 上行：`input` 为报文 hex，`expect` 为期望的上报字段 (按字段名称或 code)，只检查列出的字段；
+//! - 下行：`cmd` + `params` 编码，`expectHex` 为期望的整帧 hex，不一致时按字段的偏移定位到具体字段。
+//!
+//! 两种向量都可以用 `expectError` 声明期望的错误码 (`ProtocolError::code`)，例如 "CRC_ERROR"。
+//!
+//! ```json
+//! {
+//!     "name": "vendor-a 协议文档 v2.1",
+//!     "vectors": [
+//!         { "name": "5.2 数据上报示例", "input": "68 0B 01 08 FC 01 34 12 08 EA 16",
+//!           "expect": { "电压": "230 V", "阀门状态": "开" } },
+//!         { "name": "6.1 开阀", "cmd": "A1", "params": { "valve": "开" },
+//!           "expectHex": "680AA15A01003C..." }
+//!     ]
+//! }
+//! ```
+//!
+//! `ProtocolSchema` 已实现 `ConformanceTarget`，手写的协议 crate 实现 `decode` (以及可选的 `encode`) 即可。
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{EnvelopeSchema, ProtocolError, ProtocolResult, ProtocolSchema, ReportField, hex_util};
+
+/// 被测的协议实现
+pub trait ConformanceTarget {
+    /// 上行解码，返回上报字段
+    fn decode(&self, bytes: &[u8]) -> ProtocolResult<Vec<ReportField>>;
+
+    /// 下行编码，返回整帧字节与字段。字段带有 offset / length 时，差异可以定位到字段
+    fn encode(
+        &self,
+        cmd: &str,
+        _params: &HashMap<String, String>,
+    ) -> ProtocolResult<(Vec<u8>, Vec<ReportField>)> {
+        Err(ProtocolError::UnsupportedMode(format!(
+            "conformance target does not support encoding cmd '{}'",
+            cmd
+        )))
+    }
+}
+
+impl ConformanceTarget for ProtocolSchema {
+    fn decode(&self, bytes: &[u8]) -> ProtocolResult<Vec<ReportField>> {
+        Ok(ProtocolSchema::decode(self, bytes)?.field_details_clone())
+    }
+
+    fn encode(
+        &self,
+        cmd: &str,
+        params: &HashMap<String, String>,
+    ) -> ProtocolResult<(Vec<u8>, Vec<ReportField>)> {
+        let mut capsule = self.new_downstream(cmd, "")?;
+        // 差异需要按字段的 offset / length 定位
+        let envelope = EnvelopeSchema {
+            field_sources: true,
+            ..self.envelope.clone()
+        };
+        self.encode_with(&envelope, params, &mut capsule)?;
+        Ok((capsule.bytes().to_vec(), capsule.field_details_clone()))
+    }
+}
+
+/// 一条测试向量
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestVector {
+    pub name: String,
+    // 上行报文 (hex，可以带空格)
+    #[serde(default)]
+    pub input: Option<String>,
+    // 期望的上报字段：字段名称或 code -> 值
+    #[serde(default)]
+    pub expect: BTreeMap<String, String>,
+    // 下行命令码与参数
+    #[serde(default)]
+    pub cmd: Option<String>,
+    #[serde(default)]
+    pub params: HashMap<String, String>,
+    // 期望的下行整帧 (hex，可以带空格)
+    #[serde(default)]
+    pub expect_hex: Option<String>,
+    // 期望的错误码
+    #[serde(default)]
+    pub expect_error: Option<String>,
+}
+
+/// 一个字段的差异，None 表示缺失 / 没有
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldDiff {
+    pub field: String,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+}
+
+impl FieldDiff {
+    fn new(field: &str, expected: Option<String>, actual: Option<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            expected,
+            actual,
+        }
+    }
+}
+
+impl fmt::Display for FieldDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |value: &Option<String>| match value {
+            Some(value) => format!("'{}'", value),
+            None => "(none)".to_string(),
+        };
+        write!(
+            f,
+            "{}: expected {}, got {}",
+            self.field,
+            show(&self.expected),
+            show(&self.actual)
+        )
+    }
+}
+
+/// 单条向量的结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VectorResult {
+    pub name: String,
+    pub diffs: Vec<FieldDiff>,
+}
+
+impl VectorResult {
+    pub fn passed(&self) -> bool {
+        self.diffs.is_empty()
+    }
+}
+
+impl TestVector {
+    /// 对 `target` 运行本向量
+    pub fn run(&self, target: &impl ConformanceTarget) -> VectorResult {
+        let diffs = match (&self.input, &self.cmd) {
+            (Some(input), _) => self._run_upstream(target, input),
+            (None, Some(cmd)) => self._run_downstream(target, cmd),
+            (None, None) => vec![FieldDiff::new("vector", Some("input or cmd".into()), None)],
+        };
+        VectorResult {
+            name: self.name.clone(),
+            diffs,
+        }
+    }
+
+    fn _run_upstream(&self, target: &impl ConformanceTarget, input: &str) -> Vec<FieldDiff> {
+        let fields = match _hex_bytes(input).and_then(|bytes| target.decode(&bytes)) {
+            Ok(fields) => fields,
+            Err(e) => return self._error_diff(Some(&e)),
+        };
+        if self.expect_error.is_some() {
+            return self._error_diff(None);
+        }
+        let mut flat = Vec::new();
+        _flatten(&fields, &mut flat);
+        self.expect
+            .iter()
+            .filter_map(|(key, expected)| {
+                let actual = flat
+                    .iter()
+                    .find(|field| field.name == *key || field.code == *key)
+                    .map(|field| field.value.clone());
+                (actual.as_ref() != Some(expected))
+                    .then(|| FieldDiff::new(key, Some(expected.clone()), actual))
+            })
+            .collect()
+    }
+
+    fn _run_downstream(&self, target: &impl ConformanceTarget, cmd: &str) -> Vec<FieldDiff> {
+        let (bytes, fields) = match target.encode(cmd, &self.params) {
+            Ok(encoded) => encoded,
+            Err(e) => return self._error_diff(Some(&e)),
+        };
+        if self.expect_error.is_some() {
+            return self._error_diff(None);
+        }
+        let Some(expect_hex) = &self.expect_hex else {
+            return Vec::new();
+        };
+        let expected = match _hex_bytes(expect_hex) {
+            Ok(expected) => expected,
+            Err(e) => return vec![FieldDiff::new("expectHex", None, Some(e.to_string()))],
+        };
+        if expected == bytes {
+            return Vec::new();
+        }
+
+        // 按字段的偏移比较对应的字节段
+        let segment = |frame: &[u8], offset: usize, len: usize| {
+            frame.get(offset..offset + len).map(hex::encode_upper)
+        };
+        let mut diffs: Vec<FieldDiff> = fields
+            .iter()
+            .filter_map(|field| {
+                let (offset, len) = (field.offset? as usize, field.length? as usize);
+                let actual = segment(&bytes, offset, len);
+                let wanted = segment(&expected, offset, len);
+                (actual != wanted).then(|| FieldDiff::new(&field.name, wanted, actual))
+            })
+            .collect();
+        // 字段都一致 (例如长度不同、没有偏移信息) 时报告整帧
+        if diffs.is_empty() || expected.len() != bytes.len() {
+            diffs.push(FieldDiff::new(
+                "frame",
+                Some(hex::encode_upper(&expected)),
+                Some(hex::encode_upper(&bytes)),
+            ));
+        }
+        diffs
+    }
+
+    // 期望错误与实际结果的比较，`error` 为 None 表示实际成功
+    fn _error_diff(&self, error: Option<&ProtocolError>) -> Vec<FieldDiff> {
+        let actual = error.map(|e| e.code().to_string());
+        if actual.is_some() && actual == self.expect_error {
+            return Vec::new();
+        }
+        let actual = error
+            .map(|e| format!("{} ({})", e.code(), e))
+            .or(Some("ok".into()));
+        vec![FieldDiff::new("error", self.expect_error.clone(), actual)]
+    }
+}
+
+/// 一组测试向量，通常对应一份厂家协议文档
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConformanceSuite {
+    #[serde(default)]
+    pub name: String,
+    pub vectors: Vec<TestVector>,
+}
+
+impl ConformanceSuite {
+    pub fn from_json(text: &str) -> ProtocolResult<Self> {
+        serde_json::from_str(text).map_err(ProtocolError::external)
+    }
+
+    #[cfg(feature = "toml")]
+    pub fn from_toml(text: &str) -> ProtocolResult<Self> {
+        toml::from_str(text).map_err(ProtocolError::external)
+    }
+
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml(text: &str) -> ProtocolResult<Self> {
+        serde_yaml::from_str(text).map_err(ProtocolError::external)
+    }
+
+    /// 按扩展名 (json / toml / yaml / yml) 选择格式加载
+    pub fn from_file(path: impl AsRef<Path>) -> ProtocolResult<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(ProtocolError::external)?;
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match extension.as_str() {
+            "json" => Self::from_json(&text),
+            #[cfg(feature = "toml")]
+            "toml" => Self::from_toml(&text),
+            #[cfg(feature = "yaml")]
+            "yaml" | "yml" => Self::from_yaml(&text),
+            _ => Err(ProtocolError::UnsupportedMode(format!(
+                "conformance file '{}' (supported: json, toml with the `toml` feature, yaml with the `yaml` feature)",
+                path.display()
+            ))),
+        }
+    }
+
+    /// 依次运行所有向量
+    pub fn run(&self, target: &impl ConformanceTarget) -> ConformanceReport {
+        ConformanceReport {
+            name: self.name.clone(),
+            results: self
+                .vectors
+                .iter()
+                .map(|vector| vector.run(target))
+                .collect(),
+        }
+    }
+}
+
+/// 一致性测试报告。`Display` 输出每条失败向量的字段差异
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConformanceReport {
+    pub name: String,
+    pub results: Vec<VectorResult>,
+}
+
+impl ConformanceReport {
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|result| result.passed()).count()
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &VectorResult> {
+        self.results.iter().filter(|result| !result.passed())
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.results.iter().all(VectorResult::passed)
+    }
+}
+
+impl fmt::Display for ConformanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}/{} passed",
+            self.name,
+            self.passed(),
+            self.results.len()
+        )?;
+        for result in self.failures() {
+            write!(f, "\n  FAIL {}", result.name)?;
+            for diff in &result.diffs {
+                write!(f, "\n    {}", diff)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// 文档中的报文通常按字节用空格分隔
+fn _hex_bytes(hex: &str) -> ProtocolResult<Vec<u8>> {
+    hex_util::hex_to_bytes(&hex.split_whitespace().collect::<String>())
+}
+
+// 子字段 (重复记录、分组) 与顶层字段一起参与匹配
+fn _flatten<'a>(fields: &'a [ReportField], flat: &mut Vec<&'a ReportField>) {
+    for field in fields {
+        flat.push(field);
+        _flatten(&field.children, flat);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"{
+        "name": "vendor-a",
+        "envelope": {
+            "head": "68",
+            "tail": "16",
+            "crc": "crc16-modbus",
+            "crcIndex": [0, 3],
+            "lengthIndex": [1, 2]
+        },
+        "commands": [
+            {
+                "code": "01",
+                "title": "数据上报",
+                "direction": "upstream",
+                "fields": [
+                    { "title": "电压", "type": "u16", "scale": 0.1, "unit": "V" },
+                    { "title": "阀门状态", "type": "u8", "enum": { "0": "关", "1": "开" } },
+                    { "title": "表号", "type": "bcd", "length": 2, "swap": true }
+                ]
+            },
+            {
+                "code": "A1",
+                "title": "阀门控制",
+                "direction": "downstream",
+                "fields": [
+                    { "title": "类型", "type": "u8", "compare": "5A" },
+                    { "code": "valve", "title": "阀门", "type": "u8", "enum": { "00": "关", "01": "开" } },
+                    { "code": "interval", "title": "上报间隔", "type": "u16", "default": "60" }
+                ]
+            }
+        ]
+    }"#;
+
+    const VECTORS: &str = r#"{
+        "name": "vendor-a v2.1",
+        "vectors": [
+            { "name": "5.2 数据上报", "input": "68 0B 01 08 FC 01 34 12 08 EA 16",
+              "expect": { "电压": "230 V", "阀门状态": "开", "表号": "1234" } },
+            { "name": "5.3 CRC 错误", "input": "68 0B 01 08 FC 01 34 12 00 00 16",
+              "expectError": "CRC_ERROR" },
+            { "name": "6.1 开阀", "cmd": "A1", "params": { "valve": "开" },
+              "expectHex": "680AA15A01003CBC0C16" },
+            { "name": "6.2 关阀 (文档有误)", "cmd": "A1", "params": { "valve": "关" },
+              "expectHex": "680AA15A01003CBC0C16" },
+            { "name": "5.4 电压", "input": "68 0B 01 08 FC 01 34 12 08 EA 16",
+              "expect": { "电压": "23 V", "信号": "-75" } }
+        ]
+    }"#;
+
+    #[test]
+    fn test_conformance_report() {
+        let schema = ProtocolSchema::from_json(SCHEMA).unwrap();
+        let report = ConformanceSuite::from_json(VECTORS).unwrap().run(&schema);
+        assert_eq!(report.passed(), 3, "{}", report);
+
+        let failures: Vec<&VectorResult> = report.failures().collect();
+        // 下行差异定位到阀门字段 (以及随之变化的 CRC)
+        assert_eq!(
+            failures[0].diffs[0],
+            FieldDiff::new("阀门", Some("01".into()), Some("00".into()))
+        );
+        assert_eq!(
+            failures[1].diffs,
+            vec![
+                FieldDiff::new("信号", Some("-75".into()), None),
+                FieldDiff::new("电压", Some("23 V".into()), Some("230 V".into())),
+            ]
+        );
+        assert!(report.to_string().contains("FAIL 6.2 关阀"));
+    }
+}
@@ -0,0 +1,99 @@
+//! 重复帧检测。NB-IoT 等不稳定的链路上，设备收不到应答会重发同一帧，
+//! 计费相关的上报 (抄表、结算) 如果重复入账会造成多扣费。
+//!
+//! `DedupWindow` 是一个按时间滑动的窗口，key 为 设备唯一标识 + 帧哈希 (或序号字段的值)：
+//! 窗口内第二次出现的 key 视为重传帧，通过 `RawCapsule::is_duplicate` 标记，是否丢弃由调用方决定。
+//! 窗口时长应覆盖设备的最大重传间隔；序号会回绕的设备，窗口应明显小于回绕周期。
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    time::Duration,
+};
+
+use moka::sync::Cache;
+
+use crate::{Cmd, RawCapsule};
+
+/// 默认最多记录的 key 数
+pub const DEFAULT_DEDUP_CAPACITY: u64 = 100_000;
+
+/// 去重使用的 key
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum DedupKey {
+    // 整帧字节的哈希，适合重传时报文完全相同的设备
+    #[default]
+    FrameHash,
+    // 按字段 code 取上行序号，字段缺失时退回帧哈希。适合报文中带时间戳等每次变化的内容
+    Field(String),
+}
+
+/// 按时间滑动的去重窗口。可廉价克隆，克隆后共享同一份记录
+#[derive(Clone)]
+pub struct DedupWindow {
+    pub(crate) key: DedupKey,
+    seen: Cache<String, ()>,
+}
+
+impl DedupWindow {
+    /// 窗口时长为 `window`，使用帧哈希
+    pub fn new(window: Duration) -> Self {
+        Self::with_capacity(window, DEFAULT_DEDUP_CAPACITY)
+    }
+
+    pub fn with_capacity(window: Duration, max_capacity: u64) -> Self {
+        Self {
+            key: DedupKey::default(),
+            seen: Cache::builder()
+                .max_capacity(max_capacity)
+                .time_to_live(window)
+                .build(),
+        }
+    }
+
+    pub fn key(mut self, key: DedupKey) -> Self {
+        self.key = key;
+        self
+    }
+
+    /// 记录 `token`，窗口内已经出现过时返回 true
+    pub fn check(&self, unique: &str, token: &str) -> bool {
+        // entry 的插入是原子的，并发收到同一帧时只有一个被视为首次
+        !self
+            .seen
+            .entry(format!("{}::{}", unique, token))
+            .or_insert(())
+            .is_fresh()
+    }
+
+    /// 按整帧字节去重
+    pub fn check_frame(&self, unique: &str, bytes: &[u8]) -> bool {
+        self.check(unique, &_frame_hash(bytes))
+    }
+
+    /// 按窗口的 key 规则检查解码后的 capsule，重传帧标记为 duplicate 并返回 true
+    pub fn mark<T: Cmd + 'static>(&self, unique: &str, capsule: &mut RawCapsule<T>) -> bool {
+        let sequence = match &self.key {
+            DedupKey::Field(code) => capsule.get_field(code).map(|field| field.value.clone()),
+            DedupKey::FrameHash => None,
+        };
+        let duplicate = match sequence {
+            Some(sequence) => self.check(unique, &sequence),
+            None => self.check_frame(unique, capsule.bytes()),
+        };
+        capsule.set_duplicate(duplicate);
+        duplicate
+    }
+
+    /// 清空记录
+    pub fn clear(&self) {
+        self.seen.invalidate_all();
+    }
+}
+
+fn _frame_hash(bytes: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    // 与序号字段的值区分开
+    format!("#{:016X}", hasher.finish())
+}
@@ -0,0 +1,270 @@
+//! 字节流分帧：在 TCP、串口等流式连接收到的字节中，按 `ProtocolConfig` 找出完整的一帧。
+//!
+//! - 有长度字段：找到帧头后按长度字段取整帧，再校验帧尾；
+//! - 没有长度字段：找到帧头后查找帧尾，有 CRC 时跳过 CRC 不对的候选 (帧尾字节也可能出现在数据域中)；
+//! - 帧头之前的字节、长度不合理或帧尾不对的候选都视为噪声丢弃，从下一个帧头重新同步。
+//!   `max_frame_len` 为 0 (不限制) 时按 `DEFAULT_MAX_FRAME_LEN` 判断长度是否合理，
+//!   否则噪声中解出的超大长度会让分帧一直等待数据。
+//!
+//! 不检查完整的帧结构，取出的帧仍然交给解码流程 (`decode_upstream` 等) 校验。
+
+use crate::{ProtocolConfig, ProtocolError, ProtocolResult, crc_util, hex_util};
+
+// CRC16 的字节数
+const CRC_LEN: usize = 2;
+
+/// 协议没有设置 `max_frame_len` 时，分帧认为合理的最大帧长
+pub const DEFAULT_MAX_FRAME_LEN: usize = 4096;
+
+/// 分帧结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameScan {
+    // 先丢弃 `skip` 字节的噪声，之后的 `len` 字节是完整的一帧
+    Frame { skip: usize, len: usize },
+    // 还没有完整的帧，可以先丢弃 `skip` 字节的噪声，等待更多数据
+    Partial { skip: usize },
+}
+
+/// 在 `buffer` 中查找下一帧。协议既没有帧头也没有 (长度字段或帧尾) 时无法分帧，返回 `UnsupportedMode`
+pub fn scan_frame(config: &impl ProtocolConfig, buffer: &[u8]) -> ProtocolResult<FrameScan> {
+    let head = hex_util::hex_to_bytes(&config.head_tag())?;
+    let tail = hex_util::hex_to_bytes(&config.tail_tag())?;
+    if head.is_empty() || (!config.has_length() && tail.is_empty()) {
+        return Err(ProtocolError::UnsupportedMode(
+            "stream framing needs a head tag and either a length field or a tail tag".into(),
+        ));
+    }
+
+    let mut start = 0;
+    loop {
+        let Some(found) = _find(&buffer[start..], &head) else {
+            // 末尾可能是半个帧头，保留
+            let keep = (head.len() - 1).min(buffer.len() - start);
+            return Ok(FrameScan::Partial {
+                skip: buffer.len() - keep,
+            });
+        };
+        start += found;
+        let candidate = &buffer[start..];
+        let result = if config.has_length() {
+            _by_length(config, candidate, &tail)
+        } else {
+            _by_tail(config, candidate, head.len(), &tail)
+        };
+        match result {
+            Some(Some(len)) => return Ok(FrameScan::Frame { skip: start, len }),
+            Some(None) => return Ok(FrameScan::Partial { skip: start }),
+            // 不是有效的帧，从下一个字节重新查找帧头
+            None => start += 1,
+        }
+    }
+}
+
+// 按长度字段取帧。None 表示不是有效的帧；Some(None) 表示数据还不够
+fn _by_length(
+    config: &impl ProtocolConfig,
+    candidate: &[u8],
+    tail: &[u8],
+) -> Option<Option<usize>> {
+    let (start, end) = config.length_index();
+    let Some(length_bytes) = candidate.get(start as usize..end as usize) else {
+        return Some(None);
+    };
+    let length = length_bytes
+        .iter()
+        .fold(0u64, |acc, b| (acc << 8) | *b as u64) as usize;
+    if length < end as usize + tail.len() || length > _max_frame_len(config) {
+        return None;
+    }
+    let Some(frame) = candidate.get(..length) else {
+        return Some(None);
+    };
+    if frame.ends_with(tail) {
+        Some(Some(length))
+    } else {
+        None
+    }
+}
+
+// 按帧尾取帧
+fn _by_tail(
+    config: &impl ProtocolConfig,
+    candidate: &[u8],
+    head_len: usize,
+    tail: &[u8],
+) -> Option<Option<usize>> {
+    let max = _max_frame_len(config);
+    let mut from = head_len;
+    while let Some(found) = _find(&candidate[from..], tail) {
+        let len = from + found + tail.len();
+        if len > max {
+            return None;
+        }
+        if !config.has_crc() || _crc_matches(config, &candidate[..len], tail.len()) {
+            return Some(Some(len));
+        }
+        from += found + 1;
+    }
+    if candidate.len() >= max {
+        return None;
+    }
+    Some(None)
+}
+
+fn _max_frame_len(config: &impl ProtocolConfig) -> usize {
+    match config.max_frame_len() {
+        0 => DEFAULT_MAX_FRAME_LEN,
+        max => max,
+    }
+}
+
+// 与解码一致：CRC 紧挨帧尾，只接受 `crc_little_endian` 指定的字节序
+fn _crc_matches(config: &impl ProtocolConfig, frame: &[u8], tail_len: usize) -> bool {
+    let (start, end) = config.crc_index();
+    let (start, end) = (start as usize, frame.len().saturating_sub(end as usize));
+    let Some(crc_end) = frame.len().checked_sub(tail_len) else {
+        return false;
+    };
+    if start > end || crc_end < CRC_LEN {
+        return false;
+    }
+    let actual = &frame[crc_end - CRC_LEN..crc_end];
+    crc_util::calculate_from_bytes(config.crc_mode(), &frame[start..end]).is_ok_and(|crc| {
+        let expected = if config.crc_little_endian() {
+            crc.to_le_bytes()
+        } else {
+            crc.to_be_bytes()
+        };
+        actual == expected
+    })
+}
+
+fn _find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CrcType;
+
+    struct Delimited;
+
+    impl ProtocolConfig for Delimited {
+        fn head_tag(&self) -> String {
+            "68".into()
+        }
+        fn tail_tag(&self) -> String {
+            "16".into()
+        }
+        fn crc_mode(&self) -> CrcType {
+            CrcType::Crc16Modbus
+        }
+        fn crc_index(&self) -> (u8, u8) {
+            (0, 3)
+        }
+        fn length_index(&self) -> (u8, u8) {
+            (0, 0)
+        }
+    }
+
+    struct LengthPrefixed;
+
+    impl ProtocolConfig for LengthPrefixed {
+        fn head_tag(&self) -> String {
+            "68".into()
+        }
+        fn tail_tag(&self) -> String {
+            "16".into()
+        }
+        fn crc_mode(&self) -> CrcType {
+            CrcType::Crc16Modbus
+        }
+        fn crc_index(&self) -> (u8, u8) {
+            (0, 0)
+        }
+        fn length_index(&self) -> (u8, u8) {
+            (1, 2)
+        }
+        fn max_frame_len(&self) -> usize {
+            32
+        }
+    }
+
+    #[test]
+    fn test_scan_by_length() {
+        // 噪声 + 长度为 0xFF 的假帧头 + 完整帧 + 下一帧的开头
+        let stream = [0x00, 0x68, 0xFF, 0x68, 0x05, 0x01, 0x02, 0x16, 0x68, 0x05];
+        assert_eq!(
+            scan_frame(&LengthPrefixed, &stream).unwrap(),
+            FrameScan::Frame { skip: 3, len: 5 }
+        );
+        assert_eq!(
+            scan_frame(&LengthPrefixed, &stream[8..]).unwrap(),
+            FrameScan::Partial { skip: 0 }
+        );
+        assert_eq!(
+            scan_frame(&LengthPrefixed, &[0x01, 0x02]).unwrap(),
+            FrameScan::Partial { skip: 2 }
+        );
+    }
+
+    // 2 字节长度字段，不限制帧长
+    struct WideLength;
+
+    impl ProtocolConfig for WideLength {
+        fn head_tag(&self) -> String {
+            "68".into()
+        }
+        fn tail_tag(&self) -> String {
+            "16".into()
+        }
+        fn crc_mode(&self) -> CrcType {
+            CrcType::Crc16Modbus
+        }
+        fn crc_index(&self) -> (u8, u8) {
+            (0, 0)
+        }
+        fn length_index(&self) -> (u8, u8) {
+            (1, 3)
+        }
+    }
+
+    #[test]
+    fn test_scan_resyncs_on_corrupt_length() {
+        // 噪声中的 68 FF FF 解出 65535 字节的长度，超过默认上限，丢弃后从下一个帧头同步
+        let stream = [0x68, 0xFF, 0xFF, 0x68, 0x00, 0x06, 0x01, 0x02, 0x16];
+        assert_eq!(
+            scan_frame(&WideLength, &stream).unwrap(),
+            FrameScan::Frame { skip: 3, len: 6 }
+        );
+        assert_eq!(
+            scan_frame(&WideLength, &stream[..3]).unwrap(),
+            FrameScan::Partial { skip: 3 }
+        );
+    }
+
+    #[test]
+    fn test_scan_by_tail_with_crc() {
+        // 数据域中含有 0x16，CRC 不对的候选被跳过
+        let mut frame = vec![0x68, 0x16, 0x01];
+        let crc = crc_util::calculate_from_bytes(CrcType::Crc16Modbus, &frame).unwrap();
+        frame.extend(crc.to_be_bytes());
+        frame.push(0x16);
+        let mut stream = vec![0xAA];
+        stream.extend(&frame);
+        assert_eq!(
+            scan_frame(&Delimited, &stream).unwrap(),
+            FrameScan::Frame {
+                skip: 1,
+                len: frame.len()
+            }
+        );
+        assert_eq!(
+            scan_frame(&Delimited, &stream[..4]).unwrap(),
+            FrameScan::Partial { skip: 1 }
+        );
+    }
+}
@@ -2,6 +2,16 @@
 #[macro_export]
 macro_rules! handle_int {
     ($type:ty, $len:expr, $bytes:expr, $scale:expr) => {{
+        handle_int!(
+            $type,
+            $len,
+            $bytes,
+            $scale,
+            math_util::DEFAULT_PRECISION,
+            DecimalRoundingMode::HalfUp
+        )
+    }};
+    ($type:ty, $len:expr, $bytes:expr, $scale:expr, $precision:expr, $rounding:expr) => {{
         // 1. 检查长度
         if $bytes.len() != $len {
             return Err(ProtocolError::ValidationFailed(format!(
@@ -18,8 +28,7 @@ macro_rules! handle_int {
         // 4. 执行缩放 (如果需要)
         if $scale != 1.0 && $scale != 0.0 {
             // 假设 scale=1.0 表示不缩放
-            let scaled_value =
-                math_util::multiply(6, DecimalRoundingMode::HalfUp, &[value_f64, $scale])?;
+            let scaled_value = math_util::scale(value_f64, $scale, $precision, $rounding)?;
             Ok(scaled_value.to_string())
         } else if $scale == 0.0 {
             Err(ProtocolError::ValidationFailed(
@@ -36,29 +45,52 @@ macro_rules! handle_int {
 #[macro_export]
 macro_rules! handle_int_encode {
     ($type:ty, $len:expr, $input:expr, $scale:expr) => {{
-        // 1. 解析输入字符串为f64
-        let parsed_value: f64 = $input.parse().map_err(|_| {
-            ProtocolError::ValidationFailed(format!("Failed to parse input '{}' as f64", $input))
+        handle_int_encode!($type, $len, $input, $scale, DecimalRoundingMode::HalfUp)
+    }};
+    ($type:ty, $len:expr, $input:expr, $scale:expr, $rounding:expr) => {{
+        // 1. 解析输入并反缩放 (input / scale)，按舍入模式取整。全程走 Decimal，避免 0.1 之类的二进制误差
+        let int_value = math_util::unscale_to_integer_with($input, $scale, $rounding)?;
+
+        // 2. 转换为目标整数类型 (超出范围报错，而不是静默饱和)
+        let int_value: $type = <$type>::try_from(int_value).map_err(|_| {
+            ProtocolError::ValidationFailed(format!(
+                "Input '{}' is out of range for {}",
+                $input,
+                stringify!($type)
+            ))
         })?;
 
-        // 2. 执行反缩放（如果需要）
-        let final_value = if $scale != 1.0 && $scale != 0.0 {
-            // 假设 scale=1.0 表示不缩放
-            math_util::divide(parsed_value, $scale, 6, DecimalRoundingMode::HalfUp)?
+        // 3. 转换为大端字节
+        Ok(int_value.to_be_bytes().to_vec())
+    }};
+}
+
+// 内部辅助宏，64 位整数的解码缩放：全程走 Decimal，避免 `as f64` 在 2^53 以上丢失精度
+#[macro_export]
+macro_rules! handle_int_decimal {
+    ($type:ty, $len:expr, $bytes:expr, $scale:expr, $precision:expr, $rounding:expr) => {{
+        // 1. 检查长度
+        if $bytes.len() != $len {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "Invalid byte length for {}. Expected {}, got {}",
+                stringify!($type),
+                $len,
+                $bytes.len()
+            )));
+        }
+        // 2. 从大端字节转换
+        let value = <$type>::from_be_bytes($bytes.try_into().unwrap());
+        // 3. 执行缩放 (如果需要)
+        if $scale != 1.0 && $scale != 0.0 {
+            let scaled_value =
+                math_util::scale_integer(value as i128, $scale, $precision, $rounding)?;
+            Ok(scaled_value.to_string())
         } else if $scale == 0.0 {
-            return Err(ProtocolError::ValidationFailed(
+            Err(ProtocolError::ValidationFailed(
                 "Scale factor cannot be zero.".to_string(),
-            ));
+            ))
         } else {
-            parsed_value
-        };
-
-        // 3. 转换为目标整数类型
-        let int_value: $type = final_value as $type;
-
-        // 4. 转换为大端字节
-        let bytes = int_value.to_be_bytes();
-
-        Ok(bytes.to_vec())
+            Ok(value.to_string())
+        }
     }};
 }
@@ -0,0 +1,215 @@
+//! 协议指标门面：解码流程、加解密在内部统一埋点，看板不需要在每个调用处包装。
+//!
+//! 默认不记录 (`NoopRecorder`)。启动时通过 `set_recorder` 安装一次记录器：
+//! - 开启 `metrics` feature 后可以使用 `MetricsCrateRecorder`，转发给 `metrics` crate
+//!   (再由 prometheus 等 exporter 导出)；
+//! - 也可以自行实现 `MetricsRecorder` 对接其他监控系统。
+//!
+//! 记录的指标 (名称见本模块的常量)：
+//! - 按命令统计的解码帧数、解码失败数 (按错误类别)、CRC 校验失败数；
+//! - 解码耗时 (直方图，秒)，分位数由后端计算；
+//! - 加解密失败数 (按操作、模式)；
+//! - 会话管理中待应答的下行命令数 (gauge)。
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// 解码成功的帧数，标签 `cmd`
+pub const FRAMES_DECODED: &str = "protocol_frames_decoded_total";
+/// 解码失败数，标签 `code` (`ProtocolError::code`)
+pub const DECODE_ERRORS: &str = "protocol_decode_errors_total";
+/// CRC 校验失败数
+pub const CRC_FAILURES: &str = "protocol_crc_failures_total";
+/// 解码耗时 (秒)，标签 `cmd`
+pub const DECODE_LATENCY: &str = "protocol_decode_latency_seconds";
+/// 加解密失败数，标签 `operation` (encrypt / decrypt)、`mode`
+pub const CIPHER_ERRORS: &str = "protocol_cipher_errors_total";
+/// 待应答的下行命令数
+pub const PENDING_COMMANDS: &str = "protocol_pending_commands";
+
+/// 指标标签 (名称, 值)
+pub type Labels<'a> = &'a [(&'static str, &'a str)];
+
+/// 计数器：只增不减
+pub trait Counter: Send + Sync {
+    fn increment_counter(&self, _name: &'static str, _labels: Labels<'_>, _value: u64) {}
+}
+
+/// 仪表：记录当前值
+pub trait Gauge: Send + Sync {
+    fn set_gauge(&self, _name: &'static str, _labels: Labels<'_>, _value: f64) {}
+}
+
+/// 直方图：记录分布，用于计算耗时分位数
+pub trait Histogram: Send + Sync {
+    fn record_histogram(&self, _name: &'static str, _labels: Labels<'_>, _value: f64) {}
+}
+
+/// 指标记录器。各方法默认不做任何事，只需实现关心的部分
+pub trait MetricsRecorder: Counter + Gauge + Histogram {}
+
+impl<R: Counter + Gauge + Histogram> MetricsRecorder for R {}
+
+/// 不记录任何指标 (默认)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopRecorder;
+
+impl Counter for NoopRecorder {}
+impl Gauge for NoopRecorder {}
+impl Histogram for NoopRecorder {}
+
+/// 转发给 `metrics` crate 的记录器
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MetricsCrateRecorder;
+
+#[cfg(feature = "metrics")]
+impl Counter for MetricsCrateRecorder {
+    fn increment_counter(&self, name: &'static str, labels: Labels<'_>, value: u64) {
+        ::metrics::counter!(name, _to_labels(labels)).increment(value);
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl Gauge for MetricsCrateRecorder {
+    fn set_gauge(&self, name: &'static str, labels: Labels<'_>, value: f64) {
+        ::metrics::gauge!(name, _to_labels(labels)).set(value);
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl Histogram for MetricsCrateRecorder {
+    fn record_histogram(&self, name: &'static str, labels: Labels<'_>, value: f64) {
+        ::metrics::histogram!(name, _to_labels(labels)).record(value);
+    }
+}
+
+#[cfg(feature = "metrics")]
+fn _to_labels(labels: Labels<'_>) -> Vec<::metrics::Label> {
+    labels
+        .iter()
+        .map(|(key, value)| ::metrics::Label::new(*key, value.to_string()))
+        .collect()
+}
+
+static RECORDER: OnceLock<Box<dyn MetricsRecorder>> = OnceLock::new();
+
+/// 安装全局记录器，只能安装一次，已安装时返回 false
+pub fn set_recorder(recorder: impl MetricsRecorder + 'static) -> bool {
+    RECORDER.set(Box::new(recorder)).is_ok()
+}
+
+/// 当前的全局记录器，未安装时为 `NoopRecorder`
+pub fn recorder() -> &'static dyn MetricsRecorder {
+    match RECORDER.get() {
+        Some(recorder) => recorder.as_ref(),
+        None => &NoopRecorder,
+    }
+}
+
+// --- 内部埋点 ---
+
+pub(crate) fn frame_decoded(cmd_code: &str, latency: Option<Duration>) {
+    let recorder = recorder();
+    let labels = [("cmd", cmd_code)];
+    recorder.increment_counter(FRAMES_DECODED, &labels, 1);
+    if let Some(latency) = latency {
+        recorder.record_histogram(DECODE_LATENCY, &labels, latency.as_secs_f64());
+    }
+}
+
+pub(crate) fn decode_failed(error: &crate::ProtocolError) {
+    let recorder = recorder();
+    recorder.increment_counter(DECODE_ERRORS, &[("code", error.code())], 1);
+    if error.is_crc_error() {
+        recorder.increment_counter(CRC_FAILURES, &[], 1);
+    }
+}
+
+pub(crate) fn cipher_failed(operation: &str, mode: &str) {
+    recorder().increment_counter(
+        CIPHER_ERRORS,
+        &[("operation", operation), ("mode", mode)],
+        1,
+    );
+}
+
+#[cfg(feature = "cache")]
+pub(crate) fn pending_commands(count: usize) {
+    recorder().set_gauge(PENDING_COMMANDS, &[], count as f64);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::ProtocolError;
+
+    // 记录计数器调用 (名称, 标签值) 与仪表的取值
+    #[derive(Default)]
+    struct Recording(
+        Mutex<Vec<(&'static str, String)>>,
+        Mutex<Vec<(&'static str, f64)>>,
+    );
+
+    impl Counter for &'static Recording {
+        fn increment_counter(&self, name: &'static str, labels: Labels<'_>, _value: u64) {
+            let labels = labels.iter().map(|(_, v)| *v).collect::<Vec<_>>().join(",");
+            self.0.lock().unwrap().push((name, labels));
+        }
+    }
+    impl Gauge for &'static Recording {
+        fn set_gauge(&self, name: &'static str, _labels: Labels<'_>, value: f64) {
+            self.1.lock().unwrap().push((name, value));
+        }
+    }
+    impl Histogram for &'static Recording {}
+
+    #[test]
+    fn test_recorder() {
+        static RECORDING: OnceLock<Recording> = OnceLock::new();
+        let recording = RECORDING.get_or_init(Recording::default);
+        assert!(set_recorder(recording));
+        assert!(!set_recorder(NoopRecorder));
+
+        frame_decoded("A1", Some(Duration::from_millis(1)));
+        decode_failed(&ProtocolError::CrcError {
+            ori_crc: 1,
+            calc_crc: 2,
+        });
+        let calls = recording.0.lock().unwrap();
+        // 其他测试的解码也会经过全局记录器，只检查本测试的调用
+        assert!(calls.contains(&(FRAMES_DECODED, "A1".into())));
+        assert!(calls.contains(&(DECODE_ERRORS, "CRC_ERROR".into())));
+        assert!(calls.contains(&(CRC_FAILURES, String::new())));
+        drop(calls);
+
+        // 待应答命令数在登记、应答时都会更新
+        #[cfg(feature = "cache")]
+        {
+            use crate::core::{cache::DeviceCache, session::SessionManager};
+            let manager = SessionManager::with_cache(
+                DeviceCache::builder().max_capacity(16).build(),
+                Default::default(),
+            );
+            manager.submit("0001", "A1", None, vec![0x68]).unwrap();
+            assert!(
+                recording
+                    .1
+                    .lock()
+                    .unwrap()
+                    .contains(&(PENDING_COMMANDS, 1.0))
+            );
+            recording.1.lock().unwrap().clear();
+            manager.on_response("0001", "A1").unwrap();
+            assert!(
+                recording
+                    .1
+                    .lock()
+                    .unwrap()
+                    .contains(&(PENDING_COMMANDS, 0.0))
+            );
+        }
+    }
+}
@@ -1,22 +1,87 @@
+use core::{fmt, str::FromStr};
+#[cfg(feature = "std")]
+use std::{collections::HashMap, sync::RwLock};
+
 use crate::defi::{ProtocolResult, error::ProtocolError};
-use serde::{Deserialize, Serialize};
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+#[cfg(feature = "std")]
+use once_cell::sync::Lazy;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+#[cfg(feature = "async")]
+pub mod async_cache;
+#[cfg(feature = "cache")]
 pub mod cache;
+#[cfg(feature = "codec")]
+pub mod codec;
+#[cfg(feature = "std")]
+pub mod codegen;
+#[cfg(feature = "std")]
+pub mod conformance;
+#[cfg(feature = "cache")]
+pub mod dedup;
+pub mod framing;
 mod macro_plugin;
+#[cfg(feature = "std")]
+pub mod metrics;
 pub mod parts;
+#[cfg(feature = "std")]
+pub mod pipeline;
+#[cfg(feature = "std")]
+pub mod protocol_schema;
 pub mod reader;
+#[cfg(feature = "std")]
+pub mod replay;
+#[cfg(feature = "serial")]
+pub mod serial;
+#[cfg(feature = "cache")]
+pub mod session;
+#[cfg(feature = "std")]
+pub mod simulator;
+#[cfg(feature = "std")]
+pub mod template;
+pub mod tlv;
 pub mod type_converter;
 pub mod writer;
 
-#[derive(Debug, Clone)]
+/// 命令的读写属性。serde 为小写的 "read" / "write" / "readwrite"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "lowercase")]
 pub enum RW {
-    Read,
-    Write,
-    WriteThenRead,
+    Read,  // 只读：下行只发查询，不带参数
+    Write, // 只写
+    #[serde(alias = "writethenread")]
+    ReadWrite, // 可读可写 (写入后读回)
 }
 
-#[derive(Debug, Clone)]
-/// 方向
+impl RW {
+    /// 旧版本中 `ReadWrite` 的名称
+    #[deprecated(note = "use RW::ReadWrite")]
+    #[allow(non_upper_case_globals)]
+    pub const WriteThenRead: RW = RW::ReadWrite;
+
+    pub fn is_read(&self) -> bool {
+        matches!(self, RW::Read | RW::ReadWrite)
+    }
+
+    pub fn is_write(&self) -> bool {
+        matches!(self, RW::Write | RW::ReadWrite)
+    }
+
+    /// 只读命令，下行编码时不允许携带参数
+    pub fn is_read_only(&self) -> bool {
+        matches!(self, RW::Read)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "lowercase")]
+/// 方向。serde 与 `Display` 均为小写的 "upstream" / "downstream" / "both"，
+/// `from_str` 还接受大小写变体以及 "up"、"down"、"上行"、"下行"、"双向"
 pub enum DirectionEnum {
     Upstream,   // 上行
     Downstream, // 下行
@@ -24,6 +89,14 @@ pub enum DirectionEnum {
 }
 
 impl DirectionEnum {
+    pub fn code(&self) -> &'static str {
+        match self {
+            DirectionEnum::Upstream => "upstream",
+            DirectionEnum::Downstream => "downstream",
+            DirectionEnum::Both => "both",
+        }
+    }
+
     pub fn is_upstream(&self) -> bool {
         match self {
             DirectionEnum::Upstream => true,
@@ -57,14 +130,36 @@ impl DirectionEnum {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+impl fmt::Display for DirectionEnum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl FromStr for DirectionEnum {
+    type Err = ProtocolError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "upstream" | "up" | "上行" => Ok(DirectionEnum::Upstream),
+            "downstream" | "down" | "下行" => Ok(DirectionEnum::Downstream),
+            "both" | "双向" => Ok(DirectionEnum::Both),
+            _ => Err(ProtocolError::ValidationFailed(format!(
+                "unknown direction '{}'",
+                s
+            ))),
+        }
+    }
+}
+
+/// 消息类型。serde 序列化为 `code()` 字符串，反序列化同时接受 code、旧版本的写法
+/// (变体名，如 "BalanceSync"，以及 "dataReport")；其他字符串视为扩展类型 `Custom`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum MsgTypeEnum {
-    #[serde(rename = "signin")]
-    SignIn, //("signin", "注册"),
-    #[serde(rename = "dataReport")]
-    DataReport, //("data_report", "数据上报"),
-    #[serde(rename = "valve_operation")]
-    ValveOperation, //("valve_operation", "阀门控制"),
+    SignIn,             //("signin", "注册"),
+    DataReport,         //("data_report", "数据上报"),
+    ValveOperation,     //("valve_operation", "阀门控制"),
     BalanceSync,        //("sync_balance_centre_charging", "余额同步"),
     Recharge,           //("charge_operation", "充值"),
     UpdateGasPrice,     //("update_gas_price", "调价"),
@@ -75,9 +170,43 @@ pub enum MsgTypeEnum {
 
     NotifyTerminal, //("notify_terminal","告知平台并下发结束帧")
 
+    // 厂商扩展的消息类型 (固件升级、阶梯价同步等)，内容为 code，描述见 `MsgTypeEnum::register_custom`
+    Custom(String),
+
     Unknown,
 }
 
+impl Serialize for MsgTypeEnum {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for MsgTypeEnum {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        Ok(Self::_builtin(&code)
+            .or_else(|| Self::_alias(&code))
+            .unwrap_or(MsgTypeEnum::Custom(code)))
+    }
+}
+
+impl Serialize for Symbol {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.tag())
+    }
+}
+
+impl<'de> Deserialize<'de> for Symbol {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Symbol::from_tag(&String::deserialize(deserializer)?))
+    }
+}
+
+// 扩展消息类型的注册表：code -> 描述。no_std 下没有全局注册表，不支持扩展类型的注册
+#[cfg(feature = "std")]
+static CUSTOM_MSG_TYPES: Lazy<RwLock<HashMap<String, String>>> = Lazy::new(Default::default);
+
 impl MsgTypeEnum {
     pub fn code(&self) -> String {
         match self {
@@ -92,6 +221,7 @@ impl MsgTypeEnum {
             MsgTypeEnum::ErrorRespond => "error_respond".to_string(),
             MsgTypeEnum::HeartBeat => "heart_beat".to_string(),
             MsgTypeEnum::NotifyTerminal => "notify_terminal".to_string(),
+            MsgTypeEnum::Custom(code) => code.clone(),
             MsgTypeEnum::Unknown => "unknown".to_string(),
         }
     }
@@ -109,11 +239,86 @@ impl MsgTypeEnum {
             MsgTypeEnum::ErrorRespond => "表端回复异常".to_string(),
             MsgTypeEnum::HeartBeat => "心跳包".to_string(),
             MsgTypeEnum::NotifyTerminal => "告知平台并下发结束帧".to_string(),
+            MsgTypeEnum::Custom(code) => {
+                Self::_custom_description(code).unwrap_or_else(|| code.clone())
+            }
             MsgTypeEnum::Unknown => "未知".to_string(),
         }
     }
 
+    /// 注册扩展的消息类型 (需要 `std`)，例如 `register_custom("firmware_upgrade", "固件升级")`。
+    /// 注册后 `code_of` 能识别该 code，`description` 返回注册的描述；重复注册会覆盖描述。
+    /// code 与内置类型重复时报错
+    #[cfg(feature = "std")]
+    pub fn register_custom(code: &str, description: &str) -> ProtocolResult<Self> {
+        if Self::_builtin(code).is_some() || code == "unknown" {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "msg-type '{}' is a built-in type",
+                code
+            )));
+        }
+        CUSTOM_MSG_TYPES
+            .write()
+            .map_err(|_| ProtocolError::CommonError("msg-type registry is poisoned".into()))?
+            .insert(code.to_string(), description.to_string());
+        Ok(MsgTypeEnum::Custom(code.to_string()))
+    }
+
+    pub fn is_custom(&self) -> bool {
+        matches!(self, MsgTypeEnum::Custom(_))
+    }
+
+    /// 按 code 查找，也接受旧版本 serde 的写法；未知或未注册的 code 报错
     pub fn code_of(code: &str) -> ProtocolResult<Self> {
+        let builtin = Self::_builtin(code)
+            .or_else(|| Self::_alias(code).filter(|f| *f != MsgTypeEnum::Unknown));
+        if let Some(msg_type) = builtin {
+            return Ok(msg_type);
+        }
+        if Self::_custom_description(code).is_some() {
+            Ok(MsgTypeEnum::Custom(code.to_string()))
+        } else {
+            Err(ProtocolError::CommError(
+                crate::defi::error::CommError::UnknownMsgType(code.to_string()),
+            ))
+        }
+    }
+
+    // 已注册的扩展类型的描述
+    #[cfg(feature = "std")]
+    fn _custom_description(code: &str) -> Option<String> {
+        CUSTOM_MSG_TYPES
+            .read()
+            .ok()
+            .and_then(|types| types.get(code).cloned())
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn _custom_description(_code: &str) -> Option<String> {
+        None
+    }
+
+    // 旧版本 serde 的写法：变体名，以及 DataReport 曾经的 "dataReport"
+    fn _alias(name: &str) -> Option<Self> {
+        let f = match name {
+            "SignIn" => MsgTypeEnum::SignIn,
+            "DataReport" | "dataReport" => MsgTypeEnum::DataReport,
+            "ValveOperation" => MsgTypeEnum::ValveOperation,
+            "BalanceSync" => MsgTypeEnum::BalanceSync,
+            "Recharge" => MsgTypeEnum::Recharge,
+            "UpdateGasPrice" => MsgTypeEnum::UpdateGasPrice,
+            "DeviceParamSetting" => MsgTypeEnum::DeviceParamSetting,
+            "ServerTerminalOver" => MsgTypeEnum::ServerTerminalOver,
+            "ErrorRespond" => MsgTypeEnum::ErrorRespond,
+            "HeartBeat" => MsgTypeEnum::HeartBeat,
+            "NotifyTerminal" => MsgTypeEnum::NotifyTerminal,
+            "Unknown" | "unknown" => MsgTypeEnum::Unknown,
+            _ => return None,
+        };
+        Some(f)
+    }
+
+    fn _builtin(code: &str) -> Option<Self> {
         let f = match code {
             "signin" => MsgTypeEnum::SignIn,
             "data_report" => MsgTypeEnum::DataReport,
@@ -126,18 +331,16 @@ impl MsgTypeEnum {
             "error_respond" => MsgTypeEnum::ErrorRespond,
             "heart_beat" => MsgTypeEnum::HeartBeat,
             "notify_terminal" => MsgTypeEnum::NotifyTerminal,
-            _ => MsgTypeEnum::Unknown,
+            _ => return None,
         };
-        match f {
-            MsgTypeEnum::Unknown => Err(ProtocolError::CommError(
-                crate::defi::error::comm_error::CommError::UnknownMsgType(code.to_string()),
-            )),
-            _ => Ok(f),
-        }
+        Some(f)
     }
 }
 
-#[derive(Debug, Clone)]
+/// 单位符号。协议特有的单位 (如 "Nm³"、"步") 用 `Symbol::Custom` 表示，不必修改本枚举。
+/// serde 序列化为单位符号 (`tag()`)，反序列化时未知的符号视为 `Custom`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Symbol {
     Empty,
     Percent,
@@ -156,6 +359,18 @@ pub enum Symbol {
     CubicMeterPerHour,
     CubicMeterPerSec,
     Yuan,
+    WattHour,
+    KiloWattHour,
+    MPA,
+    Bar,
+    Ppm,
+    Hertz,
+    DBm,
+    Second,
+    Minute,
+    Hour,
+    // 自定义单位，内容即单位符号
+    Custom(String),
 }
 
 impl Symbol {
@@ -178,6 +393,261 @@ impl Symbol {
             Symbol::CubicMeterPerHour => "m³/h".to_string(),
             Symbol::CubicMeterPerSec => "m³/s".to_string(),
             Symbol::Yuan => "元".to_string(),
+            Symbol::WattHour => "Wh".to_string(),
+            Symbol::KiloWattHour => "kWh".to_string(),
+            Symbol::MPA => "MPa".to_string(),
+            Symbol::Bar => "bar".to_string(),
+            Symbol::Ppm => "ppm".to_string(),
+            Symbol::Hertz => "Hz".to_string(),
+            Symbol::DBm => "dBm".to_string(),
+            Symbol::Second => "s".to_string(),
+            Symbol::Minute => "min".to_string(),
+            Symbol::Hour => "h".to_string(),
+            Symbol::Custom(tag) => tag.clone(),
+        }
+    }
+
+    pub fn custom(tag: &str) -> Self {
+        Symbol::Custom(tag.to_string())
+    }
+
+    /// 按单位符号查找，内置单位返回对应变体，其余返回 `Custom`
+    pub fn from_tag(tag: &str) -> Self {
+        match tag {
+            "" => Symbol::Empty,
+            "%" => Symbol::Percent,
+            "V" => Symbol::Voltage,
+            "mV" => Symbol::MilliVoltage,
+            "mA" => Symbol::MilliAmperage,
+            "A" => Symbol::Amber,
+            "m³" => Symbol::CubicMeter,
+            "L" => Symbol::Liter,
+            "mL" => Symbol::MilliLiter,
+            "℃" => Symbol::Celsius,
+            "m/s" => Symbol::MeterPerSec,
+            "m/h" => Symbol::MeterPerHour,
+            "Pa" => Symbol::PA,
+            "kPa" => Symbol::KPA,
+            "m³/h" => Symbol::CubicMeterPerHour,
+            "m³/s" => Symbol::CubicMeterPerSec,
+            "元" => Symbol::Yuan,
+            "Wh" => Symbol::WattHour,
+            "kWh" => Symbol::KiloWattHour,
+            "MPa" => Symbol::MPA,
+            "bar" => Symbol::Bar,
+            "ppm" => Symbol::Ppm,
+            "Hz" => Symbol::Hertz,
+            "dBm" => Symbol::DBm,
+            "s" => Symbol::Second,
+            "min" => Symbol::Minute,
+            "h" => Symbol::Hour,
+            _ => Symbol::custom(tag),
+        }
+    }
+
+    pub fn is_custom(&self) -> bool {
+        matches!(self, Symbol::Custom(_))
+    }
+
+    /// 把以当前单位表示的 `value` 换算为 `target` 单位，例如 L -> m³、Pa -> kPa、mV -> V。
+    /// 量纲不同 (或自定义单位之间不相同) 时报错
+    pub fn convert_to(&self, target: &Symbol, value: Decimal) -> ProtocolResult<Decimal> {
+        if self == target {
+            return Ok(value);
+        }
+        match (self._scale(), target._scale()) {
+            (Some((from_dim, from)), Some((to_dim, to))) if from_dim == to_dim => {
+                Ok((value * from / to).normalize())
+            }
+            _ => Err(ProtocolError::ValidationFailed(format!(
+                "cannot convert unit '{}' to '{}'",
+                self.tag(),
+                target.tag()
+            ))),
+        }
+    }
+
+    // 量纲及换算到该量纲基准单位的倍数 (流量、速度以小时为基准，保证倍数是精确的十进制数)
+    fn _scale(&self) -> Option<(&'static str, Decimal)> {
+        let scale = match self {
+            Symbol::Voltage => ("voltage", Decimal::ONE),
+            Symbol::MilliVoltage => ("voltage", Decimal::new(1, 3)),
+            Symbol::Amber => ("current", Decimal::ONE),
+            Symbol::MilliAmperage => ("current", Decimal::new(1, 3)),
+            Symbol::CubicMeter => ("volume", Decimal::ONE),
+            Symbol::Liter => ("volume", Decimal::new(1, 3)),
+            Symbol::MilliLiter => ("volume", Decimal::new(1, 6)),
+            Symbol::PA => ("pressure", Decimal::ONE),
+            Symbol::KPA => ("pressure", Decimal::from(1000)),
+            Symbol::MPA => ("pressure", Decimal::from(1_000_000)),
+            Symbol::Bar => ("pressure", Decimal::from(100_000)),
+            Symbol::WattHour => ("energy", Decimal::ONE),
+            Symbol::KiloWattHour => ("energy", Decimal::from(1000)),
+            Symbol::Second => ("time", Decimal::ONE),
+            Symbol::Minute => ("time", Decimal::from(60)),
+            Symbol::Hour => ("time", Decimal::from(3600)),
+            Symbol::CubicMeterPerHour => ("flow", Decimal::ONE),
+            Symbol::CubicMeterPerSec => ("flow", Decimal::from(3600)),
+            Symbol::MeterPerHour => ("speed", Decimal::ONE),
+            Symbol::MeterPerSec => ("speed", Decimal::from(3600)),
+            _ => return None,
+        };
+        Some(scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rw() {
+        assert!(RW::Read.is_read() && !RW::Read.is_write());
+        assert!(RW::ReadWrite.is_read() && RW::ReadWrite.is_write());
+        assert_eq!(
+            serde_json::to_string(&RW::ReadWrite).unwrap(),
+            "\"readwrite\""
+        );
+        assert_eq!(
+            serde_json::from_str::<RW>("\"writethenread\"").unwrap(),
+            RW::ReadWrite
+        );
+    }
+
+    #[test]
+    fn test_direction_parsing() {
+        assert_eq!(
+            "upstream".parse::<DirectionEnum>().unwrap(),
+            DirectionEnum::Upstream
+        );
+        assert_eq!(
+            "Down".parse::<DirectionEnum>().unwrap(),
+            DirectionEnum::Downstream
+        );
+        assert_eq!(
+            "双向".parse::<DirectionEnum>().unwrap(),
+            DirectionEnum::Both
+        );
+        assert!("sideways".parse::<DirectionEnum>().is_err());
+        for direction in [DirectionEnum::Upstream, DirectionEnum::Both] {
+            let json = serde_json::to_string(&direction).unwrap();
+            assert_eq!(json, format!("\"{}\"", direction));
+            assert_eq!(
+                serde_json::from_str::<DirectionEnum>(&json).unwrap(),
+                direction
+            );
+        }
+    }
+
+    #[test]
+    fn test_custom_msg_type() {
+        assert!(MsgTypeEnum::code_of("ladder_price_sync").is_err());
+        let custom = MsgTypeEnum::register_custom("ladder_price_sync", "阶梯价同步").unwrap();
+        assert!(custom.is_custom());
+        assert_eq!(custom.code(), "ladder_price_sync");
+        assert_eq!(custom.description(), "阶梯价同步");
+        let parsed = MsgTypeEnum::code_of("ladder_price_sync").unwrap();
+        assert_eq!(parsed.description(), "阶梯价同步");
+        assert!(MsgTypeEnum::register_custom("heart_beat", "心跳").is_err());
+        assert!(matches!(
+            MsgTypeEnum::code_of("heart_beat"),
+            Ok(MsgTypeEnum::HeartBeat)
+        ));
+    }
+
+    #[test]
+    fn test_msg_type_serde_round_trip() {
+        let types = [
+            MsgTypeEnum::SignIn,
+            MsgTypeEnum::DataReport,
+            MsgTypeEnum::BalanceSync,
+            MsgTypeEnum::Custom("firmware_upgrade".into()),
+            MsgTypeEnum::Unknown,
+        ];
+        for msg_type in types {
+            let json = serde_json::to_string(&msg_type).unwrap();
+            assert_eq!(json, format!("\"{}\"", msg_type.code()));
+            let parsed: MsgTypeEnum = serde_json::from_str(&json).unwrap();
+            assert_eq!(parsed, msg_type);
+        }
+        // 旧版本的写法
+        for (old, expected) in [
+            ("\"dataReport\"", MsgTypeEnum::DataReport),
+            ("\"BalanceSync\"", MsgTypeEnum::BalanceSync),
+            ("\"valve_operation\"", MsgTypeEnum::ValveOperation),
+        ] {
+            assert_eq!(serde_json::from_str::<MsgTypeEnum>(old).unwrap(), expected);
+        }
+        assert_eq!(
+            MsgTypeEnum::code_of("dataReport").unwrap(),
+            MsgTypeEnum::DataReport
+        );
+        assert!(MsgTypeEnum::code_of("unknown").is_err());
+    }
+
+    #[test]
+    fn test_custom_symbol() {
+        assert_eq!(Symbol::from_tag("kPa"), Symbol::KPA);
+        let nm3 = Symbol::from_tag("Nm³");
+        assert!(nm3.is_custom());
+        assert_eq!(nm3.tag(), "Nm³");
+
+        use crate::{FieldConvertDecoder, FieldTranslator, FieldType};
+        let decoder = FieldConvertDecoder::new(
+            "步数",
+            FieldType::UnsignedU16(1.0),
+            Some(Symbol::custom("步")),
+            false,
+        );
+        let field = decoder.translate(&[0x00, 0x0A]).unwrap();
+        assert_eq!(field.value, "10 步");
+    }
+
+    #[test]
+    fn test_convert_units() {
+        let convert = |from: Symbol, to: Symbol, value: i64, scale: u32| {
+            from.convert_to(&to, Decimal::new(value, scale))
+        };
+        assert_eq!(
+            convert(Symbol::Liter, Symbol::CubicMeter, 1500, 0).unwrap(),
+            Decimal::new(15, 1)
+        );
+        assert_eq!(
+            convert(Symbol::KPA, Symbol::PA, 12, 1).unwrap(),
+            Decimal::from(1200)
+        );
+        assert_eq!(
+            convert(Symbol::MilliVoltage, Symbol::Voltage, 3600, 0).unwrap(),
+            Decimal::new(36, 1)
+        );
+        assert_eq!(
+            convert(Symbol::CubicMeterPerSec, Symbol::CubicMeterPerHour, 2, 0).unwrap(),
+            Decimal::from(7200)
+        );
+        assert!(convert(Symbol::Liter, Symbol::PA, 1, 0).is_err());
+        assert!(convert(Symbol::custom("步"), Symbol::custom("步"), 1, 0).is_ok());
+        assert!(convert(Symbol::custom("步"), Symbol::Liter, 1, 0).is_err());
+        assert_eq!(
+            convert(Symbol::MPA, Symbol::Bar, 16, 1).unwrap(),
+            Decimal::from(16)
+        );
+        assert_eq!(
+            convert(Symbol::Hour, Symbol::Minute, 15, 1).unwrap(),
+            Decimal::from(90)
+        );
+        assert!(convert(Symbol::DBm, Symbol::Hertz, 1, 0).is_err());
+    }
+
+    #[test]
+    fn test_symbol_serde() {
+        for symbol in [
+            Symbol::KiloWattHour,
+            Symbol::CubicMeter,
+            Symbol::custom("Nm³"),
+        ] {
+            let json = serde_json::to_string(&symbol).unwrap();
+            assert_eq!(json, format!("\"{}\"", symbol.tag()));
+            assert_eq!(serde_json::from_str::<Symbol>(&json).unwrap(), symbol);
         }
     }
 }
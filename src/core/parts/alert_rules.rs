@@ -0,0 +1,142 @@
+use std::{collections::HashMap, fmt};
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::ReportField;
+
+/// 单条告警规则。数值规则取 `ReportField::numeric_value`，没有时尝试把 value 解析为数值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "rule", content = "value", rename_all = "camelCase")]
+pub enum AlertRule {
+    // 低于下限 (不含) 时告警
+    Min(Decimal),
+    // 高于上限 (不含) 时告警
+    Max(Decimal),
+    // 取值 (翻译后的 value) 在黑名单中时告警，例如 "阀门故障"
+    Blacklist(Vec<String>),
+    // 与上一次上报的差值 (绝对值) 超过限制时告警，没有上一次的值时不检查
+    Delta(Decimal),
+}
+
+impl AlertRule {
+    pub fn is_triggered(&self, field: &ReportField, previous: Option<Decimal>) -> bool {
+        let number = field
+            .numeric_value
+            .or_else(|| field.value.trim().parse().ok());
+        match self {
+            AlertRule::Min(min) => number.is_some_and(|n| n < *min),
+            AlertRule::Max(max) => number.is_some_and(|n| n > *max),
+            AlertRule::Blacklist(values) => values.contains(&field.value),
+            AlertRule::Delta(limit) => number
+                .zip(previous)
+                .is_some_and(|(n, p)| (n - p).abs() > *limit),
+        }
+    }
+}
+
+impl fmt::Display for AlertRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlertRule::Min(min) => write!(f, "min {}", min),
+            AlertRule::Max(max) => write!(f, "max {}", max),
+            AlertRule::Blacklist(values) => write!(f, "blacklist [{}]", values.join(", ")),
+            AlertRule::Delta(limit) => write!(f, "delta {}", limit),
+        }
+    }
+}
+
+/// 按字段 code 配置的告警规则。默认解码流程通过 `ProtocolConfig::alert_rules` 应用，
+/// 命中时设置 `ReportField::alert`，并把命中的规则记录到 `ReportField::alert_rule`。
+///
+/// ```ignore
+/// let rules = AlertRules::new()
+///     .rule("dian_ya", AlertRule::Min(dec!(3.3)))
+///     .rule("fa_men_zhuang_tai", AlertRule::Blacklist(vec!["故障".into()]))
+///     .rule("lei_ji_yong_liang", AlertRule::Delta(dec!(100)));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AlertRules {
+    rules: HashMap<String, Vec<AlertRule>>,
+}
+
+impl AlertRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 为字段 code 追加一条规则，同一字段可以有多条
+    pub fn rule(mut self, code: &str, rule: AlertRule) -> Self {
+        self.rules.entry(code.to_string()).or_default().push(rule);
+        self
+    }
+
+    pub fn rules_of(&self, code: &str) -> &[AlertRule] {
+        self.rules.get(code).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// 检查字段 (含子字段)，返回命中告警的字段数。差值规则不检查
+    pub fn apply(&self, fields: &mut [ReportField]) -> usize {
+        self.apply_with_previous(fields, &HashMap::new())
+    }
+
+    /// 同 `apply`，`previous` 为各字段 code 上一次上报的数值，用于差值规则
+    pub fn apply_with_previous(
+        &self,
+        fields: &mut [ReportField],
+        previous: &HashMap<String, Decimal>,
+    ) -> usize {
+        fields
+            .iter_mut()
+            .map(|field| {
+                let mut count = self.apply_with_previous(&mut field.children, previous);
+                let triggered = self
+                    .rules_of(&field.code)
+                    .iter()
+                    .find(|rule| rule.is_triggered(field, previous.get(&field.code).copied()));
+                if let Some(rule) = triggered {
+                    field.alert = true;
+                    field.alert_rule = Some(rule.to_string());
+                    count += 1;
+                }
+                count
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_rules() {
+        let rules = AlertRules::new()
+            .rule("dian_ya", AlertRule::Min(Decimal::new(33, 1)))
+            .rule("famen", AlertRule::Blacklist(vec!["故障".into()]))
+            .rule("yongliang", AlertRule::Delta(Decimal::from(100)));
+        let mut fields = vec![
+            ReportField::new("电压", "dian_ya", "3.2 V".into())
+                .with_numeric(Decimal::new(32, 1), Some("V")),
+            ReportField::new("阀门", "famen", "开".into()),
+            ReportField::new("用量", "yongliang", "350".into()),
+        ];
+        assert_eq!(rules.apply(&mut fields), 1);
+        assert!(fields[0].alert);
+        assert_eq!(fields[0].alert_rule.as_deref(), Some("min 3.3"));
+        assert!(!fields[2].alert);
+
+        fields[1].value = "故障".into();
+        let previous = HashMap::from([("yongliang".to_string(), Decimal::from(200))]);
+        assert_eq!(rules.apply_with_previous(&mut fields, &previous), 3);
+        assert_eq!(fields[2].alert_rule.as_deref(), Some("delta 100"));
+
+        let json = serde_json::to_string(&rules.rules_of("famen")[0]).unwrap();
+        assert_eq!(json, r#"{"rule":"blacklist","value":["故障"]}"#);
+    }
+}
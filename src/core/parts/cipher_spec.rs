@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    defi::{ProtocolResult, error::ProtocolError},
+    hex_util,
+    utils::generate_secure_bytes,
+};
+
+/// 加密向量 (IV) 的来源
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum IvStrategy {
+    // 不需要 IV (例如 ECB)
+    #[default]
+    None,
+    // 全 0
+    Zero,
+    // 固定值 (hex)
+    Fixed(String),
+    // 每帧随机生成，随帧一起发送
+    Random,
+}
+
+impl IvStrategy {
+    /// 生成 `len` 字节的 IV，`None` 返回空
+    pub fn resolve(&self, len: usize) -> ProtocolResult<Vec<u8>> {
+        match self {
+            IvStrategy::None => Ok(Vec::new()),
+            IvStrategy::Zero => Ok(vec![0u8; len]),
+            IvStrategy::Fixed(hex) => {
+                let iv = hex_util::hex_to_bytes(hex)?;
+                if iv.len() != len {
+                    return Err(ProtocolError::ValidationFailed(format!(
+                        "fixed iv has {} bytes, expected {}",
+                        iv.len(),
+                        len
+                    )));
+                }
+                Ok(iv)
+            }
+            IvStrategy::Random => generate_secure_bytes(len),
+        }
+    }
+}
+
+/// 报文的加密方式：密钥槽位、算法、IV 来源、是否需要 MAC。
+/// 由 `Transport::cipher_spec` 返回，帧处理层据此自动加解密。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CipherSpec {
+    // 密钥槽位，含义同 `Transport::cipher_slot`
+    pub slot: i8,
+    // 算法名称，例如 "AES-128-CBC"，空字符串表示未指定
+    pub algorithm: String,
+    #[serde(default)]
+    pub iv: IvStrategy,
+    #[serde(default)]
+    pub mac_required: bool,
+}
+
+impl CipherSpec {
+    pub fn new(slot: i8, algorithm: &str) -> Self {
+        Self {
+            slot,
+            algorithm: algorithm.to_string(),
+            iv: IvStrategy::None,
+            mac_required: false,
+        }
+    }
+
+    /// 只有槽位信息 (旧的 `cipher_slot`)，算法未指定
+    pub fn from_slot(slot: i8) -> Self {
+        Self::new(slot, "")
+    }
+
+    pub fn with_iv(mut self, iv: IvStrategy) -> Self {
+        self.iv = iv;
+        self
+    }
+
+    pub fn with_mac(mut self, mac_required: bool) -> Self {
+        self.mac_required = mac_required;
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.slot >= 0
+    }
+}
@@ -0,0 +1,279 @@
+use std::collections::HashMap;
+
+use crate::{
+    DirectionEnum, MsgTypeEnum,
+    core::parts::traits::Cmd,
+    defi::{
+        ProtocolResult,
+        error::{HexDigestError, ProtocolError},
+    },
+};
+
+/// 命令注册表：协议实现在启动时注册所有 `Cmd`，桥接层按 `cmd_code` 查找，
+/// 不必在每个调用方写一大段 match。
+///
+/// 同时也是命令码与消息类型 (`MsgTypeEnum`) 的对照表，可以双向查询，
+/// 桥接层据此为只知道命令码的返回补全 msg_type (`JniResponse::fill_msg_type`)。
+///
+/// 遍历顺序即注册顺序。需要全局共享时可以放进 `once_cell::sync::Lazy`。
+#[derive(Default)]
+pub struct CmdRegistry {
+    cmds: Vec<Box<dyn Cmd + Send + Sync>>,
+    index: HashMap<String, usize>,
+    // 覆盖 `Cmd::msg_type` 的对照，用于命令本身没有声明消息类型的情况
+    msg_types: HashMap<String, MsgTypeEnum>,
+}
+
+impl CmdRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册一个命令，code 重复时报错
+    pub fn register(&mut self, cmd: impl Cmd + Send + Sync + 'static) -> ProtocolResult<()> {
+        let code = cmd.code();
+        if self.index.contains_key(&code) {
+            return Err(ProtocolError::CommonError(format!(
+                "cmd '{}' is already registered",
+                code
+            )));
+        }
+        self.index.insert(code, self.cmds.len());
+        self.cmds.push(Box::new(cmd));
+        Ok(())
+    }
+
+    /// 批量注册，例如某个命令枚举的所有变体
+    pub fn register_all<C: Cmd + Send + Sync + 'static>(
+        &mut self,
+        cmds: impl IntoIterator<Item = C>,
+    ) -> ProtocolResult<()> {
+        cmds.into_iter().try_for_each(|cmd| self.register(cmd))
+    }
+
+    /// 按命令码查找，返回一份克隆
+    pub fn lookup(&self, code: &str) -> Option<Box<dyn Cmd>> {
+        self.get(code)
+            .map(|cmd| dyn_clone::clone_box(cmd) as Box<dyn Cmd>)
+    }
+
+    /// 按命令码查找，返回引用
+    pub fn get(&self, code: &str) -> Option<&(dyn Cmd + Send + Sync + 'static)> {
+        self.index.get(code).map(|i| self.cmds[*i].as_ref())
+    }
+
+    /// 同 `get`，找不到时返回 `HexDigestError::UnknownCmdCode`，并附带最接近的已注册命令码
+    pub fn resolve(&self, code: &str) -> ProtocolResult<&(dyn Cmd + Send + Sync + 'static)> {
+        self.get(code).ok_or_else(|| {
+            HexDigestError::UnknownCmdCode {
+                code: code.to_string(),
+                nearest: self.nearest(code),
+            }
+            .into()
+        })
+    }
+
+    /// 最接近 `code` 的已注册命令码：编辑距离最小 (不超过长度的一半，至少为 1)，
+    /// 相同时取数值 (hex) 差最小的，再相同取先注册的
+    pub fn nearest(&self, code: &str) -> Option<String> {
+        let code = code.to_ascii_uppercase();
+        let limit = (code.len() / 2).max(1);
+        let numeric = |c: &str| u64::from_str_radix(c, 16).ok();
+        self.iter()
+            .map(|cmd| cmd.code())
+            .filter_map(|candidate| {
+                let distance = _edit_distance(&code, &candidate.to_ascii_uppercase());
+                let gap = numeric(&code)
+                    .zip(numeric(&candidate))
+                    .map_or(u64::MAX, |(a, b)| a.abs_diff(b));
+                (distance <= limit).then_some((distance, gap, candidate))
+            })
+            .min_by_key(|(distance, gap, _)| (*distance, *gap))
+            .map(|(_, _, candidate)| candidate)
+    }
+
+    /// 为已注册的命令指定消息类型，覆盖 `Cmd::msg_type`
+    pub fn bind_msg_type(&mut self, code: &str, msg_type: MsgTypeEnum) -> ProtocolResult<()> {
+        self.resolve(code)?;
+        self.msg_types.insert(code.to_string(), msg_type);
+        Ok(())
+    }
+
+    /// 命令码对应的消息类型
+    pub fn msg_type_of(&self, code: &str) -> Option<MsgTypeEnum> {
+        self.msg_types
+            .get(code)
+            .cloned()
+            .or_else(|| self.get(code).and_then(|cmd| cmd.msg_type()))
+    }
+
+    /// 命令码对应的方向
+    pub fn direction_of(&self, code: &str) -> Option<DirectionEnum> {
+        self.get(code).map(|cmd| cmd.direction())
+    }
+
+    /// 属于某个消息类型的所有命令码 (按注册顺序)
+    pub fn codes_of(&self, msg_type: &MsgTypeEnum) -> Vec<String> {
+        self.codes()
+            .into_iter()
+            .filter(|code| self.msg_type_of(code).as_ref() == Some(msg_type))
+            .collect()
+    }
+
+    /// 某个消息类型在指定方向上的命令码，例如阀门控制的下行指令与上行应答。
+    /// `DirectionEnum::Both` 表示不限方向；有多个时取先注册的
+    pub fn code_for(&self, msg_type: &MsgTypeEnum, direction: DirectionEnum) -> Option<String> {
+        self.codes_of(msg_type).into_iter().find(|code| {
+            self.direction_of(code).is_some_and(|d| match direction {
+                DirectionEnum::Upstream => d.is_upstream(),
+                DirectionEnum::Downstream => d.is_downstream(),
+                DirectionEnum::Both => true,
+            })
+        })
+    }
+
+    pub fn contains(&self, code: &str) -> bool {
+        self.index.contains_key(code)
+    }
+
+    /// 按注册顺序遍历所有命令 (可取 code、title、direction、msg_type 等)
+    pub fn iter(&self) -> impl Iterator<Item = &(dyn Cmd + Send + Sync + 'static)> {
+        self.cmds.iter().map(|cmd| cmd.as_ref())
+    }
+
+    pub fn codes(&self) -> Vec<String> {
+        self.iter().map(|cmd| cmd.code()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cmds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cmds.is_empty()
+    }
+}
+
+// Levenshtein 编辑距离
+fn _edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectionEnum, MsgTypeEnum};
+
+    #[derive(Clone)]
+    enum MeterCmd {
+        Report,
+        Valve,
+    }
+
+    impl Cmd for MeterCmd {
+        fn code(&self) -> String {
+            match self {
+                MeterCmd::Report => "01".into(),
+                MeterCmd::Valve => "A2".into(),
+            }
+        }
+
+        fn title(&self) -> String {
+            match self {
+                MeterCmd::Report => "数据上报".into(),
+                MeterCmd::Valve => "阀门控制".into(),
+            }
+        }
+
+        fn direction(&self) -> DirectionEnum {
+            match self {
+                MeterCmd::Report => DirectionEnum::Upstream,
+                MeterCmd::Valve => DirectionEnum::Downstream,
+            }
+        }
+
+        fn msg_type(&self) -> Option<MsgTypeEnum> {
+            match self {
+                MeterCmd::Report => Some(MsgTypeEnum::DataReport),
+                MeterCmd::Valve => Some(MsgTypeEnum::ValveOperation),
+            }
+        }
+    }
+
+    #[test]
+    fn test_register_and_lookup() {
+        let mut registry = CmdRegistry::new();
+        registry
+            .register_all([MeterCmd::Report, MeterCmd::Valve])
+            .unwrap();
+        assert!(registry.register(MeterCmd::Valve).is_err());
+
+        let valve = registry.lookup("A2").unwrap();
+        assert_eq!(valve.title(), "阀门控制");
+        assert!(valve.direction().is_downstream_only());
+        assert!(registry.lookup("FF").is_none());
+        assert_eq!(registry.codes(), ["01", "A2"]);
+        assert_eq!(registry.len(), 2);
+    }
+
+    #[test]
+    fn test_msg_type_mapping() {
+        let mut registry = CmdRegistry::new();
+        registry
+            .register_all([MeterCmd::Report, MeterCmd::Valve])
+            .unwrap();
+        assert_eq!(registry.msg_type_of("01"), Some(MsgTypeEnum::DataReport));
+        assert_eq!(
+            registry.code_for(&MsgTypeEnum::ValveOperation, DirectionEnum::Downstream),
+            Some("A2".to_string())
+        );
+        assert!(
+            registry
+                .code_for(&MsgTypeEnum::ValveOperation, DirectionEnum::Upstream)
+                .is_none()
+        );
+
+        registry
+            .bind_msg_type("A2", MsgTypeEnum::DeviceParamSetting)
+            .unwrap();
+        assert!(registry.codes_of(&MsgTypeEnum::ValveOperation).is_empty());
+        assert_eq!(registry.codes_of(&MsgTypeEnum::DeviceParamSetting), ["A2"]);
+        assert!(
+            registry
+                .bind_msg_type("FF", MsgTypeEnum::HeartBeat)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_resolve_suggests_nearest() {
+        let mut registry = CmdRegistry::new();
+        registry
+            .register_all([MeterCmd::Report, MeterCmd::Valve])
+            .unwrap();
+        assert_eq!(registry.resolve("A2").unwrap().title(), "阀门控制");
+        assert_eq!(registry.nearest("a3").as_deref(), Some("A2"));
+        assert_eq!(registry.nearest("02").as_deref(), Some("01"));
+        assert!(registry.nearest("FF").is_none());
+
+        let Err(err) = registry.resolve("A3") else {
+            panic!("A3 is not registered");
+        };
+        assert!(err.is_frame_error());
+        assert_eq!(
+            err.to_string(),
+            "unknown cmd code 'A3', nearest registered cmd code is 'A2'"
+        );
+    }
+}
@@ -1,7 +1,21 @@
+#[cfg(feature = "std")]
+pub mod alert_rules;
+#[cfg(feature = "std")]
+pub mod cipher_spec;
+#[cfg(feature = "std")]
+pub mod cmd_registry;
+pub mod param_descriptor;
+pub mod param_value;
 pub mod placeholder;
+#[cfg(feature = "std")]
 pub mod raw_capsule;
+#[cfg(feature = "std")]
+pub mod raw_capsule_ref;
+#[cfg(feature = "std")]
 pub mod raw_chamber;
 pub mod rawfield;
 pub mod traits;
+#[cfg(feature = "std")]
 pub mod transport_carrier;
+#[cfg(feature = "std")]
 pub mod transport_pair;
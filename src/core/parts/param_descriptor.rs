@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::parts::traits::{AutoEncodingParam, FieldCondition, RepeatSpec, ValidationRule};
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+
+/// 下发参数的描述，供前端自动生成下行命令的表单
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ParamDescriptor {
+    pub code: String,
+    pub title: String,
+    // string / int / float
+    pub input_field_type: String,
+    // 0 表示变长
+    pub byte_length: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_value: Option<String>,
+    // 默认值在编码时动态生成 (例如当前时间、序列号)
+    #[serde(default)]
+    pub dynamic_default: bool,
+    pub required: bool,
+    #[serde(default)]
+    pub constraints: Vec<ValidationRule>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<FieldCondition>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeat: Option<RepeatSpec>,
+}
+
+impl ParamDescriptor {
+    pub fn from_param(param: &impl AutoEncodingParam) -> Self {
+        let default_value = Some(param.default_value()).filter(|value| !value.is_empty());
+        Self {
+            code: param.code(),
+            title: param.title(),
+            input_field_type: param.input_field_type(),
+            byte_length: param.byte_length(),
+            default_value,
+            dynamic_default: param.default_provider().is_some(),
+            required: param.required(),
+            constraints: param.rules(),
+            order: param.order(),
+            group: param.group(),
+            condition: param.condition(),
+            repeat: param.repeat(),
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::{
+    core::type_converter::FieldType,
+    defi::{ProtocolResult, error::ProtocolError},
+};
+
+/// 下发参数的值。JSON 中可以直接写数字和布尔值，不必都写成字符串。
+/// 旧的字符串参数会解析为 `Text`，与原来的行为一致。
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+#[serde(untagged)]
+pub enum ParamValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    // 超出 i64 的正整数 (untagged 按顺序匹配，必须排在 Float 之前，否则会被解析为浮点数)
+    UInt(u64),
+    Float(f64),
+    Text(String),
+    // 数组参数 (例如四档阶梯价格)，对应 `AutoEncodingParam::repeat`
+    List(Vec<ParamValue>),
+}
+
+impl ParamValue {
+    /// 是否为空值 (null、空字符串或空数组)，空值按未填写处理，使用默认值
+    pub fn is_empty(&self) -> bool {
+        match self {
+            ParamValue::Null => true,
+            ParamValue::Text(text) => text.is_empty(),
+            ParamValue::List(items) => items.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// 按字段类型转换为 `FieldType::encode` 能接受的输入字符串。
+    /// 布尔值转为 1/0；字符串类字段 (BCD/ASCII) 不接受浮点数。
+    pub fn coerce_for(&self, field_type: &FieldType) -> ProtocolResult<String> {
+        match (self, field_type) {
+            (ParamValue::Float(value), FieldType::StringOrBCD | FieldType::Ascii) => {
+                Err(ProtocolError::ValidationFailed(format!(
+                    "float value {} is not allowed for a string field",
+                    value
+                )))
+            }
+            (ParamValue::List(_), _) => Err(ProtocolError::ValidationFailed(format!(
+                "list value [{}] is not allowed for a single field",
+                self
+            ))),
+            _ => Ok(self.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for ParamValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamValue::Null => Ok(()),
+            ParamValue::Bool(value) => write!(f, "{}", if *value { 1 } else { 0 }),
+            ParamValue::Int(value) => write!(f, "{}", value),
+            ParamValue::UInt(value) => write!(f, "{}", value),
+            ParamValue::Float(value) => write!(f, "{}", value),
+            ParamValue::Text(value) => f.write_str(value),
+            ParamValue::List(items) => {
+                let items: Vec<String> = items.iter().map(ToString::to_string).collect();
+                f.write_str(&items.join(","))
+            }
+        }
+    }
+}
+
+impl From<&str> for ParamValue {
+    fn from(value: &str) -> Self {
+        ParamValue::Text(value.to_string())
+    }
+}
+
+impl From<String> for ParamValue {
+    fn from(value: String) -> Self {
+        ParamValue::Text(value)
+    }
+}
+
+impl From<bool> for ParamValue {
+    fn from(value: bool) -> Self {
+        ParamValue::Bool(value)
+    }
+}
+
+impl From<i64> for ParamValue {
+    fn from(value: i64) -> Self {
+        ParamValue::Int(value)
+    }
+}
+
+impl From<u64> for ParamValue {
+    fn from(value: u64) -> Self {
+        i64::try_from(value).map_or(ParamValue::UInt(value), ParamValue::Int)
+    }
+}
+
+impl From<f64> for ParamValue {
+    fn from(value: f64) -> Self {
+        ParamValue::Float(value)
+    }
+}
+
+impl<T: Into<ParamValue>> From<Vec<T>> for ParamValue {
+    fn from(values: Vec<T>) -> Self {
+        ParamValue::List(values.into_iter().map(Into::into).collect())
+    }
+}
+
+/// `AutoEncoding::auto_process` 接受的参数值类型：原有的字符串，或带类型的 `ParamValue`
+pub trait EncodingInput {
+    fn to_input(&self, field_type: &FieldType) -> ProtocolResult<String>;
+
+    // 数组参数的各个元素。字符串以逗号分隔，例如 "100,200,300"
+    fn to_inputs(&self, field_type: &FieldType) -> ProtocolResult<Vec<String>>;
+
+    // 不区分字段类型的文本形式，用于条件字段的比较
+    fn to_text(&self) -> String;
+}
+
+impl EncodingInput for String {
+    fn to_input(&self, _field_type: &FieldType) -> ProtocolResult<String> {
+        Ok(self.clone())
+    }
+
+    fn to_inputs(&self, _field_type: &FieldType) -> ProtocolResult<Vec<String>> {
+        if self.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(self
+            .split(',')
+            .map(|item| item.trim().to_string())
+            .collect())
+    }
+
+    fn to_text(&self) -> String {
+        self.clone()
+    }
+}
+
+impl EncodingInput for ParamValue {
+    fn to_input(&self, field_type: &FieldType) -> ProtocolResult<String> {
+        self.coerce_for(field_type)
+    }
+
+    fn to_inputs(&self, field_type: &FieldType) -> ProtocolResult<Vec<String>> {
+        match self {
+            ParamValue::List(items) => items
+                .iter()
+                .map(|item| item.coerce_for(field_type))
+                .collect(),
+            ParamValue::Text(text) => text.to_inputs(field_type),
+            ParamValue::Null => Ok(Vec::new()),
+            _ => Ok(vec![self.coerce_for(field_type)?]),
+        }
+    }
+
+    fn to_text(&self) -> String {
+        self.to_string()
+    }
+}
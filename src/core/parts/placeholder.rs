@@ -1,3 +1,6 @@
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+
 // 占位符
 #[derive(Debug, Clone, Default)]
 pub struct PlaceHolder {
@@ -1,43 +1,80 @@
-use crate::{DirectionEnum, ProtocolError, ReportField, core::parts::traits::Cmd};
+use crate::{
+    DirectionEnum, MsgTypeEnum, ProtocolError, ProtocolWarning, ReportField,
+    core::parts::{
+        rawfield::Rawfield,
+        traits::{Cmd, ProtocolConfig},
+    },
+    hex_util,
+    utils::buffer_pool,
+};
 use dyn_clone::DynClone;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::OnceLock,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 // 报文上/下行解析 处理之后的结果 第二小解析单位，比RawField大
 #[derive(Debug, Clone)]
 pub struct RawCapsule<T: Cmd> {
     pub(crate) bytes: Vec<u8>,
-    pub(crate) hex: String,
+    // 由 bytes 按需生成 (大写)，不打印报文时不产生额外开销。修改 bytes 后需要重置
+    pub(crate) hex: OnceLock<String>,
     pub(crate) field_details: Vec<ReportField>,
     pub(crate) cmd: Option<T>,
+    // 消息类型，默认取 `Cmd::msg_type`，可以用 `set_msg_type` 覆盖
+    pub(crate) msg_type: Option<MsgTypeEnum>,
     pub(crate) device_no: Option<String>,
     pub(crate) device_id: Option<String>,
     // 临时二进制存放处
     pub(crate) temp_bytes: Vec<u8>,
     pub(crate) direction: DirectionEnum,
     pub(crate) success: bool,
+    // 翻译前的原始字段 (bytes/hex/偏移)，默认不保留，用于审计时把上报值对应回原始字节
+    pub(crate) raw_fields: Option<Vec<Rawfield>>,
+    // 收到报文的时间、解码完成的时间，由默认解码流程填充
+    pub(crate) received_at: Option<SystemTime>,
+    pub(crate) decoded_at: Option<SystemTime>,
+    // 解码中发现的非致命异常，不影响 success
+    pub(crate) warnings: Vec<ProtocolWarning>,
+    // 去重窗口内已经收到过的重传帧 (`DedupWindow`)，计费类上报不应重复入账
+    pub(crate) duplicate: bool,
+    // 上行 CRC 校验结果，由默认解码流程填充；未校验 CRC 时为 None
+    pub(crate) crc_valid: Option<bool>,
 }
 
 impl<T: Cmd + 'static> RawCapsule<T> {
     pub fn new_upstream(bytes: &[u8]) -> Self {
-        let hex = hex::encode_upper(bytes);
         Self {
-            bytes: bytes.to_vec(),
-            hex,
+            bytes: buffer_pool::copy(bytes),
+            hex: OnceLock::new(),
             field_details: Vec::new(),
             cmd: None,
+            msg_type: None,
             device_no: None,
             device_id: None,
             temp_bytes: Vec::new(),
             direction: DirectionEnum::Upstream,
             success: true,
+            raw_fields: None,
+            received_at: None,
+            decoded_at: None,
+            warnings: Vec::new(),
+            duplicate: false,
+            crc_valid: None,
         }
     }
 
     pub fn new_downstream(cmd: T, device_no: &str, device_id: &str) -> Self {
+        let msg_type = cmd.msg_type();
         Self {
             bytes: Vec::new(),
-            hex: String::new(),
+            hex: OnceLock::new(),
             field_details: Vec::new(),
             cmd: Some(cmd),
+            msg_type,
             device_no: Some(device_no.into()),
             device_id: if device_id.is_empty() {
                 None
@@ -47,6 +84,12 @@ impl<T: Cmd + 'static> RawCapsule<T> {
             temp_bytes: Vec::new(),
             direction: DirectionEnum::Downstream,
             success: true,
+            raw_fields: None,
+            received_at: None,
+            decoded_at: None,
+            warnings: Vec::new(),
+            duplicate: false,
+            crc_valid: None,
         }
     }
 
@@ -73,6 +116,11 @@ impl<T: Cmd + 'static> RawCapsule<T> {
         crate::md5_digester::Md5Digester::digest_str_with_salt(&device_no, &device_id)
     }
 
+    /// 下行报文的构建器，见 `DownstreamBuilder`
+    pub fn downstream_builder(cmd: T) -> DownstreamBuilder<T> {
+        DownstreamBuilder::new(cmd)
+    }
+
     pub fn new_downstream_from_upstream(up_stream_capsule: &RawCapsule<T>) -> Self {
         let device_no = if up_stream_capsule.device_no.is_some() {
             up_stream_capsule.device_no.clone()
@@ -87,19 +135,26 @@ impl<T: Cmd + 'static> RawCapsule<T> {
         };
         Self {
             bytes: Vec::new(),
-            hex: String::new(),
+            hex: OnceLock::new(),
             field_details: Vec::new(),
             cmd: up_stream_capsule.cmd_clone(),
+            msg_type: up_stream_capsule.msg_type.clone(),
             device_no,
             device_id,
             temp_bytes: Vec::new(),
             direction: DirectionEnum::Downstream,
             success: true,
+            raw_fields: None,
+            received_at: None,
+            decoded_at: None,
+            warnings: Vec::new(),
+            duplicate: false,
+            crc_valid: None,
         }
     }
 
-    pub fn into_fields(self) -> Vec<ReportField> {
-        self.field_details
+    pub fn into_fields(mut self) -> Vec<ReportField> {
+        std::mem::take(&mut self.field_details)
     }
 
     pub fn fail(&mut self) {
@@ -118,12 +173,13 @@ impl<T: Cmd + 'static> RawCapsule<T> {
         self.bytes.clone()
     }
 
+    /// 报文的大写 hex，首次调用时由 bytes 生成并缓存
     pub fn hex(&self) -> &str {
-        &self.hex
+        self.hex.get_or_init(|| hex::encode_upper(&self.bytes))
     }
 
     pub fn hex_clone(&self) -> String {
-        self.hex.clone()
+        self.hex().to_string()
     }
 
     pub fn field_details(&self) -> &[ReportField] {
@@ -134,6 +190,38 @@ impl<T: Cmd + 'static> RawCapsule<T> {
         self.field_details.clone()
     }
 
+    /// 按 code 查找字段 (含分组中的子字段)，返回第一个匹配的字段
+    pub fn get_field(&self, code: &str) -> Option<&ReportField> {
+        _find_field(&self.field_details, code)
+    }
+
+    /// 按 code 读取字段值并解析为 `V`。数值型字段使用不带单位的 `numeric_value`
+    pub fn get_field_value_as<V: FromStr>(&self, code: &str) -> crate::defi::ProtocolResult<V> {
+        let field = self.get_field(code).ok_or_else(|| {
+            ProtocolError::ValidationFailed(format!("field '{}' not found", code))
+        })?;
+        let value = field
+            .numeric_value
+            .map(|n| n.to_string())
+            .unwrap_or_else(|| field.value.clone());
+        value.trim().parse().map_err(|_| {
+            ProtocolError::ValidationFailed(format!(
+                "field '{}' has value '{}' that cannot be parsed",
+                code, value
+            ))
+        })
+    }
+
+    /// code -> value，分组字段展开为子字段。code 重复时保留第一个
+    pub fn fields_as_map(&self) -> HashMap<String, String> {
+        let mut map = HashMap::new();
+        for field in self.field_details.iter().flat_map(ReportField::leaves) {
+            map.entry(field.code.clone())
+                .or_insert_with(|| field.value.clone());
+        }
+        map
+    }
+
     pub fn cmd(&self) -> Option<&T> {
         self.cmd.as_ref()
     }
@@ -145,6 +233,14 @@ impl<T: Cmd + 'static> RawCapsule<T> {
         self.cmd.as_ref().map(|cmd| dyn_clone::clone(cmd))
     }
 
+    pub fn msg_type(&self) -> Option<&MsgTypeEnum> {
+        self.msg_type.as_ref()
+    }
+
+    pub fn msg_type_clone(&self) -> Option<MsgTypeEnum> {
+        self.msg_type.clone()
+    }
+
     pub fn device_no(&self) -> Option<&str> {
         self.device_no.as_deref()
     }
@@ -181,10 +277,11 @@ impl<T: Cmd + 'static> RawCapsule<T> {
         self.success
     }
 
-    // 把二进制塞回去，同时自动生成hex,通常用于出口的capsule
+    // 把二进制塞回去，hex 在下次读取时重新生成,通常用于出口的capsule
     pub fn set_bytes_and_generate_hex(&mut self, bytes: &[u8]) -> crate::defi::ProtocolResult<()> {
-        self.bytes = bytes.to_vec();
-        self.hex = crate::utils::hex_util::bytes_to_hex(bytes)?;
+        self.bytes.clear();
+        self.bytes.extend_from_slice(bytes);
+        self.hex = OnceLock::new();
         Ok(())
     }
 
@@ -204,25 +301,439 @@ impl<T: Cmd + 'static> RawCapsule<T> {
         self.device_no = Some(device_no.into());
     }
 
+    // 设置 cmd，msg_type 尚未设置时同时取 cmd 的 msg_type
     pub fn set_cmd(&mut self, cmd: T) {
+        if self.msg_type.is_none() {
+            self.msg_type = cmd.msg_type();
+        }
         self.cmd = Some(cmd);
     }
 
+    pub fn set_msg_type(&mut self, msg_type: MsgTypeEnum) {
+        self.msg_type = Some(msg_type);
+    }
+
     pub fn set_temp_bytes(&mut self, bytes: &[u8]) {
         self.temp_bytes = bytes.to_vec();
     }
 
+    /// 设置字段。没有 index 的字段按在 `fields` 中的位置排列，有 index 的字段插入到该位置
+    /// (同一位置时排在前面)；之后所有字段按顺序重新编号，index 保持唯一
     pub fn set_fields(&mut self, fields: Vec<ReportField>) {
-        self.field_details = fields;
+        set_indexed(&mut self.field_details, fields);
     }
 
+    /// 追加字段，没有 index 的字段排在已有字段之后，有 index 的字段插入到该位置；
+    /// 之后所有字段按顺序重新编号，index 保持唯一
     pub fn append_fields(&mut self, fields: Vec<ReportField>) {
-        self.field_details.extend(fields);
+        append_indexed(&mut self.field_details, fields);
+    }
+
+    /// 保留原始字段。`ProtocolConfig::retain_raw_fields` 为 true 时由默认编解码流程填充
+    pub fn set_raw_fields(&mut self, raw_fields: Vec<Rawfield>) {
+        self.raw_fields = Some(raw_fields);
+    }
+
+    pub fn raw_fields(&self) -> Option<&[Rawfield]> {
+        self.raw_fields.as_deref()
+    }
+
+    pub fn take_raw_fields(&mut self) -> Option<Vec<Rawfield>> {
+        self.raw_fields.take()
+    }
+
+    pub fn received_at(&self) -> Option<SystemTime> {
+        self.received_at
+    }
+
+    pub fn set_received_at(&mut self, received_at: SystemTime) {
+        self.received_at = Some(received_at);
+    }
+
+    pub fn decoded_at(&self) -> Option<SystemTime> {
+        self.decoded_at
+    }
+
+    // 记录解码完成的时间 (当前时间)
+    pub fn mark_decoded(&mut self) {
+        self.decoded_at = Some(SystemTime::now());
+    }
+
+    /// 处理耗时：从收到报文到解码完成，任一时间缺失时为 None
+    pub fn processing_latency(&self) -> Option<Duration> {
+        self.decoded_at?.duration_since(self.received_at?).ok()
+    }
+
+    /// 是否为去重窗口内的重传帧
+    pub fn is_duplicate(&self) -> bool {
+        self.duplicate
+    }
+
+    pub fn set_duplicate(&mut self, duplicate: bool) {
+        self.duplicate = duplicate;
+    }
+
+    /// 上行 CRC 校验结果，没有校验 CRC 时为 None
+    pub fn crc_valid(&self) -> Option<bool> {
+        self.crc_valid
+    }
+
+    pub fn set_crc_valid(&mut self, crc_valid: Option<bool>) {
+        self.crc_valid = crc_valid;
+    }
+
+    pub fn warnings(&self) -> &[ProtocolWarning] {
+        &self.warnings
+    }
+
+    /// 记录一条非致命告警，报文仍视为解析成功
+    pub fn add_warning(&mut self, warning: ProtocolWarning) {
+        self.warnings.push(warning);
+    }
+
+    pub fn take_warnings(&mut self) -> Vec<ProtocolWarning> {
+        std::mem::take(&mut self.warnings)
     }
 
+    /// 在最前面插入字段：已有字段的 index 整体后移，新字段按位置编号
     pub fn prepend_fields(&mut self, fields: Vec<ReportField>) {
+        let shift = fields.len() as u32;
+        self.field_details
+            .iter_mut()
+            .for_each(|field| field.index = field.index.map(|index| index + shift));
         let mut new_fields = fields;
+        index_fields(&mut new_fields, 0);
         new_fields.append(&mut self.field_details);
         self.field_details = new_fields;
+        sort_fields(&mut self.field_details);
+    }
+}
+
+// 深度优先查找，先检查字段本身再检查子字段
+fn _find_field<'a>(fields: &'a [ReportField], code: &str) -> Option<&'a ReportField> {
+    fields.iter().find_map(|field| {
+        (field.code == code)
+            .then_some(field)
+            .or_else(|| _find_field(&field.children, code))
+    })
+}
+
+// 给没有 index 的字段按位置编号 (从 start 开始)，并按 index 稳定排序
+fn index_fields(fields: &mut [ReportField], start: u32) {
+    fields
+        .iter_mut()
+        .enumerate()
+        .filter(|(_, field)| field.index.is_none())
+        .for_each(|(i, field)| field.index = Some(start + i as u32));
+    sort_fields(fields);
+}
+
+// 替换全部字段并重新编号。没有 index 的字段以位置为 index，与已有 index 相同时排在后面
+pub(crate) fn set_indexed(fields: &mut Vec<ReportField>, incoming: Vec<ReportField>) {
+    let mut keyed: Vec<(u32, bool, ReportField)> = incoming
+        .into_iter()
+        .enumerate()
+        .map(|(i, field)| match field.index {
+            Some(index) => (index, false, field),
+            None => (i as u32, true, field),
+        })
+        .collect();
+    keyed.sort_by_key(|(index, positional, _)| (*index, *positional));
+    *fields = keyed.into_iter().map(|(_, _, field)| field).collect();
+    renumber_fields(fields);
+}
+
+// 追加字段并重新编号。新字段放在前面再稳定排序，index 相同时新字段排在已有字段之前
+pub(crate) fn append_indexed(fields: &mut Vec<ReportField>, mut incoming: Vec<ReportField>) {
+    index_fields(&mut incoming, next_index(fields));
+    incoming.append(fields);
+    sort_fields(&mut incoming);
+    renumber_fields(&mut incoming);
+    *fields = incoming;
+}
+
+fn renumber_fields(fields: &mut [ReportField]) {
+    fields
+        .iter_mut()
+        .enumerate()
+        .for_each(|(i, field)| field.index = Some(i as u32));
+}
+
+fn next_index(fields: &[ReportField]) -> u32 {
+    fields
+        .iter()
+        .filter_map(|field| field.index)
+        .max()
+        .map_or(0, |max| max + 1)
+}
+
+fn sort_fields(fields: &mut [ReportField]) {
+    fields.sort_by_key(|field| field.index);
+}
+
+/// 下行 RawCapsule 的构建器：按顺序写入数据域字段，`finalize` 时按 `ProtocolConfig`
+/// 补上帧头、长度、CRC、帧尾。
+///
+/// ```ignore
+/// let capsule = RawCapsule::downstream_builder(MeterCmd::Valve)
+///     .device("0001", "")
+///     .field("控制码", &[0xA2], "A2")
+///     .field("阀门", &[0x01], "开阀")
+///     .finalize(&MeterConfig)?;
+/// ```
+pub struct DownstreamBuilder<T: Cmd> {
+    cmd: T,
+    device_no: Option<String>,
+    device_id: Option<String>,
+    // (title, bytes, value)
+    fields: Vec<(String, Vec<u8>, String)>,
+}
+
+impl<T: Cmd + 'static> DownstreamBuilder<T> {
+    pub fn new(cmd: T) -> Self {
+        Self {
+            cmd,
+            device_no: None,
+            device_id: None,
+            fields: Vec::new(),
+        }
+    }
+
+    /// 设备号与设备 id，device_id 为空时视为未设置
+    pub fn device(mut self, device_no: &str, device_id: &str) -> Self {
+        self.device_no = Some(device_no.to_string());
+        self.device_id = (!device_id.is_empty()).then(|| device_id.to_string());
+        self
+    }
+
+    /// 追加一个数据域字段，value 为翻译后的值
+    pub fn field(mut self, title: &str, bytes: &[u8], value: &str) -> Self {
+        self.fields
+            .push((title.to_string(), bytes.to_vec(), value.to_string()));
+        self
+    }
+
+    /// 写入帧头/长度/数据域/CRC/帧尾，生成下行 RawCapsule
+    pub fn finalize(
+        self,
+        config: &impl ProtocolConfig,
+    ) -> crate::defi::ProtocolResult<RawCapsule<T>> {
+        let mut capsule = RawCapsule::new_downstream(self.cmd, "", "");
+        capsule.device_no = self.device_no;
+        capsule.device_id = self.device_id;
+        let fields = self.fields;
+        crate::core::pipeline::encode_frame(config, &mut capsule, |writer| {
+            for (title, bytes, value) in &fields {
+                writer.write_bytes(title, bytes, value)?;
+            }
+            Ok(())
+        })?;
+        Ok(capsule)
+    }
+}
+
+// RawCapsule 的序列化形式：cmd 只保留 code/title，bytes 以 hex 表示，
+// temp_bytes 与 raw_fields 不保存 (ReportField 中已有 hex 与偏移)
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CapsuleRecord {
+    hex: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cmd_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cmd_title: Option<String>,
+    // MsgTypeEnum::code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    msg_type: Option<String>,
+    #[serde(default)]
+    device_no: Option<String>,
+    #[serde(default)]
+    device_id: Option<String>,
+    direction: DirectionEnum,
+    success: bool,
+    #[serde(default)]
+    fields: Vec<ReportField>,
+    // 毫秒级 Unix 时间戳
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    received_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    decoded_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<ProtocolWarning>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    duplicate: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    crc_valid: Option<bool>,
+}
+
+// 报文缓冲区归还到复用池
+impl<T: Cmd> Drop for RawCapsule<T> {
+    fn drop(&mut self) {
+        buffer_pool::give(std::mem::take(&mut self.bytes));
+        buffer_pool::give(std::mem::take(&mut self.temp_bytes));
+    }
+}
+
+fn _to_millis(time: Option<SystemTime>) -> Option<u64> {
+    time.and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+}
+
+fn _from_millis(millis: Option<u64>) -> Option<SystemTime> {
+    millis.map(|ms| UNIX_EPOCH + Duration::from_millis(ms))
+}
+
+/// 序列化为 JSON 等格式用于归档 (例如写入 Kafka)。cmd 只输出 `cmdCode` / `cmdTitle`，
+/// 反序列化后 cmd 为 None，需要时通过 `set_cmd` 按 code 还原。
+impl<T: Cmd> Serialize for RawCapsule<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        CapsuleRecord {
+            hex: self
+                .hex
+                .get()
+                .cloned()
+                .unwrap_or_else(|| hex::encode_upper(&self.bytes)),
+            cmd_code: self.cmd.as_ref().map(|cmd| cmd.code()),
+            cmd_title: self.cmd.as_ref().map(|cmd| cmd.title()),
+            msg_type: self.msg_type.as_ref().map(MsgTypeEnum::code),
+            device_no: self.device_no.clone(),
+            device_id: self.device_id.clone(),
+            direction: self.direction.clone(),
+            success: self.success,
+            fields: self.field_details.clone(),
+            received_at: _to_millis(self.received_at),
+            decoded_at: _to_millis(self.decoded_at),
+            warnings: self.warnings.clone(),
+            duplicate: self.duplicate,
+            crc_valid: self.crc_valid,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, T: Cmd> Deserialize<'de> for RawCapsule<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let record = CapsuleRecord::deserialize(deserializer)?;
+        let bytes = hex_util::hex_to_bytes(&record.hex).map_err(serde::de::Error::custom)?;
+        Ok(Self {
+            bytes,
+            hex: OnceLock::new(),
+            field_details: record.fields,
+            cmd: None,
+            msg_type: record
+                .msg_type
+                .and_then(|code| MsgTypeEnum::code_of(&code).ok()),
+            device_no: record.device_no,
+            device_id: record.device_id,
+            temp_bytes: Vec::new(),
+            direction: record.direction,
+            success: record.success,
+            raw_fields: None,
+            received_at: _from_millis(record.received_at),
+            decoded_at: _from_millis(record.decoded_at),
+            warnings: record.warnings,
+            duplicate: record.duplicate,
+            crc_valid: record.crc_valid,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct Report;
+
+    impl Cmd for Report {
+        fn code(&self) -> String {
+            "01".into()
+        }
+        fn title(&self) -> String {
+            "数据上报".into()
+        }
+    }
+
+    fn names(capsule: &RawCapsule<Report>) -> Vec<(&str, Option<u32>)> {
+        capsule
+            .field_details()
+            .iter()
+            .map(|f| (f.name.as_str(), f.index))
+            .collect()
+    }
+
+    #[test]
+    fn test_field_index_order() {
+        let mut capsule = RawCapsule::<Report>::new_upstream(&[0x68, 0x16]);
+        capsule.set_fields(vec![
+            ReportField::new("帧头", "zhentou", "68".into()),
+            ReportField::new("帧尾", "zhenwei", "16".into()),
+        ]);
+        capsule.append_fields(vec![ReportField::new("电压", "dianya", "3.6".into())]);
+        capsule.prepend_fields(vec![ReportField::new("设备号", "shebeihao", "0001".into())]);
+        assert_eq!(
+            names(&capsule),
+            [
+                ("设备号", Some(0)),
+                ("帧头", Some(1)),
+                ("帧尾", Some(2)),
+                ("电压", Some(3))
+            ]
+        );
+
+        // 已有 index 的字段按 index 插入到对应位置，之后的字段顺延，index 不重复
+        capsule.append_fields(vec![
+            ReportField::new("长度", "changdu", "2".into()).with_index(1),
+        ]);
+        assert_eq!(
+            names(&capsule),
+            [
+                ("设备号", Some(0)),
+                ("长度", Some(1)),
+                ("帧头", Some(2)),
+                ("帧尾", Some(3)),
+                ("电压", Some(4))
+            ]
+        );
+
+        // 混合输入：有 index 的字段插入到该位置，其余按位置排列，整体重新编号
+        capsule.set_fields(vec![
+            ReportField::new("帧头", "zhentou", "68".into()),
+            ReportField::new("电压", "dianya", "3.6".into()),
+            ReportField::new("设备号", "shebeihao", "0001".into()).with_index(1),
+            ReportField::new("帧尾", "zhenwei", "16".into()),
+        ]);
+        assert_eq!(
+            names(&capsule),
+            [
+                ("帧头", Some(0)),
+                ("设备号", Some(1)),
+                ("电压", Some(2)),
+                ("帧尾", Some(3))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_field_lookup() {
+        let mut capsule = RawCapsule::<Report>::new_upstream(&[0x68, 0x16]);
+        capsule.set_fields(vec![
+            ReportField::new("累计用量", "leiji", "3.75 m³".into())
+                .with_numeric(rust_decimal::Decimal::new(375, 2), Some("m³")),
+            ReportField::group(
+                "记录",
+                "jilu",
+                vec![ReportField::new("次数", "cishu", "12".into())],
+            ),
+        ]);
+        assert_eq!(capsule.get_field("leiji").unwrap().value, "3.75 m³");
+        assert_eq!(capsule.get_field_value_as::<f64>("leiji").unwrap(), 3.75);
+        assert_eq!(capsule.get_field_value_as::<u32>("cishu").unwrap(), 12);
+        assert!(capsule.get_field_value_as::<u32>("jilu").is_err());
+        assert!(capsule.get_field("missing").is_none());
+
+        let map = capsule.fields_as_map();
+        assert_eq!(map.get("cishu").map(String::as_str), Some("12"));
+        assert!(!map.contains_key("jilu"));
     }
 }
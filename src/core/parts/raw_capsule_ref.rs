@@ -0,0 +1,264 @@
+use std::{
+    borrow::Cow,
+    sync::OnceLock,
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    DirectionEnum, ProtocolWarning, RawCapsule, Rawfield, ReportField,
+    core::parts::{
+        raw_capsule::{append_indexed, set_indexed},
+        traits::Cmd,
+    },
+    utils::buffer_pool,
+};
+
+/// `RawCapsule` 的借用版本，用于上行解码的热路径：报文字节直接借用输入，
+/// 设备号等字符串用 `Cow` 保存 (可借用报文解析出的 &str)。
+/// 只有需要保留该帧 (缓存、回复、归档) 时才调用 `into_owned` 转换为 `RawCapsule`。
+#[derive(Debug, Clone)]
+pub struct RawCapsuleRef<'a, T: Cmd> {
+    pub(crate) bytes: &'a [u8],
+    pub(crate) field_details: Vec<ReportField>,
+    pub(crate) cmd: Option<T>,
+    pub(crate) device_no: Option<Cow<'a, str>>,
+    pub(crate) device_id: Option<Cow<'a, str>>,
+    // 临时二进制存放处，借用 bytes 中的一段
+    pub(crate) temp_bytes: &'a [u8],
+    pub(crate) success: bool,
+    pub(crate) raw_fields: Option<Vec<Rawfield>>,
+    pub(crate) received_at: Option<SystemTime>,
+    pub(crate) decoded_at: Option<SystemTime>,
+    pub(crate) warnings: Vec<ProtocolWarning>,
+    pub(crate) crc_valid: Option<bool>,
+}
+
+impl<'a, T: Cmd + 'static> RawCapsuleRef<'a, T> {
+    pub fn new_upstream(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            field_details: Vec::new(),
+            cmd: None,
+            device_no: None,
+            device_id: None,
+            temp_bytes: &[],
+            success: true,
+            raw_fields: None,
+            received_at: None,
+            decoded_at: None,
+            warnings: Vec::new(),
+            crc_valid: None,
+        }
+    }
+
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// 报文的大写 hex，每次调用都会重新生成
+    pub fn hex(&self) -> String {
+        hex::encode_upper(self.bytes)
+    }
+
+    pub fn field_details(&self) -> &[ReportField] {
+        &self.field_details
+    }
+
+    pub fn cmd(&self) -> Option<&T> {
+        self.cmd.as_ref()
+    }
+
+    pub fn device_no(&self) -> Option<&str> {
+        self.device_no.as_deref()
+    }
+
+    pub fn device_id(&self) -> Option<&str> {
+        self.device_id.as_deref()
+    }
+
+    pub fn temp_bytes(&self) -> &'a [u8] {
+        self.temp_bytes
+    }
+
+    pub fn direction(&self) -> DirectionEnum {
+        DirectionEnum::Upstream
+    }
+
+    pub fn success(&self) -> bool {
+        self.success
+    }
+
+    pub fn received_at(&self) -> Option<SystemTime> {
+        self.received_at
+    }
+
+    pub fn decoded_at(&self) -> Option<SystemTime> {
+        self.decoded_at
+    }
+
+    /// 同 `RawCapsule::processing_latency`
+    pub fn processing_latency(&self) -> Option<Duration> {
+        self.decoded_at?.duration_since(self.received_at?).ok()
+    }
+
+    pub fn raw_fields(&self) -> Option<&[Rawfield]> {
+        self.raw_fields.as_deref()
+    }
+
+    pub fn warnings(&self) -> &[ProtocolWarning] {
+        &self.warnings
+    }
+
+    /// 同 `RawCapsule::crc_valid`
+    pub fn crc_valid(&self) -> Option<bool> {
+        self.crc_valid
+    }
+
+    pub fn set_crc_valid(&mut self, crc_valid: Option<bool>) {
+        self.crc_valid = crc_valid;
+    }
+
+    pub fn fail(&mut self) {
+        self.success = false;
+    }
+
+    pub fn set_cmd(&mut self, cmd: T) {
+        self.cmd = Some(cmd);
+    }
+
+    pub fn set_device_no(&mut self, device_no: impl Into<Cow<'a, str>>) {
+        self.device_no = Some(device_no.into());
+    }
+
+    pub fn set_device_id(&mut self, device_id: impl Into<Cow<'a, str>>) {
+        self.device_id = Some(device_id.into());
+    }
+
+    pub fn set_temp_bytes(&mut self, bytes: &'a [u8]) {
+        self.temp_bytes = bytes;
+    }
+
+    pub fn set_received_at(&mut self, received_at: SystemTime) {
+        self.received_at = Some(received_at);
+    }
+
+    // 记录解码完成的时间 (当前时间)
+    pub fn mark_decoded(&mut self) {
+        self.decoded_at = Some(SystemTime::now());
+    }
+
+    /// 同 `RawCapsule::set_raw_fields`
+    pub fn set_raw_fields(&mut self, raw_fields: Vec<Rawfield>) {
+        self.raw_fields = Some(raw_fields);
+    }
+
+    /// 同 `RawCapsule::set_fields`
+    pub fn set_fields(&mut self, fields: Vec<ReportField>) {
+        set_indexed(&mut self.field_details, fields);
+    }
+
+    /// 同 `RawCapsule::append_fields`
+    pub fn append_fields(&mut self, fields: Vec<ReportField>) {
+        append_indexed(&mut self.field_details, fields);
+    }
+
+    pub fn add_warning(&mut self, warning: ProtocolWarning) {
+        self.warnings.push(warning);
+    }
+
+    /// 转换为拥有所有权的 `RawCapsule`，此时才复制报文字节
+    pub fn into_owned(self) -> RawCapsule<T> {
+        RawCapsule {
+            bytes: buffer_pool::copy(self.bytes),
+            hex: OnceLock::new(),
+            field_details: self.field_details,
+            msg_type: self.cmd.as_ref().and_then(|cmd| cmd.msg_type()),
+            cmd: self.cmd,
+            device_no: self.device_no.map(Cow::into_owned),
+            device_id: self.device_id.map(Cow::into_owned),
+            temp_bytes: self.temp_bytes.to_vec(),
+            direction: DirectionEnum::Upstream,
+            success: self.success,
+            raw_fields: self.raw_fields,
+            received_at: self.received_at,
+            decoded_at: self.decoded_at,
+            warnings: self.warnings,
+            duplicate: false,
+            crc_valid: self.crc_valid,
+        }
+    }
+}
+
+impl<'a, T: Cmd + 'static> From<RawCapsuleRef<'a, T>> for RawCapsule<T> {
+    fn from(capsule: RawCapsuleRef<'a, T>) -> Self {
+        capsule.into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug)]
+    struct Report;
+
+    impl Cmd for Report {
+        fn code(&self) -> String {
+            "01".into()
+        }
+
+        fn title(&self) -> String {
+            "数据上报".into()
+        }
+
+        fn direction(&self) -> DirectionEnum {
+            DirectionEnum::Upstream
+        }
+    }
+
+    #[test]
+    fn test_into_owned() {
+        let frame = [0x68, 0x00, 0x01, 0x01, 0x16];
+        let device_no = "0001".to_string();
+        let mut capsule = RawCapsuleRef::new_upstream(&frame);
+        capsule.set_cmd(Report);
+        capsule.set_device_no(device_no.as_str());
+        capsule.set_temp_bytes(&frame[1..4]);
+        assert!(matches!(capsule.device_no, Some(Cow::Borrowed(_))));
+        assert_eq!(capsule.hex(), "6800010116");
+
+        let owned: RawCapsule<Report> = capsule.into();
+        assert_eq!(owned.bytes(), frame);
+        assert_eq!(owned.hex(), "6800010116");
+        assert_eq!(owned.device_no(), Some("0001"));
+        assert_eq!(owned.temp_bytes(), [0x00, 0x01, 0x01]);
+        assert!(owned.is_upstream() && owned.is_success());
+    }
+
+    #[test]
+    fn test_append_fields_renumbers() {
+        let mut capsule = RawCapsuleRef::<Report>::new_upstream(&[0x68, 0x16]);
+        capsule.set_fields(vec![
+            ReportField::new("帧头", "zhentou", "68".into()),
+            ReportField::new("帧尾", "zhenwei", "16".into()),
+        ]);
+        capsule.append_fields(vec![
+            ReportField::new("电压", "dianya", "3.6".into()),
+            ReportField::new("长度", "changdu", "2".into()).with_index(1),
+        ]);
+        let names: Vec<(&str, Option<u32>)> = capsule
+            .field_details()
+            .iter()
+            .map(|f| (f.name.as_str(), f.index))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("帧头", Some(0)),
+                ("长度", Some(1)),
+                ("帧尾", Some(2)),
+                ("电压", Some(3))
+            ]
+        );
+    }
+}
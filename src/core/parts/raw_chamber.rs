@@ -1,13 +1,20 @@
+use crate::MsgTypeEnum;
 use crate::core::parts::raw_capsule::RawCapsule;
 use crate::core::parts::traits::Cmd;
+use serde::{Deserialize, Deserializer, Serialize};
 
 /// 对上行而言，它通常需要回复。因此上行需要2个raw-capsule，一上一下. RawChamber用来组合2个raw-capsule
+/// 有的上报需要回复多帧 (例如应答 + 参数下发)，此时下行按发送顺序保存多个raw-capsule
 /// 对下行而言，它只需要一个下行的raw-capsule. 此时不需要RawChamber
 
-#[derive(Debug, Clone, Default)]
+// 序列化规则见 RawCapsule
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", bound = "")]
 pub struct RawChamber<T: Cmd + Clone> {
     pub(crate) upstream: Option<RawCapsule<T>>,
-    pub(crate) downstream: Option<RawCapsule<T>>,
+    // 下行按发送顺序排列，兼容旧版本归档中的单个 downstream
+    #[serde(default, alias = "downstream", deserialize_with = "_one_or_many")]
+    pub(crate) downstreams: Vec<RawCapsule<T>>,
     pub(crate) cmd_code: String,
     pub(crate) success: bool,
 }
@@ -27,12 +34,39 @@ impl<T: Cmd + Clone> RawChamber<T> {
 
         Self {
             upstream: Some(in_capsule.clone()),
-            downstream: Some(out_capsule.clone()),
+            downstreams: vec![out_capsule.clone()],
             cmd_code,
             success,
         }
     }
 
+    /// 一条上行对应多帧下行，`out_capsules` 即发送顺序。cmd_code 取第一帧下行的命令
+    pub fn with_downstreams(in_capsule: &RawCapsule<T>, out_capsules: Vec<RawCapsule<T>>) -> Self {
+        let cmd_code = out_capsules
+            .iter()
+            .find_map(|capsule| capsule.cmd.as_ref().map(|cmd| cmd.code()))
+            .or_else(|| in_capsule.cmd.as_ref().map(|cmd| cmd.code()))
+            .unwrap_or_default();
+        let success = in_capsule.success && out_capsules.iter().all(|capsule| capsule.success);
+        Self {
+            upstream: Some(in_capsule.clone()),
+            downstreams: out_capsules,
+            cmd_code,
+            success,
+        }
+    }
+
+    /// 追加一帧下行，排在已有下行之后发送
+    pub fn push_downstream(&mut self, capsule: RawCapsule<T>) {
+        if self.cmd_code.is_empty()
+            && let Some(cmd) = capsule.cmd.as_ref()
+        {
+            self.cmd_code = cmd.code();
+        }
+        self.success &= capsule.success;
+        self.downstreams.push(capsule);
+    }
+
     // Getter methods
     pub fn upstream(&self) -> Option<&RawCapsule<T>> {
         self.upstream.as_ref()
@@ -42,12 +76,22 @@ impl<T: Cmd + Clone> RawChamber<T> {
         self.upstream.clone()
     }
 
+    /// 第一帧下行
     pub fn downstream(&self) -> Option<&RawCapsule<T>> {
-        self.downstream.as_ref()
+        self.downstreams.first()
     }
 
     pub fn downstream_clone(&self) -> Option<RawCapsule<T>> {
-        self.downstream.clone()
+        self.downstreams.first().cloned()
+    }
+
+    /// 全部下行，按发送顺序
+    pub fn downstreams(&self) -> &[RawCapsule<T>] {
+        &self.downstreams
+    }
+
+    pub fn downstreams_clone(&self) -> Vec<RawCapsule<T>> {
+        self.downstreams.clone()
     }
 
     pub fn cmd_code(&self) -> &str {
@@ -62,6 +106,17 @@ impl<T: Cmd + Clone> RawChamber<T> {
         self.success
     }
 
+    /// 消息类型：优先取上行，上行没有时取第一帧下行
+    pub fn msg_type(&self) -> Option<&MsgTypeEnum>
+    where
+        T: 'static,
+    {
+        self.upstream
+            .as_ref()
+            .and_then(|cap| cap.msg_type())
+            .or_else(|| self.downstream().and_then(|cap| cap.msg_type()))
+    }
+
     pub fn device_no(&self) -> Option<&str>
     where
         T: 'static,
@@ -69,7 +124,7 @@ impl<T: Cmd + Clone> RawChamber<T> {
         self.upstream
             .as_ref()
             .and_then(|cap| cap.device_no())
-            .or_else(|| self.downstream.as_ref().and_then(|cap| cap.device_no()))
+            .or_else(|| self.downstream().and_then(|cap| cap.device_no()))
     }
 
     pub fn device_no_clone(&self) -> Option<String>
@@ -79,11 +134,7 @@ impl<T: Cmd + Clone> RawChamber<T> {
         self.upstream
             .as_ref()
             .and_then(|cap| cap.device_no_clone())
-            .or_else(|| {
-                self.downstream
-                    .as_ref()
-                    .and_then(|cap| cap.device_no_clone())
-            })
+            .or_else(|| self.downstream().and_then(|cap| cap.device_no_clone()))
     }
 
     pub fn device_id(&self) -> Option<&str>
@@ -93,7 +144,7 @@ impl<T: Cmd + Clone> RawChamber<T> {
         self.upstream
             .as_ref()
             .and_then(|cap| cap.device_id())
-            .or_else(|| self.downstream.as_ref().and_then(|cap| cap.device_id()))
+            .or_else(|| self.downstream().and_then(|cap| cap.device_id()))
     }
 
     pub fn device_id_clone(&self) -> Option<String>
@@ -103,10 +154,98 @@ impl<T: Cmd + Clone> RawChamber<T> {
         self.upstream
             .as_ref()
             .and_then(|cap| cap.device_id_clone())
-            .or_else(|| {
-                self.downstream
-                    .as_ref()
-                    .and_then(|cap| cap.device_id_clone())
-            })
+            .or_else(|| self.downstream().and_then(|cap| cap.device_id_clone()))
+    }
+}
+
+// 旧版本归档中 downstream 是单个对象 (或 null)，新版本是数组
+fn _one_or_many<'de, D, T>(deserializer: D) -> Result<Vec<RawCapsule<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Cmd,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged, bound = "")]
+    enum OneOrMany<T: Cmd> {
+        Many(Vec<RawCapsule<T>>),
+        One(Option<Box<RawCapsule<T>>>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::Many(capsules) => capsules,
+        OneOrMany::One(capsule) => capsule.map(|capsule| *capsule).into_iter().collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug)]
+    struct Ack;
+
+    impl Cmd for Ack {
+        fn code(&self) -> String {
+            "81".into()
+        }
+        fn title(&self) -> String {
+            "应答".into()
+        }
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let mut up = RawCapsule::<Ack>::new_upstream(&[0x68, 0x01, 0x16]);
+        up.set_device_no("0001");
+        let mut down = RawCapsule::new_downstream_from_upstream(&up);
+        down.set_cmd(Ack);
+        down.set_bytes_and_generate_hex(&[0x68, 0x81, 0x16])
+            .unwrap();
+        let chamber = RawChamber::new(&up, &down);
+
+        let json = serde_json::to_value(&chamber).unwrap();
+        assert_eq!(json["cmdCode"], "81");
+        assert_eq!(json["downstreams"][0]["cmdTitle"], "应答");
+        assert_eq!(json["upstream"]["direction"], "upstream");
+
+        let restored: RawChamber<Ack> = serde_json::from_value(json).unwrap();
+        let restored_down = restored.downstream().unwrap();
+        assert_eq!(restored_down.bytes(), &[0x68, 0x81, 0x16]);
+        assert!(restored_down.cmd().is_none());
+        assert!(matches!(
+            restored_down.msg_type(),
+            Some(MsgTypeEnum::DeviceParamSetting)
+        ));
+        assert_eq!(restored.device_no(), Some("0001"));
+
+        // 旧格式：单个 downstream
+        let mut legacy = serde_json::to_value(&chamber).unwrap();
+        let down_json = legacy["downstreams"][0].take();
+        legacy.as_object_mut().unwrap().remove("downstreams");
+        legacy["downstream"] = down_json;
+        let restored: RawChamber<Ack> = serde_json::from_value(legacy).unwrap();
+        assert_eq!(restored.downstreams().len(), 1);
+    }
+
+    #[test]
+    fn test_multiple_downstreams() {
+        let up = RawCapsule::<Ack>::new_upstream(&[0x68, 0x01, 0x16]);
+        let mut ack = RawCapsule::new_downstream(Ack, "0001", "");
+        ack.set_bytes_and_generate_hex(&[0x68, 0x81, 0x16]).unwrap();
+        let mut push = RawCapsule::new_downstream(Ack, "0001", "");
+        push.set_bytes_and_generate_hex(&[0x68, 0x82, 0x16])
+            .unwrap();
+        let mut chamber = RawChamber::with_downstreams(&up, vec![ack]);
+        chamber.push_downstream(push);
+
+        let hexes: Vec<&str> = chamber.downstreams().iter().map(|c| c.hex()).collect();
+        assert_eq!(hexes, ["688116", "688216"]);
+        assert_eq!(chamber.downstream().unwrap().hex(), "688116");
+        assert_eq!(chamber.cmd_code(), "81");
+        assert!(chamber.success());
+
+        let response = crate::JniResponse::upstream_response(&chamber).unwrap();
+        assert_eq!(response.rsp_hex(), "688116");
+        assert_eq!(response.rsp_hexes(), ["688116", "688216"]);
+        assert_eq!(response.msg_type(), Some("device_param_setting"));
     }
 }
@@ -1,3 +1,7 @@
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use rust_decimal::Decimal;
+
 // 报文帧字段 最小解析单位
 #[derive(Debug, Clone, Default)]
 pub struct Rawfield {
@@ -5,6 +9,11 @@ pub struct Rawfield {
     pub(crate) title: String,
     pub(crate) hex: String,
     pub(crate) value: String,
+    // 在整帧报文中的起始字节偏移，由 Reader / Writer 记录
+    pub(crate) offset: Option<usize>,
+    // 数值型字段的数值与单位，由 FieldConvertDecoder 填充
+    pub(crate) numeric_value: Option<Decimal>,
+    pub(crate) unit: Option<String>,
 }
 
 impl Rawfield {
@@ -15,6 +24,9 @@ impl Rawfield {
             title,
             hex: hex::encode_upper(raw_bytes), // 编码为Hex字符串
             value,
+            offset: None,
+            numeric_value: None,
+            unit: None,
         }
     }
 
@@ -24,9 +36,25 @@ impl Rawfield {
             title: title.into(),
             hex: hex.into(),
             value,
+            offset: None,
+            numeric_value: None,
+            unit: None,
         }
     }
 
+    /// 设置字段在整帧报文中的起始字节偏移
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// 设置数值与单位
+    pub fn with_numeric(mut self, numeric_value: Decimal, unit: Option<String>) -> Self {
+        self.numeric_value = Some(numeric_value);
+        self.unit = unit;
+        self
+    }
+
     // pub fn hex_to_bytes(&self) -> crate::defi::ProtocolResult<Vec<u8>> {
     //     crate::utils::hex_util::hex_to_bytes(&self.hex)
     // }
@@ -63,4 +91,16 @@ impl Rawfield {
     pub fn value_clone(&self) -> String {
         self.value.clone()
     }
+
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    pub fn numeric_value(&self) -> Option<Decimal> {
+        self.numeric_value
+    }
+
+    pub fn unit(&self) -> Option<&str> {
+        self.unit.as_deref()
+    }
 }
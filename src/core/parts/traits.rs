@@ -1,15 +1,42 @@
-use std::collections::HashMap;
+use alloc::sync::Arc;
+use core::fmt;
 
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
 use crate::{
     CrcType, DirectionEnum, FieldCompareDecoder, FieldConvertDecoder, FieldEnumDecoder, FieldType,
-    MsgTypeEnum, ProtocolError, ProtocolResult, Rawfield, Reader, Symbol, TryFromBytes, Writer,
-    core::{RW, parts::transport_pair::TransportPair, type_converter::FieldTranslator},
+    HexDigestError, MsgTypeEnum, ProtocolError, ProtocolOutcome, ProtocolResult, Rawfield, Reader,
+    Symbol, TryFromBytes, Writer,
+    collections::HashMap,
+    core::{
+        RW,
+        parts::{
+            param_descriptor::ParamDescriptor,
+            param_value::{EncodingInput, ParamValue},
+        },
+        type_converter::FieldTranslator,
+    },
     hex_util,
+    math_util::{self, DecimalRoundingMode},
+};
+#[cfg(feature = "std")]
+use crate::{
+    core::parts::{
+        alert_rules::AlertRules,
+        cipher_spec::CipherSpec,
+        transport_pair::{TransportCounter, TransportPair},
+    },
+    sequence_util::SequenceGenerator,
+    timestamp_util::{self, TimestampType},
 };
 use dyn_clone::DynClone;
+#[cfg(feature = "std")]
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 
 /// Trait 定义了缓存中设备状态对象需要实现的方法。
 /// 添加了 Clone, Send, Sync, 'static 约束以用于 moka 缓存。
+#[cfg(feature = "std")]
 pub trait Transport: Send + Sync + 'static {
     // 设备号(去除补位)
     fn device_no(&self) -> Option<TransportPair>;
@@ -43,6 +70,30 @@ pub trait Transport: Send + Sync + 'static {
     // 下行消息序号(每次下行+1)
     fn downstream_count(&self) -> Option<TransportPair>;
 
+    // 可变的上行消息序号，提供后即可使用 increment_upstream
+    fn upstream_counter(&self) -> Option<&TransportCounter> {
+        None
+    }
+
+    // 可变的下行消息序号，提供后即可使用 increment_downstream
+    fn downstream_counter(&self) -> Option<&TransportCounter> {
+        None
+    }
+
+    // 上行消息序号 +1，返回新的序号 (保持原有字节宽度，溢出回绕到 0)
+    fn increment_upstream(&self) -> ProtocolResult<TransportPair> {
+        self.upstream_counter()
+            .map(TransportCounter::increment)
+            .ok_or_else(|| ProtocolError::UnsupportedMode("upstream counter is read-only".into()))
+    }
+
+    // 下行消息序号 +1，返回新的序号
+    fn increment_downstream(&self) -> ProtocolResult<TransportPair> {
+        self.downstream_counter()
+            .map(TransportCounter::increment)
+            .ok_or_else(|| ProtocolError::UnsupportedMode("downstream counter is read-only".into()))
+    }
+
     // 加密类型(-1表示不加密。0表示使用默认密钥。>=1表示使用对应的密钥)
     fn cipher_slot(&self) -> i8 {
         -1 // 提供默认实现
@@ -52,6 +103,12 @@ pub trait Transport: Send + Sync + 'static {
     fn use_cipher(&self) -> bool {
         self.cipher_slot() >= 0
     }
+
+    // 完整的加密信息 (算法、IV、MAC)，默认只由 cipher_slot 生成，算法未指定
+    fn cipher_spec(&self) -> Option<CipherSpec> {
+        self.use_cipher()
+            .then(|| CipherSpec::from_slot(self.cipher_slot()))
+    }
 }
 
 pub trait Cmd: DynClone {
@@ -76,6 +133,13 @@ pub trait Cmd: DynClone {
     }
 }
 
+/// 协议的帧结构 (报文外壳) 配置。
+/// - `head_tag` / `tail_tag`：帧头、帧尾的 hex，为空表示没有；
+/// - `crc_index`：(起始下标, 距帧尾的字节数)，CRC 覆盖 `[start, len - end)`，(0, 0) 表示不校验 CRC；
+/// - `length_index`：长度字段的 `[start, end)`，大端整数，值为整帧字节数，(0, 0) 表示没有长度字段；
+/// - `max_frame_len`：整帧的最大字节数，0 表示不限制；
+/// - `crc_little_endian`：CRC 是否按小端写入；
+/// - `requires_escaping`：帧头/帧尾字节在数据域中是否需要转义 (例如 0x7E/0x7D)。
 pub trait ProtocolConfig {
     fn head_tag(&self) -> String;
 
@@ -86,6 +150,294 @@ pub trait ProtocolConfig {
     fn crc_index(&self) -> (u8, u8);
 
     fn length_index(&self) -> (u8, u8);
+
+    fn max_frame_len(&self) -> usize {
+        0
+    }
+
+    fn crc_little_endian(&self) -> bool {
+        false
+    }
+
+    fn requires_escaping(&self) -> bool {
+        false
+    }
+
+    // 默认编解码流程是否在 RawCapsule 上保留原始字段 (Rawfield)
+    fn retain_raw_fields(&self) -> bool {
+        false
+    }
+
+    // 默认编解码流程输出的上报字段是否带来源字节信息 (hex / offset / length)，供排查时高亮报文
+    fn report_field_sources(&self) -> bool {
+        false
+    }
+
+    // 默认解码流程对上行字段应用的告警规则
+    #[cfg(feature = "std")]
+    fn alert_rules(&self) -> Option<&AlertRules> {
+        None
+    }
+
+    fn has_crc(&self) -> bool {
+        self.crc_index() != (0, 0)
+    }
+
+    /// 校验整帧长度是否超过 `max_frame_len`
+    fn check_frame_len(&self, len: usize) -> ProtocolResult<()> {
+        let max = self.max_frame_len();
+        if max > 0 && len > max {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "frame has {} bytes, exceeding the limit of {}",
+                len, max
+            )));
+        }
+        Ok(())
+    }
+
+    fn has_length(&self) -> bool {
+        let (start, end) = self.length_index();
+        start < end
+    }
+
+    /// 校验报文外壳：最大长度、帧头、帧尾、长度字段 (CRC 在读取时由 Reader 校验)
+    fn validate_envelope(&self, bytes: &[u8]) -> ProtocolResult<()> {
+        self.check_frame_len(bytes.len())?;
+        let head = hex_util::hex_to_bytes(&self.head_tag())?;
+        let tail = hex_util::hex_to_bytes(&self.tail_tag())?;
+        if bytes.len() < head.len() + tail.len() {
+            return Err(ProtocolError::InputTooShort {
+                needed: head.len() + tail.len(),
+                available: bytes.len(),
+            });
+        }
+        if !bytes.starts_with(&head) {
+            return Err(
+                ProtocolError::from(HexDigestError::InvalidHead).with_context(
+                    Some(0),
+                    Some("帧头"),
+                    None,
+                ),
+            );
+        }
+        if !bytes.ends_with(&tail) {
+            return Err(
+                ProtocolError::from(HexDigestError::InvalidTail).with_context(
+                    Some(bytes.len() - tail.len()),
+                    Some("帧尾"),
+                    None,
+                ),
+            );
+        }
+        if self.has_length() {
+            let (start, end) = self.length_index();
+            let length_bytes =
+                bytes
+                    .get(start as usize..end as usize)
+                    .ok_or(ProtocolError::InputTooShort {
+                        needed: end as usize,
+                        available: bytes.len(),
+                    })?;
+            let length = length_bytes
+                .iter()
+                .fold(0u64, |acc, b| (acc << 8) | *b as u64);
+            let actual = bytes.len() as u64;
+            if length > actual {
+                return Err(HexDigestError::TruncatedFrame {
+                    declared: length,
+                    available: bytes.len(),
+                }
+                .into());
+            }
+            if length < actual {
+                // 常见的固件差异：长度字段不计帧头帧尾、CRC，或只计长度字段之后的字节
+                let envelope = (head.len() + tail.len()) as u64;
+                let crc = if self.has_crc() { 2 } else { 0 };
+                let hint = if length == actual - envelope {
+                    Some("length field seems to exclude head and tail")
+                } else if length == actual.saturating_sub(envelope + crc) {
+                    Some("length field seems to exclude head, tail and crc")
+                } else if length == actual - end as u64 {
+                    Some("length field seems to count only the bytes after it")
+                } else {
+                    None
+                };
+                return Err(HexDigestError::LengthMismatch {
+                    declared: length,
+                    actual: bytes.len(),
+                    hint: hint.map(str::to_string),
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 条件字段：只有当依赖字段的值在 `values` 中时，才写入 (或解析) 当前字段。
+/// 编码时 `field` 是参数的 code；解码时是前面已解析字段的 title。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FieldCondition {
+    pub field: String,
+    pub values: Vec<String>,
+}
+
+impl FieldCondition {
+    pub fn new(field: &str, values: &[&str]) -> Self {
+        Self {
+            field: field.to_string(),
+            values: values.iter().map(|v| v.to_string()).collect(),
+        }
+    }
+
+    /// 依赖字段的值是否满足条件，依赖字段不存在时视为不满足
+    pub fn matches(&self, value: Option<&str>) -> bool {
+        value.is_some_and(|value| self.values.iter().any(|v| v == value))
+    }
+}
+
+/// 数组参数：可选的个数前缀 + N 个按字段规则编码的元素
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct RepeatSpec {
+    // 个数前缀的字节数 (大端)，0 表示没有前缀
+    pub count_prefix: usize,
+    pub min: usize,
+    pub max: usize,
+}
+
+impl RepeatSpec {
+    pub fn new(count_prefix: usize, min: usize, max: usize) -> Self {
+        Self {
+            count_prefix,
+            min,
+            max,
+        }
+    }
+}
+
+/// 动态默认值：每次编码时计算，例如当前时间、下一个序列号。
+/// 返回的字符串与 `default_value` 一样按字段类型编码。
+#[derive(Clone)]
+pub struct DefaultProvider(Arc<dyn Fn() -> ProtocolResult<String> + Send + Sync>);
+
+impl DefaultProvider {
+    pub fn new(provider: impl Fn() -> ProtocolResult<String> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(provider))
+    }
+
+    /// 当前本地时间，例如 `TimestampType::YyMmDdHHmmss` 配合 BCD 字段用于校时
+    #[cfg(feature = "std")]
+    pub fn timestamp(timestamp_type: TimestampType) -> Self {
+        Self::new(move || timestamp_util::now_to_timestamp(timestamp_type))
+    }
+
+    /// 序列号生成器的下一个值 (十进制)
+    #[cfg(feature = "std")]
+    pub fn sequence(generator: Arc<SequenceGenerator>) -> Self {
+        Self::new(move || Ok(generator.next().to_string()))
+    }
+
+    pub fn provide(&self) -> ProtocolResult<String> {
+        (self.0)()
+    }
+}
+
+impl fmt::Debug for DefaultProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DefaultProvider")
+    }
+}
+
+/// 字段长度不足补齐、超长截断时作用的一侧
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum PadSide {
+    // 左侧 (高位/开头)，适用于右对齐的数值字段
+    Left,
+    // 右侧 (低位/末尾)，适用于左对齐的 ASCII 字段，例如 ICCID
+    Right,
+}
+
+impl PadSide {
+    /// 把 `bytes` 调整为 `length` 字节：不足时在本侧补 `pad_byte`
+    pub fn pad(&self, bytes: Vec<u8>, length: usize, pad_byte: u8) -> Vec<u8> {
+        if bytes.len() >= length {
+            return bytes;
+        }
+        let padding = vec![pad_byte; length - bytes.len()];
+        match self {
+            PadSide::Left => [padding, bytes].concat(),
+            PadSide::Right => [bytes, padding].concat(),
+        }
+    }
+
+    /// 把 `bytes` 截断为 `length` 字节：超出时丢弃本侧的字节
+    pub fn truncate(&self, bytes: Vec<u8>, length: usize) -> Vec<u8> {
+        if bytes.len() <= length {
+            return bytes;
+        }
+        match self {
+            PadSide::Left => bytes[(bytes.len() - length)..].to_vec(),
+            PadSide::Right => bytes[..length].to_vec(),
+        }
+    }
+}
+
+/// 下发参数的校验规则，在 `to_bytes` 编码前检查
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "rule", content = "value", rename_all = "camelCase")]
+pub enum ValidationRule {
+    // 数值下限 (包含)
+    Min(f64),
+    // 数值上限 (包含)
+    Max(f64),
+    // 正则表达式，需要完整匹配时请自行加上 ^$。no_std 下不支持，校验时报 UnsupportedMode
+    Pattern(String),
+    // 允许的取值
+    OneOf(Vec<String>),
+}
+
+impl ValidationRule {
+    pub fn check(&self, input: &str) -> ProtocolResult<bool> {
+        let number = || {
+            input.trim().parse::<f64>().map_err(|_| {
+                ProtocolError::ValidationFailed(format!("'{}' is not a number", input))
+            })
+        };
+        Ok(match self {
+            ValidationRule::Min(min) => number()? >= *min,
+            ValidationRule::Max(max) => number()? <= *max,
+            #[cfg(feature = "std")]
+            ValidationRule::Pattern(pattern) => Regex::new(pattern)
+                .map_err(|e| ProtocolError::ValidationFailed(e.to_string()))?
+                .is_match(input),
+            #[cfg(not(feature = "std"))]
+            ValidationRule::Pattern(_) => {
+                return Err(ProtocolError::UnsupportedMode(
+                    "pattern rules require the std feature".into(),
+                ));
+            }
+            ValidationRule::OneOf(values) => values.iter().any(|v| v == input),
+        })
+    }
+}
+
+impl fmt::Display for ValidationRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationRule::Min(min) => write!(f, "min {}", min),
+            ValidationRule::Max(max) => write!(f, "max {}", max),
+            ValidationRule::Pattern(pattern) => write!(f, "pattern {}", pattern),
+            ValidationRule::OneOf(values) => write!(f, "one of [{}]", values.join(", ")),
+        }
+    }
 }
 
 // 下行参数设置，针对单个帧字段
@@ -123,6 +475,107 @@ pub trait AutoEncodingParam {
         true
     }
 
+    // 写入顺序。未指定的排在指定了的之后，之间保持声明顺序
+    fn order(&self) -> Option<u32> {
+        None
+    }
+
+    // 所属分组。同一分组的字段连续写入，位置由分组中第一个字段决定
+    fn group(&self) -> Option<String> {
+        None
+    }
+
+    // 写入条件，不满足时跳过该字段 (即使是必填)
+    fn condition(&self) -> Option<FieldCondition> {
+        None
+    }
+
+    // 校验规则，输入不满足时 to_bytes 返回错误 (默认值不校验)
+    fn rules(&self) -> Vec<ValidationRule> {
+        vec![]
+    }
+
+    // 按 rules 校验输入，错误信息包含字段 code 与违反的规则
+    fn validate(&self, input: &str) -> ProtocolResult<()> {
+        for rule in self.rules() {
+            let passed = rule.check(input).map_err(|e| {
+                ProtocolError::ValidationFailed(format!("Field '{}': {}", self.code(), e))
+            })?;
+            if !passed {
+                return Err(ProtocolError::ValidationFailed(format!(
+                    "Field '{}' violates rule '{}': {}",
+                    self.code(),
+                    rule,
+                    input
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    // 动态默认值，输入为空时优先于 default_value 使用
+    fn default_provider(&self) -> Option<DefaultProvider> {
+        None
+    }
+    // 长度不足时的补齐字节
+    fn pad_byte(&self) -> u8 {
+        0x00
+    }
+    // 长度不足时补齐的一侧，默认在高位补齐
+    fn pad_side(&self) -> PadSide {
+        PadSide::Left
+    }
+    // 长度超出时截断的一侧，默认丢弃高位
+    fn truncate_side(&self) -> PadSide {
+        PadSide::Left
+    }
+    // 整数字段反缩放 (input / scale) 后取整的舍入模式
+    fn rounding_mode(&self) -> DecimalRoundingMode {
+        DecimalRoundingMode::HalfUp
+    }
+    // 数组参数，不为空时参数按数组处理，byte_length 等规则作用于每个元素
+    fn repeat(&self) -> Option<RepeatSpec> {
+        None
+    }
+
+    // 数组参数：校验个数，写入个数前缀，再依次编码每个元素
+    fn to_bytes_list(&self, inputs: &[String]) -> ProtocolResult<Vec<u8>> {
+        let spec = self.repeat().unwrap_or(RepeatSpec::new(0, 0, usize::MAX));
+        if inputs.len() < spec.min || inputs.len() > spec.max {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "Field '{}' expects {}..={} items but got {}",
+                self.code(),
+                spec.min,
+                spec.max,
+                inputs.len()
+            )));
+        }
+        let mut bytes = Vec::new();
+        if spec.count_prefix > 0 {
+            if spec.count_prefix < 8 && (inputs.len() as u64) >> (spec.count_prefix * 8) != 0 {
+                return Err(ProtocolError::ValidationFailed(format!(
+                    "Field '{}' item count {} does not fit in {} bytes",
+                    self.code(),
+                    inputs.len(),
+                    spec.count_prefix
+                )));
+            }
+            bytes.extend(hex_util::hex_to_bytes(&hex_util::u64_to_hex(
+                inputs.len() as u64,
+                spec.count_prefix,
+            )?)?);
+        }
+        for input in inputs {
+            bytes.extend(self.to_bytes(input)?);
+        }
+        Ok(bytes)
+    }
+
+    // 带类型的参数值：先按字段类型转换为输入字符串，再生成bytes
+    fn to_bytes_value(&self, value: &ParamValue) -> ProtocolResult<Vec<u8>> {
+        self.to_bytes(&value.coerce_for(&self.field_type())?)
+    }
+
     // 根据实现的以上的trait规则，自动生成bytes
     fn to_bytes(&self, input: &str) -> ProtocolResult<Vec<u8>> {
         // 步骤1: 确定输入值
@@ -136,9 +589,12 @@ pub trait AutoEncodingParam {
             if !default_hex.is_empty() {
                 // 1-1: 使用 default_hex
                 bytes = hex_util::hex_to_bytes(&default_hex)?;
+            } else if let Some(provider) = self.default_provider() {
+                // 1-1: 使用动态默认值并根据 FieldType 编码
+                bytes = ft.encode_with(&provider.provide()?, self.rounding_mode())?;
             } else if !default_value.is_empty() {
                 // 1-1: 使用 default_value 并根据 FieldType 编码
-                bytes = ft.encode(&default_value)?;
+                bytes = ft.encode_with(&default_value, self.rounding_mode())?;
             } else {
                 // 1-2: 两者都为空且该值是必须的，抛错
                 if self.required() {
@@ -150,8 +606,9 @@ pub trait AutoEncodingParam {
                 bytes = Vec::new();
             }
         } else {
-            // 情况2: 输入有值
-            bytes = ft.encode(input)?;
+            // 情况2: 输入有值，先校验
+            self.validate(input)?;
+            bytes = ft.encode_with(input, self.rounding_mode())?;
         }
 
         // 步骤2: 调整字节长度
@@ -160,15 +617,13 @@ pub trait AutoEncodingParam {
 
         if expected_length > 0 && actual_length != expected_length {
             if actual_length > expected_length {
-                // 长度超过，从低位开始保留，抛弃高位
+                // 长度超过，默认从低位开始保留，抛弃高位
                 // 例如: [0x77, 0xFF, 0xBD, 0x23] 保留2字节 -> [0xBD, 0x23]
-                bytes = bytes[(actual_length - expected_length)..].to_vec();
+                bytes = self.truncate_side().truncate(bytes, expected_length);
             } else {
-                // 长度不足，在高位补0
+                // 长度不足，默认在高位补0
                 // 例如: [0xBD, 0x23] 扩展到4字节 -> [0x00, 0x00, 0xBD, 0x23]
-                let mut padded = vec![0u8; expected_length - actual_length];
-                padded.extend_from_slice(&bytes);
-                bytes = padded;
+                bytes = self.pad_side().pad(bytes, expected_length, self.pad_byte());
             }
         }
 
@@ -192,40 +647,140 @@ pub trait AutoEncoding<T: AutoEncodingParam>: Sized {
         HashMap::new()
     }
 
+    /// 分组的长度前缀字节数 (大端，值为分组内字段的总字节数)，0 表示没有长度前缀
+    fn group_length_prefix(&self, _group: &str) -> usize {
+        0
+    }
+
+    /// 按声明顺序描述每个参数 (code、title、输入类型、默认值、是否必填、校验规则等)，供前端生成表单
+    fn describe(&self) -> Vec<ParamDescriptor> {
+        self.variants()
+            .iter()
+            .map(ParamDescriptor::from_param)
+            .collect()
+    }
+
+    /// `describe` 的 JSON 形式
+    fn describe_json(&self) -> ProtocolResult<String> {
+        serde_json::to_string(&self.describe()).map_err(ProtocolError::external)
+    }
+
     // 只要定义好了trait:AutoEncodingParams，它就会自动实现它的to_bytes方法。
     // 这里只需要挨个调用AutoEncodingParams.to_bytes方法就好了
     // 返回的是整个处理的总长度
-    fn auto_process(
+    // 参数值可以是字符串，也可以是带类型的 ParamValue
+    // 写入顺序：先按 order 排序，再把同一分组的字段聚在一起 (分组有长度前缀时先写前缀)
+    fn auto_process<V: EncodingInput>(
         &self,
-        params: &HashMap<String, String>, // 输入的下发参数map
+        params: &HashMap<String, V>, // 输入的下发参数map
         writer: &mut Writer,
     ) -> ProtocolResult<u16> {
         let mut length: usize = 0;
-        let definitions = self.variants();
+        let mut definitions = self.variants();
+        // 稳定排序，order 相同或未指定时保持声明顺序
+        definitions.sort_by_key(|definition| definition.order().unwrap_or(u32::MAX));
+
+        let mut sections: Vec<(Option<String>, Vec<T>)> = Vec::new();
         for definition in definitions {
-            let code = definition.code();
-            let title = definition.title();
-            // 是否必须
-            let require = definition.required();
-
-            if let Some(input) = params.get(&code) {
-                let bytes = definition.to_bytes(input)?;
-                length += bytes.len();
-                writer.write(|| {
-                    let rf = Rawfield::new(&bytes, title, input.to_string());
-                    Ok(rf)
-                })?;
-            } else if require {
-                return Err(ProtocolError::CommonError(format!(
-                    "Required parameter '{}' not found in input params",
-                    code
-                )));
+            let group = definition.group();
+            match sections
+                .iter_mut()
+                .find(|(name, _)| group.is_some() && *name == group)
+            {
+                Some((_, members)) => members.push(definition),
+                None => sections.push((group, vec![definition])),
+            }
+        }
+
+        for (group, members) in sections {
+            let prefix_len = group
+                .as_deref()
+                .map_or(0, |name| self.group_length_prefix(name));
+            let tag = format!("group:{}", group.as_deref().unwrap_or_default());
+            if prefix_len > 0 {
+                writer.write_placeholder(&tag, prefix_len)?;
             }
+            let mut group_len: usize = 0;
+            for definition in &members {
+                group_len += _write_param(definition, params, writer)?;
+            }
+            if prefix_len > 0 {
+                let name = group.unwrap_or_default();
+                if prefix_len < 8 && (group_len as u64) >> (prefix_len * 8) != 0 {
+                    return Err(ProtocolError::ValidationFailed(format!(
+                        "group '{}' length {} does not fit in {} bytes",
+                        name, group_len, prefix_len
+                    )));
+                }
+                let prefix =
+                    hex_util::hex_to_bytes(&hex_util::u64_to_hex(group_len as u64, prefix_len)?)?;
+                writer.rewrite_placeholder(
+                    &tag,
+                    &format!("{}长度", name),
+                    &prefix,
+                    &group_len.to_string(),
+                )?;
+            }
+            length += prefix_len + group_len;
+        }
+        u16::try_from(length).map_err(|_| {
+            ProtocolError::ValidationFailed(format!(
+                "encoded length {} does not fit in u16",
+                length
+            ))
+        })
+    }
+}
+
+// 写入单个参数，返回写入的字节数。参数缺失且非必填时跳过
+fn _write_param<T: AutoEncodingParam, V: EncodingInput>(
+    definition: &T,
+    params: &HashMap<String, V>,
+    writer: &mut Writer,
+) -> ProtocolResult<usize> {
+    if let Some(condition) = definition.condition() {
+        let value = params.get(&condition.field).map(|v| v.to_text());
+        if !condition.matches(value.as_deref()) {
+            return Ok(0);
+        }
+    }
+    let code = definition.code();
+    match params.get(&code) {
+        Some(value) if definition.repeat().is_some() => {
+            let inputs = value.to_inputs(&definition.field_type())?;
+            let bytes = definition.to_bytes_list(&inputs)?;
+            let written = bytes.len();
+            writer.write(|| Ok(Rawfield::new(&bytes, definition.title(), inputs.join(","))))?;
+            Ok(written)
         }
-        Ok(length as u16)
+        Some(value) => {
+            let input = value.to_input(&definition.field_type())?;
+            let bytes = definition.to_bytes(&input)?;
+            let written = bytes.len();
+            writer.write(|| Ok(Rawfield::new(&bytes, definition.title(), input)))?;
+            Ok(written)
+        }
+        // 参数缺失但有默认值 (default_hex / default_provider / default_value) 时按空输入编码
+        None if _has_default(definition) => {
+            let bytes = definition.to_bytes("")?;
+            let written = bytes.len();
+            writer.write(|| Ok(Rawfield::new(&bytes, definition.title(), String::new())))?;
+            Ok(written)
+        }
+        None if definition.required() => Err(ProtocolError::CommonError(format!(
+            "Required parameter '{}' not found in input params",
+            code
+        ))),
+        None => Ok(0),
     }
 }
 
+fn _has_default<T: AutoEncodingParam>(definition: &T) -> bool {
+    !definition.default_hex().is_empty()
+        || definition.default_provider().is_some()
+        || !definition.default_value().is_empty()
+}
+
 /// 上行参数解码，针对单个帧字段
 /// 使用默认泛型参数解决"被迫指定无用泛型"的问题
 /// 对于不需要枚举功能的实现，可以省略泛型参数（默认使用 u8 类型）
@@ -245,6 +800,14 @@ where
     fn symbol(&self) -> Option<Symbol> {
         None
     }
+    // 缩放后保留的小数位数
+    fn precision(&self) -> u32 {
+        math_util::DEFAULT_PRECISION
+    }
+    // 缩放时的舍入模式
+    fn rounding_mode(&self) -> DecimalRoundingMode {
+        DecimalRoundingMode::HalfUp
+    }
     //帧字段类型 不为空即是: 翻译模式。
     fn field_type(&self) -> FieldType {
         FieldType::Empty
@@ -258,6 +821,11 @@ where
         vec![]
     }
 
+    // 解析条件，不满足时跳过该字段 (不读取字节)
+    fn condition(&self) -> Option<FieldCondition> {
+        None
+    }
+
     fn is_enum_mode(&self) -> bool {
         !self.enum_values().is_empty()
     }
@@ -275,8 +843,15 @@ where
             FieldCompareDecoder::new(&self.title(), self.compare_target(), self.swap())
                 .translate(bytes)
         } else if self.is_translate_mode() {
-            FieldConvertDecoder::new(&self.title(), self.field_type(), self.symbol(), self.swap())
-                .translate(bytes)
+            let mut decoder = FieldConvertDecoder::new(
+                &self.title(),
+                self.field_type(),
+                self.symbol(),
+                self.swap(),
+            );
+            decoder.set_precision(self.precision());
+            decoder.set_rounding_mode(self.rounding_mode());
+            decoder.translate(bytes)
         } else if self.is_enum_mode() {
             FieldEnumDecoder::new(&self.title(), self.enum_values(), self.swap()).translate(bytes)
         } else {
@@ -306,9 +881,558 @@ where
     fn auto_process(&self, reader: &mut Reader) -> ProtocolResult<()> {
         let definitions = self.variants();
         for definition in definitions {
-            let byte_length = definition.byte_length();
-            reader.read_and_translate_head(byte_length, |h| definition.translate(h))?;
+            if let Some(condition) = definition.condition()
+                && !condition.matches(reader.field_value(&condition.field))
+            {
+                continue;
+            }
+            _read_param(&definition, reader)?;
         }
         Ok(())
     }
+
+    // 宽松模式：单个字段解析失败 (严重程度低于 Fatal) 时记录为告警并跳过该字段的字节，继续解析后续字段
+    fn auto_process_lenient(&self, reader: &mut Reader) -> ProtocolResult<ProtocolOutcome<()>> {
+        let mut outcome = ProtocolOutcome::new(());
+        for definition in self.variants() {
+            if let Some(condition) = definition.condition()
+                && !condition.matches(reader.field_value(&condition.field))
+            {
+                continue;
+            }
+            let result = _read_param(&definition, reader);
+            if outcome.absorb(result)?.is_none() {
+                reader.read_bytes(definition.byte_length())?;
+            }
+        }
+        Ok(outcome)
+    }
+}
+
+fn _read_param<T: AutoDecodingParam<U>, U: TryFromBytes>(
+    definition: &T,
+    reader: &mut Reader,
+) -> ProtocolResult<()> {
+    reader
+        .read_and_translate_head(definition.byte_length(), |h| definition.translate(h))
+        .map_err(|e| {
+            let cmd_code = definition.cmd_code();
+            e.with_context(
+                None,
+                Some(&definition.title()),
+                Some(cmd_code.as_str()).filter(|code| !code.is_empty()),
+            )
+        })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Interval;
+
+    impl AutoEncodingParam for Interval {
+        fn code(&self) -> String {
+            "interval".into()
+        }
+        fn title(&self) -> String {
+            "上报间隔".into()
+        }
+        fn byte_length(&self) -> usize {
+            2
+        }
+        fn field_type(&self) -> FieldType {
+            FieldType::UnsignedU16(1.0)
+        }
+        fn default_value(&self) -> String {
+            "60".into()
+        }
+    }
+
+    #[test]
+    fn test_to_bytes() {
+        assert_eq!(Interval.to_bytes("").unwrap(), vec![0x00, 0x3C]);
+        assert_eq!(Interval.to_bytes("300").unwrap(), vec![0x01, 0x2C]);
+        assert_eq!(
+            Interval.to_bytes_value(&ParamValue::Int(300)).unwrap(),
+            vec![0x01, 0x2C]
+        );
+    }
+
+    #[derive(Clone, Copy)]
+    enum Valve {
+        Op,
+        Start,
+        End,
+        Mode,
+    }
+
+    impl AutoEncodingParam for Valve {
+        fn code(&self) -> String {
+            format!("{:?}", *self as u8)
+        }
+        fn title(&self) -> String {
+            self.code()
+        }
+        fn byte_length(&self) -> usize {
+            1
+        }
+        fn field_type(&self) -> FieldType {
+            FieldType::UnsignedU8(1.0)
+        }
+        fn order(&self) -> Option<u32> {
+            match self {
+                Valve::Mode => Some(0),
+                _ => None,
+            }
+        }
+        fn group(&self) -> Option<String> {
+            matches!(self, Valve::Start | Valve::End).then(|| "时段".into())
+        }
+    }
+
+    struct ValveParams;
+
+    impl AutoEncoding<Valve> for ValveParams {
+        fn variants(&self) -> Vec<Valve> {
+            vec![Valve::Op, Valve::Start, Valve::Mode, Valve::End]
+        }
+        fn group_length_prefix(&self, _group: &str) -> usize {
+            1
+        }
+    }
+
+    #[test]
+    fn test_auto_process_order_and_group() {
+        let params: HashMap<String, String> = (0..4u8)
+            .map(|i| (i.to_string(), (i + 10).to_string()))
+            .collect();
+        let mut writer = Writer::new();
+        let length = ValveParams.auto_process(&params, &mut writer).unwrap();
+        assert_eq!(length, 5);
+        // Mode(order 0) -> Op -> [长度 02] Start End
+        assert_eq!(writer.full_hex().unwrap(), "0D0A020B0C");
+    }
+
+    // 阀门控制：只有操作码为 2 (强制开阀) 时才带密码
+    #[derive(Clone, Copy)]
+    enum ValveControl {
+        Op,
+        Password,
+    }
+
+    impl AutoEncodingParam for ValveControl {
+        fn code(&self) -> String {
+            match self {
+                ValveControl::Op => "op".into(),
+                ValveControl::Password => "password".into(),
+            }
+        }
+        fn title(&self) -> String {
+            match self {
+                ValveControl::Op => "操作码".into(),
+                ValveControl::Password => "密码".into(),
+            }
+        }
+        fn byte_length(&self) -> usize {
+            match self {
+                ValveControl::Op => 1,
+                ValveControl::Password => 2,
+            }
+        }
+        fn field_type(&self) -> FieldType {
+            match self {
+                ValveControl::Op => FieldType::UnsignedU8(1.0),
+                ValveControl::Password => FieldType::StringOrBCD,
+            }
+        }
+        fn condition(&self) -> Option<FieldCondition> {
+            match self {
+                ValveControl::Password => Some(FieldCondition::new("op", &["2"])),
+                _ => None,
+            }
+        }
+    }
+
+    impl AutoEncoding<ValveControl> for ValveControl {
+        fn variants(&self) -> Vec<ValveControl> {
+            vec![ValveControl::Op, ValveControl::Password]
+        }
+    }
+
+    impl AutoDecodingParam for ValveControl {
+        fn byte_length(&self) -> usize {
+            AutoEncodingParam::byte_length(self)
+        }
+        fn title(&self) -> String {
+            AutoEncodingParam::title(self)
+        }
+        fn field_type(&self) -> FieldType {
+            AutoEncodingParam::field_type(self)
+        }
+        fn condition(&self) -> Option<FieldCondition> {
+            match self {
+                ValveControl::Password => Some(FieldCondition::new("操作码", &["2"])),
+                _ => None,
+            }
+        }
+    }
+
+    impl AutoDecoding<ValveControl> for ValveControl {
+        fn variants(&self) -> Vec<ValveControl> {
+            vec![ValveControl::Op, ValveControl::Password]
+        }
+    }
+
+    #[test]
+    fn test_conditional_fields() {
+        let encode = |op: i64| {
+            let params = HashMap::from([
+                ("op".to_string(), ParamValue::Int(op)),
+                ("password".to_string(), ParamValue::from("1234")),
+            ]);
+            let mut writer = Writer::new();
+            AutoEncoding::auto_process(&ValveControl::Op, &params, &mut writer).unwrap();
+            writer.full_hex().unwrap()
+        };
+        assert_eq!(encode(1), "01");
+        assert_eq!(encode(2), "021234");
+
+        for (hex, fields) in [("01", 1), ("021234", 2)] {
+            let bytes = hex_util::hex_to_bytes(hex).unwrap();
+            let mut reader = Reader::new(&bytes);
+            AutoDecoding::auto_process(&ValveControl::Op, &mut reader).unwrap();
+            assert_eq!(reader.to_report_fields().unwrap().len(), fields);
+        }
+    }
+
+    // 版本号固定为 01，后跟 1 字节数值
+    #[derive(Clone, Copy)]
+    enum Versioned {
+        Version,
+        Value,
+    }
+
+    impl AutoDecodingParam for Versioned {
+        fn byte_length(&self) -> usize {
+            1
+        }
+        fn title(&self) -> String {
+            match self {
+                Versioned::Version => "版本".into(),
+                Versioned::Value => "数值".into(),
+            }
+        }
+        fn field_type(&self) -> FieldType {
+            FieldType::UnsignedU8(1.0)
+        }
+        fn compare_target(&self) -> Vec<u8> {
+            match self {
+                Versioned::Version => vec![0x01],
+                Versioned::Value => vec![],
+            }
+        }
+    }
+
+    impl AutoDecoding<Versioned> for Versioned {
+        fn variants(&self) -> Vec<Versioned> {
+            vec![Versioned::Version, Versioned::Value]
+        }
+    }
+
+    #[test]
+    fn test_lenient_decoding() {
+        let bytes = [0x02, 0x05];
+        let err = AutoDecoding::auto_process(&Versioned::Value, &mut Reader::new(&bytes));
+        assert!(err.is_err());
+
+        let mut reader = Reader::new(&bytes);
+        let outcome = Versioned::Value.auto_process_lenient(&mut reader).unwrap();
+        assert_eq!(outcome.warnings().len(), 1);
+        assert_eq!(outcome.warnings()[0].field.as_deref(), Some("版本"));
+        let fields = reader.to_report_fields().unwrap();
+        assert_eq!((fields.len(), fields[0].value.as_str()), (1, "5"));
+
+        // 长度不足是 Fatal，不会降级
+        let mut reader = Reader::new(&bytes[..1]);
+        assert!(Versioned::Value.auto_process_lenient(&mut reader).is_err());
+    }
+
+    struct PriceTiers;
+
+    impl AutoEncodingParam for PriceTiers {
+        fn code(&self) -> String {
+            "prices".into()
+        }
+        fn title(&self) -> String {
+            "阶梯价格".into()
+        }
+        fn byte_length(&self) -> usize {
+            2
+        }
+        fn field_type(&self) -> FieldType {
+            FieldType::UnsignedU16(1.0)
+        }
+        fn repeat(&self) -> Option<RepeatSpec> {
+            Some(RepeatSpec::new(1, 1, 4))
+        }
+    }
+
+    #[test]
+    fn test_repeated_params() {
+        let typed = ParamValue::from(vec![100i64, 200, 300]);
+        let inputs = typed.to_inputs(&PriceTiers.field_type()).unwrap();
+        assert_eq!(
+            PriceTiers.to_bytes_list(&inputs).unwrap(),
+            vec![0x03, 0x00, 0x64, 0x00, 0xC8, 0x01, 0x2C]
+        );
+        let text = "100, 200".to_string().to_inputs(&FieldType::Empty).unwrap();
+        assert_eq!(PriceTiers.to_bytes_list(&text).unwrap().len(), 5);
+        let too_many = vec!["1".to_string(); 5];
+        assert!(PriceTiers.to_bytes_list(&too_many).is_err());
+        assert!(PriceTiers.to_bytes_list(&[]).is_err());
+    }
+
+    struct Iccid;
+
+    impl AutoEncodingParam for Iccid {
+        fn code(&self) -> String {
+            "iccid".into()
+        }
+        fn title(&self) -> String {
+            "ICCID".into()
+        }
+        fn byte_length(&self) -> usize {
+            10
+        }
+        fn field_type(&self) -> FieldType {
+            FieldType::StringOrBCD
+        }
+        fn rules(&self) -> Vec<ValidationRule> {
+            vec![ValidationRule::Pattern("^[0-9]{20}$".into())]
+        }
+    }
+
+    // 左对齐的 ASCII ICCID，不足补空格，超长截掉末尾
+    struct AsciiIccid;
+
+    impl AutoEncodingParam for AsciiIccid {
+        fn code(&self) -> String {
+            "iccid".into()
+        }
+        fn title(&self) -> String {
+            "ICCID".into()
+        }
+        fn byte_length(&self) -> usize {
+            6
+        }
+        fn field_type(&self) -> FieldType {
+            FieldType::Ascii
+        }
+        fn pad_byte(&self) -> u8 {
+            b' '
+        }
+        fn pad_side(&self) -> PadSide {
+            PadSide::Right
+        }
+        fn truncate_side(&self) -> PadSide {
+            PadSide::Right
+        }
+    }
+
+    struct Seq(Arc<SequenceGenerator>);
+
+    impl AutoEncodingParam for Seq {
+        fn code(&self) -> String {
+            "seq".into()
+        }
+        fn title(&self) -> String {
+            "序列号".into()
+        }
+        fn byte_length(&self) -> usize {
+            1
+        }
+        fn field_type(&self) -> FieldType {
+            FieldType::UnsignedU8(1.0)
+        }
+        fn default_provider(&self) -> Option<DefaultProvider> {
+            Some(DefaultProvider::sequence(self.0.clone()))
+        }
+    }
+
+    #[test]
+    fn test_default_provider() {
+        let seq = Seq(Arc::new(SequenceGenerator::new(255)));
+        assert_eq!(seq.to_bytes("").unwrap(), vec![1]);
+        assert_eq!(seq.to_bytes("").unwrap(), vec![2]);
+        // 有输入时不使用默认值
+        assert_eq!(seq.to_bytes("9").unwrap(), vec![9]);
+
+        let now = DefaultProvider::timestamp(TimestampType::YyMmDdHHmmss)
+            .provide()
+            .unwrap();
+        assert_eq!(FieldType::StringOrBCD.encode(&now).unwrap().len(), 6);
+    }
+
+    struct SeqParams(Arc<SequenceGenerator>);
+
+    impl AutoEncoding<Seq> for SeqParams {
+        fn variants(&self) -> Vec<Seq> {
+            vec![Seq(self.0.clone())]
+        }
+    }
+
+    #[test]
+    fn test_auto_process_default_provider() {
+        let params = SeqParams(Arc::new(SequenceGenerator::new(255)));
+        let empty: HashMap<String, String> = HashMap::new();
+        // 参数缺失时由 default_provider 生成
+        let mut writer = Writer::new();
+        assert_eq!(params.auto_process(&empty, &mut writer).unwrap(), 1);
+        assert_eq!(writer.full_hex().unwrap(), "01");
+        let mut writer = Writer::new();
+        params.auto_process(&empty, &mut writer).unwrap();
+        assert_eq!(writer.full_hex().unwrap(), "02");
+        // 显式给出时使用输入值
+        let given = HashMap::from([("seq".to_string(), "9".to_string())]);
+        let mut writer = Writer::new();
+        params.auto_process(&given, &mut writer).unwrap();
+        assert_eq!(writer.full_hex().unwrap(), "09");
+    }
+
+    #[test]
+    fn test_padding_and_truncation() {
+        assert_eq!(AsciiIccid.to_bytes("8986").unwrap(), b"8986  ");
+        assert_eq!(AsciiIccid.to_bytes("89860012").unwrap(), b"898600");
+        assert_eq!(
+            PadSide::Left.truncate(vec![0x77, 0xFF, 0xBD, 0x23], 2),
+            vec![0xBD, 0x23]
+        );
+        assert_eq!(
+            PadSide::Left.pad(vec![0xBD, 0x23], 4, 0),
+            vec![0, 0, 0xBD, 0x23]
+        );
+    }
+
+    // 超过 u16 范围的数据域
+    struct Blob;
+
+    impl AutoEncodingParam for Blob {
+        fn code(&self) -> String {
+            "blob".into()
+        }
+        fn title(&self) -> String {
+            "数据块".into()
+        }
+        fn byte_length(&self) -> usize {
+            70_000
+        }
+        fn field_type(&self) -> FieldType {
+            FieldType::Ascii
+        }
+    }
+
+    impl AutoEncoding<Blob> for Blob {
+        fn variants(&self) -> Vec<Blob> {
+            vec![Blob]
+        }
+    }
+
+    #[test]
+    fn test_encoded_length_overflow() {
+        let params = HashMap::from([("blob".to_string(), "A".to_string())]);
+        let mut writer = Writer::new();
+        let Err(err) = Blob.auto_process(&params, &mut writer) else {
+            panic!("70000 bytes should not fit in u16");
+        };
+        assert!(err.to_string().contains("70000"));
+    }
+
+    #[test]
+    fn test_describe_params() {
+        let described = ValveControl::Op.describe();
+        assert_eq!(described.len(), 2);
+        assert_eq!(described[1].input_field_type, "string");
+        assert_eq!(
+            described[1].condition,
+            Some(FieldCondition::new("op", &["2"]))
+        );
+        let json = ValveControl::Op.describe_json().unwrap();
+        assert!(json.contains(r#""code":"op","title":"操作码","inputFieldType":"int""#));
+        let iccid = ParamDescriptor::from_param(&Iccid);
+        assert_eq!(
+            serde_json::to_value(&iccid.constraints).unwrap(),
+            serde_json::json!([{"rule": "pattern", "value": "^[0-9]{20}$"}])
+        );
+    }
+
+    #[test]
+    fn test_validation_rules() {
+        assert!(Iccid.to_bytes("89860012345678901234").is_ok());
+        let err = Iccid.to_bytes("8986").unwrap_err().to_string();
+        assert!(err.contains("iccid") && err.contains("pattern"));
+
+        let rule = ValidationRule::OneOf(vec!["1".into(), "2".into()]);
+        assert!(rule.check("2").unwrap());
+        assert!(!ValidationRule::Max(100.0).check("100.5").unwrap());
+        assert!(ValidationRule::Min(0.0).check("abc").is_err());
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_encoding_params() {
+        use crate::{AutoEncoding, AutoEncodingParam};
+
+        #[derive(Clone, AutoEncodingParam, AutoEncoding)]
+        #[ep(cmd_code = "A1")]
+        enum PriceParams {
+            #[ep(
+                code = "price",
+                title = "单价",
+                byte_length = 4,
+                field_type = "UnsignedU32(100)",
+                rounding = "Down",
+                swap,
+                min = 0,
+                max = 99.99
+            )]
+            Price,
+            #[ep(
+                byte_length = 4,
+                field_type = "Ascii",
+                pad_byte = 0x20,
+                pad_side = "Right",
+                optional,
+                default_value = "NONE",
+                order = 0,
+                group = "备注"
+            )]
+            Remark,
+        }
+
+        let price = PriceParams::Price;
+        assert_eq!(price.code(), "price");
+        assert_eq!(price.title(), "单价");
+        assert_eq!(price.cmd_code(), "A1");
+        assert_eq!(price.field_type(), FieldType::UnsignedU32(100.0));
+        assert!(price.swap() && price.required());
+        assert_eq!(PriceParams::Remark.code(), "Remark");
+        assert!(!PriceParams::Remark.required());
+        assert_eq!(PriceParams::Remark.order(), Some(0));
+        assert_eq!(PriceParams::Remark.to_bytes("OK").unwrap(), b"OK  ");
+        assert_eq!(PriceParams::Remark.group().as_deref(), Some("备注"));
+        assert_eq!(price.rules().len(), 2);
+        assert!(price.to_bytes("100").is_err());
+        // 99 / 100 = 0.99，按 Down 取整为 0 (默认 HalfUp 为 1)
+        assert_eq!(price.to_bytes("99").unwrap(), vec![0, 0, 0, 0]);
+        assert_eq!(price.variants().len(), 2);
+        assert!(price.variants_map().contains_key("Remark"));
+
+        let params = HashMap::from([("price".to_string(), "1".to_string())]);
+        let mut writer = Writer::new();
+        // 缺省的 Remark 按 default_value 编码，order 0 排在最前
+        assert_eq!(price.auto_process(&params, &mut writer).unwrap(), 8);
+        assert!(writer.full_hex().unwrap().starts_with("4E4F4E45"));
+    }
 }
@@ -1,9 +1,14 @@
+use std::fmt;
+
+use crate::core::parts::cipher_spec::CipherSpec;
 use crate::core::parts::traits::Transport;
-use crate::core::parts::transport_pair::TransportPair;
+use crate::core::parts::transport_pair::{PairInput, TransportCounter, TransportPair};
+use crate::defi::{ProtocolResult, error::ProtocolError};
 use crate::hex_util;
+use serde::{Deserialize, Serialize};
 
 // informations with hex + bytes
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TransportCarrier {
     pub(crate) device_no: Option<TransportPair>,
     pub(crate) device_no_padding: Option<TransportPair>,
@@ -13,12 +18,180 @@ pub struct TransportCarrier {
     pub(crate) control_field: Option<TransportPair>,
     pub(crate) device_type: Option<TransportPair>,
     pub(crate) factory_code: Option<TransportPair>,
-    pub(crate) upstream_count: Option<TransportPair>,
-    pub(crate) downstream_count: Option<TransportPair>,
+    pub(crate) upstream_count: TransportCounter,
+    pub(crate) downstream_count: TransportCounter,
     pub(crate) cipher_slot: i8,
+    #[serde(default)]
+    pub(crate) cipher_spec: Option<CipherSpec>,
+}
+
+/// `TransportCarrier` 中的字段，用于声明某个协议的必填项
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransportField {
+    DeviceNo,
+    DeviceNoPadding,
+    DeviceNoLength,
+    ProtocolVersion,
+    ReportType,
+    ControlField,
+    DeviceType,
+    FactoryCode,
+    UpstreamCount,
+    DownstreamCount,
+}
+
+impl fmt::Display for TransportField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TransportField::DeviceNo => "device_no",
+            TransportField::DeviceNoPadding => "device_no_padding",
+            TransportField::DeviceNoLength => "device_no_length",
+            TransportField::ProtocolVersion => "protocol_version",
+            TransportField::ReportType => "report_type",
+            TransportField::ControlField => "control_field",
+            TransportField::DeviceType => "device_type",
+            TransportField::FactoryCode => "factory_code",
+            TransportField::UpstreamCount => "upstream_count",
+            TransportField::DownstreamCount => "downstream_count",
+        };
+        f.write_str(name)
+    }
+}
+
+/// `TransportCarrier` 的构建器。每个字段可以传 hex (`"0001"`) 或 bytes (`[0x00, 0x01]`)，
+/// 另一半自动推导；`build` 时统一校验 hex 合法性与必填项。
+#[derive(Debug, Clone)]
+pub struct TransportCarrierBuilder {
+    fields: Vec<(TransportField, PairInput)>,
+    required: Vec<TransportField>,
+    cipher_slot: i8,
+    cipher_spec: Option<CipherSpec>,
+}
+
+impl Default for TransportCarrierBuilder {
+    fn default() -> Self {
+        Self {
+            fields: Vec::new(),
+            required: Vec::new(),
+            cipher_slot: -1,
+            cipher_spec: None,
+        }
+    }
+}
+
+impl TransportCarrierBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置任意字段，重复设置时后者覆盖前者
+    pub fn field(mut self, field: TransportField, value: impl Into<PairInput>) -> Self {
+        self.fields.retain(|(f, _)| *f != field);
+        self.fields.push((field, value.into()));
+        self
+    }
+
+    pub fn device_no(self, value: impl Into<PairInput>) -> Self {
+        self.field(TransportField::DeviceNo, value)
+    }
+
+    pub fn device_no_padding(self, value: impl Into<PairInput>) -> Self {
+        self.field(TransportField::DeviceNoPadding, value)
+    }
+
+    pub fn device_no_length(self, value: impl Into<PairInput>) -> Self {
+        self.field(TransportField::DeviceNoLength, value)
+    }
+
+    pub fn protocol_version(self, value: impl Into<PairInput>) -> Self {
+        self.field(TransportField::ProtocolVersion, value)
+    }
+
+    pub fn report_type(self, value: impl Into<PairInput>) -> Self {
+        self.field(TransportField::ReportType, value)
+    }
+
+    pub fn control_field(self, value: impl Into<PairInput>) -> Self {
+        self.field(TransportField::ControlField, value)
+    }
+
+    pub fn device_type(self, value: impl Into<PairInput>) -> Self {
+        self.field(TransportField::DeviceType, value)
+    }
+
+    pub fn factory_code(self, value: impl Into<PairInput>) -> Self {
+        self.field(TransportField::FactoryCode, value)
+    }
+
+    pub fn upstream_count(self, value: impl Into<PairInput>) -> Self {
+        self.field(TransportField::UpstreamCount, value)
+    }
+
+    pub fn downstream_count(self, value: impl Into<PairInput>) -> Self {
+        self.field(TransportField::DownstreamCount, value)
+    }
+
+    pub fn cipher_slot(mut self, cipher_slot: i8) -> Self {
+        self.cipher_slot = cipher_slot;
+        self
+    }
+
+    /// 完整的加密信息，同时覆盖 cipher_slot
+    pub fn cipher_spec(mut self, cipher_spec: CipherSpec) -> Self {
+        self.cipher_slot = cipher_spec.slot;
+        self.cipher_spec = Some(cipher_spec);
+        self
+    }
+
+    /// 声明协议的必填字段，`build` 时缺少任意一个都会报错
+    pub fn required(mut self, fields: &[TransportField]) -> Self {
+        self.required.extend_from_slice(fields);
+        self
+    }
+
+    pub fn build(self) -> ProtocolResult<TransportCarrier> {
+        if let Some(missing) = self
+            .required
+            .iter()
+            .find(|r| !self.fields.iter().any(|(f, _)| f == *r))
+        {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "transport field '{}' is required",
+                missing
+            )));
+        }
+        let mut carrier = TransportCarrier {
+            cipher_slot: self.cipher_slot,
+            cipher_spec: self.cipher_spec,
+            ..Default::default()
+        };
+        for (field, value) in self.fields {
+            let pair = value
+                .into_pair()
+                .map_err(|e| e.with_context(None, Some(&field.to_string()), None))?;
+            let pair = Some(pair);
+            match field {
+                TransportField::DeviceNo => carrier.device_no = pair,
+                TransportField::DeviceNoPadding => carrier.device_no_padding = pair,
+                TransportField::DeviceNoLength => carrier.device_no_length = pair,
+                TransportField::ProtocolVersion => carrier.protocol_version = pair,
+                TransportField::ReportType => carrier.report_type = pair,
+                TransportField::ControlField => carrier.control_field = pair,
+                TransportField::DeviceType => carrier.device_type = pair,
+                TransportField::FactoryCode => carrier.factory_code = pair,
+                TransportField::UpstreamCount => carrier.upstream_count = pair.into(),
+                TransportField::DownstreamCount => carrier.downstream_count = pair.into(),
+            }
+        }
+        Ok(carrier)
+    }
 }
 
 impl TransportCarrier {
+    pub fn builder() -> TransportCarrierBuilder {
+        TransportCarrierBuilder::new()
+    }
+
     pub fn new_with_device_no_and_upstream_count_hex(
         device_no: &str,
         upstream_count: &str,
@@ -34,12 +207,13 @@ impl TransportCarrier {
             control_field: None,
             device_type: None,
             factory_code: None,
-            upstream_count: Some(TransportPair::new(
+            upstream_count: TransportCounter::new(Some(TransportPair::new(
                 upstream_count.into(),
                 upstream_count_bytes,
-            )),
-            downstream_count: None,
+            ))),
+            downstream_count: TransportCounter::default(),
             cipher_slot: -1,
+            cipher_spec: None,
         }
     }
 
@@ -61,9 +235,10 @@ impl TransportCarrier {
             protocol_version: None,
             device_type: None,
             factory_code: None,
-            upstream_count: None,
-            downstream_count: None,
+            upstream_count: TransportCounter::default(),
+            downstream_count: TransportCounter::default(),
             cipher_slot: -1,
+            cipher_spec: None,
         }
     }
 
@@ -141,6 +316,15 @@ impl TransportCarrier {
 
     pub fn set_cipher_slot(&mut self, cipher_slot: i8) {
         self.cipher_slot = cipher_slot;
+        if let Some(spec) = self.cipher_spec.as_mut() {
+            spec.slot = cipher_slot;
+        }
+    }
+
+    // 设置完整的加密信息，cipher_slot 随之更新
+    pub fn set_cipher_spec(&mut self, cipher_spec: CipherSpec) {
+        self.cipher_slot = cipher_spec.slot;
+        self.cipher_spec = Some(cipher_spec);
     }
 
     pub fn set_upstream_count(&mut self, hex: String, bytes: Vec<u8>) {
@@ -149,7 +333,7 @@ impl TransportCarrier {
     }
 
     fn _set_upstream_count(&mut self, count: Option<TransportPair>) {
-        self.upstream_count = count;
+        self.upstream_count = count.into();
     }
 
     pub fn set_downstream_count(&mut self, hex: String, bytes: Vec<u8>) {
@@ -158,7 +342,7 @@ impl TransportCarrier {
     }
 
     fn _set_downstream_count(&mut self, count: Option<TransportPair>) {
-        self.downstream_count = count;
+        self.downstream_count = count.into();
     }
 }
 
@@ -196,16 +380,31 @@ impl Transport for TransportCarrier {
     }
 
     fn upstream_count(&self) -> Option<TransportPair> {
-        self.upstream_count.clone()
+        self.upstream_count.get()
     }
 
     fn downstream_count(&self) -> Option<TransportPair> {
-        self.downstream_count.clone()
+        self.downstream_count.get()
+    }
+
+    fn upstream_counter(&self) -> Option<&TransportCounter> {
+        Some(&self.upstream_count)
+    }
+
+    fn downstream_counter(&self) -> Option<&TransportCounter> {
+        Some(&self.downstream_count)
     }
 
     fn cipher_slot(&self) -> i8 {
         self.cipher_slot
     }
+
+    fn cipher_spec(&self) -> Option<CipherSpec> {
+        match &self.cipher_spec {
+            Some(spec) => Some(spec.clone()),
+            None => (self.cipher_slot >= 0).then(|| CipherSpec::from_slot(self.cipher_slot)),
+        }
+    }
 }
 
 impl TransportCarrier {
@@ -274,23 +473,87 @@ impl TransportCarrier {
         self.factory_code.clone()
     }
 
-    pub fn upstream_count(&self) -> Option<&TransportPair> {
-        self.upstream_count.as_ref()
+    /// 上行序号的副本，同 `upstream_count_clone`
+    ///
+    /// 不兼容变更：序号可被并发递增 (`TransportCounter`)，无法再借出引用，
+    /// 返回值由 `Option<&TransportPair>` 改为 `Option<TransportPair>`；需要原子递增请用 `Transport::upstream_counter`
+    pub fn upstream_count(&self) -> Option<TransportPair> {
+        self.upstream_count_clone()
     }
 
     pub fn upstream_count_clone(&self) -> Option<TransportPair> {
-        self.upstream_count.clone()
+        self.upstream_count.get()
     }
 
-    pub fn downstream_count(&self) -> Option<&TransportPair> {
-        self.downstream_count.as_ref()
+    /// 下行序号的副本，同 `downstream_count_clone`
+    ///
+    /// 不兼容变更：返回值由 `Option<&TransportPair>` 改为 `Option<TransportPair>`，原因同 `upstream_count`
+    pub fn downstream_count(&self) -> Option<TransportPair> {
+        self.downstream_count_clone()
     }
 
     pub fn downstream_count_clone(&self) -> Option<TransportPair> {
-        self.downstream_count.clone()
+        self.downstream_count.get()
     }
 
     pub fn cipher_slot(&self) -> i8 {
         self.cipher_slot
     }
+
+    pub fn cipher_spec(&self) -> Option<&CipherSpec> {
+        self.cipher_spec.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_derives_hex_and_bytes() {
+        let carrier = TransportCarrier::builder()
+            .device_no("12345678")
+            .upstream_count([0x00, 0x0A])
+            .required(&[TransportField::DeviceNo, TransportField::UpstreamCount])
+            .build()
+            .unwrap();
+        assert_eq!(
+            carrier.device_no().unwrap().bytes(),
+            &[0x12, 0x34, 0x56, 0x78]
+        );
+        assert_eq!(carrier.upstream_count_clone().unwrap().hex(), "000A");
+        assert_eq!(carrier.cipher_slot(), -1);
+        assert!(Transport::cipher_spec(&carrier).is_none());
+
+        let spec = CipherSpec::new(1, "AES-128-CBC").with_mac(true);
+        let carrier = TransportCarrier::builder()
+            .cipher_spec(spec.clone())
+            .build()
+            .unwrap();
+        assert_eq!(carrier.cipher_slot(), 1);
+        assert_eq!(Transport::cipher_spec(&carrier), Some(spec));
+
+        let missing = TransportCarrier::builder()
+            .device_no("0001")
+            .required(&[TransportField::FactoryCode])
+            .build();
+        assert!(missing.unwrap_err().to_string().contains("factory_code"));
+        // 保留原始的 hex 错误，并带上字段名
+        let invalid = TransportCarrier::builder()
+            .device_no("XYZ")
+            .build()
+            .unwrap_err();
+        assert!(matches!(invalid.root(), ProtocolError::HexError(_)));
+        assert_eq!(
+            invalid.context().unwrap().field.as_deref(),
+            Some("device_no")
+        );
+        let mismatch = TransportPair::new("0001".into(), vec![0x00, 0x02]);
+        assert!(
+            TransportCarrier::builder()
+                .device_no(mismatch)
+                .build()
+                .is_err()
+        );
+    }
 }
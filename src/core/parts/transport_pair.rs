@@ -1,5 +1,14 @@
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    defi::{ProtocolResult, error::ProtocolError},
+    hex_util,
+};
+
 // hex + bytes
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct TransportPair {
     pub(crate) hex: String,
     pub(crate) bytes: Vec<u8>,
@@ -34,4 +43,203 @@ impl TransportPair {
     pub fn bytes_clone(&self) -> Vec<u8> {
         self.bytes.clone()
     }
+
+    /// 按指定字节宽度把计数值编码为大端 hex + bytes，超出宽度时保留低位
+    pub fn from_counter(value: u64, byte_length: usize) -> ProtocolResult<Self> {
+        let hex = hex_util::u64_to_hex(value, byte_length)?;
+        let bytes = hex_util::hex_to_bytes(&hex)?;
+        Ok(Self::new(hex, bytes))
+    }
+
+    /// 按大端无符号整数读取 (超过 8 字节时只取低 8 字节)
+    pub fn to_counter(&self) -> u64 {
+        let start = self.bytes.len().saturating_sub(8);
+        self.bytes[start..]
+            .iter()
+            .fold(0u64, |acc, b| (acc << 8) | *b as u64)
+    }
+
+    /// 调整为指定字节宽度：不足在高位补 0，超出丢弃高位
+    pub fn resized(&self, byte_length: usize) -> Self {
+        let mut bytes = vec![0u8; byte_length.saturating_sub(self.bytes.len())];
+        let start = self.bytes.len().saturating_sub(byte_length);
+        bytes.extend_from_slice(&self.bytes[start..]);
+        Self::new(hex::encode_upper(&bytes), bytes)
+    }
+
+    /// 按大端整数 +1 (保持字节长度，溢出时回绕到 0)，返回新的 hex + bytes。
+    /// 用于上/下行消息序号。空字节按 2 字节的 0 处理。
+    pub fn incremented(&self) -> TransportPair {
+        let mut bytes = if self.bytes.is_empty() {
+            vec![0u8; 2]
+        } else {
+            self.bytes.clone()
+        };
+        for byte in bytes.iter_mut().rev() {
+            let (next, overflow) = byte.overflowing_add(1);
+            *byte = next;
+            if !overflow {
+                break;
+            }
+        }
+        TransportPair::new(hex::encode_upper(&bytes), bytes)
+    }
+}
+
+/// 构建 `TransportPair` 的输入：只给 hex 或只给 bytes 时自动推导另一半，两者都给时校验是否一致
+#[derive(Debug, Clone, PartialEq)]
+pub enum PairInput {
+    Hex(String),
+    Bytes(Vec<u8>),
+    Pair(TransportPair),
+}
+
+impl PairInput {
+    pub fn into_pair(self) -> ProtocolResult<TransportPair> {
+        match self {
+            PairInput::Hex(hex) => {
+                let bytes = hex_util::hex_to_bytes(&hex)?;
+                Ok(TransportPair::new(hex_util::bytes_to_hex(&bytes)?, bytes))
+            }
+            PairInput::Bytes(bytes) => {
+                Ok(TransportPair::new(hex_util::bytes_to_hex(&bytes)?, bytes))
+            }
+            PairInput::Pair(pair) => {
+                if hex_util::hex_to_bytes(&pair.hex)? != pair.bytes {
+                    return Err(ProtocolError::ValidationFailed(format!(
+                        "hex {} does not match bytes {:02X?}",
+                        pair.hex, pair.bytes
+                    )));
+                }
+                Ok(pair)
+            }
+        }
+    }
+}
+
+impl From<&str> for PairInput {
+    fn from(hex: &str) -> Self {
+        PairInput::Hex(hex.to_string())
+    }
+}
+
+impl From<String> for PairInput {
+    fn from(hex: String) -> Self {
+        PairInput::Hex(hex)
+    }
+}
+
+impl From<&[u8]> for PairInput {
+    fn from(bytes: &[u8]) -> Self {
+        PairInput::Bytes(bytes.to_vec())
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for PairInput {
+    fn from(bytes: [u8; N]) -> Self {
+        PairInput::Bytes(bytes.to_vec())
+    }
+}
+
+impl From<Vec<u8>> for PairInput {
+    fn from(bytes: Vec<u8>) -> Self {
+        PairInput::Bytes(bytes)
+    }
+}
+
+impl From<TransportPair> for PairInput {
+    fn from(pair: TransportPair) -> Self {
+        PairInput::Pair(pair)
+    }
+}
+
+/// 可在 `&self` 下递增的消息序号 (内部可变)，缓存中以 `Arc` 共享的设备状态也能直接更新。
+/// 序列化形式与 `Option<TransportPair>` 相同。
+#[derive(Debug, Default)]
+pub struct TransportCounter(Mutex<Option<TransportPair>>);
+
+impl TransportCounter {
+    pub fn new(pair: Option<TransportPair>) -> Self {
+        Self(Mutex::new(pair))
+    }
+
+    pub fn get(&self) -> Option<TransportPair> {
+        self._lock().clone()
+    }
+
+    pub fn set(&self, pair: Option<TransportPair>) {
+        *self._lock() = pair;
+    }
+
+    pub fn get_mut(&mut self) -> &mut Option<TransportPair> {
+        self.0.get_mut().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// 原子地 +1 并返回新的序号，保持原有字节宽度；未设置时从 2 字节的 0 开始计数
+    pub fn increment(&self) -> TransportPair {
+        let mut guard = self._lock();
+        let next = guard
+            .as_ref()
+            .map(TransportPair::incremented)
+            .unwrap_or_else(|| TransportPair::default().incremented());
+        *guard = Some(next.clone());
+        next
+    }
+
+    fn _lock(&self) -> MutexGuard<'_, Option<TransportPair>> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Clone for TransportCounter {
+    fn clone(&self) -> Self {
+        Self::new(self.get())
+    }
+}
+
+impl From<Option<TransportPair>> for TransportCounter {
+    fn from(pair: Option<TransportPair>) -> Self {
+        Self::new(pair)
+    }
+}
+
+impl Serialize for TransportCounter {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.get().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for TransportCounter {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Option::<TransportPair>::deserialize(deserializer).map(Self::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Transport, TransportCarrier};
+
+    #[test]
+    fn test_counter_width_helpers() {
+        let pair = TransportPair::from_counter(258, 2).unwrap();
+        assert_eq!(pair.hex(), "0102");
+        assert_eq!(pair.to_counter(), 258);
+        assert_eq!(pair.resized(4).hex(), "00000102");
+        assert_eq!(pair.resized(1).hex(), "02");
+    }
+
+    #[test]
+    fn test_increment_through_shared_transport() {
+        let carrier = std::sync::Arc::new(
+            TransportCarrier::new_with_device_no_and_upstream_count_hex("0001", "00FF"),
+        );
+        assert_eq!(carrier.increment_upstream().unwrap().hex(), "0100");
+        assert_eq!(carrier.increment_downstream().unwrap().hex(), "0001");
+        assert_eq!(Transport::upstream_count(&*carrier).unwrap().hex(), "0100");
+
+        let json = serde_json::to_string(&*carrier).unwrap();
+        let restored: TransportCarrier = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.downstream_count_clone().unwrap().hex(), "0001");
+    }
 }
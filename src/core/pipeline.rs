@@ -0,0 +1,759 @@
+//! 基于 `ProtocolConfig` 的默认编解码流程，把 Reader / Writer 与 AutoDecoding / AutoEncoding 串起来。
+//!
+//! 帧结构约定见 `ProtocolConfig`：帧头 + [长度字段] + 数据域 + [CRC] + 帧尾，
+//! 长度字段紧跟帧头，CRC (2 字节) 紧挨帧尾。需要转义 (`requires_escaping`) 或结构不同的协议
+//! 仍然需要自行组合 Reader / Writer。
+
+use std::{collections::HashMap, time::SystemTime};
+
+use crate::{
+    AutoDecoding, AutoDecodingParam, AutoEncoding, AutoEncodingParam, Cmd, ProtocolConfig,
+    ProtocolError, ProtocolOutcome, ProtocolResult, RawCapsule, RawCapsuleRef, Rawfield,
+    ReportField, TryFromBytes,
+    core::{metrics, parts::param_value::EncodingInput, reader::Reader, writer::Writer},
+    hex_util,
+};
+
+#[cfg(feature = "cache")]
+use crate::core::dedup::DedupWindow;
+
+// CRC16 的字节数
+const CRC_LEN: usize = 2;
+
+/// 上行解码：校验外壳 -> 读取帧头/长度/帧尾/CRC -> 按 `definition` 解析数据域 -> 生成 RawCapsule。
+/// 返回的字段按在报文中的位置排序。
+pub fn decode_upstream<T, D, P, U>(
+    config: &impl ProtocolConfig,
+    definition: &D,
+    bytes: &[u8],
+) -> ProtocolResult<RawCapsule<T>>
+where
+    T: Cmd + 'static,
+    D: AutoDecoding<P, U>,
+    P: AutoDecodingParam<U>,
+    U: TryFromBytes,
+{
+    decode_frame(config, bytes, &_cmd_code(definition), |reader| {
+        definition.auto_process(reader)
+    })
+}
+
+/// 零拷贝的上行解码：同 `decode_upstream`，但返回借用 `bytes` 的 `RawCapsuleRef`，
+/// 只有调用 `into_owned` 时才复制报文。适合解码后只读取字段、不保留该帧的热路径
+pub fn decode_upstream_ref<'a, T, D, P, U>(
+    config: &impl ProtocolConfig,
+    definition: &D,
+    bytes: &'a [u8],
+) -> ProtocolResult<RawCapsuleRef<'a, T>>
+where
+    T: Cmd + 'static,
+    D: AutoDecoding<P, U>,
+    P: AutoDecodingParam<U>,
+    U: TryFromBytes,
+{
+    decode_frame_ref(config, bytes, &_cmd_code(definition), |reader| {
+        definition.auto_process(reader)
+    })
+}
+
+/// 宽松的上行解码：帧外壳 (帧头、帧尾、长度、CRC) 仍然严格校验；数据域中单个字段解析失败时
+/// 跳过该字段，错误降级为告警放在返回的 `ProtocolOutcome` 中，capsule 的 `success` 不受影响。
+/// 需要随 `JniResponse` 返回告警时，由调用方合并到 capsule (`add_warning`)。
+pub fn decode_upstream_lenient<T, D, P, U>(
+    config: &impl ProtocolConfig,
+    definition: &D,
+    bytes: &[u8],
+) -> ProtocolResult<ProtocolOutcome<RawCapsule<T>>>
+where
+    T: Cmd + 'static,
+    D: AutoDecoding<P, U>,
+    P: AutoDecodingParam<U>,
+    U: TryFromBytes,
+{
+    let mut warnings = Vec::new();
+    let capsule = decode_frame(config, bytes, &_cmd_code(definition), |reader| {
+        warnings = definition.auto_process_lenient(reader)?.into_parts().1;
+        Ok(())
+    })?;
+    Ok(ProtocolOutcome::with_warnings(capsule, warnings))
+}
+
+/// 并行批量解码：各帧互不依赖，按 rayon 线程池并行解码，结果顺序与 `frames` 一致。
+/// 单帧失败不影响其他帧
+#[cfg(feature = "rayon")]
+pub fn decode_batch<T, D, P, U>(
+    config: &(impl ProtocolConfig + Sync),
+    definition: &D,
+    frames: &[&[u8]],
+) -> Vec<ProtocolResult<RawCapsule<T>>>
+where
+    T: Cmd + Send + 'static,
+    D: AutoDecoding<P, U> + Sync,
+    P: AutoDecodingParam<U>,
+    U: TryFromBytes,
+{
+    use rayon::prelude::*;
+
+    frames
+        .par_iter()
+        .map(|bytes| decode_upstream(config, definition, bytes))
+        .collect()
+}
+
+/// 带去重的上行解码：解码成功后按 `window` 的规则检查，窗口内重传的帧标记为 duplicate (`RawCapsule::is_duplicate`)。
+/// `unique` 为设备唯一标识，解码前通常已经由连接或外层报文确定
+#[cfg(feature = "cache")]
+pub fn decode_upstream_dedup<T, D, P, U>(
+    config: &impl ProtocolConfig,
+    definition: &D,
+    bytes: &[u8],
+    unique: &str,
+    window: &DedupWindow,
+) -> ProtocolResult<RawCapsule<T>>
+where
+    T: Cmd + 'static,
+    D: AutoDecoding<P, U>,
+    P: AutoDecodingParam<U>,
+    U: TryFromBytes,
+{
+    let mut capsule = decode_upstream(config, definition, bytes)?;
+    window.mark(unique, &mut capsule);
+    Ok(capsule)
+}
+
+/// 上行解码的外壳部分，`body` 负责解析数据域。`cmd_code` 只用于指标 (`metrics`) 的标签
+pub(crate) fn decode_frame<T, F>(
+    config: &impl ProtocolConfig,
+    bytes: &[u8],
+    cmd_code: &str,
+    body: F,
+) -> ProtocolResult<RawCapsule<T>>
+where
+    T: Cmd + 'static,
+    F: FnOnce(&mut Reader) -> ProtocolResult<()>,
+{
+    decode_frame_ref(config, bytes, cmd_code, body).map(RawCapsuleRef::into_owned)
+}
+
+// 同 `decode_frame`，结果借用 `bytes`
+pub(crate) fn decode_frame_ref<'a, T, F>(
+    config: &impl ProtocolConfig,
+    bytes: &'a [u8],
+    cmd_code: &str,
+    body: F,
+) -> ProtocolResult<RawCapsuleRef<'a, T>>
+where
+    T: Cmd + 'static,
+    F: FnOnce(&mut Reader) -> ProtocolResult<()>,
+{
+    let result = _decode_frame(config, bytes, body);
+    match &result {
+        Ok(capsule) => {
+            let cmd_code = if cmd_code.is_empty() {
+                "unknown"
+            } else {
+                cmd_code
+            };
+            metrics::frame_decoded(cmd_code, capsule.processing_latency());
+        }
+        Err(error) => metrics::decode_failed(error),
+    }
+    result
+}
+
+fn _decode_frame<'a, T, F>(
+    config: &impl ProtocolConfig,
+    bytes: &'a [u8],
+    body: F,
+) -> ProtocolResult<RawCapsuleRef<'a, T>>
+where
+    T: Cmd + 'static,
+    F: FnOnce(&mut Reader) -> ProtocolResult<()>,
+{
+    let received_at = SystemTime::now();
+    _check_escaping(config)?;
+    config.validate_envelope(bytes)?;
+    let head_len = config.head_tag().len() / 2;
+    let tail_len = config.tail_tag().len() / 2;
+
+    let mut reader = Reader::new(bytes);
+    if head_len > 0 {
+        reader.read_and_translate_head(head_len, |raw| _hex_field(raw, "帧头"))?;
+    }
+    if config.has_length() {
+        let width = _length_width(config, head_len)?;
+        reader.read_and_translate_head(width, |raw| {
+            let length = raw.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64);
+            Ok(Rawfield::new(raw, "长度".into(), length.to_string()))
+        })?;
+    }
+    if tail_len > 0 {
+        reader.read_and_translate_tail(tail_len, |raw| _hex_field(raw, "帧尾"))?;
+    }
+    if config.has_crc() {
+        let (start, end) = config.crc_index();
+        reader.read_and_translate_crc_ordered(
+            CRC_LEN,
+            config.crc_mode(),
+            start as usize,
+            -(end as isize),
+            config.crc_little_endian(),
+        )?;
+    }
+    body(&mut reader)?;
+
+    let mut capsule = RawCapsuleRef::new_upstream(bytes);
+    capsule.set_crc_valid(reader.crc_valid());
+    let raw_fields = _sorted_raw(reader.fields()?.clone());
+    if config.retain_raw_fields() {
+        capsule.set_raw_fields(raw_fields.clone());
+    }
+    capsule.set_fields(_report_fields(config, raw_fields));
+    if let Some(rules) = config.alert_rules() {
+        rules.apply(&mut capsule.field_details);
+    }
+    capsule.set_received_at(received_at);
+    capsule.mark_decoded();
+    Ok(capsule)
+}
+
+/// 下行编码：写入帧头/长度占位 -> 按 `definition` 写入参数 -> 写入 CRC 占位/帧尾 -> 回填长度与 CRC，
+/// 结果写回 `capsule` (bytes / hex / 字段)。返回整帧字节数。
+/// 只读命令 (`Cmd::rw` 为 `RW::Read`) 携带参数时返回 `ValidationFailed`。
+pub fn encode_downstream<T, E, P, V>(
+    config: &impl ProtocolConfig,
+    definition: &E,
+    params: &HashMap<String, V>,
+    capsule: &mut RawCapsule<T>,
+) -> ProtocolResult<usize>
+where
+    T: Cmd + 'static,
+    E: AutoEncoding<P>,
+    P: AutoEncodingParam,
+    V: EncodingInput,
+{
+    check_read_only(capsule, params)?;
+    encode_frame(config, capsule, |writer| {
+        definition.auto_process(params, writer)?;
+        Ok(())
+    })
+}
+
+/// 只读命令 (`RW::Read`) 不允许携带下行参数。空输入 (使用默认值或固定内容) 不算参数
+pub(crate) fn check_read_only<T: Cmd + 'static, V: EncodingInput>(
+    capsule: &RawCapsule<T>,
+    params: &HashMap<String, V>,
+) -> ProtocolResult<()> {
+    if let Some(cmd) = capsule.cmd()
+        && params.values().any(|value| !value.to_text().is_empty())
+        && cmd.rw().is_some_and(|rw| rw.is_read_only())
+    {
+        return Err(ProtocolError::ValidationFailed(format!(
+            "read-only cmd {} must not carry parameters downstream",
+            cmd.code()
+        ))
+        .with_device(capsule.device_no()));
+    }
+    Ok(())
+}
+
+/// 下行编码的外壳部分：帧头/长度占位 -> `body` 写入数据域 -> CRC 占位/帧尾 -> 回填长度与 CRC，
+/// 结果写回 `capsule`。返回整帧字节数
+pub(crate) fn encode_frame<T, F>(
+    config: &impl ProtocolConfig,
+    capsule: &mut RawCapsule<T>,
+    body: F,
+) -> ProtocolResult<usize>
+where
+    T: Cmd + 'static,
+    F: FnOnce(&mut Writer) -> ProtocolResult<()>,
+{
+    _check_escaping(config)?;
+    let head = hex_util::hex_to_bytes(&config.head_tag())?;
+    let tail = hex_util::hex_to_bytes(&config.tail_tag())?;
+
+    let mut writer = Writer::new();
+    if !head.is_empty() {
+        writer.write_bytes("帧头", &head, &config.head_tag())?;
+    }
+    let length_width = if config.has_length() {
+        let width = _length_width(config, head.len())?;
+        writer.write_placeholder("length", width)?;
+        Some(width)
+    } else {
+        None
+    };
+    body(&mut writer).map_err(|e| {
+        let cmd_code = capsule.cmd().map(|cmd| cmd.code());
+        e.with_context(None, None, cmd_code.as_deref())
+            .with_device(capsule.device_no())
+    })?;
+    if config.has_crc() {
+        writer.write_placeholder("crc", CRC_LEN)?;
+    }
+    if !tail.is_empty() {
+        writer.write_bytes("帧尾", &tail, &config.tail_tag())?;
+    }
+
+    let total = writer.buffer()?.len();
+    config.check_frame_len(total)?;
+    if let Some(width) = length_width {
+        if width < 8 && (total as u64) >> (width * 8) != 0 {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "frame length {} does not fit in {} bytes",
+                total, width
+            )));
+        }
+        let length_bytes = &(total as u64).to_be_bytes()[8usize.saturating_sub(width)..];
+        writer.rewrite_placeholder("length", "长度", length_bytes, &total.to_string())?;
+    }
+    if config.has_crc() {
+        let (start, end) = config.crc_index();
+        writer.write_crc::<()>(
+            config.crc_mode(),
+            start as usize,
+            -(end as isize),
+            "crc",
+            config.crc_little_endian(),
+        )?;
+    }
+
+    let raw_fields = _sorted_raw(writer.fields()?.clone());
+    if config.retain_raw_fields() {
+        capsule.set_raw_fields(raw_fields.clone());
+    }
+    capsule.set_fields(_report_fields(config, raw_fields));
+    capsule.set_bytes_and_generate_hex(writer.buffer()?)?;
+    Ok(total)
+}
+
+// 数据域定义所属的命令码 (取第一个字段的 `cmd_code`)
+fn _cmd_code<D, P, U>(definition: &D) -> String
+where
+    D: AutoDecoding<P, U>,
+    P: AutoDecodingParam<U>,
+    U: TryFromBytes,
+{
+    definition
+        .variants()
+        .first()
+        .map(AutoDecodingParam::cmd_code)
+        .unwrap_or_default()
+}
+
+fn _check_escaping(config: &impl ProtocolConfig) -> ProtocolResult<()> {
+    if config.requires_escaping() {
+        return Err(ProtocolError::UnsupportedMode(
+            "the default pipeline does not escape frames".into(),
+        ));
+    }
+    Ok(())
+}
+
+// 默认流程只支持紧跟帧头的长度字段
+fn _length_width(config: &impl ProtocolConfig, head_len: usize) -> ProtocolResult<usize> {
+    let (start, end) = config.length_index();
+    if start as usize != head_len {
+        return Err(ProtocolError::UnsupportedMode(format!(
+            "length field at {} must directly follow the {}-byte head tag",
+            start, head_len
+        )));
+    }
+    Ok((end - start) as usize)
+}
+
+fn _hex_field(raw: &[u8], title: &str) -> ProtocolResult<Rawfield> {
+    Ok(Rawfield::new(
+        raw,
+        title.into(),
+        hex_util::bytes_to_hex(raw)?,
+    ))
+}
+
+// 按帧中顺序排列好的原始字段生成上报字段
+fn _report_fields(config: &impl ProtocolConfig, raw_fields: Vec<Rawfield>) -> Vec<ReportField> {
+    let include_sources = config.report_field_sources();
+    raw_fields
+        .into_iter()
+        .map(|field| field.to_report_field_with(include_sources))
+        .collect()
+}
+
+fn _sorted_raw(mut fields: Vec<Rawfield>) -> Vec<Rawfield> {
+    fields.sort_by_key(|field| field.offset);
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AlertRule, AlertRules, CrcType, FieldType, HexDigestError, RW, Severity};
+    use once_cell::sync::Lazy;
+
+    static RULES: Lazy<AlertRules> =
+        Lazy::new(|| AlertRules::new().rule("dian_ya", AlertRule::Max(300.into())));
+
+    struct Frame;
+
+    impl ProtocolConfig for Frame {
+        fn head_tag(&self) -> String {
+            "68".into()
+        }
+        fn tail_tag(&self) -> String {
+            "16".into()
+        }
+        fn crc_mode(&self) -> CrcType {
+            CrcType::Crc16Modbus
+        }
+        fn crc_index(&self) -> (u8, u8) {
+            (0, 3)
+        }
+        fn length_index(&self) -> (u8, u8) {
+            (1, 2)
+        }
+        fn alert_rules(&self) -> Option<&AlertRules> {
+            Some(&RULES)
+        }
+    }
+
+    // 小端 CRC、最大 8 字节，上报字段带来源字节信息
+    struct ShortFrame;
+
+    impl ProtocolConfig for ShortFrame {
+        fn head_tag(&self) -> String {
+            "68".into()
+        }
+        fn tail_tag(&self) -> String {
+            "16".into()
+        }
+        fn crc_mode(&self) -> CrcType {
+            CrcType::Crc16Modbus
+        }
+        fn crc_index(&self) -> (u8, u8) {
+            (0, 3)
+        }
+        fn length_index(&self) -> (u8, u8) {
+            (0, 0)
+        }
+        fn max_frame_len(&self) -> usize {
+            8
+        }
+        fn crc_little_endian(&self) -> bool {
+            true
+        }
+        fn retain_raw_fields(&self) -> bool {
+            true
+        }
+        fn report_field_sources(&self) -> bool {
+            true
+        }
+    }
+
+    #[derive(Clone)]
+    struct Setting;
+
+    impl Cmd for Setting {
+        fn code(&self) -> String {
+            "A1".into()
+        }
+        fn title(&self) -> String {
+            "参数设置".into()
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    enum Field {
+        Interval,
+        Voltage,
+    }
+
+    impl AutoEncodingParam for Field {
+        fn code(&self) -> String {
+            match self {
+                Field::Interval => "interval".into(),
+                Field::Voltage => "voltage".into(),
+            }
+        }
+        fn title(&self) -> String {
+            match self {
+                Field::Interval => "上报间隔".into(),
+                Field::Voltage => "电压".into(),
+            }
+        }
+        fn byte_length(&self) -> usize {
+            2
+        }
+        fn field_type(&self) -> FieldType {
+            FieldType::UnsignedU16(1.0)
+        }
+    }
+
+    impl AutoEncoding<Field> for Field {
+        fn variants(&self) -> Vec<Field> {
+            vec![Field::Interval, Field::Voltage]
+        }
+    }
+
+    impl AutoDecodingParam for Field {
+        fn byte_length(&self) -> usize {
+            2
+        }
+        fn title(&self) -> String {
+            AutoEncodingParam::title(self)
+        }
+        fn field_type(&self) -> FieldType {
+            FieldType::UnsignedU16(1.0)
+        }
+    }
+
+    impl AutoDecoding<Field> for Field {
+        fn variants(&self) -> Vec<Field> {
+            vec![Field::Interval, Field::Voltage]
+        }
+    }
+
+    #[test]
+    fn test_encode_then_decode() {
+        let params = HashMap::from([
+            ("interval".to_string(), "60".to_string()),
+            ("voltage".to_string(), "360".to_string()),
+        ]);
+        let mut capsule = RawCapsule::new_downstream(Setting, "0001", "");
+        let total = encode_downstream(&Frame, &Field::Interval, &params, &mut capsule).unwrap();
+        assert_eq!(total, 9);
+        assert!(capsule.hex().starts_with("6809003C0168"));
+        assert!(capsule.hex().ends_with("16"));
+
+        let decoded: RawCapsule<Setting> =
+            decode_upstream(&Frame, &Field::Interval, capsule.bytes()).unwrap();
+        let values: Vec<&str> = decoded
+            .field_details()
+            .iter()
+            .map(|f| f.value.as_str())
+            .collect();
+        assert_eq!(values[..4], ["68", "9", "60", "360"]);
+        let voltage = &decoded.field_details()[3];
+        assert!(voltage.alert);
+        assert_eq!(voltage.alert_rule.as_deref(), Some("max 300"));
+        // 默认不带来源字节信息
+        assert!(voltage.hex.is_none() && voltage.byte_range().is_none());
+        assert_eq!(decoded.field_details().last().unwrap().name, "帧尾");
+        assert!(decoded.processing_latency().is_some());
+        assert_eq!(decoded.crc_valid(), Some(true));
+
+        // 零拷贝解码得到相同的字段，报文字节直接借用输入
+        let borrowed: RawCapsuleRef<Setting> =
+            decode_upstream_ref(&Frame, &Field::Interval, capsule.bytes()).unwrap();
+        assert!(std::ptr::eq(borrowed.bytes(), capsule.bytes()));
+        assert_eq!(borrowed.field_details(), decoded.field_details());
+        assert_eq!(borrowed.crc_valid(), Some(true));
+        assert!(borrowed.processing_latency().is_some());
+
+        let mut broken = capsule.bytes_clone();
+        broken[3] ^= 0xFF;
+        let Err(err) = decode_upstream::<Setting, _, _, _>(&Frame, &Field::Interval, &broken)
+        else {
+            panic!("crc mismatch should fail");
+        };
+        assert!(err.is_crc_error());
+        let context = err.context().unwrap();
+        assert_eq!(
+            (context.field.as_deref(), context.offset),
+            (Some("crc"), Some(6))
+        );
+
+        let mut shifted = capsule.bytes_clone();
+        shifted[1] = 7;
+        let err = Frame.validate_envelope(&shifted).unwrap_err();
+        assert!(
+            err.to_string()
+                .ends_with("(length field seems to exclude head and tail)")
+        );
+        shifted[1] = 12;
+        assert!(matches!(
+            Frame.validate_envelope(&shifted),
+            Err(ProtocolError::HexDigestError(
+                HexDigestError::TruncatedFrame { declared: 12, .. }
+            ))
+        ));
+
+        // 帧头帧尾不符是帧错误 (Fatal)，带上位置
+        let mut bad_head = capsule.bytes_clone();
+        bad_head[0] = 0x69;
+        let Err(err) = decode_upstream::<Setting, _, _, _>(&Frame, &Field::Interval, &bad_head)
+        else {
+            panic!("head mismatch should fail");
+        };
+        assert!(matches!(
+            err.root(),
+            ProtocolError::HexDigestError(HexDigestError::InvalidHead)
+        ));
+        assert!(err.is_frame_error() && err.severity() == Severity::Fatal);
+        assert_eq!(err.numeric_code(), 1002);
+        assert_eq!(err.context().unwrap().offset, Some(0));
+
+        let mut bad_tail = capsule.bytes_clone();
+        let last = bad_tail.len() - 1;
+        bad_tail[last] = 0x17;
+        let err = Frame.validate_envelope(&bad_tail).unwrap_err();
+        assert!(matches!(
+            err.root(),
+            ProtocolError::HexDigestError(HexDigestError::InvalidTail)
+        ));
+        assert_eq!(err.context().unwrap().field.as_deref(), Some("帧尾"));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_decode_batch() {
+        let frames: Vec<Vec<u8>> = (0..16)
+            .map(|interval| {
+                let params = HashMap::from([
+                    ("interval".to_string(), interval.to_string()),
+                    ("voltage".to_string(), "220".to_string()),
+                ]);
+                let mut capsule = RawCapsule::new_downstream(Setting, "0001", "");
+                encode_downstream(&Frame, &Field::Interval, &params, &mut capsule).unwrap();
+                capsule.bytes_clone()
+            })
+            .chain([vec![0x00]])
+            .collect();
+        let frames: Vec<&[u8]> = frames.iter().map(Vec::as_slice).collect();
+        let results = decode_batch::<Setting, _, _, _>(&Frame, &Field::Interval, &frames);
+        assert_eq!(results.len(), 17);
+        for (interval, result) in results[..16].iter().enumerate() {
+            let capsule = result.as_ref().unwrap();
+            assert_eq!(capsule.field_details()[2].value, interval.to_string());
+        }
+        assert!(results[16].is_err());
+    }
+
+    #[cfg(feature = "cache")]
+    #[test]
+    fn test_decode_dedup() {
+        use crate::{DedupKey, DedupWindow};
+        use std::time::Duration;
+
+        let params = HashMap::from([
+            ("interval".to_string(), "60".to_string()),
+            ("voltage".to_string(), "220".to_string()),
+        ]);
+        let mut capsule = RawCapsule::new_downstream(Setting, "0001", "");
+        encode_downstream(&Frame, &Field::Interval, &params, &mut capsule).unwrap();
+        let window = DedupWindow::new(Duration::from_secs(60));
+        let decode = |unique: &str, bytes: &[u8], window: &DedupWindow| {
+            decode_upstream_dedup::<Setting, _, _, _>(
+                &Frame,
+                &Field::Interval,
+                bytes,
+                unique,
+                window,
+            )
+            .unwrap()
+            .is_duplicate()
+        };
+        assert!(!decode("0001", capsule.bytes(), &window));
+        assert!(decode("0001", capsule.bytes(), &window));
+        assert!(!decode("0002", capsule.bytes(), &window));
+
+        // 按序号字段去重：其他内容变化的重传帧同样被识别
+        let window = DedupWindow::new(Duration::from_secs(60))
+            .key(DedupKey::Field(crate::to_pinyin("上报间隔")));
+        let mut changed = RawCapsule::new_downstream(Setting, "0001", "");
+        let params = HashMap::from([
+            ("interval".to_string(), "60".to_string()),
+            ("voltage".to_string(), "221".to_string()),
+        ]);
+        encode_downstream(&Frame, &Field::Interval, &params, &mut changed).unwrap();
+        assert!(!decode("0001", capsule.bytes(), &window));
+        assert!(decode("0001", changed.bytes(), &window));
+    }
+
+    #[derive(Clone)]
+    struct Query;
+
+    impl Cmd for Query {
+        fn code(&self) -> String {
+            "B1".into()
+        }
+        fn title(&self) -> String {
+            "参数查询".into()
+        }
+        fn rw(&self) -> Option<RW> {
+            Some(RW::Read)
+        }
+    }
+
+    #[test]
+    fn test_read_only_cmd_rejects_params() {
+        let params = HashMap::from([("interval".to_string(), "60".to_string())]);
+        let mut capsule = RawCapsule::new_downstream(Query, "0001", "");
+        let Err(err) = encode_downstream(&Frame, &Field::Interval, &params, &mut capsule) else {
+            panic!("read-only cmd with params should fail");
+        };
+        assert_eq!(err.code(), "VALIDATION_FAILED");
+        assert_eq!(
+            err.context().and_then(|c| c.device_no.as_deref()),
+            Some("0001")
+        );
+    }
+
+    #[test]
+    fn test_downstream_builder() {
+        let params = HashMap::from([
+            ("interval".to_string(), "60".to_string()),
+            ("voltage".to_string(), "360".to_string()),
+        ]);
+        let mut expected = RawCapsule::new_downstream(Setting, "0001", "");
+        encode_downstream(&Frame, &Field::Interval, &params, &mut expected).unwrap();
+
+        let capsule = RawCapsule::downstream_builder(Setting)
+            .device("0001", "")
+            .field("上报间隔", &[0x00, 0x3C], "60")
+            .field("电压", &[0x01, 0x68], "360")
+            .finalize(&Frame)
+            .unwrap();
+        assert_eq!(capsule.hex(), expected.hex());
+        assert_eq!(capsule.device_no(), Some("0001"));
+        assert!(capsule.device_id().is_none());
+        assert_eq!(capsule.field_details()[2].name, "上报间隔");
+    }
+
+    #[test]
+    fn test_frame_limit_and_crc_order() {
+        let params = HashMap::from([
+            ("interval".to_string(), "60".to_string()),
+            ("voltage".to_string(), "360".to_string()),
+        ]);
+        let mut capsule = RawCapsule::new_downstream(Setting, "0001", "");
+        assert_eq!(
+            encode_downstream(&ShortFrame, &Field::Interval, &params, &mut capsule).unwrap(),
+            8
+        );
+        let bytes = capsule.bytes();
+        let raw = capsule.raw_fields().unwrap();
+        assert_eq!(raw[1].bytes(), &[0x00, 0x3C]);
+        assert_eq!(raw[1].offset(), Some(1));
+        let interval = &capsule.field_details()[1];
+        assert_eq!(interval.byte_range(), Some(1..3));
+        assert_eq!(interval.hex.as_deref(), Some("003C"));
+        let crc = crate::crc_util::calculate_from_bytes(CrcType::Crc16Modbus, &bytes[..5]).unwrap();
+        assert_eq!(bytes[5..7], crc.to_le_bytes());
+        let decoded: RawCapsule<Setting> =
+            decode_upstream(&ShortFrame, &Field::Interval, bytes).unwrap();
+        assert_eq!(decoded.crc_valid(), Some(true));
+        // 只接受配置的字节序：大端写入的 CRC 不能通过小端配置的校验
+        let mut swapped = bytes.to_vec();
+        swapped.swap(5, 6);
+        let Err(err) = decode_upstream::<Setting, _, _, _>(&ShortFrame, &Field::Interval, &swapped)
+        else {
+            panic!("crc in the other byte order should fail");
+        };
+        assert!(err.is_crc_error());
+
+        // 带长度字段的 9 字节帧超过 ShortFrame 的上限
+        let mut capsule = RawCapsule::new_downstream(Setting, "0001", "");
+        encode_downstream(&Frame, &Field::Interval, &params, &mut capsule).unwrap();
+        assert!(ShortFrame.validate_envelope(capsule.bytes()).is_err());
+    }
+}
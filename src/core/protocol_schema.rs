@@ -0,0 +1,833 @@
+//! 声明式协议定义：用一个 JSON / TOML / YAML 文件描述帧外壳 (帧头、帧尾、长度、CRC)、
+//! 命令以及每个命令的字段布局，加载后直接用于上行解码与下行编码，
+//! 简单的厂家变体不必再单独写一个 crate。
+//!
+//! 解码、编码仍然走 `pipeline` 的默认流程 (Reader / Writer / FieldType)：
+//! `EnvelopeSchema` 实现 `ProtocolConfig`，`CmdSchema` 实现 `Cmd` 与 `AutoDecoding` / `AutoEncoding`，
+//! `FieldSchema` 实现 `AutoDecodingParam` / `AutoEncodingParam`。
+//! 数据域约定为：命令码 (`cmdLength` 字节，紧跟帧头与长度字段) + 按声明顺序排列的字段。
+//!
+//! ```ignore
+//! let schema = ProtocolSchema::from_file("vendor_a.toml")?;
+//! let capsule = schema.decode(&bytes)?;
+//!
+//! let mut capsule = schema.new_downstream("A1", "0001")?;
+//! schema.encode(&params, &mut capsule)?;
+//! ```
+//!
+//! 一个最小的 TOML 定义：
+//!
+//! ```toml
+//! name = "vendor-a"
+//!
+//! [envelope]
+//! head = "68"
+//! tail = "16"
+//! crc = "crc16-modbus"
+//! crcIndex = [0, 3]
+//! lengthIndex = [1, 2]
+//!
+//! [[commands]]
+//! code = "01"
+//! title = "数据上报"
+//! direction = "upstream"
+//! fields = [
+//!     { title = "电压", type = "u16", scale = 0.1, unit = "V" },
+//!     { title = "阀门状态", type = "u8", enum = { "00" = "关", "01" = "开" } },
+//! ]
+//! ```
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AutoDecoding, AutoDecodingParam, AutoEncoding, AutoEncodingParam, Cmd, CmdRegistry, CrcType,
+    DirectionEnum, FieldCompareDecoder, FieldCondition, FieldType, HexDigestError, MsgTypeEnum,
+    ProtocolConfig, ProtocolError, ProtocolResult, RawCapsule, Symbol, ValidationRule,
+    core::{
+        RW,
+        parts::param_value::EncodingInput,
+        pipeline::{check_read_only, decode_frame, encode_frame},
+        type_converter::FieldTranslator,
+    },
+    hex_util, math_util,
+};
+
+/// 字段的存储类型。serde 为小写，例如 "u16"、"bcd"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "lowercase")]
+pub enum FieldKind {
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+    Float,
+    Double,
+    // BCD 或原样的 hex
+    #[serde(alias = "hex")]
+    Bcd,
+    Ascii,
+}
+
+impl FieldKind {
+    /// 固定的字节数，BCD / ASCII 需要在字段上声明 `length`
+    pub fn width(&self) -> Option<usize> {
+        match self {
+            FieldKind::U8 | FieldKind::I8 => Some(1),
+            FieldKind::U16 | FieldKind::I16 => Some(2),
+            FieldKind::U32 | FieldKind::I32 | FieldKind::Float => Some(4),
+            FieldKind::U64 | FieldKind::I64 | FieldKind::Double => Some(8),
+            FieldKind::Bcd | FieldKind::Ascii => None,
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        !matches!(
+            self,
+            FieldKind::Float | FieldKind::Double | FieldKind::Bcd | FieldKind::Ascii
+        )
+    }
+
+    /// 对应的 `FieldType`，`scale` 只作用于整数类型
+    pub fn field_type(&self, scale: f64) -> FieldType {
+        match self {
+            FieldKind::U8 => FieldType::UnsignedU8(scale),
+            FieldKind::U16 => FieldType::UnsignedU16(scale),
+            FieldKind::U32 => FieldType::UnsignedU32(scale),
+            FieldKind::U64 => FieldType::UnsignedU64(scale),
+            FieldKind::I8 => FieldType::SignedI8(scale),
+            FieldKind::I16 => FieldType::SignedI16(scale),
+            FieldKind::I32 => FieldType::SignedI32(scale),
+            FieldKind::I64 => FieldType::SignedI64(scale),
+            FieldKind::Float => FieldType::Float,
+            FieldKind::Double => FieldType::Double,
+            FieldKind::Bcd => FieldType::StringOrBCD,
+            FieldKind::Ascii => FieldType::Ascii,
+        }
+    }
+}
+
+/// 单个字段的布局。
+/// - `enum`：取值表，键为字段字节的 hex (已按 `swap` 调整字节序)，解码时输出对应的文字，
+///   编码时既可以传文字也可以传数值；
+/// - `compare`：固定内容 (hex)，解码时校验，编码时自动写入；
+/// - `code`：下行参数名，省略时使用 `title`。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+pub struct FieldSchema {
+    #[serde(default)]
+    pub code: String,
+    // 代码生成 (`codegen`) 使用的枚举变体名，省略时由 title 的拼音生成
+    #[serde(default)]
+    pub ident: Option<String>,
+    pub title: String,
+    #[serde(rename = "type")]
+    pub kind: FieldKind,
+    // 字节数，0 表示使用类型的固定长度
+    #[serde(default)]
+    pub length: usize,
+    #[serde(default = "_unit_scale")]
+    pub scale: f64,
+    // true=小端 false=大端
+    #[serde(default)]
+    pub swap: bool,
+    #[serde(default)]
+    pub unit: Option<Symbol>,
+    #[serde(default)]
+    pub precision: Option<u32>,
+    #[serde(default, rename = "enum")]
+    pub enum_map: BTreeMap<String, String>,
+    #[serde(default)]
+    pub compare: Option<String>,
+    #[serde(default)]
+    pub default: Option<String>,
+    #[serde(default = "_required")]
+    pub required: bool,
+    #[serde(default)]
+    pub rules: Vec<ValidationRule>,
+    #[serde(default)]
+    pub condition: Option<FieldCondition>,
+    // 所属命令的命令码，加载时填充，用于错误上下文
+    #[serde(skip)]
+    pub(crate) cmd_code: String,
+}
+
+fn _unit_scale() -> f64 {
+    1.0
+}
+
+fn _required() -> bool {
+    true
+}
+
+impl FieldSchema {
+    // 补全 code、长度，规范化取值表与固定内容的 hex
+    fn _prepare(&mut self, cmd_code: &str) -> ProtocolResult<()> {
+        if self.code.is_empty() {
+            self.code = self.title.clone();
+        }
+        self.cmd_code = cmd_code.to_string();
+        match (self.kind.width(), self.length) {
+            (Some(width), 0) => self.length = width,
+            (Some(width), length) if width != length => {
+                return Err(self._invalid(format!(
+                    "type {:?} is {} bytes but length is {}",
+                    self.kind, width, length
+                )));
+            }
+            (None, 0) => return Err(self._invalid(format!("type {:?} needs a length", self.kind))),
+            _ => {}
+        }
+        let mut enum_map = BTreeMap::new();
+        for (key, label) in std::mem::take(&mut self.enum_map) {
+            enum_map.insert(self._normalize_hex(&key)?, label);
+        }
+        self.enum_map = enum_map;
+        if let Some(compare) = self.compare.take() {
+            self.compare = Some(self._normalize_hex(&compare)?);
+        }
+        Ok(())
+    }
+
+    // 大写并在左侧补 0 到字段长度
+    fn _normalize_hex(&self, hex: &str) -> ProtocolResult<String> {
+        let hex = hex.trim().to_ascii_uppercase();
+        let width = self.length * 2;
+        if hex.len() > width || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self._invalid(format!("'{}' is not a {}-byte hex value", hex, self.length)));
+        }
+        Ok(format!("{:0>width$}", hex))
+    }
+
+    fn _invalid(&self, reason: String) -> ProtocolError {
+        ProtocolError::ValidationFailed(format!("schema field '{}': {}", self.title, reason))
+    }
+
+    // 编码输入：取值表中的文字换成对应的值 (整数类型为十进制，其余为 hex)
+    pub(crate) fn resolve_label(&self, input: String) -> String {
+        match self.enum_map.iter().find(|(_, label)| **label == input) {
+            Some((key, _)) if self.kind.is_integer() => u64::from_str_radix(key, 16)
+                .map(|value| value.to_string())
+                .unwrap_or(input),
+            Some((key, _)) => key.clone(),
+            None => input,
+        }
+    }
+}
+
+impl AutoDecodingParam<String> for FieldSchema {
+    fn byte_length(&self) -> usize {
+        self.length
+    }
+
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn swap(&self) -> bool {
+        self.swap
+    }
+
+    fn cmd_code(&self) -> String {
+        self.cmd_code.clone()
+    }
+
+    fn symbol(&self) -> Option<Symbol> {
+        self.unit.clone()
+    }
+
+    fn precision(&self) -> u32 {
+        self.precision.unwrap_or(math_util::DEFAULT_PRECISION)
+    }
+
+    // 有取值表时走枚举模式，不能同时是翻译模式
+    fn field_type(&self) -> FieldType {
+        if self.enum_map.is_empty() {
+            self.kind.field_type(self.scale)
+        } else {
+            FieldType::Empty
+        }
+    }
+
+    fn compare_target(&self) -> Vec<u8> {
+        self.compare
+            .as_deref()
+            .and_then(|hex| hex_util::hex_to_bytes(hex).ok())
+            .unwrap_or_default()
+    }
+
+    fn enum_values(&self) -> Vec<(String, String)> {
+        self.enum_map
+            .iter()
+            .map(|(key, label)| (key.clone(), label.clone()))
+            .collect()
+    }
+
+    fn condition(&self) -> Option<FieldCondition> {
+        self.condition.clone()
+    }
+}
+
+impl AutoEncodingParam for FieldSchema {
+    fn code(&self) -> String {
+        self.code.clone()
+    }
+
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn byte_length(&self) -> usize {
+        self.length
+    }
+
+    fn cmd_code(&self) -> String {
+        self.cmd_code.clone()
+    }
+
+    fn field_type(&self) -> FieldType {
+        self.kind.field_type(self.scale)
+    }
+
+    fn default_value(&self) -> String {
+        self.default.clone().unwrap_or_default()
+    }
+
+    fn default_hex(&self) -> String {
+        self.compare.clone().unwrap_or_default()
+    }
+
+    fn swap(&self) -> bool {
+        self.swap
+    }
+
+    fn required(&self) -> bool {
+        self.required
+    }
+
+    fn condition(&self) -> Option<FieldCondition> {
+        self.condition.clone()
+    }
+
+    fn rules(&self) -> Vec<ValidationRule> {
+        self.rules.clone()
+    }
+}
+
+/// 一个命令：命令码 (hex)、名称、方向、读写属性、消息类型与字段布局
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+pub struct CmdSchema {
+    pub code: String,
+    // 代码生成 (`codegen`) 使用的枚举变体名，省略时由 title 的拼音生成
+    #[serde(default)]
+    pub ident: Option<String>,
+    pub title: String,
+    #[serde(default = "_both")]
+    pub direction: DirectionEnum,
+    #[serde(default)]
+    pub rw: Option<RW>,
+    #[serde(default)]
+    pub msg_type: Option<MsgTypeEnum>,
+    #[serde(default)]
+    pub fields: Vec<FieldSchema>,
+}
+
+fn _both() -> DirectionEnum {
+    DirectionEnum::Both
+}
+
+impl CmdSchema {
+    // 下行参数：按字段类型转换为字符串，取值表中的文字换成值
+    fn _inputs<V: EncodingInput>(
+        &self,
+        params: &HashMap<String, V>,
+    ) -> ProtocolResult<HashMap<String, String>> {
+        let mut inputs = HashMap::new();
+        for field in &self.fields {
+            let field_type = AutoEncodingParam::field_type(field);
+            // 缺失的参数由 auto_process 按默认值 (compare / default) 编码
+            if let Some(value) = params.get(&field.code) {
+                let input = field.resolve_label(value.to_input(&field_type)?);
+                inputs.insert(field.code.clone(), input);
+            }
+        }
+        Ok(inputs)
+    }
+}
+
+impl Cmd for CmdSchema {
+    fn code(&self) -> String {
+        self.code.clone()
+    }
+
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn direction(&self) -> DirectionEnum {
+        self.direction.clone()
+    }
+
+    fn rw(&self) -> Option<RW> {
+        self.rw
+    }
+
+    fn msg_type(&self) -> Option<MsgTypeEnum> {
+        self.msg_type.clone()
+    }
+}
+
+impl AutoDecoding<FieldSchema, String> for CmdSchema {
+    fn variants(&self) -> Vec<FieldSchema> {
+        self.fields.clone()
+    }
+}
+
+impl AutoEncoding<FieldSchema> for CmdSchema {
+    fn variants(&self) -> Vec<FieldSchema> {
+        self.fields.clone()
+    }
+}
+
+/// 帧外壳，字段含义同 `ProtocolConfig`。`crc` 为空表示不校验 CRC
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvelopeSchema {
+    #[serde(default)]
+    pub head: String,
+    #[serde(default)]
+    pub tail: String,
+    #[serde(default)]
+    pub crc: Option<CrcType>,
+    #[serde(default)]
+    pub crc_index: (u8, u8),
+    #[serde(default)]
+    pub length_index: (u8, u8),
+    #[serde(default)]
+    pub max_frame_len: usize,
+    #[serde(default)]
+    pub crc_little_endian: bool,
+    // 命令码的字节数，紧跟帧头与长度字段；0 表示没有命令码 (只能有一个上行命令)
+    #[serde(default = "_cmd_length")]
+    pub cmd_length: usize,
+    // 上报字段是否带来源字节信息 (hex / offset / length)
+    #[serde(default)]
+    pub field_sources: bool,
+}
+
+fn _cmd_length() -> usize {
+    1
+}
+
+// 帧头、帧尾生成为 0~2 字节的合法 hex，长度等取值限制在常见范围内，
+// 否则 fuzz 输入几乎都停在帧头的 hex 解析上
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for EnvelopeSchema {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let tag = |u: &mut arbitrary::Unstructured<'a>| -> arbitrary::Result<String> {
+            let len = u.int_in_range(0..=2)?;
+            Ok(hex::encode_upper(u.bytes(len)?))
+        };
+        Ok(Self {
+            head: tag(u)?,
+            tail: tag(u)?,
+            crc: u.arbitrary()?,
+            crc_index: u.arbitrary()?,
+            length_index: u.arbitrary()?,
+            max_frame_len: u.int_in_range(0..=1024)?,
+            crc_little_endian: u.arbitrary()?,
+            cmd_length: u.int_in_range(0..=2)?,
+            field_sources: u.arbitrary()?,
+        })
+    }
+}
+
+impl EnvelopeSchema {
+    // 命令码在帧中的起始位置
+    pub(crate) fn cmd_offset(&self) -> usize {
+        let (start, end) = self.length_index;
+        self.head.len() / 2 + end.saturating_sub(start) as usize
+    }
+}
+
+impl ProtocolConfig for EnvelopeSchema {
+    fn head_tag(&self) -> String {
+        self.head.clone()
+    }
+
+    fn tail_tag(&self) -> String {
+        self.tail.clone()
+    }
+
+    fn crc_mode(&self) -> CrcType {
+        self.crc.unwrap_or(CrcType::Crc16Modbus)
+    }
+
+    fn crc_index(&self) -> (u8, u8) {
+        if self.crc.is_some() {
+            self.crc_index
+        } else {
+            (0, 0)
+        }
+    }
+
+    fn length_index(&self) -> (u8, u8) {
+        self.length_index
+    }
+
+    fn max_frame_len(&self) -> usize {
+        self.max_frame_len
+    }
+
+    fn crc_little_endian(&self) -> bool {
+        self.crc_little_endian
+    }
+
+    fn report_field_sources(&self) -> bool {
+        self.field_sources
+    }
+}
+
+/// 从文件加载的完整协议定义
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+pub struct ProtocolSchema {
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    pub envelope: EnvelopeSchema,
+    #[serde(default)]
+    pub commands: Vec<CmdSchema>,
+}
+
+impl ProtocolSchema {
+    pub fn from_json(text: &str) -> ProtocolResult<Self> {
+        serde_json::from_str::<Self>(text)
+            .map_err(ProtocolError::external)?
+            ._prepare()
+    }
+
+    #[cfg(feature = "toml")]
+    pub fn from_toml(text: &str) -> ProtocolResult<Self> {
+        toml::from_str::<Self>(text)
+            .map_err(ProtocolError::external)?
+            ._prepare()
+    }
+
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml(text: &str) -> ProtocolResult<Self> {
+        serde_yaml::from_str::<Self>(text)
+            .map_err(ProtocolError::external)?
+            ._prepare()
+    }
+
+    /// 按扩展名 (json / toml / yaml / yml) 选择格式加载
+    pub fn from_file(path: impl AsRef<Path>) -> ProtocolResult<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(ProtocolError::external)?;
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match extension.as_str() {
+            "json" => Self::from_json(&text),
+            #[cfg(feature = "toml")]
+            "toml" => Self::from_toml(&text),
+            #[cfg(feature = "yaml")]
+            "yaml" | "yml" => Self::from_yaml(&text),
+            _ => Err(ProtocolError::UnsupportedMode(format!(
+                "schema file '{}' (supported: json, toml with the `toml` feature, yaml with the `yaml` feature)",
+                path.display()
+            ))),
+        }
+    }
+
+    // 校验并补全加载后的定义
+    fn _prepare(mut self) -> ProtocolResult<Self> {
+        let envelope = &mut self.envelope;
+        envelope.head = envelope.head.to_ascii_uppercase();
+        envelope.tail = envelope.tail.to_ascii_uppercase();
+        hex_util::hex_to_bytes(&envelope.head)?;
+        hex_util::hex_to_bytes(&envelope.tail)?;
+        if envelope.crc.is_some() && envelope.crc_index == (0, 0) {
+            return Err(ProtocolError::ValidationFailed(
+                "schema envelope: crc needs a crcIndex".into(),
+            ));
+        }
+        let cmd_length = envelope.cmd_length;
+
+        let mut upstream = HashSet::new();
+        let mut downstream = HashSet::new();
+        for cmd in &mut self.commands {
+            cmd.code = cmd.code.to_ascii_uppercase();
+            if cmd_length > 0
+                && (cmd.code.len() != cmd_length * 2 || hex_util::hex_to_bytes(&cmd.code).is_err())
+            {
+                return Err(ProtocolError::ValidationFailed(format!(
+                    "schema cmd '{}' is not a {}-byte hex code",
+                    cmd.code, cmd_length
+                )));
+            }
+            let duplicated = (cmd.direction.is_upstream() && !upstream.insert(cmd.code.clone()))
+                || (cmd.direction.is_downstream() && !downstream.insert(cmd.code.clone()));
+            if duplicated {
+                return Err(ProtocolError::ValidationFailed(format!(
+                    "schema cmd '{}' is declared twice for the same direction",
+                    cmd.code
+                )));
+            }
+            let code = cmd.code.clone();
+            for field in &mut cmd.fields {
+                field._prepare(&code)?;
+            }
+        }
+        if cmd_length == 0 && upstream.len() > 1 {
+            return Err(ProtocolError::ValidationFailed(
+                "schema without cmd codes (cmdLength 0) can have only one upstream cmd".into(),
+            ));
+        }
+        Ok(self)
+    }
+
+    /// 按命令码与方向查找命令，`DirectionEnum::Both` 表示不限方向
+    pub fn command(&self, code: &str, direction: DirectionEnum) -> Option<&CmdSchema> {
+        self.commands
+            .iter()
+            .find(|cmd| cmd.code.eq_ignore_ascii_case(code) && _in_direction(cmd, &direction))
+    }
+
+    /// 为下行命令创建 capsule，之后交给 `encode`
+    pub fn new_downstream(
+        &self,
+        code: &str,
+        device_no: &str,
+    ) -> ProtocolResult<RawCapsule<CmdSchema>> {
+        let cmd = self
+            .command(code, DirectionEnum::Downstream)
+            .ok_or_else(|| self._unknown(code, DirectionEnum::Downstream))?;
+        Ok(RawCapsule::new_downstream(cmd.clone(), device_no, ""))
+    }
+
+    /// 上行解码：按帧中的命令码选择命令，再走默认解码流程
+    pub fn decode(&self, bytes: &[u8]) -> ProtocolResult<RawCapsule<CmdSchema>> {
+        let envelope = &self.envelope;
+        envelope.validate_envelope(bytes)?;
+        let cmd = if envelope.cmd_length == 0 {
+            self.commands
+                .iter()
+                .find(|cmd| cmd.direction.is_upstream())
+                .ok_or_else(|| self._unknown("", DirectionEnum::Upstream))?
+        } else {
+            let start = envelope.cmd_offset();
+            let raw = bytes.get(start..start + envelope.cmd_length).ok_or(
+                ProtocolError::InputTooShort {
+                    needed: start + envelope.cmd_length,
+                    available: bytes.len(),
+                },
+            )?;
+            let code = hex_util::bytes_to_hex(raw)?;
+            self.command(&code, DirectionEnum::Upstream)
+                .ok_or_else(|| self._unknown(&code, DirectionEnum::Upstream))?
+        };
+
+        let mut capsule = decode_frame(envelope, bytes, &cmd.code, |reader| {
+            if envelope.cmd_length > 0 {
+                let target = hex_util::hex_to_bytes(&cmd.code)?;
+                reader.read_and_translate_head(envelope.cmd_length, |raw| {
+                    FieldCompareDecoder::new("命令码", target, false).translate(raw)
+                })?;
+            }
+            AutoDecoding::auto_process(cmd, reader)
+        })?;
+        capsule.set_cmd(cmd.clone());
+        Ok(capsule)
+    }
+
+    /// 下行编码：写入命令码与各字段，结果写回 `capsule`，返回整帧字节数
+    pub fn encode<V: EncodingInput>(
+        &self,
+        params: &HashMap<String, V>,
+        capsule: &mut RawCapsule<CmdSchema>,
+    ) -> ProtocolResult<usize> {
+        self.encode_with(&self.envelope, params, capsule)
+    }
+
+    // 按指定的帧外壳编码 (一致性测试需要临时打开字段来源信息)
+    pub(crate) fn encode_with<V: EncodingInput>(
+        &self,
+        envelope: &EnvelopeSchema,
+        params: &HashMap<String, V>,
+        capsule: &mut RawCapsule<CmdSchema>,
+    ) -> ProtocolResult<usize> {
+        let cmd = capsule.cmd().cloned().ok_or_else(|| {
+            ProtocolError::ValidationFailed("capsule has no cmd to encode".into())
+        })?;
+        check_read_only(capsule, params)?;
+        let inputs = cmd._inputs(params)?;
+        let cmd_length = envelope.cmd_length;
+        encode_frame(envelope, capsule, |writer| {
+            if cmd_length > 0 {
+                writer.write_bytes("命令码", &hex_util::hex_to_bytes(&cmd.code)?, &cmd.code)?;
+            }
+            AutoEncoding::auto_process(&cmd, &inputs, writer)?;
+            Ok(())
+        })
+    }
+
+    // 找不到命令时附带同方向上最接近的命令码
+    fn _unknown(&self, code: &str, direction: DirectionEnum) -> ProtocolError {
+        let mut registry = CmdRegistry::new();
+        for cmd in self
+            .commands
+            .iter()
+            .filter(|cmd| _in_direction(cmd, &direction))
+        {
+            // 同方向的命令码加载时已去重
+            let _ = registry.register(cmd.clone());
+        }
+        HexDigestError::UnknownCmdCode {
+            code: code.to_string(),
+            nearest: registry.nearest(code),
+        }
+        .into()
+    }
+}
+
+fn _in_direction(cmd: &CmdSchema, direction: &DirectionEnum) -> bool {
+    match direction {
+        DirectionEnum::Upstream => cmd.direction.is_upstream(),
+        DirectionEnum::Downstream => cmd.direction.is_downstream(),
+        DirectionEnum::Both => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"{
+        "name": "vendor-a",
+        "envelope": {
+            "head": "68",
+            "tail": "16",
+            "crc": "crc16-modbus",
+            "crcIndex": [0, 3],
+            "lengthIndex": [1, 2]
+        },
+        "commands": [
+            {
+                "code": "01",
+                "title": "数据上报",
+                "direction": "upstream",
+                "msgType": "data_report",
+                "fields": [
+                    { "title": "电压", "type": "u16", "scale": 0.1, "unit": "V" },
+                    { "title": "阀门状态", "type": "u8", "enum": { "0": "关", "1": "开" } },
+                    { "title": "表号", "type": "bcd", "length": 2, "swap": true }
+                ]
+            },
+            {
+                "code": "A1",
+                "title": "阀门控制",
+                "direction": "downstream",
+                "fields": [
+                    { "title": "类型", "type": "u8", "compare": "5A" },
+                    { "code": "valve", "title": "阀门", "type": "u8", "enum": { "00": "关", "01": "开" } },
+                    { "code": "interval", "title": "上报间隔", "type": "u16", "default": "60",
+                      "rules": [{ "rule": "max", "value": 1440 }] }
+                ]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_schema_decode() {
+        let schema = ProtocolSchema::from_json(SCHEMA).unwrap();
+        let mut frame = vec![0x68, 0x0B, 0x01, 0x08, 0xFC, 0x01, 0x34, 0x12];
+        let crc = crate::crc_util::calculate_from_bytes(CrcType::Crc16Modbus, &frame).unwrap();
+        frame.extend(crc.to_be_bytes());
+        frame.push(0x16);
+
+        let capsule = schema.decode(&frame).unwrap();
+        assert_eq!(capsule.cmd().unwrap().title, "数据上报");
+        let values: Vec<(&str, &str)> = capsule
+            .field_details()
+            .iter()
+            .map(|f| (f.name.as_str(), f.value.as_str()))
+            .collect();
+        assert_eq!(
+            values[2..6],
+            [
+                ("命令码", "01"),
+                ("电压", "230 V"),
+                ("阀门状态", "开"),
+                ("表号", "1234")
+            ]
+        );
+
+        frame[2] = 0x02;
+        let Err(err) = schema.decode(&frame) else {
+            panic!("unknown cmd code should fail");
+        };
+        assert!(matches!(
+            err,
+            ProtocolError::HexDigestError(HexDigestError::UnknownCmdCode { ref nearest, .. })
+                if nearest.as_deref() == Some("01")
+        ));
+    }
+
+    #[test]
+    fn test_schema_encode() {
+        let schema = ProtocolSchema::from_json(SCHEMA).unwrap();
+        let params = HashMap::from([("valve".to_string(), "开".to_string())]);
+        let mut capsule = schema.new_downstream("a1", "0001").unwrap();
+        assert_eq!(schema.encode(&params, &mut capsule).unwrap(), 10);
+        assert!(capsule.hex().starts_with("680AA15A01003C"));
+
+        let params = HashMap::from([
+            ("valve".to_string(), "1".to_string()),
+            ("interval".to_string(), "2000".to_string()),
+        ]);
+        assert!(schema.encode(&params, &mut capsule).is_err());
+
+        let broken = SCHEMA.replace(r#""length": 2, "swap""#, r#""swap""#);
+        assert!(ProtocolSchema::from_json(&broken).is_err());
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_schema_toml() {
+        let schema = ProtocolSchema::from_toml(
+            r#"
+            name = "vendor-b"
+
+            [envelope]
+            head = "7e"
+            cmdLength = 0
+
+            [[commands]]
+            code = ""
+            title = "心跳"
+            direction = "upstream"
+            fields = [{ title = "信号", type = "i8", unit = "dBm" }]
+            "#,
+        )
+        .unwrap();
+        let capsule = schema.decode(&[0x7E, 0xB5]).unwrap();
+        assert_eq!(capsule.field_details()[1].value, "-75 dBm");
+    }
+}
@@ -1,6 +1,10 @@
+#[cfg(feature = "std")]
+use crate::defi::bridge::ReportField;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
 use crate::{
     core::parts::rawfield::Rawfield,
-    defi::{ProtocolResult, bridge::ReportField, crc_enum::CrcType, error::ProtocolError},
+    defi::{ProtocolResult, crc_enum::CrcType, error::ProtocolError},
     utils::{crc_util, hex_util},
 };
 
@@ -13,6 +17,7 @@ pub struct Reader<'a> {
     total: usize,
     fields: Vec<Rawfield>,           // 收集所有解析出的字段
     current_field: Option<Rawfield>, // 当前正在解析的字段
+    crc_valid: Option<bool>,         // CRC 校验结果，未校验时为 None
 }
 
 impl<'a> Reader<'a> {
@@ -25,6 +30,7 @@ impl<'a> Reader<'a> {
             total: buffer.len(),
             fields: Vec::new(),
             current_field: None,
+            crc_valid: None,
         }
     }
     /// 返回总字节数
@@ -39,7 +45,8 @@ impl<'a> Reader<'a> {
             Err(ProtocolError::InputTooShort {
                 needed: len,
                 available: remaining,
-            })
+            }
+            .with_context(Some(self.pos), None, None))
         } else {
             Ok(())
         }
@@ -71,14 +78,43 @@ impl<'a> Reader<'a> {
         Ok(())
     }
 
+    /// 已解析字段中，标题为 `title` 的最后一个字段的值
+    pub fn field_value(&self, title: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .rev()
+            .find(|field| field.title == title)
+            .map(|field| field.value.as_str())
+    }
+
     /// 返回剩余未读字节的数量 (pos 和 sop 之间的距离)
     pub fn remaining_len(&self) -> usize {
         self.sop.saturating_sub(self.pos)
     }
 
+    /// `read_and_translate_crc` 的校验结果，未校验过 CRC 时为 None
+    pub fn crc_valid(&self) -> Option<bool> {
+        self.crc_valid
+    }
+
+    /// (非消耗) 获取已读取的所有字段
+    pub fn fields(&self) -> ProtocolResult<&Vec<Rawfield>> {
+        Ok(&self.fields)
+    }
+
+    #[cfg(feature = "std")]
     pub fn to_report_fields(&self) -> ProtocolResult<Vec<ReportField>> {
+        self.to_report_fields_with(false)
+    }
+
+    /// 同 `to_report_fields`，`include_sources` 为 true 时每个字段带上来源字节信息
+    #[cfg(feature = "std")]
+    pub fn to_report_fields_with(&self, include_sources: bool) -> ProtocolResult<Vec<ReportField>> {
         let fields = self.fields.clone();
-        let r: Vec<ReportField> = fields.into_iter().map(|f| f.to_report_field()).collect();
+        let r: Vec<ReportField> = fields
+            .into_iter()
+            .map(|f| f.to_report_field_with(include_sources))
+            .collect();
         Ok(r)
     }
 
@@ -119,8 +155,11 @@ impl<'a> Reader<'a> {
     where
         F: FnOnce(&[u8]) -> ProtocolResult<Rawfield>,
     {
+        let offset = self.pos;
         let remaining_bytes = self.read_remaining()?;
-        let raw_field = translator(&remaining_bytes)?;
+        let raw_field = translator(&remaining_bytes)
+            .map_err(|e| e.with_context(Some(offset), None, None))?
+            .with_offset(offset);
         self.current_field = Some(raw_field.clone());
         // 3. 创建并存储 Rawfield
         self.fields.push(raw_field);
@@ -142,7 +181,9 @@ impl<'a> Reader<'a> {
         let raw_bytes = &self.buffer[self.pos..self.pos + len];
 
         // 2. 调用翻译闭包
-        let raw_field = translator(raw_bytes)?;
+        let raw_field = translator(raw_bytes)
+            .map_err(|e| e.with_context(Some(self.pos), None, None))?
+            .with_offset(self.pos);
         self.current_field = Some(raw_field.clone());
         // 3. 创建并存储 Rawfield
         self.fields.push(raw_field);
@@ -174,7 +215,9 @@ impl<'a> Reader<'a> {
         let raw_bytes = &self.buffer[new_sop..self.sop];
 
         // 4. 调用翻译
-        let raw_field = translator(raw_bytes)?;
+        let raw_field = translator(raw_bytes)
+            .map_err(|e| e.with_context(Some(new_sop), None, None))?
+            .with_offset(new_sop);
         self.current_field = Some(raw_field.clone());
         self.fields.push(raw_field);
 
@@ -184,12 +227,43 @@ impl<'a> Reader<'a> {
         Ok(self)
     }
 
+    /// 读取并校验尾部的 CRC，大端或小端都认为匹配
     pub fn read_and_translate_crc(
         &mut self,
         len: usize,
         crc_mode: CrcType,
         crc_start_pos: usize,
         crc_end_pos: isize,
+    ) -> ProtocolResult<&mut Self> {
+        self._read_crc(len, crc_mode, crc_start_pos, crc_end_pos, None)
+    }
+
+    /// 同 `read_and_translate_crc`，但只接受 `little_endian` 指定的字节序
+    pub fn read_and_translate_crc_ordered(
+        &mut self,
+        len: usize,
+        crc_mode: CrcType,
+        crc_start_pos: usize,
+        crc_end_pos: isize,
+        little_endian: bool,
+    ) -> ProtocolResult<&mut Self> {
+        self._read_crc(
+            len,
+            crc_mode,
+            crc_start_pos,
+            crc_end_pos,
+            Some(little_endian),
+        )
+    }
+
+    // little_endian 为 None 时不限字节序
+    fn _read_crc(
+        &mut self,
+        len: usize,
+        crc_mode: CrcType,
+        crc_start_pos: usize,
+        crc_end_pos: isize,
+        little_endian: Option<bool>,
     ) -> ProtocolResult<&mut Self> {
         // 1. 检查总剩余空间
         self.check_remaining(len)?;
@@ -204,10 +278,17 @@ impl<'a> Reader<'a> {
         // 4. 计算crc并且进行比较
         let expected_crc_bytes = self.read_by_index_not_move(crc_start_pos, crc_end_pos)?;
         let calculated_crc_bytes = crc_util::calculate_from_bytes(crc_mode, expected_crc_bytes)?;
-        crc_util::compare_crc(&crc_hex, calculated_crc_bytes)?;
+        let compared = match little_endian {
+            Some(little_endian) => {
+                crc_util::compare_crc_ordered(crc_bytes, calculated_crc_bytes, little_endian)
+            }
+            None => crc_util::compare_crc(&crc_hex, calculated_crc_bytes),
+        };
+        self.crc_valid = Some(compared.is_ok());
+        compared.map_err(|e| e.with_context(Some(new_sop), Some("crc"), None))?;
 
         // 4. 创建 Rawfield (注意：是 *原始* 字节 `raw_bytes`)
-        let raw_field = Rawfield::new(crc_bytes, "crc".into(), crc_hex);
+        let raw_field = Rawfield::new(crc_bytes, "crc".into(), crc_hex).with_offset(new_sop);
         self.current_field = Some(raw_field.clone());
         self.fields.push(raw_field);
 
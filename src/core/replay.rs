@@ -0,0 +1,436 @@
+//! 报文抓取与回放：把生产环境的报文连同当时的解码结果记录下来，
+//! 升级 crate 前用新版本重新解码 (`replay_capture`)，逐条比较结果，提前发现行为回归。
+//!
+//! 抓取文件为紧凑的二进制格式 (多字节整数均为大端)：
+//! - 文件头：魔数 `PCCF` + 版本号 (1 字节)；
+//! - 每条记录：时间戳 (毫秒，u64) + 方向 (u8：0 上行 / 1 下行 / 2 双向)
+//!   + 设备唯一标识 (u16 长度 + UTF-8) + 原始报文 (u32 长度 + 字节)
+//!   + 解码结果 (u32 长度 + `CaptureOutcome` 的 JSON)。
+//!
+//! ```ignore
+//! let mut capture = CaptureWriter::new(File::create("gateway.pccf")?)?;
+//! let result = decode_upstream(&config, &definition, &bytes);
+//! capture.record(DirectionEnum::Upstream, &unique, &bytes, &result)?;
+//!
+//! // 新版本上线前
+//! let reader = CaptureReader::new(File::open("gateway.pccf")?)?;
+//! let report = replay_capture(reader, |record| {
+//!     CaptureOutcome::from_result(&decode_upstream(&config, &definition, &record.bytes))
+//! })?;
+//! assert!(report.is_clean(), "{:?}", report.mismatches);
+//! ```
+
+use std::{
+    io::{ErrorKind, Read, Write},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Cmd, DirectionEnum, ProtocolError, ProtocolResult, RawCapsule, ReportField};
+
+/// 抓取文件的魔数
+pub const CAPTURE_MAGIC: &[u8; 4] = b"PCCF";
+/// 当前的抓取文件版本
+pub const CAPTURE_VERSION: u8 = 1;
+
+/// 记录时的解码结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum CaptureOutcome {
+    // 只记录了原始报文，回放时跳过
+    NotDecoded,
+    #[serde(rename_all = "camelCase")]
+    Decoded {
+        cmd_code: Option<String>,
+        fields: Vec<ReportField>,
+    },
+    // 错误码 (`ProtocolError::code`) 与当时的错误信息
+    Failed {
+        code: String,
+        message: String,
+    },
+}
+
+impl CaptureOutcome {
+    pub fn from_capsule<T: Cmd + 'static>(capsule: &RawCapsule<T>) -> Self {
+        CaptureOutcome::Decoded {
+            cmd_code: capsule.cmd().map(|cmd| cmd.code()),
+            fields: capsule.field_details_clone(),
+        }
+    }
+
+    pub fn from_error(error: &ProtocolError) -> Self {
+        CaptureOutcome::Failed {
+            code: error.code().to_string(),
+            message: error.to_string(),
+        }
+    }
+
+    pub fn from_result<T: Cmd + 'static>(result: &ProtocolResult<RawCapsule<T>>) -> Self {
+        match result {
+            Ok(capsule) => Self::from_capsule(capsule),
+            Err(error) => Self::from_error(error),
+        }
+    }
+
+    /// 回放时是否视为相同的结果。失败只比较错误码，错误信息的措辞 (以及语言) 可能随版本变化
+    pub fn matches(&self, other: &CaptureOutcome) -> bool {
+        match (self, other) {
+            (
+                CaptureOutcome::Failed { code, .. },
+                CaptureOutcome::Failed {
+                    code: other_code, ..
+                },
+            ) => code == other_code,
+            _ => self == other,
+        }
+    }
+}
+
+/// 一条抓取记录
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaptureRecord {
+    pub timestamp: SystemTime,
+    pub direction: DirectionEnum,
+    // 设备唯一标识
+    pub unique: String,
+    pub bytes: Vec<u8>,
+    pub outcome: CaptureOutcome,
+}
+
+impl CaptureRecord {
+    /// 时间戳为当前时间
+    pub fn new(
+        direction: DirectionEnum,
+        unique: &str,
+        bytes: &[u8],
+        outcome: CaptureOutcome,
+    ) -> Self {
+        Self {
+            timestamp: SystemTime::now(),
+            direction,
+            unique: unique.to_string(),
+            bytes: bytes.to_vec(),
+            outcome,
+        }
+    }
+}
+
+/// 写入抓取文件
+pub struct CaptureWriter<W: Write> {
+    pub(crate) writer: W,
+}
+
+impl<W: Write> CaptureWriter<W> {
+    /// 写入文件头
+    pub fn new(mut writer: W) -> ProtocolResult<Self> {
+        writer.write_all(CAPTURE_MAGIC)?;
+        writer.write_all(&[CAPTURE_VERSION])?;
+        Ok(Self { writer })
+    }
+
+    pub fn write(&mut self, record: &CaptureRecord) -> ProtocolResult<()> {
+        let unique = record.unique.as_bytes();
+        let unique_len = u16::try_from(unique.len()).map_err(|_| {
+            ProtocolError::ValidationFailed(format!(
+                "capture unique '{}' is longer than {} bytes",
+                record.unique,
+                u16::MAX
+            ))
+        })?;
+        let outcome = serde_json::to_vec(&record.outcome).map_err(ProtocolError::external)?;
+        let timestamp = record
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        self.writer.write_all(&timestamp.to_be_bytes())?;
+        self.writer
+            .write_all(&[_direction_tag(&record.direction)])?;
+        self.writer.write_all(&unique_len.to_be_bytes())?;
+        self.writer.write_all(unique)?;
+        _write_block(&mut self.writer, &record.bytes)?;
+        _write_block(&mut self.writer, &outcome)?;
+        Ok(())
+    }
+
+    /// 记录一帧报文及其解码结果，时间戳为当前时间
+    pub fn record<T: Cmd + 'static>(
+        &mut self,
+        direction: DirectionEnum,
+        unique: &str,
+        bytes: &[u8],
+        result: &ProtocolResult<RawCapsule<T>>,
+    ) -> ProtocolResult<()> {
+        self.write(&CaptureRecord::new(
+            direction,
+            unique,
+            bytes,
+            CaptureOutcome::from_result(result),
+        ))
+    }
+
+    pub fn flush(&mut self) -> ProtocolResult<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// 读取抓取文件，按顺序迭代记录
+pub struct CaptureReader<R: Read> {
+    pub(crate) reader: R,
+    // 读取出错后停止迭代
+    pub(crate) failed: bool,
+}
+
+impl<R: Read> CaptureReader<R> {
+    /// 读取并校验文件头
+    pub fn new(mut reader: R) -> ProtocolResult<Self> {
+        let mut header = [0u8; 5];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != CAPTURE_MAGIC {
+            return Err(ProtocolError::ValidationFailed(
+                "not a capture file (bad magic)".into(),
+            ));
+        }
+        if header[4] != CAPTURE_VERSION {
+            return Err(ProtocolError::UnsupportedMode(format!(
+                "capture file version {} (supported: {})",
+                header[4], CAPTURE_VERSION
+            )));
+        }
+        Ok(Self {
+            reader,
+            failed: false,
+        })
+    }
+
+    /// 读取下一条记录，文件在记录边界处结束时返回 None
+    pub fn read_record(&mut self) -> ProtocolResult<Option<CaptureRecord>> {
+        // 只有在记录的第一个字节处结束才是正常结束，记录中间结束说明文件被截断
+        let mut timestamp = [0u8; 8];
+        loop {
+            match self.reader.read(&mut timestamp[..1]) {
+                Ok(0) => return Ok(None),
+                Ok(_) => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        self.reader.read_exact(&mut timestamp[1..])?;
+        let mut tag = [0u8; 1];
+        self.reader.read_exact(&mut tag)?;
+        let mut unique_len = [0u8; 2];
+        self.reader.read_exact(&mut unique_len)?;
+        let mut unique = vec![0u8; u16::from_be_bytes(unique_len) as usize];
+        self.reader.read_exact(&mut unique)?;
+        let bytes = _read_block(&mut self.reader)?;
+        let outcome = _read_block(&mut self.reader)?;
+
+        Ok(Some(CaptureRecord {
+            timestamp: UNIX_EPOCH + Duration::from_millis(u64::from_be_bytes(timestamp)),
+            direction: _direction_from_tag(tag[0])?,
+            unique: String::from_utf8(unique).map_err(ProtocolError::external)?,
+            bytes,
+            outcome: serde_json::from_slice(&outcome).map_err(ProtocolError::external)?,
+        }))
+    }
+}
+
+impl<R: Read> Iterator for CaptureReader<R> {
+    type Item = ProtocolResult<CaptureRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let result = self.read_record();
+        self.failed = result.is_err();
+        result.transpose()
+    }
+}
+
+/// 回放中结果不一致的记录
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayMismatch {
+    // 记录在文件中的序号 (从 0 开始)
+    pub index: usize,
+    pub record: CaptureRecord,
+    pub actual: CaptureOutcome,
+}
+
+/// 回放结果汇总
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplayReport {
+    pub total: usize,
+    pub matched: usize,
+    // 未记录解码结果 (`NotDecoded`) 的记录数
+    pub skipped: usize,
+    pub mismatches: Vec<ReplayMismatch>,
+}
+
+impl ReplayReport {
+    /// 所有记录的结果都与抓取时一致
+    pub fn is_clean(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// 逐条回放：`decode` 用当前版本重新处理报文，结果与抓取时的 `outcome` 比较 (`CaptureOutcome::matches`)
+pub fn replay_capture<R: Read>(
+    reader: CaptureReader<R>,
+    mut decode: impl FnMut(&CaptureRecord) -> CaptureOutcome,
+) -> ProtocolResult<ReplayReport> {
+    let mut report = ReplayReport::default();
+    for (index, record) in reader.enumerate() {
+        let record = record?;
+        report.total += 1;
+        if record.outcome == CaptureOutcome::NotDecoded {
+            report.skipped += 1;
+            continue;
+        }
+        let actual = decode(&record);
+        if record.outcome.matches(&actual) {
+            report.matched += 1;
+        } else {
+            report.mismatches.push(ReplayMismatch {
+                index,
+                record,
+                actual,
+            });
+        }
+    }
+    Ok(report)
+}
+
+fn _write_block(writer: &mut impl Write, block: &[u8]) -> ProtocolResult<()> {
+    let len = u32::try_from(block.len()).map_err(|_| {
+        ProtocolError::ValidationFailed(format!(
+            "capture block of {} bytes is too long",
+            block.len()
+        ))
+    })?;
+    writer.write_all(&len.to_be_bytes())?;
+    writer.write_all(block)?;
+    Ok(())
+}
+
+fn _read_block(reader: &mut impl Read) -> ProtocolResult<Vec<u8>> {
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len)?;
+    let mut block = Vec::new();
+    // 长度来自文件，按实际读到的字节数分配，避免损坏的长度导致一次性的大分配
+    reader
+        .take(u32::from_be_bytes(len) as u64)
+        .read_to_end(&mut block)?;
+    if block.len() != u32::from_be_bytes(len) as usize {
+        return Err(std::io::Error::from(ErrorKind::UnexpectedEof).into());
+    }
+    Ok(block)
+}
+
+fn _direction_tag(direction: &DirectionEnum) -> u8 {
+    match direction {
+        DirectionEnum::Upstream => 0,
+        DirectionEnum::Downstream => 1,
+        DirectionEnum::Both => 2,
+    }
+}
+
+fn _direction_from_tag(tag: u8) -> ProtocolResult<DirectionEnum> {
+    match tag {
+        0 => Ok(DirectionEnum::Upstream),
+        1 => Ok(DirectionEnum::Downstream),
+        2 => Ok(DirectionEnum::Both),
+        _ => Err(ProtocolError::ValidationFailed(format!(
+            "capture record has unknown direction {}",
+            tag
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn _decoded(value: &str) -> CaptureOutcome {
+        CaptureOutcome::Decoded {
+            cmd_code: Some("01".into()),
+            fields: vec![ReportField::new("电压", "voltage", value.into())],
+        }
+    }
+
+    #[test]
+    fn test_capture_round_trip_and_replay() {
+        let records = vec![
+            CaptureRecord::new(
+                DirectionEnum::Upstream,
+                "0001",
+                &[0x68, 0x01],
+                _decoded("230"),
+            ),
+            CaptureRecord::new(
+                DirectionEnum::Upstream,
+                "0002",
+                &[0x68, 0x02],
+                CaptureOutcome::from_error(&ProtocolError::CrcError {
+                    ori_crc: 1,
+                    calc_crc: 2,
+                }),
+            ),
+            CaptureRecord::new(
+                DirectionEnum::Downstream,
+                "0001",
+                &[0x68, 0xA1],
+                CaptureOutcome::NotDecoded,
+            ),
+        ];
+        let mut writer = CaptureWriter::new(Vec::new()).unwrap();
+        for record in &records {
+            writer.write(record).unwrap();
+        }
+        let file = writer.into_inner();
+
+        let read: Vec<CaptureRecord> = CaptureReader::new(file.as_slice())
+            .unwrap()
+            .collect::<ProtocolResult<_>>()
+            .unwrap();
+        assert_eq!(read.len(), 3);
+        // 时间戳按毫秒保存
+        let millis = |time: SystemTime| time.duration_since(UNIX_EPOCH).unwrap().as_millis();
+        assert_eq!(millis(read[0].timestamp), millis(records[0].timestamp));
+        assert_eq!(read[0].bytes, records[0].bytes);
+        assert_eq!(read[1].outcome, records[1].outcome);
+        assert_eq!(read[2].direction, DirectionEnum::Downstream);
+
+        // 0001 的解码结果变了，0002 仍然是 CRC 错误 (错误信息不同也视为一致)
+        let report =
+            replay_capture(
+                CaptureReader::new(file.as_slice()).unwrap(),
+                |record| match record.unique.as_str() {
+                    "0001" => _decoded("23.0"),
+                    _ => CaptureOutcome::Failed {
+                        code: "CRC_ERROR".into(),
+                        message: "reworded".into(),
+                    },
+                },
+            )
+            .unwrap();
+        assert_eq!((report.total, report.matched, report.skipped), (3, 1, 1));
+        assert_eq!(report.mismatches.len(), 1);
+        assert_eq!(report.mismatches[0].index, 0);
+        assert!(!report.is_clean());
+
+        // 截断的文件报错，而不是静默结束
+        let truncated = &file[..file.len() - 3];
+        let results: Vec<_> = CaptureReader::new(truncated).unwrap().collect();
+        assert!(results.last().unwrap().is_err());
+        assert!(CaptureReader::new(&b"PCAP\x01"[..]).is_err());
+    }
+}
@@ -0,0 +1,215 @@
+//! 串口 (RTU) 读帧，用于 RS-485 集中器场景。
+//!
+//! RTU 规定帧与帧之间至少有 3.5 个字符时间的静默：串口的读超时设为静默时长，
+//! 读超时即认为当前帧已经结束，还没凑成完整帧的字节作为残帧丢弃。
+//! 静默之前已经能按 `ProtocolConfig` 切出完整帧 (`framing::scan_frame`) 时直接返回，
+//! 因此连续到达的多帧也能正确切分。读到的帧可以直接交给 `decode_upstream` 解码 (`decode_next`)。
+//!
+//! 静默时长的精度受操作系统调度影响，只能做到毫秒级，帧的边界仍以帧头、长度字段、帧尾为准。
+
+use std::io::{ErrorKind, Read};
+use std::time::{Duration, Instant};
+
+use serialport::SerialPort;
+
+use crate::core::framing::{FrameScan, scan_frame};
+use crate::core::pipeline::decode_upstream;
+use crate::utils::buffer_pool;
+use crate::{
+    AutoDecoding, AutoDecodingParam, Cmd, ProtocolConfig, ProtocolError, ProtocolResult,
+    RawCapsule, TryFromBytes,
+};
+
+// 每个字符 11 位：起始位 + 8 数据位 + 校验位 + 停止位
+const BITS_PER_CHAR: f64 = 11.0;
+// 波特率高于 19200 时，Modbus RTU 规定使用固定的 1.75ms 静默
+const FIXED_SILENCE_BAUD: u32 = 19200;
+const FIXED_SILENCE: Duration = Duration::from_micros(1750);
+// 默认的应答超时
+const DEFAULT_RESPONSE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// 3.5 个字符时间的帧间静默
+pub fn silence_interval(baud_rate: u32) -> Duration {
+    if baud_rate > FIXED_SILENCE_BAUD {
+        return FIXED_SILENCE;
+    }
+    Duration::from_secs_f64(3.5 * BITS_PER_CHAR / baud_rate.max(1) as f64)
+}
+
+/// 按帧间静默和 `ProtocolConfig` 从串口读取完整的帧。
+/// `port` 的读超时应等于静默时长 (`open` 会自动设置)，读超时即视为静默
+pub struct SerialFrameReader<C: ProtocolConfig, R: Read = Box<dyn SerialPort>> {
+    pub(crate) config: C,
+    pub(crate) port: R,
+    // 等待一帧的最长时间，超时 `read_frame` 返回 None，调用方可以借此检查退出条件
+    pub(crate) response_timeout: Duration,
+    pub(crate) buffer: Vec<u8>,
+}
+
+impl<C: ProtocolConfig> SerialFrameReader<C, Box<dyn SerialPort>> {
+    /// 打开串口 (如 "/dev/ttyUSB0"、"COM3")，读超时设为该波特率下的帧间静默
+    pub fn open(path: &str, baud_rate: u32, config: C) -> ProtocolResult<Self> {
+        let port = serialport::new(path, baud_rate)
+            .timeout(silence_interval(baud_rate))
+            .open()
+            .map_err(ProtocolError::external)?;
+        Ok(Self::new(port, config))
+    }
+}
+
+impl<C: ProtocolConfig, R: Read> SerialFrameReader<C, R> {
+    pub fn new(port: R, config: C) -> Self {
+        Self {
+            config,
+            port,
+            response_timeout: DEFAULT_RESPONSE_TIMEOUT,
+            buffer: Vec::new(),
+        }
+    }
+
+    pub fn with_response_timeout(mut self, timeout: Duration) -> Self {
+        self.response_timeout = timeout;
+        self
+    }
+
+    pub fn config(&self) -> &C {
+        &self.config
+    }
+
+    /// 底层串口，可用于发送下行报文
+    pub fn port_mut(&mut self) -> &mut R {
+        &mut self.port
+    }
+
+    pub fn into_inner(self) -> R {
+        self.port
+    }
+
+    /// 读取下一帧，超过应答超时仍没有完整的帧时返回 None
+    pub fn read_frame(&mut self) -> ProtocolResult<Option<Vec<u8>>> {
+        let started = Instant::now();
+        let mut chunk = [0u8; 256];
+        loop {
+            if let Some(frame) = self._take_frame()? {
+                return Ok(Some(frame));
+            }
+            match self.port.read(&mut chunk) {
+                Ok(0) => {
+                    // 非串口的数据源 (文件、管道) 读完
+                    self.buffer.clear();
+                    return Ok(None);
+                }
+                Ok(n) => self.buffer.extend_from_slice(&chunk[..n]),
+                Err(e) if matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock) => {
+                    // 静默：当前帧已结束，剩下的是残帧
+                    self.buffer.clear();
+                    if started.elapsed() >= self.response_timeout {
+                        return Ok(None);
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// 读取下一帧并解码
+    pub fn decode_next<T, D, P, U>(
+        &mut self,
+        definition: &D,
+    ) -> ProtocolResult<Option<RawCapsule<T>>>
+    where
+        T: Cmd + 'static,
+        D: AutoDecoding<P, U>,
+        P: AutoDecodingParam<U>,
+        U: TryFromBytes,
+    {
+        match self.read_frame()? {
+            Some(frame) => decode_upstream(&self.config, definition, &frame).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn _take_frame(&mut self) -> ProtocolResult<Option<Vec<u8>>> {
+        match scan_frame(&self.config, &self.buffer)? {
+            FrameScan::Frame { skip, len } => {
+                let frame = buffer_pool::copy(&self.buffer[skip..skip + len]);
+                self.buffer.drain(..skip + len);
+                Ok(Some(frame))
+            }
+            FrameScan::Partial { skip } => {
+                self.buffer.drain(..skip);
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::io;
+
+    use super::*;
+    use crate::CrcType;
+
+    struct Meter;
+
+    impl ProtocolConfig for Meter {
+        fn head_tag(&self) -> String {
+            "68".into()
+        }
+        fn tail_tag(&self) -> String {
+            "16".into()
+        }
+        fn crc_mode(&self) -> CrcType {
+            CrcType::Crc16Modbus
+        }
+        fn crc_index(&self) -> (u8, u8) {
+            (0, 0)
+        }
+        fn length_index(&self) -> (u8, u8) {
+            (1, 2)
+        }
+    }
+
+    // 按顺序返回数据块，None 表示一次读超时 (静默)
+    struct MockPort(VecDeque<Option<Vec<u8>>>);
+
+    impl Read for MockPort {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.pop_front() {
+                Some(Some(chunk)) => {
+                    buf[..chunk.len()].copy_from_slice(&chunk);
+                    Ok(chunk.len())
+                }
+                _ => Err(ErrorKind::TimedOut.into()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_silence_interval() {
+        assert_eq!(silence_interval(9600).as_micros(), 4010);
+        assert_eq!(silence_interval(115200), FIXED_SILENCE);
+    }
+
+    #[test]
+    fn test_read_frames_split_by_silence() {
+        let port = MockPort(VecDeque::from([
+            // 残帧 + 静默，丢弃
+            Some(vec![0x68, 0x05]),
+            None,
+            // 一帧分两次到达
+            Some(vec![0x68, 0x05, 0x01]),
+            Some(vec![0x02, 0x16]),
+        ]));
+        let mut reader = SerialFrameReader::new(port, Meter).with_response_timeout(Duration::ZERO);
+        assert_eq!(reader.read_frame().unwrap(), None);
+        assert_eq!(
+            reader.read_frame().unwrap(),
+            Some(vec![0x68, 0x05, 0x01, 0x02, 0x16])
+        );
+        assert_eq!(reader.read_frame().unwrap(), None);
+    }
+}
@@ -0,0 +1,369 @@
+//! 下行会话管理：按设备记录已发出、等待应答的下行命令，处理应答匹配、重发和超时。
+//!
+//! 典型流程：
+//! 1. 发出下行报文后调用 `submit` (或 `submit_capsule`) 登记；
+//! 2. 收到上行帧后调用 `on_response` (或 `on_upstream`)，按期望的应答命令码匹配最早的待应答命令；
+//! 3. 定时调用 `poll_timeouts`：超时未应答的命令在重试次数内返回 `Retry` (调用方重发 `frame`)，
+//!    重试用完返回 `Failed` 并移出队列。
+//!
+//! 应答会刷新 `DeviceCache` 中设备的最近上报时间，设备号的命名空间与缓存视图一致。
+//! 所有事件同时返回给调用方并通知 `on_event` 注册的监听器。
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use crate::core::cache::{DeviceCache, ProtocolCache, namespaced_key};
+use crate::core::metrics;
+use crate::{Cmd, ProtocolError, ProtocolResult, RawCapsule};
+
+/// 默认应答超时
+pub const DEFAULT_RESPONSE_TIMEOUT: Duration = Duration::from_secs(30);
+/// 默认最大重试次数 (不含首次发送)
+pub const DEFAULT_MAX_RETRIES: u32 = 2;
+/// 默认每个设备最多的待应答命令数
+pub const DEFAULT_MAX_PENDING: usize = 16;
+
+/// 会话配置
+#[derive(Debug, Clone)]
+pub struct SessionConfig {
+    pub response_timeout: Duration,
+    pub max_retries: u32,
+    pub max_pending: usize,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            response_timeout: DEFAULT_RESPONSE_TIMEOUT,
+            max_retries: DEFAULT_MAX_RETRIES,
+            max_pending: DEFAULT_MAX_PENDING,
+        }
+    }
+}
+
+/// 等待应答的下行命令
+#[derive(Debug, Clone)]
+pub struct PendingCommand {
+    pub(crate) id: u64,
+    pub(crate) unique: String,
+    pub(crate) cmd_code: String,
+    // 期望的应答命令码，None 表示与下行命令码相同
+    pub(crate) expected_response: Option<String>,
+    pub(crate) frame: Vec<u8>,
+    // 已发送次数 (含首次)
+    pub(crate) attempts: u32,
+    pub(crate) submitted_at: Instant,
+    pub(crate) sent_at: Instant,
+}
+
+impl PendingCommand {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn unique(&self) -> &str {
+        &self.unique
+    }
+
+    pub fn cmd_code(&self) -> &str {
+        &self.cmd_code
+    }
+
+    pub fn expected_response(&self) -> &str {
+        self.expected_response.as_deref().unwrap_or(&self.cmd_code)
+    }
+
+    /// 需要重发时使用的报文
+    pub fn frame(&self) -> &[u8] {
+        &self.frame
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// 从首次发送到现在的时长
+    pub fn elapsed(&self) -> Duration {
+        self.submitted_at.elapsed()
+    }
+
+    fn _matches(&self, cmd_code: &str) -> bool {
+        self.expected_response().eq_ignore_ascii_case(cmd_code)
+    }
+}
+
+/// 会话事件
+#[derive(Debug, Clone)]
+pub enum SessionEvent {
+    // 收到应答
+    Completed(PendingCommand),
+    // 应答超时，调用方应重发 `frame`
+    Retry(PendingCommand),
+    // 重试用完仍未应答，已移出队列
+    Failed(PendingCommand),
+}
+
+impl SessionEvent {
+    pub fn command(&self) -> &PendingCommand {
+        match self {
+            SessionEvent::Completed(command)
+            | SessionEvent::Retry(command)
+            | SessionEvent::Failed(command) => command,
+        }
+    }
+}
+
+type SessionListener = Arc<dyn Fn(&SessionEvent) + Send + Sync>;
+
+/// 按设备管理待应答的下行命令。可廉价克隆，克隆后共享同一份状态
+#[derive(Clone)]
+pub struct SessionManager {
+    pub(crate) config: SessionConfig,
+    pub(crate) cache: DeviceCache,
+    sessions: Arc<Mutex<HashMap<String, VecDeque<PendingCommand>>>>,
+    next_id: Arc<AtomicU64>,
+    listeners: Vec<SessionListener>,
+}
+
+impl Default for SessionManager {
+    fn default() -> Self {
+        Self::new(SessionConfig::default())
+    }
+}
+
+impl SessionManager {
+    /// 使用全局缓存 (`ProtocolCache::global`)
+    pub fn new(config: SessionConfig) -> Self {
+        Self::with_cache(ProtocolCache::global().clone(), config)
+    }
+
+    /// 使用指定的缓存 (或其命名空间视图)
+    pub fn with_cache(cache: DeviceCache, config: SessionConfig) -> Self {
+        Self {
+            config,
+            cache,
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(AtomicU64::new(1)),
+            listeners: Vec::new(),
+        }
+    }
+
+    /// 注册事件监听器 (完成、重试、失败)
+    pub fn on_event(mut self, listener: impl Fn(&SessionEvent) + Send + Sync + 'static) -> Self {
+        self.listeners.push(Arc::new(listener));
+        self
+    }
+
+    pub fn config(&self) -> &SessionConfig {
+        &self.config
+    }
+
+    /// 登记一条已发出的下行命令，返回命令 id。设备的待应答命令已满时返回 `ValidationFailed`
+    pub fn submit(
+        &self,
+        unique: &str,
+        cmd_code: &str,
+        expected_response: Option<&str>,
+        frame: Vec<u8>,
+    ) -> ProtocolResult<u64> {
+        let mut sessions = self._lock();
+        let queue = sessions.entry(self._key(unique)).or_default();
+        if queue.len() >= self.config.max_pending {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "device {} already has {} pending commands",
+                unique,
+                queue.len()
+            )));
+        }
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let now = Instant::now();
+        queue.push_back(PendingCommand {
+            id,
+            unique: unique.to_string(),
+            cmd_code: cmd_code.to_string(),
+            expected_response: expected_response.map(str::to_string),
+            frame,
+            attempts: 1,
+            submitted_at: now,
+            sent_at: now,
+        });
+        _update_gauge(&sessions);
+        Ok(id)
+    }
+
+    /// 按编码后的下行 capsule 登记，设备号、命令码、报文取自 capsule
+    pub fn submit_capsule<T: Cmd + 'static>(
+        &self,
+        capsule: &RawCapsule<T>,
+        expected_response: Option<&str>,
+    ) -> ProtocolResult<u64> {
+        let (unique, cmd_code) = _capsule_key(capsule)?;
+        self.submit(unique, &cmd_code, expected_response, capsule.bytes_clone())
+    }
+
+    /// 收到设备的应答：匹配并移除最早的待应答命令，同时刷新设备的最近上报时间
+    pub fn on_response(&self, unique: &str, cmd_code: &str) -> Option<SessionEvent> {
+        self.cache.touch(unique);
+        let command = {
+            let mut sessions = self._lock();
+            let key = self._key(unique);
+            let queue = sessions.get_mut(&key)?;
+            let index = queue.iter().position(|c| c._matches(cmd_code))?;
+            let command = queue.remove(index);
+            if queue.is_empty() {
+                sessions.remove(&key);
+            }
+            _update_gauge(&sessions);
+            command
+        }?;
+        let event = SessionEvent::Completed(command);
+        self._emit(&event);
+        Some(event)
+    }
+
+    /// 按解码后的上行 capsule 匹配应答
+    pub fn on_upstream<T: Cmd + 'static>(
+        &self,
+        capsule: &RawCapsule<T>,
+    ) -> ProtocolResult<Option<SessionEvent>> {
+        let (unique, cmd_code) = _capsule_key(capsule)?;
+        Ok(self.on_response(unique, &cmd_code))
+    }
+
+    /// 检查超时：重试次数内的命令重新计时并返回 `Retry`，重试用完的移出队列并返回 `Failed`
+    pub fn poll_timeouts(&self) -> Vec<SessionEvent> {
+        let mut events = Vec::new();
+        {
+            let mut sessions = self._lock();
+            for queue in sessions.values_mut() {
+                queue.retain_mut(|command| {
+                    if command.sent_at.elapsed() < self.config.response_timeout {
+                        return true;
+                    }
+                    if command.attempts > self.config.max_retries {
+                        events.push(SessionEvent::Failed(command.clone()));
+                        return false;
+                    }
+                    command.attempts += 1;
+                    command.sent_at = Instant::now();
+                    events.push(SessionEvent::Retry(command.clone()));
+                    true
+                });
+            }
+            sessions.retain(|_, queue| !queue.is_empty());
+            _update_gauge(&sessions);
+        }
+        events.iter().for_each(|event| self._emit(event));
+        events
+    }
+
+    /// 设备当前待应答的命令 (按发送顺序)
+    pub fn pending(&self, unique: &str) -> Vec<PendingCommand> {
+        self._lock()
+            .get(&self._key(unique))
+            .map(|queue| queue.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// 所有设备的待应答命令总数
+    pub fn pending_count(&self) -> usize {
+        self._lock().values().map(VecDeque::len).sum()
+    }
+
+    /// 取消设备的所有待应答命令 (例如设备注销)，不产生事件
+    pub fn cancel(&self, unique: &str) -> Vec<PendingCommand> {
+        let mut sessions = self._lock();
+        let cancelled = sessions.remove(&self._key(unique));
+        _update_gauge(&sessions);
+        cancelled.map(Vec::from).unwrap_or_default()
+    }
+
+    fn _key(&self, unique: &str) -> String {
+        namespaced_key(self.cache.namespace_name(), unique)
+    }
+
+    fn _lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, VecDeque<PendingCommand>>> {
+        // 监听器在锁外调用，锁内不会 panic，中毒时直接取回数据
+        self.sessions
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn _emit(&self, event: &SessionEvent) {
+        self.listeners.iter().for_each(|listener| listener(event));
+    }
+}
+
+// 待应答队列每次变化后更新指标
+fn _update_gauge(sessions: &HashMap<String, VecDeque<PendingCommand>>) {
+    metrics::pending_commands(sessions.values().map(VecDeque::len).sum());
+}
+
+fn _capsule_key<T: Cmd + 'static>(capsule: &RawCapsule<T>) -> ProtocolResult<(&str, String)> {
+    let unique = capsule
+        .device_no()
+        .ok_or_else(|| ProtocolError::ValidationFailed("capsule has no device number".into()))?;
+    let cmd_code = capsule
+        .cmd()
+        .map(Cmd::code)
+        .ok_or_else(|| ProtocolError::ValidationFailed("capsule has no cmd".into()))?;
+    Ok((unique, cmd_code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn _manager(max_retries: u32) -> SessionManager {
+        let cache = DeviceCache::builder().max_capacity(16).build();
+        SessionManager::with_cache(
+            cache,
+            SessionConfig {
+                response_timeout: Duration::ZERO,
+                max_retries,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_session_response_matching() {
+        let manager = _manager(0);
+        manager
+            .submit("0001", "A1", Some("B1"), vec![0x68])
+            .unwrap();
+        manager.submit("0001", "A2", None, vec![0x68]).unwrap();
+        // 其他设备、不匹配的命令码不影响队列
+        assert!(manager.on_response("0002", "B1").is_none());
+        assert!(manager.on_response("0001", "A1").is_none());
+
+        let event = manager.on_response("0001", "a2").unwrap();
+        assert!(matches!(event, SessionEvent::Completed(_)));
+        assert_eq!(event.command().cmd_code(), "A2");
+        assert_eq!(manager.pending("0001").len(), 1);
+        assert!(manager.cache.is_online("0001", Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn test_session_retry_then_fail() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&events);
+        let manager = _manager(1).on_event(move |event| {
+            recorded.lock().unwrap().push(event.command().attempts());
+        });
+        manager.submit("0001", "A1", None, vec![0x68]).unwrap();
+
+        let retry = manager.poll_timeouts();
+        assert!(matches!(retry.as_slice(), [SessionEvent::Retry(c)] if c.attempts() == 2));
+        let failed = manager.poll_timeouts();
+        assert!(matches!(failed.as_slice(), [SessionEvent::Failed(_)]));
+        assert_eq!(manager.pending_count(), 0);
+        assert_eq!(*events.lock().unwrap(), vec![2, 2]);
+    }
+}
@@ -0,0 +1,505 @@
+//! 模拟设备：按协议定义生成逼真的上行帧 (长度、CRC、补齐、序号、可选加密均正确)，
+//! 不需要真实的表计即可对平台做压测。
+//!
+//! 协议定义可以是 `ProtocolSchema` (`simulate_schema`)，也可以是 `Cmd` + 字段定义 (`simulate`)。
+//! 字段取值默认按类型随机 (数值受 `Min` / `Max` 规则约束，有 `OneOf` 规则时从中选取，取值表字段随机选一项)，
+//! 也可以通过 `range` 按字段 code 指定 `ValueRange`。固定内容 (`default_hex` / `compare`) 的字段原样写入。
+//!
+//! ```ignore
+//! let mut simulator = DeviceSimulator::with_seed(7)
+//!     .range("电压", ValueRange::Between { min: 210.0, max: 240.0 })
+//!     .range("seq", ValueRange::Sequence { max: 255 })
+//!     .range("累计流量", ValueRange::Counter { start: 1000.0, max_step: 0.5 });
+//! for device in 0..10_000 {
+//!     let capsule = simulator.simulate_schema(&schema, "01", &format!("{:08}", device))?;
+//!     send(capsule.bytes());
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::aes_digester::{AesCipher, AesMode};
+use crate::core::pipeline::encode_frame;
+use crate::math_util::{self, DecimalRoundingMode};
+use crate::sequence_util::SequenceGenerator;
+use crate::utils::rand_string_from;
+use crate::{
+    AutoEncoding, AutoEncodingParam, Cmd, CmdSchema, DirectionEnum, FieldType, IvStrategy,
+    ProtocolConfig, ProtocolError, ProtocolResult, ProtocolSchema, RandCharset, RawCapsule,
+    ValidationRule, Writer, hex_util,
+};
+
+// 未指定范围时，浮点字段的取值范围
+const DEFAULT_FLOAT_RANGE: (f64, f64) = (0.0, 1000.0);
+// 浮点字段保留的小数位数
+const FLOAT_DECIMALS: usize = 3;
+// 变长 (byte_length = 0) 的 Ascii / BCD 字段生成的字节数
+const DEFAULT_VARIABLE_LEN: usize = 4;
+// 缩放后的输入值保留的小数位数 (Decimal 的上限)，保证输入恰好是 原始整数 * scale
+const INPUT_PRECISION: u32 = 28;
+// 加密使用的 IV 长度 (AES 分组长度)
+const IV_LEN: usize = 16;
+
+/// 字段的取值范围
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueRange {
+    // 数值在 [min, max] 内均匀分布，整数字段按 scale 对齐
+    Between { min: f64, max: f64 },
+    // 从候选值中随机选取
+    OneOf(Vec<String>),
+    // 固定值
+    Fixed(String),
+    // 按设备自增的序号，从 1 开始，超过 max 后回绕到 0
+    Sequence { max: u64 },
+    // 按设备单调递增的累计量 (例如表底数)，第一帧为 start，之后每帧增加 [0, max_step]
+    Counter { start: f64, max_step: f64 },
+}
+
+// 数据域的加密方式
+struct SimulatorCipher {
+    key: Vec<u8>,
+    mode: AesMode,
+    iv: IvStrategy,
+}
+
+/// 模拟设备。序号、累计量按设备号分别记录，同一设备的连续帧是连贯的
+pub struct DeviceSimulator {
+    ranges: HashMap<String, ValueRange>,
+    cipher: Option<SimulatorCipher>,
+    rng: StdRng,
+    // 按 设备号::字段 code 记录
+    sequences: HashMap<String, SequenceGenerator>,
+    counters: HashMap<String, f64>,
+}
+
+impl Default for DeviceSimulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeviceSimulator {
+    pub fn new() -> Self {
+        Self::_with_rng(StdRng::from_rng(&mut rand::rng()))
+    }
+
+    /// 固定随机种子，生成的帧可逐字节复现 (随机 IV 除外，它始终使用操作系统随机源)
+    pub fn with_seed(seed: u64) -> Self {
+        Self::_with_rng(StdRng::seed_from_u64(seed))
+    }
+
+    fn _with_rng(rng: StdRng) -> Self {
+        Self {
+            ranges: HashMap::new(),
+            cipher: None,
+            rng,
+            sequences: HashMap::new(),
+            counters: HashMap::new(),
+        }
+    }
+
+    /// 为字段 (按 code) 指定取值范围
+    pub fn range(mut self, code: &str, range: ValueRange) -> Self {
+        self.ranges.insert(code.to_string(), range);
+        self
+    }
+
+    /// 加密数据域 (命令码之后的所有字段)。`IvStrategy::Random` 时 IV 放在密文之前随帧发送
+    pub fn cipher(mut self, key: &[u8], mode: AesMode, iv: IvStrategy) -> Self {
+        self.cipher = Some(SimulatorCipher {
+            key: key.to_vec(),
+            mode,
+            iv,
+        });
+        self
+    }
+
+    /// 生成单个字段的输入值
+    pub fn value<P: AutoEncodingParam>(
+        &mut self,
+        device_no: &str,
+        param: &P,
+    ) -> ProtocolResult<String> {
+        if !param.default_hex().is_empty() {
+            return Ok(String::new());
+        }
+        let code = param.code();
+        match self.ranges.get(&code).cloned() {
+            Some(range) => self._ranged(device_no, param, range),
+            None => self._random(param, None),
+        }
+    }
+
+    /// 生成所有字段的输入值 (按字段 code)
+    pub fn params<P: AutoEncodingParam, E: AutoEncoding<P>>(
+        &mut self,
+        device_no: &str,
+        definition: &E,
+    ) -> ProtocolResult<HashMap<String, String>> {
+        let mut params = HashMap::new();
+        for param in definition.variants() {
+            let value = self.value(device_no, &param)?;
+            params.insert(param.code(), value);
+        }
+        Ok(params)
+    }
+
+    /// 按 `Cmd` 与字段定义生成一帧上行报文
+    pub fn simulate<T, E, P>(
+        &mut self,
+        config: &impl ProtocolConfig,
+        cmd: T,
+        definition: &E,
+        device_no: &str,
+    ) -> ProtocolResult<RawCapsule<T>>
+    where
+        T: Cmd + 'static,
+        E: AutoEncoding<P>,
+        P: AutoEncodingParam,
+    {
+        let params = self.params(device_no, definition)?;
+        let mut capsule = _upstream(cmd, device_no);
+        encode_frame(config, &mut capsule, |writer| {
+            self._write_body(writer, |body| {
+                definition.auto_process(&params, body)?;
+                Ok(())
+            })
+        })?;
+        Ok(capsule)
+    }
+
+    /// 按协议定义中的上行命令 (`code`) 生成一帧上行报文
+    pub fn simulate_schema(
+        &mut self,
+        schema: &ProtocolSchema,
+        code: &str,
+        device_no: &str,
+    ) -> ProtocolResult<RawCapsule<CmdSchema>> {
+        let cmd = schema
+            .command(code, DirectionEnum::Upstream)
+            .cloned()
+            .ok_or_else(|| {
+                ProtocolError::ValidationFailed(format!("schema has no upstream cmd '{}'", code))
+            })?;
+        let mut params = HashMap::new();
+        for field in &cmd.fields {
+            // 取值表字段没有指定范围时随机选一项，文字换成对应的值
+            let value = if field.compare.is_none()
+                && !field.enum_map.is_empty()
+                && !self.ranges.contains_key(&field.code)
+            {
+                let index = self.rng.random_range(0..field.enum_map.len());
+                let label = field.enum_map.values().nth(index).cloned();
+                field.resolve_label(label.unwrap_or_default())
+            } else {
+                self.value(device_no, field)?
+            };
+            params.insert(field.code.clone(), value);
+        }
+
+        let cmd_length = schema.envelope.cmd_length;
+        let mut capsule = _upstream(cmd.clone(), device_no);
+        encode_frame(&schema.envelope, &mut capsule, |writer| {
+            if cmd_length > 0 {
+                writer.write_bytes("命令码", &hex_util::hex_to_bytes(&cmd.code)?, &cmd.code)?;
+            }
+            self._write_body(writer, |body| {
+                AutoEncoding::auto_process(&cmd, &params, body)?;
+                Ok(())
+            })
+        })?;
+        Ok(capsule)
+    }
+
+    // 写入数据域，配置了加密时先写入单独的 Writer 再整体加密
+    fn _write_body(
+        &self,
+        writer: &mut Writer,
+        body: impl FnOnce(&mut Writer) -> ProtocolResult<()>,
+    ) -> ProtocolResult<()> {
+        let Some(cipher) = &self.cipher else {
+            return body(writer);
+        };
+        let mut plain = Writer::new();
+        body(&mut plain)?;
+        let iv = cipher.iv.resolve(IV_LEN)?;
+        let mut bytes = Vec::new();
+        if cipher.iv == IvStrategy::Random {
+            bytes.extend_from_slice(&iv);
+        }
+        bytes.extend(AesCipher::new(&cipher.key, cipher.mode)?.encrypt(plain.buffer()?, &iv)?);
+        writer.write_bytes("密文", &bytes, &hex_util::bytes_to_hex(&bytes)?)?;
+        Ok(())
+    }
+
+    fn _ranged<P: AutoEncodingParam>(
+        &mut self,
+        device_no: &str,
+        param: &P,
+        range: ValueRange,
+    ) -> ProtocolResult<String> {
+        let key = format!("{}::{}", device_no, param.code());
+        match range {
+            ValueRange::Between { min, max } => self._random(param, Some((min, max))),
+            ValueRange::OneOf(values) if !values.is_empty() => {
+                Ok(values[self.rng.random_range(0..values.len())].clone())
+            }
+            ValueRange::OneOf(_) => Err(ProtocolError::ValidationFailed(format!(
+                "simulator range of field '{}' has no values",
+                param.code()
+            ))),
+            ValueRange::Fixed(value) => Ok(value),
+            ValueRange::Sequence { max } => {
+                let sequence = self
+                    .sequences
+                    .entry(key)
+                    .or_insert_with(|| SequenceGenerator::new(max))
+                    .next();
+                Ok(_format(param, sequence as f64))
+            }
+            ValueRange::Counter { start, max_step } => {
+                let step = self.rng.random_range(0.0..=max_step.max(0.0));
+                let value = match self.counters.get_mut(&key) {
+                    Some(total) => {
+                        *total += step;
+                        *total
+                    }
+                    None => *self.counters.entry(key).or_insert(start),
+                };
+                Ok(_format(param, value))
+            }
+        }
+    }
+
+    // 按字段类型随机取值，`bounds` 为空时使用类型的取值范围与 Min / Max 规则
+    fn _random<P: AutoEncodingParam>(
+        &mut self,
+        param: &P,
+        bounds: Option<(f64, f64)>,
+    ) -> ProtocolResult<String> {
+        let rules = param.rules();
+        if bounds.is_none()
+            && let Some(values) = rules.iter().find_map(|rule| match rule {
+                ValidationRule::OneOf(values) if !values.is_empty() => Some(values),
+                _ => None,
+            })
+        {
+            return Ok(values[self.rng.random_range(0..values.len())].clone());
+        }
+        let field_type = param.field_type();
+        let int_bounds = _int_bounds(&field_type);
+        let (mut min, mut max) = bounds.unwrap_or(match int_bounds {
+            Some((low, high, scale)) => (low as f64 * scale, high as f64 * scale),
+            None => DEFAULT_FLOAT_RANGE,
+        });
+        if bounds.is_none() {
+            for rule in &rules {
+                match rule {
+                    ValidationRule::Min(low) => min = min.max(*low),
+                    ValidationRule::Max(high) => max = max.min(*high),
+                    _ => {}
+                }
+            }
+        }
+        if min.is_nan() || max.is_nan() || min > max {
+            return Err(_empty_range(param, min, max));
+        }
+
+        // 整数按 scale 对齐到原始值，再缩放回输入值
+        if let Some((low, high, scale)) = int_bounds {
+            let low = ((min / scale).ceil() as i128).max(low);
+            let high = ((max / scale).floor() as i128).min(high);
+            if low > high {
+                return Err(_empty_range(param, min, max));
+            }
+            return _scaled(self.rng.random_range(low..=high), scale);
+        }
+        let length = match param.byte_length() {
+            0 => DEFAULT_VARIABLE_LEN,
+            length => length,
+        };
+        Ok(match field_type {
+            // BCD 字段生成十进制数字，与表号、地址等常见内容一致
+            FieldType::StringOrBCD => {
+                rand_string_from(&mut self.rng, length * 2, RandCharset::Digits)
+            }
+            FieldType::Ascii => rand_string_from(&mut self.rng, length, RandCharset::Alphanumeric),
+            FieldType::Float | FieldType::Double => {
+                format!("{:.*}", FLOAT_DECIMALS, self.rng.random_range(min..=max))
+            }
+            _ => String::new(),
+        })
+    }
+}
+
+fn _empty_range<P: AutoEncodingParam>(param: &P, min: f64, max: f64) -> ProtocolError {
+    ProtocolError::ValidationFailed(format!(
+        "simulator range [{}, {}] has no valid value for field '{}'",
+        min,
+        max,
+        param.code()
+    ))
+}
+
+// 上行 capsule，由 `encode_frame` 写入报文
+fn _upstream<T: Cmd + 'static>(cmd: T, device_no: &str) -> RawCapsule<T> {
+    let mut capsule = RawCapsule::new_upstream(&[]);
+    capsule.set_cmd(cmd);
+    capsule.set_device_no(device_no);
+    capsule
+}
+
+// 整数类型的原始值范围与 scale
+fn _int_bounds(field_type: &FieldType) -> Option<(i128, i128, f64)> {
+    match field_type {
+        FieldType::UnsignedU8(scale) => Some((0, u8::MAX as i128, *scale)),
+        FieldType::UnsignedU16(scale) => Some((0, u16::MAX as i128, *scale)),
+        FieldType::UnsignedU32(scale) => Some((0, u32::MAX as i128, *scale)),
+        FieldType::UnsignedU64(scale) => Some((0, u64::MAX as i128, *scale)),
+        FieldType::SignedI8(scale) => Some((i8::MIN as i128, i8::MAX as i128, *scale)),
+        FieldType::SignedI16(scale) => Some((i16::MIN as i128, i16::MAX as i128, *scale)),
+        FieldType::SignedI32(scale) => Some((i32::MIN as i128, i32::MAX as i128, *scale)),
+        FieldType::SignedI64(scale) => Some((i64::MIN as i128, i64::MAX as i128, *scale)),
+        _ => None,
+    }
+}
+
+// 原始整数乘以 scale，得到与上行解码一致的输入值
+fn _scaled(raw: i128, scale: f64) -> ProtocolResult<String> {
+    let value = math_util::scale_integer(raw, scale, INPUT_PRECISION, DecimalRoundingMode::HalfUp)?;
+    Ok(value.to_string())
+}
+
+// 序号、累计量按字段类型格式化：整数按 scale 取整，BCD 补齐为十进制数字
+fn _format<P: AutoEncodingParam>(param: &P, value: f64) -> String {
+    match param.field_type() {
+        FieldType::StringOrBCD => {
+            let width = param.byte_length() * 2;
+            format!("{:0width$}", value.max(0.0).round() as u64)
+        }
+        FieldType::Float | FieldType::Double => format!("{:.*}", FLOAT_DECIMALS, value),
+        FieldType::Ascii => format!("{}", value.round() as i64),
+        field_type => match _int_bounds(&field_type) {
+            Some((_, _, scale)) if scale != 0.0 => _scaled((value / scale).round() as i128, scale)
+                .unwrap_or_else(|_| value.to_string()),
+            _ => value.to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"{
+        "name": "simulated",
+        "envelope": {
+            "head": "68",
+            "tail": "16",
+            "crc": "crc16-modbus",
+            "crcIndex": [0, 3],
+            "lengthIndex": [1, 2]
+        },
+        "commands": [
+            {
+                "code": "01",
+                "title": "数据上报",
+                "direction": "upstream",
+                "fields": [
+                    { "code": "seq", "title": "序号", "type": "u8" },
+                    { "code": "voltage", "title": "电压", "type": "u16", "scale": 0.1, "unit": "V" },
+                    { "code": "total", "title": "累计流量", "type": "u32", "scale": 0.01 },
+                    { "title": "阀门状态", "type": "u8", "enum": { "0": "关", "1": "开" } },
+                    { "title": "表号", "type": "bcd", "length": 4, "swap": true },
+                    { "title": "版本", "type": "u8", "compare": "02" }
+                ]
+            }
+        ]
+    }"#;
+
+    fn _simulator(seed: u64) -> DeviceSimulator {
+        DeviceSimulator::with_seed(seed)
+            .range("seq", ValueRange::Sequence { max: 255 })
+            .range(
+                "voltage",
+                ValueRange::Between {
+                    min: 210.0,
+                    max: 240.0,
+                },
+            )
+            .range(
+                "total",
+                ValueRange::Counter {
+                    start: 100.0,
+                    max_step: 2.0,
+                },
+            )
+    }
+
+    fn _value(capsule: &RawCapsule<CmdSchema>, name: &str) -> String {
+        capsule
+            .field_details()
+            .iter()
+            .find(|field| field.name == name)
+            .map(|field| field.value.clone())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_simulated_frames_decode() {
+        let schema = ProtocolSchema::from_json(SCHEMA).unwrap();
+        let mut simulator = _simulator(7);
+        let mut last_total = 100.0_f64;
+        for expected_seq in 1..=3 {
+            let frame = simulator.simulate_schema(&schema, "01", "0001").unwrap();
+            let capsule = schema.decode(frame.bytes()).unwrap();
+            assert_eq!(_value(&capsule, "序号"), expected_seq.to_string());
+            let voltage: f64 = _value(&capsule, "电压")
+                .trim_end_matches(" V")
+                .parse()
+                .unwrap();
+            assert!((210.0..=240.0).contains(&voltage));
+            let total: f64 = _value(&capsule, "累计流量").parse().unwrap();
+            assert!(total >= last_total);
+            last_total = total;
+            assert!(["关", "开"].contains(&_value(&capsule, "阀门状态").as_str()));
+            assert_eq!(_value(&capsule, "版本"), "02");
+        }
+        // 序号按设备分别计数
+        let other = simulator.simulate_schema(&schema, "01", "0002").unwrap();
+        assert_eq!(_value(&schema.decode(other.bytes()).unwrap(), "序号"), "1");
+
+        // 同一种子生成相同的帧
+        let first = _simulator(7)
+            .simulate_schema(&schema, "01", "0001")
+            .unwrap();
+        let again = _simulator(7)
+            .simulate_schema(&schema, "01", "0001")
+            .unwrap();
+        assert_eq!(first.bytes(), again.bytes());
+    }
+
+    #[test]
+    fn test_encrypted_body() {
+        let schema = ProtocolSchema::from_json(SCHEMA).unwrap();
+        let key = [0x11u8; 16];
+        let plain = _simulator(9)
+            .simulate_schema(&schema, "01", "0001")
+            .unwrap();
+        let encrypted = _simulator(9)
+            .cipher(&key, AesMode::CBC, IvStrategy::Zero)
+            .simulate_schema(&schema, "01", "0001")
+            .unwrap();
+
+        // 帧头 1 + 长度 1 + 命令码 1，CRC 2 + 帧尾 1
+        let body = |bytes: &[u8]| bytes[3..bytes.len() - 3].to_vec();
+        let decrypted = AesCipher::new(&key, AesMode::CBC)
+            .unwrap()
+            .decrypt(&body(encrypted.bytes()), &[0u8; 16])
+            .unwrap();
+        assert_eq!(decrypted, body(plain.bytes()));
+    }
+}
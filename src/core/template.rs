@@ -0,0 +1,168 @@
+//! 帧模板：把帧结构声明为一串固定字节 (literal) 和占位符 (slot)，再按 tag 从值表中回填。
+//!
+//! ```ignore
+//! let template = FrameTemplate::new()
+//!     .literal("帧头", "68")
+//!     .slot("addr", "地址", 4)
+//!     .literal("控制码", "01")
+//!     .slot("data", "数据", 2)
+//!     .literal("帧尾", "16");
+//! let values = HashMap::from([("addr".into(), "12345678".into()), ("data".into(), "0A0B".into())]);
+//! let (bytes, fields) = template.fill(&values)?;
+//! ```
+
+use std::collections::HashMap;
+
+use crate::{
+    core::{
+        parts::{placeholder::PlaceHolder, rawfield::Rawfield},
+        writer::Writer,
+    },
+    defi::{ProtocolResult, error::ProtocolError},
+    hex_util,
+};
+
+/// 模板中的一段
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateSegment {
+    // 固定字节 (hex)
+    Literal {
+        title: String,
+        hex: String,
+    },
+    // 占位符，按 tag 回填，capacity 为字节数
+    Slot {
+        tag: String,
+        title: String,
+        capacity: usize,
+    },
+}
+
+/// 由固定字节与占位符组成的帧模板
+#[derive(Debug, Clone, Default)]
+pub struct FrameTemplate {
+    segments: Vec<TemplateSegment>,
+}
+
+impl FrameTemplate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn literal(mut self, title: &str, hex: &str) -> Self {
+        self.segments.push(TemplateSegment::Literal {
+            title: title.to_string(),
+            hex: hex.to_string(),
+        });
+        self
+    }
+
+    pub fn slot(mut self, tag: &str, title: &str, capacity: usize) -> Self {
+        self.segments.push(TemplateSegment::Slot {
+            tag: tag.to_string(),
+            title: title.to_string(),
+            capacity,
+        });
+        self
+    }
+
+    pub fn segments(&self) -> &[TemplateSegment] {
+        &self.segments
+    }
+
+    /// 模板中所有占位符的位置 (pos 为其在字段列表中的下标)
+    pub fn placeholders(&self) -> ProtocolResult<Vec<PlaceHolder>> {
+        let mut offset = 0;
+        let mut placeholders = Vec::new();
+        for (pos, segment) in self.segments.iter().enumerate() {
+            let len = match segment {
+                TemplateSegment::Literal { hex, .. } => hex_util::hex_to_bytes(hex)?.len(),
+                TemplateSegment::Slot { tag, capacity, .. } => {
+                    placeholders.push(PlaceHolder::new(tag, pos, offset, offset + capacity));
+                    *capacity
+                }
+            };
+            offset += len;
+        }
+        Ok(placeholders)
+    }
+
+    /// 写入模板并回填 `values` (tag -> hex) 中已有的占位符。
+    /// 没有提供值的占位符留在 Writer 中，可以继续回填 (例如长度、CRC)。
+    pub fn writer(&self, values: &HashMap<String, String>) -> ProtocolResult<Writer> {
+        let mut writer = Writer::new();
+        for segment in &self.segments {
+            match segment {
+                TemplateSegment::Literal { title, hex } => {
+                    writer.write_bytes(title, &hex_util::hex_to_bytes(hex)?, hex)?;
+                }
+                TemplateSegment::Slot { tag, capacity, .. } => {
+                    writer.write_placeholder(tag, *capacity)?;
+                }
+            }
+        }
+        // 从后往前回填：回填会往字段列表中插入，先插后面的不影响前面占位符的下标
+        for segment in self.segments.iter().rev() {
+            if let TemplateSegment::Slot { tag, title, .. } = segment
+                && let Some(hex) = values.get(tag)
+            {
+                writer.rewrite_placeholder(tag, title, &hex_util::hex_to_bytes(hex)?, hex)?;
+            }
+        }
+        Ok(writer)
+    }
+
+    /// 回填全部占位符，返回整帧字节与各字段。缺少任意占位符的值时报错
+    pub fn fill(
+        &self,
+        values: &HashMap<String, String>,
+    ) -> ProtocolResult<(Vec<u8>, Vec<Rawfield>)> {
+        if let Some(TemplateSegment::Slot { tag, .. }) = self.segments.iter().find(|segment| {
+            matches!(segment, TemplateSegment::Slot { tag, .. } if !values.contains_key(tag))
+        }) {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "no value for placeholder '{}'",
+                tag
+            )));
+        }
+        let writer = self.writer(values)?;
+        Ok((writer.buffer()?.to_vec(), writer.fields()?.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template() -> FrameTemplate {
+        FrameTemplate::new()
+            .literal("帧头", "68")
+            .slot("addr", "地址", 2)
+            .literal("控制码", "01")
+            .slot("data", "数据", 2)
+            .literal("帧尾", "16")
+    }
+
+    #[test]
+    fn test_fill_template() {
+        let values = HashMap::from([
+            ("addr".to_string(), "1234".to_string()),
+            ("data".to_string(), "0A0B".to_string()),
+        ]);
+        let (bytes, fields) = template().fill(&values).unwrap();
+        assert_eq!(bytes, [0x68, 0x12, 0x34, 0x01, 0x0A, 0x0B, 0x16]);
+        let titles: Vec<&str> = fields.iter().map(|f| f.title.as_str()).collect();
+        assert_eq!(titles, ["帧头", "地址", "控制码", "数据", "帧尾"]);
+
+        let data = &template().placeholders().unwrap()[1];
+        assert_eq!((data.start_index(), data.capacity()), (4, 2));
+
+        let partial = HashMap::from([("addr".to_string(), "1234".to_string())]);
+        assert!(template().fill(&partial).is_err());
+        let wrong = HashMap::from([
+            ("addr".to_string(), "12".to_string()),
+            ("data".to_string(), "0A0B".to_string()),
+        ]);
+        assert!(template().fill(&wrong).is_err());
+    }
+}
@@ -0,0 +1,282 @@
+//! TLV (tag-length-value) 数据域：数据域由若干 tag + length + value 三元组依次组成。
+//!
+//! `TlvFormat` 描述 tag、length 的字节数 (1 ~ 4) 与字节序；`TlvReader` 在 `Reader` 当前的游标之间
+//! 逐个读取 TLV，每个 value 翻译为一个 `Rawfield` (偏移为 value 的起始位置)，tag、length 不记录为字段；
+//! `TlvWriter` 把 `Rawfield` 按同样的格式写入 `Writer`，两边得到的字段一致。
+//!
+//! ```ignore
+//! let format = TlvFormat::new(1, 2)?.little_endian();
+//! let mut reader = Reader::new(data_area);
+//! let fields = TlvReader::new(&mut reader, format).read_all_with(|tag, bytes| match tag {
+//!     0x01 => FieldConvertDecoder::new("电压", FieldType::UnsignedU16(0.01), None, true).translate(bytes),
+//!     _ => TlvReader::raw_field(tag, 1, bytes),
+//! })?;
+//! ```
+
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::{
+    core::{parts::rawfield::Rawfield, reader::Reader, writer::Writer},
+    defi::{ProtocolResult, error::ProtocolError},
+    utils::hex_util,
+};
+
+// tag、length 的最大字节数 (u32)
+const MAX_WIDTH: usize = 4;
+
+/// TLV 的格式：tag 与 length 的字节数、字节序
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TlvFormat {
+    tag_width: usize,
+    length_width: usize,
+    little_endian: bool,
+}
+
+impl TlvFormat {
+    /// tag、length 的字节数须在 1 ~ 4 之间，默认大端
+    pub fn new(tag_width: usize, length_width: usize) -> ProtocolResult<Self> {
+        for (name, width) in [("tag", tag_width), ("length", length_width)] {
+            if width == 0 || width > MAX_WIDTH {
+                return Err(ProtocolError::ValidationFailed(format!(
+                    "TLV {} width must be 1..={} bytes, got {}",
+                    name, MAX_WIDTH, width
+                )));
+            }
+        }
+        Ok(Self {
+            tag_width,
+            length_width,
+            little_endian: false,
+        })
+    }
+
+    /// tag、length 按小端排列
+    pub fn little_endian(mut self) -> Self {
+        self.little_endian = true;
+        self
+    }
+
+    pub fn tag_width(&self) -> usize {
+        self.tag_width
+    }
+
+    pub fn length_width(&self) -> usize {
+        self.length_width
+    }
+
+    pub fn is_little_endian(&self) -> bool {
+        self.little_endian
+    }
+
+    /// tag + length 的字节数
+    pub fn header_len(&self) -> usize {
+        self.tag_width + self.length_width
+    }
+
+    // 读取 width 字节的无符号整数
+    fn _read_uint(&self, reader: &mut Reader, width: usize) -> ProtocolResult<u32> {
+        let bytes = if self.little_endian {
+            reader.read_bytes_le(width)?
+        } else {
+            reader.read_bytes(width)?
+        };
+        Ok(bytes.iter().fold(0u32, |acc, b| (acc << 8) | *b as u32))
+    }
+
+    // 把 value 编码为 width 字节，超出范围时报错
+    fn _encode_uint(&self, name: &str, value: u32, width: usize) -> ProtocolResult<Vec<u8>> {
+        if width < MAX_WIDTH && value >> (width * 8) != 0 {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "TLV {} {} does not fit in {} bytes",
+                name, value, width
+            )));
+        }
+        let mut bytes = value.to_be_bytes()[MAX_WIDTH - width..].to_vec();
+        if self.little_endian {
+            bytes.reverse();
+        }
+        Ok(bytes)
+    }
+}
+
+/// 在 `Reader` 的游标之间逐个读取 TLV。读取的字段同时记录在 `Reader` 中
+#[derive(Debug)]
+pub struct TlvReader<'r, 'a> {
+    reader: &'r mut Reader<'a>,
+    format: TlvFormat,
+}
+
+impl<'r, 'a> TlvReader<'r, 'a> {
+    pub fn new(reader: &'r mut Reader<'a>, format: TlvFormat) -> Self {
+        Self { reader, format }
+    }
+
+    /// 未翻译的 TLV 字段：标题为 tag 的 hex，值为 value 的 hex
+    pub fn raw_field(tag: u32, tag_width: usize, bytes: &[u8]) -> ProtocolResult<Rawfield> {
+        let title = hex_util::u32_to_hex(tag, tag_width)?;
+        let value = hex_util::bytes_to_hex(bytes)?;
+        Ok(Rawfield::new(bytes, title, value))
+    }
+
+    /// 读取下一个 TLV，由 `translator(tag, value)` 翻译 value；没有剩余字节时返回 None
+    pub fn next_with<F>(&mut self, translator: F) -> ProtocolResult<Option<Rawfield>>
+    where
+        F: FnOnce(u32, &[u8]) -> ProtocolResult<Rawfield>,
+    {
+        if self.reader.remaining_len() == 0 {
+            return Ok(None);
+        }
+        let tag = self.format._read_uint(self.reader, self.format.tag_width)?;
+        let len = self
+            .format
+            ._read_uint(self.reader, self.format.length_width)? as usize;
+        self.reader
+            .read_and_translate_head(len, |bytes| translator(tag, bytes))?;
+        self.reader.get_current_field_cloned()
+    }
+
+    /// 读取剩余的所有 TLV
+    pub fn read_all_with<F>(&mut self, mut translator: F) -> ProtocolResult<Vec<Rawfield>>
+    where
+        F: FnMut(u32, &[u8]) -> ProtocolResult<Rawfield>,
+    {
+        let mut fields = Vec::new();
+        while let Some(field) = self.next_with(&mut translator)? {
+            fields.push(field);
+        }
+        Ok(fields)
+    }
+
+    /// 读取剩余的所有 TLV，不翻译 (见 `raw_field`)
+    pub fn read_all(&mut self) -> ProtocolResult<Vec<Rawfield>> {
+        let tag_width = self.format.tag_width;
+        self.read_all_with(|tag, bytes| Self::raw_field(tag, tag_width, bytes))
+    }
+}
+
+/// 不翻译地逐个读取 TLV，出错后停止
+impl Iterator for TlvReader<'_, '_> {
+    type Item = ProtocolResult<Rawfield>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tag_width = self.format.tag_width;
+        let result = self.next_with(|tag, bytes| Self::raw_field(tag, tag_width, bytes));
+        if result.is_err() {
+            // 跳过剩余字节，避免从错位的位置继续读取
+            let _ = self.reader.read_remaining();
+        }
+        result.transpose()
+    }
+}
+
+/// 按 `TlvFormat` 把字段写入 `Writer`，length 取字段的字节数
+#[derive(Debug)]
+pub struct TlvWriter<'w> {
+    writer: &'w mut Writer,
+    format: TlvFormat,
+}
+
+impl<'w> TlvWriter<'w> {
+    pub fn new(writer: &'w mut Writer, format: TlvFormat) -> Self {
+        Self { writer, format }
+    }
+
+    /// 写入一个 TLV：闭包 `translator` 生成 value 的 `Rawfield`，tag、length 写在它之前
+    pub fn write<F>(&mut self, tag: u32, translator: F) -> ProtocolResult<&mut Self>
+    where
+        F: FnOnce() -> ProtocolResult<Rawfield>,
+    {
+        let field = translator()?;
+        let len = u32::try_from(field.bytes().len())
+            .map_err(|_| ProtocolError::ValidationFailed("TLV value is too long".to_string()))?;
+        let mut header = self
+            .format
+            ._encode_uint("tag", tag, self.format.tag_width)?;
+        header.extend(
+            self.format
+                ._encode_uint("length", len, self.format.length_width)?,
+        );
+        self.writer.write_raw(&header)?;
+        self.writer.write(|| Ok(field))?;
+        Ok(self)
+    }
+
+    /// 便捷方法：写入原始字节值，`title`、`value` 为字段的标题与显示值
+    pub fn write_bytes(
+        &mut self,
+        tag: u32,
+        title: &str,
+        data: &[u8],
+        value: &str,
+    ) -> ProtocolResult<&mut Self> {
+        self.write(tag, || Ok(Rawfield::new(data, title.into(), value.into())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FieldConvertDecoder, FieldTranslator, FieldType};
+
+    #[test]
+    fn test_tlv_round_trip() {
+        let voltage = FieldConvertDecoder::new("电压", FieldType::UnsignedU16(0.01), None, false);
+        for format in [
+            TlvFormat::new(1, 1).unwrap(),
+            TlvFormat::new(2, 2).unwrap().little_endian(),
+        ] {
+            let mut writer = Writer::new();
+            let mut tlv = TlvWriter::new(&mut writer, format);
+            tlv.write(0x01, || voltage.translate(&[0x01, 0x68]))
+                .unwrap();
+            tlv.write_bytes(0x7F, "7F", &[], "").unwrap();
+            tlv.write_bytes(0x02, "02", &[0xAB, 0xCD, 0xEF], "ABCDEF")
+                .unwrap();
+
+            let buffer = writer.buffer().unwrap();
+            let mut reader = Reader::new(buffer);
+            let fields = TlvReader::new(&mut reader, format)
+                .read_all_with(|tag, bytes| match tag {
+                    0x01 => voltage.translate(bytes),
+                    _ => TlvReader::raw_field(tag, 1, bytes),
+                })
+                .unwrap();
+            let expected = writer.fields().unwrap();
+            assert_eq!(fields.len(), 3);
+            for (read, written) in fields.iter().zip(expected) {
+                assert_eq!(read.title(), written.title());
+                assert_eq!(read.value(), written.value());
+                assert_eq!(read.bytes(), written.bytes());
+                assert_eq!(read.offset(), written.offset());
+            }
+            assert_eq!(fields[0].value(), "3.6");
+        }
+        let mut writer = Writer::new();
+        let format = TlvFormat::new(2, 2).unwrap().little_endian();
+        TlvWriter::new(&mut writer, format)
+            .write_bytes(0x0102, "", &[0xFF], "")
+            .unwrap();
+        assert_eq!(writer.buffer().unwrap(), &[0x02, 0x01, 0x01, 0x00, 0xFF]);
+    }
+
+    #[test]
+    fn test_tlv_errors() {
+        assert!(TlvFormat::new(0, 1).is_err());
+        assert!(TlvFormat::new(1, 5).is_err());
+        let format = TlvFormat::new(1, 1).unwrap();
+        let mut writer = Writer::new();
+        assert!(
+            TlvWriter::new(&mut writer, format)
+                .write_bytes(0x100, "", &[], "")
+                .is_err()
+        );
+        // 声明 4 字节，只剩 2 字节
+        let mut reader = Reader::new(&[0x01, 0x04, 0xAA, 0xBB]);
+        let mut tlv = TlvReader::new(&mut reader, format);
+        assert!(matches!(
+            tlv.next(),
+            Some(Err(ProtocolError::WithContext { .. }))
+        ));
+        assert!(tlv.next().is_none());
+    }
+}
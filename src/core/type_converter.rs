@@ -1,12 +1,19 @@
-use std::fmt::Display;
-use std::marker::PhantomData;
+use core::fmt::Display;
+use core::marker::PhantomData;
+use core::str::FromStr;
+
+use rust_decimal::Decimal;
 
 use crate::math_util::{self, DecimalRoundingMode};
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
 use crate::{
-    ProtocolError, ProtocolResult, Rawfield, Symbol, handle_int, handle_int_encode, hex_util,
+    ProtocolError, ProtocolResult, Rawfield, Symbol, handle_int, handle_int_decimal,
+    handle_int_encode, hex_util,
 };
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 /// 字段类型
 pub enum FieldType {
     Empty,
@@ -26,24 +33,62 @@ pub enum FieldType {
 
 impl PartialEq for FieldType {
     fn eq(&self, other: &Self) -> bool {
-        std::mem::discriminant(self) == std::mem::discriminant(other)
+        core::mem::discriminant(self) == core::mem::discriminant(other)
     }
 }
 
 impl FieldType {
+    /// 整数 (含缩放) 与浮点类型，解码结果是数值
+    pub fn is_numeric(&self) -> bool {
+        !matches!(
+            self,
+            FieldType::Empty | FieldType::StringOrBCD | FieldType::Ascii
+        )
+    }
+
     /// 根据FieldType将大端字节切片转换为字符串表示。 上行解码
     pub fn decode(&self, bytes: &[u8]) -> ProtocolResult<String> {
+        self.decode_with(
+            bytes,
+            math_util::DEFAULT_PRECISION,
+            DecimalRoundingMode::HalfUp,
+        )
+    }
+
+    /// 同 `decode`，但可指定缩放时的精度 (小数位数) 和舍入模式
+    pub fn decode_with(
+        &self,
+        bytes: &[u8],
+        precision: u32,
+        rounding_mode: DecimalRoundingMode,
+    ) -> ProtocolResult<String> {
         match self {
             FieldType::Empty => Ok("".to_string()),
             FieldType::StringOrBCD => hex_util::bytes_to_hex(bytes),
-            FieldType::UnsignedU8(scale) => handle_int!(u8, 1, bytes, *scale),
-            FieldType::UnsignedU16(scale) => handle_int!(u16, 2, bytes, *scale),
-            FieldType::UnsignedU32(scale) => handle_int!(u32, 4, bytes, *scale),
-            FieldType::UnsignedU64(scale) => handle_int!(u64, 8, bytes, *scale),
-            FieldType::SignedI8(scale) => handle_int!(i8, 1, bytes, *scale),
-            FieldType::SignedI16(scale) => handle_int!(i16, 2, bytes, *scale),
-            FieldType::SignedI32(scale) => handle_int!(i32, 4, bytes, *scale),
-            FieldType::SignedI64(scale) => handle_int!(i64, 8, bytes, *scale),
+            FieldType::UnsignedU8(scale) => {
+                handle_int!(u8, 1, bytes, *scale, precision, rounding_mode)
+            }
+            FieldType::UnsignedU16(scale) => {
+                handle_int!(u16, 2, bytes, *scale, precision, rounding_mode)
+            }
+            FieldType::UnsignedU32(scale) => {
+                handle_int!(u32, 4, bytes, *scale, precision, rounding_mode)
+            }
+            FieldType::UnsignedU64(scale) => {
+                handle_int_decimal!(u64, 8, bytes, *scale, precision, rounding_mode)
+            }
+            FieldType::SignedI8(scale) => {
+                handle_int!(i8, 1, bytes, *scale, precision, rounding_mode)
+            }
+            FieldType::SignedI16(scale) => {
+                handle_int!(i16, 2, bytes, *scale, precision, rounding_mode)
+            }
+            FieldType::SignedI32(scale) => {
+                handle_int!(i32, 4, bytes, *scale, precision, rounding_mode)
+            }
+            FieldType::SignedI64(scale) => {
+                handle_int_decimal!(i64, 8, bytes, *scale, precision, rounding_mode)
+            }
             FieldType::Float => {
                 if bytes.len() != 4 {
                     return Err(ProtocolError::ValidationFailed(format!(
@@ -77,22 +122,41 @@ impl FieldType {
         }
     }
 
-    // 下行编码
+    /// 根据FieldType将输入字符串编码为大端字节。 下行编码
+    ///
+    /// 整数类型会自动反缩放：`UnsignedU16(0.1)` 输入 `"12.5"` 编码为 125，余下的小数按 HalfUp 舍入。
     pub fn encode(&self, input: &str) -> ProtocolResult<Vec<u8>> {
+        self.encode_with(input, DecimalRoundingMode::HalfUp)
+    }
+
+    /// 同 `encode`，但可指定反缩放后取整的舍入模式。超出目标整数范围时报错
+    pub fn encode_with(
+        &self,
+        input: &str,
+        rounding_mode: DecimalRoundingMode,
+    ) -> ProtocolResult<Vec<u8>> {
         match self {
             FieldType::Empty => Ok(vec![]),
             FieldType::StringOrBCD => {
                 let bytes = hex_util::hex_to_bytes(input)?;
                 Ok(bytes)
             }
-            FieldType::UnsignedU8(scale) => handle_int_encode!(u8, 1, input, *scale),
-            FieldType::UnsignedU16(scale) => handle_int_encode!(u16, 2, input, *scale),
-            FieldType::UnsignedU32(scale) => handle_int_encode!(u32, 4, input, *scale),
-            FieldType::UnsignedU64(scale) => handle_int_encode!(u64, 8, input, *scale),
-            FieldType::SignedI8(scale) => handle_int_encode!(i8, 1, input, *scale),
-            FieldType::SignedI16(scale) => handle_int_encode!(i16, 2, input, *scale),
-            FieldType::SignedI32(scale) => handle_int_encode!(i32, 4, input, *scale),
-            FieldType::SignedI64(scale) => handle_int_encode!(i64, 8, input, *scale),
+            FieldType::UnsignedU8(scale) => handle_int_encode!(u8, 1, input, *scale, rounding_mode),
+            FieldType::UnsignedU16(scale) => {
+                handle_int_encode!(u16, 2, input, *scale, rounding_mode)
+            }
+            FieldType::UnsignedU32(scale) => {
+                handle_int_encode!(u32, 4, input, *scale, rounding_mode)
+            }
+            FieldType::UnsignedU64(scale) => {
+                handle_int_encode!(u64, 8, input, *scale, rounding_mode)
+            }
+            FieldType::SignedI8(scale) => handle_int_encode!(i8, 1, input, *scale, rounding_mode),
+            FieldType::SignedI16(scale) => handle_int_encode!(i16, 2, input, *scale, rounding_mode),
+            FieldType::SignedI32(scale) => handle_int_encode!(i32, 4, input, *scale, rounding_mode),
+            FieldType::SignedI64(scale) => {
+                handle_int_encode!(i64, 8, input, *scale, rounding_mode)
+            }
             FieldType::Float => {
                 let value: f32 = input.parse().map_err(|_| {
                     ProtocolError::ValidationFailed(format!(
@@ -134,6 +198,10 @@ pub struct FieldConvertDecoder {
     pub filed_type: FieldType, // 帧字段类型 不为空即是: 翻译模式。
     // 翻译之后的符号
     pub symbol: Option<Symbol>,
+    // 缩放后保留的小数位数
+    pub precision: u32,
+    // 缩放时的舍入模式
+    pub rounding_mode: DecimalRoundingMode,
 }
 
 #[derive(Debug, Clone)]
@@ -160,12 +228,22 @@ impl FieldConvertDecoder {
             filed_type,
             swap,
             symbol,
+            precision: math_util::DEFAULT_PRECISION,
+            rounding_mode: DecimalRoundingMode::HalfUp,
         }
     }
 
     pub fn set_symbol(&mut self, symbol: Symbol) {
         self.symbol = Some(symbol);
     }
+
+    pub fn set_precision(&mut self, precision: u32) {
+        self.precision = precision;
+    }
+
+    pub fn set_rounding_mode(&mut self, rounding_mode: DecimalRoundingMode) {
+        self.rounding_mode = rounding_mode;
+    }
 }
 
 impl FieldCompareDecoder {
@@ -235,7 +313,18 @@ impl FieldTranslator for FieldConvertDecoder {
             copied_bytes
         };
         let ft = &self.filed_type;
-        let mut value = ft.decode(&input_bytes)?;
+        let mut value = ft.decode_with(&input_bytes, self.precision, self.rounding_mode)?;
+        // 数值型字段在拼接单位前记录数值
+        let numeric_value = if ft.is_numeric() {
+            Decimal::from_str(&value).ok()
+        } else {
+            None
+        };
+        let unit = self
+            .symbol
+            .as_ref()
+            .map(|symbol| symbol.tag())
+            .filter(|tag| !tag.is_empty());
         // 如果有符号，拼接上去
         if self.symbol.is_some() {
             let symbol_some_clone = self.symbol.clone();
@@ -243,7 +332,11 @@ impl FieldTranslator for FieldConvertDecoder {
             value += " ";
             value += symbol.tag().as_str();
         }
-        Ok(Rawfield::new(bytes, self.title.clone(), value))
+        let rf = Rawfield::new(bytes, self.title.clone(), value);
+        Ok(match numeric_value {
+            Some(numeric_value) => rf.with_numeric(numeric_value, unit),
+            None => rf,
+        })
     }
 }
 
@@ -1,10 +1,13 @@
-use std::collections::HashMap;
-
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
 use crate::{
+    collections::HashMap,
     core::parts::{placeholder::PlaceHolder, rawfield::Rawfield},
-    defi::{ProtocolResult, bridge::ReportField, crc_enum::CrcType, error::ProtocolError},
+    defi::{ProtocolResult, crc_enum::CrcType, error::ProtocolError},
     utils::{crc_util, hex_util},
 };
+#[cfg(feature = "std")]
+use crate::{defi::bridge::ReportField, utils::buffer_pool};
 
 #[derive(Debug, Default)]
 pub struct Writer {
@@ -16,6 +19,9 @@ pub struct Writer {
 impl Writer {
     pub fn new() -> Self {
         Self {
+            #[cfg(feature = "std")]
+            buffer: buffer_pool::take(0),
+            #[cfg(not(feature = "std"))]
             buffer: Vec::new(),
             fields: Vec::new(),
             placeholders: HashMap::new(),
@@ -32,9 +38,19 @@ impl Writer {
         Ok(&self.fields)
     }
 
+    #[cfg(feature = "std")]
     pub fn to_report_fields(&self) -> ProtocolResult<Vec<ReportField>> {
+        self.to_report_fields_with(false)
+    }
+
+    /// 同 `to_report_fields`，`include_sources` 为 true 时每个字段带上来源字节信息
+    #[cfg(feature = "std")]
+    pub fn to_report_fields_with(&self, include_sources: bool) -> ProtocolResult<Vec<ReportField>> {
         let fields = self.fields.clone();
-        let r: Vec<ReportField> = fields.into_iter().map(|f| f.to_report_field()).collect();
+        let r: Vec<ReportField> = fields
+            .into_iter()
+            .map(|f| f.to_report_field_with(include_sources))
+            .collect();
         Ok(r)
     }
 
@@ -66,7 +82,7 @@ impl Writer {
         F: FnOnce() -> ProtocolResult<Rawfield>,
     {
         // 1. 调用闭包，获取“翻译”结果
-        let field = translator()?;
+        let field = translator()?.with_offset(self.buffer.len());
 
         // 2. 从 Rawfield 中提取字节
         let bytes_to_write = field.bytes.clone();
@@ -87,12 +103,18 @@ impl Writer {
         data: &[u8],
         value: &str,
     ) -> ProtocolResult<&mut Self> {
-        let field = Rawfield::new(data, title.into(), value.into()); //
+        let field = Rawfield::new(data, title.into(), value.into()).with_offset(self.buffer.len());
         self.buffer.extend_from_slice(data);
         self.fields.push(field);
         Ok(self)
     }
 
+    /// 只追加字节，不记录字段 (TLV 的 tag、length 等结构性字节)
+    pub(crate) fn write_raw(&mut self, data: &[u8]) -> ProtocolResult<&mut Self> {
+        self.buffer.extend_from_slice(data);
+        Ok(self)
+    }
+
     /// 写入 N 字节的占位符 (默认为 0x00)，并返回其在缓冲区中的起始位置。
     ///
     /// 这用于稍后 "回填" 动态数据 (如总长度或 CRC)。
@@ -165,7 +187,8 @@ impl Writer {
         dest_slice.copy_from_slice(bytes);
 
         // 5. 创建 Rawfield
-        let field = Rawfield::new(bytes, title.into(), hex.into());
+        let field =
+            Rawfield::new(bytes, title.into(), hex.into()).with_offset(placeholder.start_index);
 
         // 6. 将 Rawfield 插入到 fields 列表的正确位置
         self.fields.insert(placeholder.pos, field);
@@ -250,3 +273,11 @@ impl Writer {
         Ok(self)
     }
 }
+
+// 缓冲区归还到复用池
+#[cfg(feature = "std")]
+impl Drop for Writer {
+    fn drop(&mut self) {
+        buffer_pool::give(core::mem::take(&mut self.buffer));
+    }
+}
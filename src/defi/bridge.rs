@@ -1,19 +1,52 @@
-use std::collections::HashMap;
+use std::{borrow::Cow, collections::HashMap, time::Duration};
 
-use serde::{Deserialize, Serialize};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 
 use crate::{
-    Cmd, ProtocolError, ProtocolResult, RawCapsule, RawChamber, core::parts::rawfield::Rawfield,
+    Cmd, CmdRegistry, HexError, MsgTypeEnum, ProtocolError, ProtocolResult, RawCapsule, RawChamber,
+    Symbol,
+    core::parts::{param_value::ParamValue, rawfield::Rawfield},
     utils,
 };
 
+pub use crate::defi::error::ProtocolWarning;
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 #[serde(rename_all = "camelCase")]
 pub struct ReportField {
     pub name: String,
     pub code: String,
     pub value: String,
     pub alert: bool,
+    // 触发告警的规则，见 `AlertRules`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alert_rule: Option<String>,
+    // 稳定的排序序号 (解码位置)，RawCapsule 中的字段按它排序
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<u32>,
+    // 该字段对应的原始字节 (hex)，用于平台排查时高亮报文
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hex: Option<String>,
+    // 在整帧报文中的起始字节偏移
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>,
+    // 字节长度
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub length: Option<u64>,
+    // 数值型字段的数值 (不含单位)，平台做计算时不必再解析 value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
+    pub numeric_value: Option<Decimal>,
+    // 单位，例如 "m³"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    // 子字段：重复记录、TLV 分组等按层级返回，不再用带下标的名称平铺
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<ReportField>,
 }
 
 // 实现一个便捷的构造函数
@@ -24,31 +57,125 @@ impl ReportField {
             code: code.to_string(),
             value,
             alert: false, // 默认为false
+            alert_rule: None,
+            index: None,
+            hex: None,
+            offset: None,
+            length: None,
+            numeric_value: None,
+            unit: None,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn with_index(mut self, index: u32) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// 分组字段，例如第 N 条记录、一个 TLV 组。value 为空
+    pub fn group(name: &str, code: &str, children: Vec<ReportField>) -> Self {
+        Self::new(name, code, String::new()).with_children(children)
+    }
+
+    pub fn with_children(mut self, children: Vec<ReportField>) -> Self {
+        self.children = children;
+        self
+    }
+
+    pub fn is_group(&self) -> bool {
+        !self.children.is_empty()
+    }
+
+    /// 深度优先展开所有叶子字段
+    pub fn leaves(&self) -> Vec<&ReportField> {
+        if self.children.is_empty() {
+            return vec![self];
         }
+        self.children.iter().flat_map(ReportField::leaves).collect()
+    }
+
+    /// 附加数值与单位
+    pub fn with_numeric(mut self, numeric_value: Decimal, unit: Option<&str>) -> Self {
+        self.numeric_value = Some(numeric_value);
+        self.unit = unit.map(str::to_string);
+        self
+    }
+
+    /// 单位对应的 `Symbol`，没有单位时为 None
+    pub fn symbol(&self) -> Option<Symbol> {
+        self.unit.as_deref().map(Symbol::from_tag)
+    }
+
+    /// 附加来源字节信息 (hex、起始偏移)，长度由 hex 推算
+    pub fn with_source(mut self, hex: &str, offset: usize) -> Self {
+        self.length = Some((hex.len() / 2) as u64);
+        self.hex = Some(hex.to_string());
+        self.offset = Some(offset as u64);
+        self
+    }
+
+    /// 字节范围 `[offset, offset + length)`，未记录来源时为 None
+    pub fn byte_range(&self) -> Option<std::ops::Range<u64>> {
+        Some(self.offset?..self.offset? + self.length?)
+    }
+
+    /// 去掉来源字节信息，减小返回体积
+    pub fn clear_source(&mut self) {
+        self.hex = None;
+        self.offset = None;
+        self.length = None;
+        self.children.iter_mut().for_each(ReportField::clear_source);
     }
 }
 
 impl Rawfield {
+    /// 转换为上报字段，不带来源字节信息 (hex / offset / length)
     pub fn to_report_field(self) -> ReportField {
+        self.to_report_field_with(false)
+    }
+
+    /// 同 `to_report_field`，`include_sources` 为 true 时带上来源字节信息
+    pub fn to_report_field_with(self, include_sources: bool) -> ReportField {
         let title = self.title;
         let code = utils::to_pinyin(&title);
+        let (hex, offset, length) = if include_sources {
+            (
+                Some(self.hex),
+                self.offset.map(|offset| offset as u64),
+                Some(self.bytes.len() as u64),
+            )
+        } else {
+            (None, None, None)
+        };
         ReportField {
             name: title,
             code,
             value: self.value,
             alert: false,
+            alert_rule: None,
+            index: None,
+            length,
+            hex,
+            offset,
+            numeric_value: self.numeric_value,
+            unit: self.unit,
+            children: Vec::new(),
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 #[serde(rename_all = "camelCase")]
 pub struct JniRequest {
     #[serde(default)]
     pub(crate) device_id: Option<String>,
     #[serde(default)]
     pub(crate) device_no: Option<String>,
-    #[serde(default)]
+    // 兼容旧版本拼错的 msgtType
+    #[serde(default, alias = "msgtType")]
     pub(crate) msg_type: Option<String>,
     #[serde(default)]
     pub(crate) cmd_code: Option<String>,
@@ -57,14 +184,121 @@ pub struct JniRequest {
     #[serde(default)]
     pub(crate) uri: Option<String>,
     #[serde(default)]
-    pub(crate) params: Option<HashMap<String, String>>,
+    pub(crate) params: Option<HashMap<String, ParamValue>>,
+    // 链路追踪 id，解码/编码后原样带回 JniResponse
+    #[serde(default)]
+    pub(crate) trace_id: Option<String>,
+}
+
+/// `JniRequest` 的构建器，`build` 时统一校验 hex / cmd_code / uri
+#[derive(Debug, Clone, Default)]
+pub struct JniRequestBuilder {
+    pub(crate) request: JniRequest,
+}
+
+impl JniRequestBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn device_id(mut self, device_id: &str) -> Self {
+        self.request.device_id = Some(device_id.to_string());
+        self
+    }
+
+    pub fn device_no(mut self, device_no: &str) -> Self {
+        self.request.device_no = Some(device_no.to_string());
+        self
+    }
+
+    pub fn msg_type(mut self, msg_type: &str) -> Self {
+        self.request.msg_type = Some(msg_type.to_string());
+        self
+    }
+
+    pub fn cmd_code(mut self, cmd_code: &str) -> Self {
+        self.request.cmd_code = Some(cmd_code.to_string());
+        self
+    }
+
+    pub fn hex(mut self, hex: &str) -> Self {
+        self.request.hex = hex.to_string();
+        self
+    }
+
+    pub fn uri(mut self, uri: &str) -> Self {
+        self.request.uri = Some(uri.to_string());
+        self
+    }
+
+    /// 添加一个参数，重复的 key 会覆盖。值可以是字符串、整数、浮点数或布尔值
+    pub fn param(mut self, key: &str, value: impl Into<ParamValue>) -> Self {
+        self.request
+            .params
+            .get_or_insert_with(HashMap::new)
+            .insert(key.to_string(), value.into());
+        self
+    }
+
+    pub fn params(mut self, params: HashMap<String, ParamValue>) -> Self {
+        self.request.params = Some(params);
+        self
+    }
+
+    pub fn trace_id(mut self, trace_id: &str) -> Self {
+        self.request.trace_id = Some(trace_id.to_string());
+        self
+    }
+
+    /// 校验并生成请求：
+    /// - hex 与 cmd_code 至少有一个 (上行解码需要 hex，下行编码需要 cmd_code)；
+    /// - hex 必须是合法的 16 进制字符串 (允许空格)；
+    /// - cmd_code / uri 设置时不能为空白。
+    pub fn build(self) -> ProtocolResult<JniRequest> {
+        let request = self.request;
+        if request.hex.trim().is_empty() && request.cmd_code.is_none() {
+            return Err(ProtocolError::ValidationFailed(
+                "either hex or cmd_code is required".into(),
+            ));
+        }
+        if !request.hex.trim().is_empty() {
+            let cleaned: String = request.hex.split_whitespace().collect();
+            if !cleaned.len().is_multiple_of(2) || !cleaned.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(HexError::NotHex(request.hex).into());
+            }
+        }
+        if request
+            .cmd_code
+            .as_deref()
+            .is_some_and(|c| c.trim().is_empty())
+        {
+            return Err(ProtocolError::ValidationFailed(
+                "cmd_code must not be blank".into(),
+            ));
+        }
+        if request
+            .uri
+            .as_deref()
+            .is_some_and(|u| u.trim().is_empty() || u.contains(char::is_whitespace))
+        {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "invalid uri {:?}",
+                request.uri
+            )));
+        }
+        Ok(request)
+    }
 }
 
 impl JniRequest {
+    pub fn builder() -> JniRequestBuilder {
+        JniRequestBuilder::new()
+    }
+
     pub fn new(
         device_id: Option<String>,
         device_no: Option<String>,
-        msgt_type: Option<String>,
+        msg_type: Option<String>,
         cmd_code: Option<String>,
         hex: String,
         uri: Option<String>,
@@ -73,26 +307,26 @@ impl JniRequest {
         JniRequest {
             device_id,
             device_no,
-            msg_type: msgt_type,
+            msg_type,
             cmd_code,
             hex,
             uri,
-            params,
+            params: params.map(|params| {
+                params
+                    .into_iter()
+                    .map(|(key, value)| (key, ParamValue::Text(value)))
+                    .collect()
+            }),
+            trace_id: None,
         }
     }
 
     pub fn to_bytes(&self) -> ProtocolResult<Vec<u8>> {
-        let json_string =
-            serde_json::to_string(self).map_err(|e| ProtocolError::CommonError(e.to_string()))?;
-        Ok(json_string.into_bytes())
+        _to_json_bytes(self)
     }
 
     pub fn from(data: &[u8]) -> ProtocolResult<Self> {
-        let json_string =
-            std::str::from_utf8(data).map_err(|e| ProtocolError::CommonError(e.to_string()))?;
-        let request = serde_json::from_str(json_string)
-            .map_err(|e| ProtocolError::CommonError(e.to_string()))?;
-        Ok(request)
+        _from_json_bytes(data)
     }
 
     // Getter methods
@@ -144,16 +378,206 @@ impl JniRequest {
         self.uri.clone().unwrap_or_default()
     }
 
-    pub fn params(&self) -> Option<&HashMap<String, String>> {
+    pub fn params(&self) -> Option<&HashMap<String, ParamValue>> {
         self.params.as_ref()
     }
 
+    pub fn param(&self, key: &str) -> Option<&ParamValue> {
+        self.params.as_ref().and_then(|params| params.get(key))
+    }
+
+    // 参数统一转为字符串 (布尔值为 1/0)，兼容只接受字符串参数的旧代码
     pub fn params_clone(&self) -> HashMap<String, String> {
-        self.params.clone().unwrap_or_default()
+        self.params
+            .iter()
+            .flatten()
+            .map(|(key, value)| (key.clone(), value.to_string()))
+            .collect()
+    }
+
+    pub fn trace_id(&self) -> Option<&str> {
+        self.trace_id.as_deref()
+    }
+
+    pub fn trace_id_clone(&self) -> String {
+        self.trace_id.clone().unwrap_or_default()
+    }
+
+    pub fn set_trace_id(&mut self, trace_id: &str) {
+        self.trace_id = Some(trace_id.to_string());
     }
+
+    /// 本次请求的 tracing span，字段包含 trace_id / device_no / cmd_code / msg_type
+    #[cfg(feature = "tracing")]
+    pub fn span(&self) -> tracing::Span {
+        tracing::info_span!(
+            "protocol_bridge",
+            trace_id = self.trace_id(),
+            device_no = self.device_no(),
+            cmd_code = self.cmd_code(),
+            msg_type = self.msg_type(),
+        )
+    }
+}
+
+/// `JniRequest` 的借用版本，用于上行解码的热路径。
+/// 字符串字段直接借用输入的 JSON 字节 (含转义字符时才会分配)，需要修改或长期持有时调用 `into_owned`。
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct JniRequestRef<'a> {
+    #[serde(default, borrow)]
+    pub(crate) device_id: Option<Cow<'a, str>>,
+    #[serde(default, borrow)]
+    pub(crate) device_no: Option<Cow<'a, str>>,
+    #[serde(default, borrow, alias = "msgtType")]
+    pub(crate) msg_type: Option<Cow<'a, str>>,
+    #[serde(default, borrow)]
+    pub(crate) cmd_code: Option<Cow<'a, str>>,
+    #[serde(default, borrow)]
+    pub(crate) hex: Cow<'a, str>,
+    #[serde(default, borrow)]
+    pub(crate) uri: Option<Cow<'a, str>>,
+    // 参数只在下行编码时使用，不走零拷贝
+    #[serde(default)]
+    pub(crate) params: Option<HashMap<String, ParamValue>>,
+    #[serde(default, borrow)]
+    pub(crate) trace_id: Option<Cow<'a, str>>,
+}
+
+impl<'a> JniRequestRef<'a> {
+    pub fn from(data: &'a [u8]) -> ProtocolResult<Self> {
+        serde_json::from_slice(data).map_err(ProtocolError::external)
+    }
+
+    pub fn device_id(&self) -> Option<&str> {
+        self.device_id.as_deref()
+    }
+
+    pub fn device_no(&self) -> Option<&str> {
+        self.device_no.as_deref()
+    }
+
+    pub fn msg_type(&self) -> Option<&str> {
+        self.msg_type.as_deref()
+    }
+
+    pub fn cmd_code(&self) -> Option<&str> {
+        self.cmd_code.as_deref()
+    }
+
+    pub fn hex(&self) -> &str {
+        &self.hex
+    }
+
+    /// 直接把 hex 解码为报文字节
+    pub fn hex_bytes(&self) -> ProtocolResult<Vec<u8>> {
+        utils::hex_util::hex_to_bytes(&self.hex)
+    }
+
+    pub fn uri(&self) -> Option<&str> {
+        self.uri.as_deref()
+    }
+
+    pub fn params(&self) -> Option<&HashMap<String, ParamValue>> {
+        self.params.as_ref()
+    }
+
+    pub fn trace_id(&self) -> Option<&str> {
+        self.trace_id.as_deref()
+    }
+
+    /// 转换为拥有所有权的 `JniRequest`
+    pub fn into_owned(self) -> JniRequest {
+        JniRequest {
+            device_id: self.device_id.map(Cow::into_owned),
+            device_no: self.device_no.map(Cow::into_owned),
+            msg_type: self.msg_type.map(Cow::into_owned),
+            cmd_code: self.cmd_code.map(Cow::into_owned),
+            hex: self.hex.into_owned(),
+            uri: self.uri.map(Cow::into_owned),
+            params: self.params,
+            trace_id: self.trace_id.map(Cow::into_owned),
+        }
+    }
+}
+
+/// 返回给调用方的结构化错误信息
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[serde(rename_all = "camelCase")]
+pub struct JniError {
+    // 错误类别代码，见 `ProtocolError::code`
+    pub code: String,
+    // 数字错误码，见 `ProtocolError::numeric_code`，0 表示未知
+    #[serde(default)]
+    pub numeric_code: u32,
+    pub message: String,
+    // 解析失败的字段名称
+    #[serde(default)]
+    pub field: Option<String>,
+    // 解析失败的位置 (字节偏移)
+    #[serde(default)]
+    pub offset: Option<u64>,
+}
+
+impl JniError {
+    pub fn new(code: &str, message: &str) -> Self {
+        Self {
+            code: code.to_string(),
+            numeric_code: 0,
+            message: message.to_string(),
+            field: None,
+            offset: None,
+        }
+    }
+
+    /// 补充失败的字段名称与字节偏移
+    pub fn with_field(mut self, field: &str, offset: usize) -> Self {
+        self.field = Some(field.to_string());
+        self.offset = Some(offset as u64);
+        self
+    }
+}
+
+impl From<&ProtocolError> for JniError {
+    fn from(err: &ProtocolError) -> Self {
+        let mut error = JniError::new(err.code(), &err.localized());
+        error.numeric_code = err.numeric_code();
+        if let Some(context) = err.context() {
+            error.field = context.field.clone();
+            error.offset = context.offset.map(|offset| offset as u64);
+        }
+        error
+    }
+}
+
+impl From<ProtocolError> for JniError {
+    fn from(err: ProtocolError) -> Self {
+        JniError::from(&err)
+    }
+}
+
+/// 协议层的处理指标，平台按设备型号统计解析耗时与 CRC 失败率
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[serde(rename_all = "camelCase")]
+pub struct JniMetrics {
+    // 解码/编码耗时 (微秒)
+    #[serde(default)]
+    pub duration_micros: Option<u64>,
+    // 报文帧长度 (字节)
+    #[serde(default)]
+    pub frame_len: Option<u64>,
+    // CRC 校验结果，没有校验 CRC 时为 None
+    #[serde(default)]
+    pub crc_valid: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 #[serde(rename_all = "camelCase")]
 pub struct JniResponse {
     pub(crate) success: bool,
@@ -161,7 +585,7 @@ pub struct JniResponse {
     pub(crate) device_id: Option<String>,
     #[serde(default)]
     pub(crate) device_no: Option<String>,
-    #[serde(rename = "msgType", default)]
+    #[serde(rename = "msgType", default, alias = "msgtType")]
     pub(crate) msg_type: Option<String>,
     #[serde(default)]
     pub(crate) cmd_code: Option<String>,
@@ -169,19 +593,29 @@ pub struct JniResponse {
     pub(crate) req_hex: String,
     #[serde(default)]
     pub(crate) rsp_hex: String,
+    // 需要回复多帧时，按发送顺序列出全部下行 hex (rsp_hex 为第一帧)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) rsp_hexes: Vec<String>,
     #[serde(default)]
     pub(crate) req_jsons: Vec<ReportField>,
     #[serde(default)]
     pub(crate) rsp_jsons: Vec<ReportField>,
     #[serde(default)]
     pub(crate) err_msg: Option<String>,
+    #[serde(default)]
+    pub(crate) error: Option<JniError>,
+    #[serde(default)]
+    pub(crate) trace_id: Option<String>,
+    #[serde(default)]
+    pub(crate) metrics: Option<JniMetrics>,
+    // 非致命告警，不影响 success
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) warnings: Vec<ProtocolWarning>,
 }
 
 impl JniResponse {
     pub fn to_bytes(&self) -> ProtocolResult<Vec<u8>> {
-        let json_string =
-            serde_json::to_string(self).map_err(|e| ProtocolError::CommonError(e.to_string()))?;
-        Ok(json_string.into_bytes())
+        _to_json_bytes(self)
     }
 
     pub fn new_with_err_msg(device_no: &str, cmd_code: &str, err_msg: &str) -> Self {
@@ -193,18 +627,29 @@ impl JniResponse {
             cmd_code: Some(cmd_code.into()),
             req_hex: String::new(),
             rsp_hex: String::new(),
+            rsp_hexes: Vec::new(),
             req_jsons: Vec::new(),
             rsp_jsons: Vec::new(),
             err_msg: Some(err_msg.into()),
+            error: None,
+            trace_id: None,
+            metrics: None,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// 根据 ProtocolError 构造失败的返回，同时填充 err_msg 与结构化的 error
+    pub fn new_with_error(device_no: &str, cmd_code: &str, err: &ProtocolError) -> Self {
+        let mut response = Self::new_with_err_msg(device_no, cmd_code, &err.localized());
+        response.error = Some(JniError::from(err));
+        if err.is_crc_error() {
+            response.metrics_mut().crc_valid = Some(false);
         }
+        response
     }
 
     pub fn from(data: &[u8]) -> ProtocolResult<Self> {
-        let json_string =
-            std::str::from_utf8(data).map_err(|e| ProtocolError::CommonError(e.to_string()))?;
-        let response = serde_json::from_str(json_string)
-            .map_err(|e| ProtocolError::CommonError(e.to_string()))?;
-        Ok(response)
+        _from_json_bytes(data)
     }
 
     // Getter methods
@@ -260,6 +705,11 @@ impl JniResponse {
         self.rsp_hex.clone()
     }
 
+    /// 全部下行帧的 hex，按发送顺序。只有一帧时为空，直接使用 `rsp_hex`
+    pub fn rsp_hexes(&self) -> &[String] {
+        &self.rsp_hexes
+    }
+
     pub fn req_jsons(&self) -> &[ReportField] {
         &self.req_jsons
     }
@@ -284,7 +734,61 @@ impl JniResponse {
         self.err_msg = Some(err_msg.to_string());
     }
 
+    pub fn error(&self) -> Option<&JniError> {
+        self.error.as_ref()
+    }
+
+    // 设置结构化错误，err_msg 同步为错误信息，并标记为失败
+    pub fn set_error(&mut self, error: JniError) {
+        self.success = false;
+        self.err_msg = Some(error.message.clone());
+        self.error = Some(error);
+    }
+
+    pub fn trace_id(&self) -> Option<&str> {
+        self.trace_id.as_deref()
+    }
+
     // Setter methods
+    pub fn set_trace_id(&mut self, trace_id: &str) {
+        self.trace_id = Some(trace_id.to_string());
+    }
+
+    pub fn metrics(&self) -> Option<&JniMetrics> {
+        self.metrics.as_ref()
+    }
+
+    pub fn metrics_mut(&mut self) -> &mut JniMetrics {
+        self.metrics.get_or_insert_with(JniMetrics::default)
+    }
+
+    pub fn set_metrics(&mut self, metrics: JniMetrics) {
+        self.metrics = Some(metrics);
+    }
+
+    pub fn warnings(&self) -> &[ProtocolWarning] {
+        &self.warnings
+    }
+
+    pub fn add_warning(&mut self, warning: ProtocolWarning) {
+        self.warnings.push(warning);
+    }
+
+    /// 记录处理耗时。处理器已经自行记录时不覆盖
+    pub fn record_duration(&mut self, elapsed: Duration) {
+        let metrics = self.metrics_mut();
+        if metrics.duration_micros.is_none() {
+            metrics.duration_micros = Some(u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX));
+        }
+    }
+
+    /// 尚未设置 trace_id 时沿用请求中的 trace_id (自行对接宿主时在处理完成后调用)
+    pub fn inherit_trace_id(&mut self, trace_id: Option<String>) {
+        if self.trace_id.is_none() {
+            self.trace_id = trace_id;
+        }
+    }
+
     pub fn set_success(&mut self, success: bool) {
         self.success = success;
     }
@@ -297,8 +801,27 @@ impl JniResponse {
         self.device_no = Some(device_no.to_string());
     }
 
+    pub fn set_msg_type(&mut self, msg_type: &str) {
+        self.msg_type = Some(msg_type.to_string());
+    }
+
+    #[deprecated(note = "use set_msg_type")]
     pub fn set_msgt_type(&mut self, msgt_type: &str) {
-        self.msg_type = Some(msgt_type.to_string());
+        self.set_msg_type(msgt_type);
+    }
+
+    /// msg_type 为空时按 cmd_code 从对照表中补全，返回是否补全
+    pub fn fill_msg_type(&mut self, registry: &CmdRegistry) -> bool {
+        if self.msg_type.as_deref().is_some_and(|t| !t.is_empty()) {
+            return false;
+        }
+        match self.cmd_code().and_then(|code| registry.msg_type_of(code)) {
+            Some(msg_type) => {
+                self.msg_type = Some(msg_type.code());
+                true
+            }
+            None => false,
+        }
     }
 
     pub fn set_cmd_code(&mut self, cmd_code: &str) {
@@ -313,6 +836,10 @@ impl JniResponse {
         self.rsp_hex = rsp_hex.to_string();
     }
 
+    pub fn set_rsp_hexes(&mut self, rsp_hexes: Vec<String>) {
+        self.rsp_hexes = rsp_hexes;
+    }
+
     pub fn set_req_jsons(&mut self, req_jsons: Vec<ReportField>) {
         self.req_jsons = req_jsons;
     }
@@ -321,6 +848,37 @@ impl JniResponse {
         self.rsp_jsons = rsp_jsons;
     }
 
+    /// 去掉所有字段的来源字节信息 (hex / offset / length)，不需要排查时可减小返回体积
+    pub fn clear_field_sources(&mut self) {
+        self.req_jsons
+            .iter_mut()
+            .chain(self.rsp_jsons.iter_mut())
+            .for_each(ReportField::clear_source);
+    }
+
+    /// 序列化后按 `threshold` 字节拆分成分片。未超过阈值时只有一个分片。
+    pub fn to_chunks(&self, threshold: usize) -> ProtocolResult<Vec<JniResponseChunk>> {
+        if threshold == 0 {
+            return Err(ProtocolError::ValidationFailed(
+                "chunk threshold must be positive".into(),
+            ));
+        }
+        let data = self.to_bytes()?;
+        let total = u32::try_from(data.len().div_ceil(threshold))
+            .map_err(|_| ProtocolError::ValidationFailed("too many chunks".into()))?;
+        let chunk_id = utils::generate_rand(16);
+        Ok(data
+            .chunks(threshold)
+            .enumerate()
+            .map(|(sequence, part)| JniResponseChunk {
+                chunk_id: chunk_id.clone(),
+                sequence: sequence as u32,
+                total,
+                part: BASE64.encode(part),
+            })
+            .collect())
+    }
+
     // 上行的返回
     pub fn upstream_response<T: Cmd + Clone + 'static>(
         chamber: &RawChamber<T>,
@@ -335,25 +893,62 @@ impl JniResponse {
         } else {
             (String::new(), Vec::new())
         };
-        // 获取 downstream 的 hex 和 field_details
-        let (rsp_hex, rsp_jsons) = if let Some(downstream) = chamber.downstream() {
-            (downstream.hex_clone(), downstream.field_details_clone())
+        // 获取 downstream 的 hex 和 field_details，多帧下行时字段按帧顺序拼接
+        let downstreams = chamber.downstreams();
+        let rsp_hex = chamber
+            .downstream()
+            .map(|downstream| downstream.hex_clone())
+            .unwrap_or_default();
+        let rsp_hexes = if downstreams.len() > 1 {
+            downstreams
+                .iter()
+                .map(|capsule| capsule.hex_clone())
+                .collect()
         } else {
-            (String::new(), Vec::new())
+            Vec::new()
+        };
+        let rsp_jsons = downstreams
+            .iter()
+            .flat_map(|capsule| capsule.field_details().iter().cloned())
+            .collect();
+        // msg_type 取自 capsule (默认来自 Cmd::msg_type)，未知时为空字符串
+        let msg_type = Some(
+            chamber
+                .msg_type()
+                .map(MsgTypeEnum::code)
+                .unwrap_or_default(),
+        );
+        // 上下行的告警合并返回
+        let warnings = chamber
+            .upstream()
+            .into_iter()
+            .chain(downstreams)
+            .flat_map(|capsule| capsule.warnings().iter().cloned())
+            .collect();
+        let metrics = JniMetrics {
+            duration_micros: None,
+            frame_len: chamber
+                .upstream()
+                .map(|upstream| upstream.bytes.len() as u64),
+            // 取解码流程记录的校验结果，校验失败的帧由 new_with_error 记录
+            crc_valid: chamber.upstream().and_then(RawCapsule::crc_valid),
         };
-        // msgt_type 暂时设置为空字符串，根据实际需求调整
-        let msgt_type = Some(String::new());
         Ok(Self {
             success: chamber.success(),
             device_id,
             device_no,
-            msg_type: msgt_type,
+            msg_type,
             cmd_code: Some(cmd_code),
             req_hex,
             rsp_hex,
+            rsp_hexes,
             req_jsons,
             rsp_jsons,
             err_msg: None,
+            error: None,
+            trace_id: None,
+            metrics: Some(metrics),
+            warnings,
         })
     }
 
@@ -376,20 +971,911 @@ impl JniResponse {
         let rsp_hex = capsule.hex_clone();
         let rsp_jsons = capsule.field_details_clone();
 
-        // msgt_type 暂时设置为空字符串
-        let msgt_type = Some(String::new());
+        let msg_type = Some(
+            capsule
+                .msg_type()
+                .map(MsgTypeEnum::code)
+                .unwrap_or_default(),
+        );
+        let metrics = JniMetrics {
+            duration_micros: None,
+            frame_len: Some(capsule.bytes.len() as u64),
+            crc_valid: None,
+        };
 
         Ok(Self {
             success: capsule.success(),
             device_id,
             device_no,
-            msg_type: msgt_type,
+            msg_type,
             cmd_code: Some(cmd_code),
             req_hex,
             rsp_hex,
+            rsp_hexes: Vec::new(),
             req_jsons,
             rsp_jsons,
             err_msg: None,
+            error: None,
+            trace_id: None,
+            metrics: Some(metrics),
+            warnings: capsule.warnings().to_vec(),
         })
     }
 }
+
+// JSON 序列化，供各个桥接类型复用
+fn _to_json_bytes<T: Serialize + ?Sized>(value: &T) -> ProtocolResult<Vec<u8>> {
+    let json_string = serde_json::to_string(value).map_err(ProtocolError::external)?;
+    Ok(json_string.into_bytes())
+}
+
+fn _from_json_bytes<T: DeserializeOwned>(data: &[u8]) -> ProtocolResult<T> {
+    let json_string = std::str::from_utf8(data).map_err(ProtocolError::external)?;
+    serde_json::from_str(json_string).map_err(ProtocolError::external)
+}
+
+/// 导出桥接与上报类型的 JSON Schema，key 为类型名
+#[cfg(feature = "schema")]
+pub fn json_schemas() -> Vec<(&'static str, serde_json::Value)> {
+    vec![
+        ("ReportField", schemars::schema_for!(ReportField).to_value()),
+        ("JniRequest", schemars::schema_for!(JniRequest).to_value()),
+        ("JniResponse", schemars::schema_for!(JniResponse).to_value()),
+        (
+            "JarEncodeRequest",
+            schemars::schema_for!(JarEncodeRequest).to_value(),
+        ),
+        (
+            "JarEncodeResponse",
+            schemars::schema_for!(JarEncodeResponse).to_value(),
+        ),
+        (
+            "JarDecodeResponse",
+            schemars::schema_for!(JarDecodeResponse).to_value(),
+        ),
+        (
+            "ProtocolWarning",
+            schemars::schema_for!(ProtocolWarning).to_value(),
+        ),
+        (
+            "ParamDescriptor",
+            schemars::schema_for!(crate::ParamDescriptor).to_value(),
+        ),
+    ]
+}
+
+/// 默认分片阈值：序列化后超过 512KB 的返回会被拆分
+pub const DEFAULT_CHUNK_THRESHOLD: usize = 512 * 1024;
+
+/// 超大返回 (例如固件传输的解码结果) 的分片。
+/// 所有分片共用同一个 `chunk_id`，`sequence` 从 0 开始，`part` 为该片数据的 base64 编码。
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JniResponseChunk {
+    pub chunk_id: String,
+    pub sequence: u32,
+    pub total: u32,
+    pub part: String,
+}
+
+impl JniResponseChunk {
+    pub fn to_bytes(&self) -> ProtocolResult<Vec<u8>> {
+        _to_json_bytes(self)
+    }
+
+    pub fn from(data: &[u8]) -> ProtocolResult<Self> {
+        _from_json_bytes(data)
+    }
+
+    /// 将同一个返回的全部分片重新组装 (分片顺序可以是乱的)
+    pub fn reassemble(mut chunks: Vec<JniResponseChunk>) -> ProtocolResult<JniResponse> {
+        let first = chunks
+            .first()
+            .ok_or_else(|| ProtocolError::ValidationFailed("no chunks to reassemble".into()))?;
+        let (chunk_id, total) = (first.chunk_id.clone(), first.total);
+        if chunks.len() != total as usize {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "expected {} chunks, but got {}",
+                total,
+                chunks.len()
+            )));
+        }
+        chunks.sort_by_key(|chunk| chunk.sequence);
+        let mut data = Vec::new();
+        for (index, chunk) in chunks.iter().enumerate() {
+            if chunk.chunk_id != chunk_id || chunk.total != total {
+                return Err(ProtocolError::ValidationFailed(format!(
+                    "chunk {} does not belong to response {}",
+                    chunk.sequence, chunk_id
+                )));
+            }
+            if chunk.sequence as usize != index {
+                return Err(ProtocolError::ValidationFailed(format!(
+                    "missing chunk {}",
+                    index
+                )));
+            }
+            let part = BASE64
+                .decode(&chunk.part)
+                .map_err(ProtocolError::external)?;
+            data.extend_from_slice(&part);
+        }
+        JniResponse::from(&data)
+    }
+}
+
+/// 桥接消息的线上格式。JSON 为默认格式，二进制格式以格式字节开头以便对端识别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WireFormat {
+    #[default]
+    Json,
+    // CBOR，需要开启 `binary` feature
+    Cbor,
+}
+
+// 二进制格式的格式字节。JSON 总是以 '{' 开头，不会与之冲突
+const WIRE_FORMAT_CBOR: u8 = 0x01;
+
+impl WireFormat {
+    /// 根据首字节识别格式
+    pub fn detect(data: &[u8]) -> WireFormat {
+        match data.first() {
+            Some(&WIRE_FORMAT_CBOR) => WireFormat::Cbor,
+            _ => WireFormat::Json,
+        }
+    }
+}
+
+/// 桥接消息的编解码，JSON 之外可选二进制格式
+pub trait BridgeMessage: Serialize + DeserializeOwned {
+    /// 按指定格式序列化。二进制格式会在开头写入格式字节
+    fn to_bytes_with(&self, format: WireFormat) -> ProtocolResult<Vec<u8>> {
+        match format {
+            WireFormat::Json => _to_json_bytes(self),
+            WireFormat::Cbor => _to_cbor_bytes(self),
+        }
+    }
+
+    /// 根据首字节自动识别格式并反序列化
+    fn from_bytes_negotiated(data: &[u8]) -> ProtocolResult<Self> {
+        match WireFormat::detect(data) {
+            WireFormat::Json => _from_json_bytes(data),
+            WireFormat::Cbor => _from_cbor_bytes(data),
+        }
+    }
+
+    /// 序列化为二进制 (CBOR) 格式
+    fn to_bytes_binary(&self) -> ProtocolResult<Vec<u8>> {
+        self.to_bytes_with(WireFormat::Cbor)
+    }
+
+    /// 从二进制 (CBOR) 格式反序列化，首字节必须是二进制格式字节
+    fn from_bytes_binary(data: &[u8]) -> ProtocolResult<Self> {
+        match WireFormat::detect(data) {
+            WireFormat::Cbor => _from_cbor_bytes(data),
+            WireFormat::Json => Err(ProtocolError::CommonError(
+                "data is not in binary wire format".into(),
+            )),
+        }
+    }
+}
+
+impl BridgeMessage for JniRequest {}
+impl BridgeMessage for JniResponse {}
+impl BridgeMessage for JarEncodeRequest {}
+impl BridgeMessage for JarEncodeResponse {}
+impl BridgeMessage for JarDecodeResponse {}
+
+#[cfg(feature = "binary")]
+fn _to_cbor_bytes<T: Serialize + ?Sized>(value: &T) -> ProtocolResult<Vec<u8>> {
+    let mut bytes = vec![WIRE_FORMAT_CBOR];
+    ciborium::into_writer(value, &mut bytes).map_err(ProtocolError::external)?;
+    Ok(bytes)
+}
+
+#[cfg(feature = "binary")]
+fn _from_cbor_bytes<T: DeserializeOwned>(data: &[u8]) -> ProtocolResult<T> {
+    ciborium::from_reader(&data[1..]).map_err(ProtocolError::external)
+}
+
+#[cfg(not(feature = "binary"))]
+fn _to_cbor_bytes<T: Serialize + ?Sized>(_value: &T) -> ProtocolResult<Vec<u8>> {
+    Err(ProtocolError::UnsupportedMode(
+        "binary wire format requires the `binary` feature".into(),
+    ))
+}
+
+#[cfg(not(feature = "binary"))]
+fn _from_cbor_bytes<T: DeserializeOwned>(_data: &[u8]) -> ProtocolResult<T> {
+    Err(ProtocolError::UnsupportedMode(
+        "binary wire format requires the `binary` feature".into(),
+    ))
+}
+
+/// 下行编码请求：平台要求向设备下发一条指令
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct JarEncodeRequest {
+    #[serde(default)]
+    pub(crate) device_id: Option<String>,
+    #[serde(default)]
+    pub(crate) device_no: Option<String>,
+    #[serde(default)]
+    pub(crate) msg_type: Option<String>,
+    pub(crate) cmd_code: String,
+    #[serde(default)]
+    pub(crate) uri: Option<String>,
+    #[serde(default)]
+    pub(crate) params: HashMap<String, ParamValue>,
+}
+
+impl JarEncodeRequest {
+    pub fn new(
+        device_id: Option<String>,
+        device_no: Option<String>,
+        cmd_code: &str,
+        params: HashMap<String, ParamValue>,
+    ) -> Self {
+        Self {
+            device_id,
+            device_no,
+            msg_type: None,
+            cmd_code: cmd_code.to_string(),
+            uri: None,
+            params,
+        }
+    }
+
+    pub fn to_bytes(&self) -> ProtocolResult<Vec<u8>> {
+        _to_json_bytes(self)
+    }
+
+    pub fn from(data: &[u8]) -> ProtocolResult<Self> {
+        _from_json_bytes(data)
+    }
+
+    // Getter methods
+    pub fn device_id(&self) -> Option<&str> {
+        self.device_id.as_deref()
+    }
+
+    pub fn device_no(&self) -> Option<&str> {
+        self.device_no.as_deref()
+    }
+
+    pub fn msg_type(&self) -> Option<&str> {
+        self.msg_type.as_deref()
+    }
+
+    pub fn cmd_code(&self) -> &str {
+        &self.cmd_code
+    }
+
+    pub fn uri(&self) -> Option<&str> {
+        self.uri.as_deref()
+    }
+
+    pub fn params(&self) -> &HashMap<String, ParamValue> {
+        &self.params
+    }
+
+    pub fn param(&self, key: &str) -> Option<&ParamValue> {
+        self.params.get(key)
+    }
+
+    // Setter methods
+    pub fn set_msg_type(&mut self, msg_type: &str) {
+        self.msg_type = Some(msg_type.to_string());
+    }
+
+    pub fn set_uri(&mut self, uri: &str) {
+        self.uri = Some(uri.to_string());
+    }
+}
+
+impl TryFrom<JniRequest> for JarEncodeRequest {
+    type Error = ProtocolError;
+
+    // 旧的 JniRequest 中，下行请求必须带 cmd_code
+    fn try_from(request: JniRequest) -> ProtocolResult<Self> {
+        let cmd_code = request.cmd_code.ok_or_else(|| {
+            ProtocolError::ValidationFailed("cmd_code is required for encoding".into())
+        })?;
+        Ok(Self {
+            device_id: request.device_id,
+            device_no: request.device_no,
+            msg_type: request.msg_type,
+            cmd_code,
+            uri: request.uri,
+            params: request.params.unwrap_or_default(),
+        })
+    }
+}
+
+/// 下行编码结果：编码得到的 hex 与字段明细
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct JarEncodeResponse {
+    pub(crate) success: bool,
+    #[serde(default)]
+    pub(crate) device_id: Option<String>,
+    #[serde(default)]
+    pub(crate) device_no: Option<String>,
+    #[serde(default)]
+    pub(crate) cmd_code: Option<String>,
+    #[serde(default)]
+    pub(crate) hex: String,
+    #[serde(default)]
+    pub(crate) fields: Vec<ReportField>,
+    #[serde(default)]
+    pub(crate) err_msg: Option<String>,
+}
+
+impl JarEncodeResponse {
+    pub fn from_capsule<T: Cmd + Clone + 'static>(capsule: &RawCapsule<T>) -> Self {
+        Self {
+            success: capsule.success(),
+            device_id: capsule.device_id_clone(),
+            device_no: capsule.device_no_clone(),
+            cmd_code: capsule.cmd().map(|cmd| cmd.code()),
+            hex: capsule.hex_clone(),
+            fields: capsule.field_details_clone(),
+            err_msg: None,
+        }
+    }
+
+    pub fn new_with_err_msg(device_no: &str, cmd_code: &str, err_msg: &str) -> Self {
+        Self {
+            device_no: Some(device_no.into()),
+            cmd_code: Some(cmd_code.into()),
+            err_msg: Some(err_msg.into()),
+            ..Default::default()
+        }
+    }
+
+    pub fn to_bytes(&self) -> ProtocolResult<Vec<u8>> {
+        _to_json_bytes(self)
+    }
+
+    pub fn from(data: &[u8]) -> ProtocolResult<Self> {
+        _from_json_bytes(data)
+    }
+
+    // Getter methods
+    pub fn success(&self) -> bool {
+        self.success
+    }
+
+    pub fn device_id(&self) -> Option<&str> {
+        self.device_id.as_deref()
+    }
+
+    pub fn device_no(&self) -> Option<&str> {
+        self.device_no.as_deref()
+    }
+
+    pub fn cmd_code(&self) -> Option<&str> {
+        self.cmd_code.as_deref()
+    }
+
+    pub fn hex(&self) -> &str {
+        &self.hex
+    }
+
+    pub fn fields(&self) -> &[ReportField] {
+        &self.fields
+    }
+
+    pub fn err_msg(&self) -> Option<&str> {
+        self.err_msg.as_deref()
+    }
+}
+
+/// 上行解码结果：设备上报的字段，以及需要回复设备的应答帧 (如有)
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct JarDecodeResponse {
+    pub(crate) success: bool,
+    #[serde(default)]
+    pub(crate) device_id: Option<String>,
+    #[serde(default)]
+    pub(crate) device_no: Option<String>,
+    // 兼容旧版本拼错的 msgtType
+    #[serde(default, alias = "msgtType")]
+    pub(crate) msg_type: Option<String>,
+    #[serde(default)]
+    pub(crate) cmd_code: Option<String>,
+    #[serde(default)]
+    pub(crate) hex: String,
+    #[serde(default)]
+    pub(crate) fields: Vec<ReportField>,
+    #[serde(default)]
+    pub(crate) reply_hex: Option<String>,
+    // 需要回复多帧时，按发送顺序列出全部应答帧 hex (reply_hex 为第一帧)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) reply_hexes: Vec<String>,
+    #[serde(default)]
+    pub(crate) reply_fields: Vec<ReportField>,
+    #[serde(default)]
+    pub(crate) err_msg: Option<String>,
+}
+
+impl JarDecodeResponse {
+    pub fn from_chamber<T: Cmd + Clone + 'static>(chamber: &RawChamber<T>) -> Self {
+        let (hex, fields) = chamber
+            .upstream()
+            .map(|upstream| (upstream.hex_clone(), upstream.field_details_clone()))
+            .unwrap_or_default();
+        let (reply_hex, reply_fields) = chamber
+            .downstream()
+            .map(|downstream| {
+                (
+                    Some(downstream.hex_clone()),
+                    downstream.field_details_clone(),
+                )
+            })
+            .unwrap_or_default();
+        let downstreams = chamber.downstreams();
+        let reply_hexes = if downstreams.len() > 1 {
+            downstreams
+                .iter()
+                .map(|capsule| capsule.hex_clone())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        Self {
+            success: chamber.success(),
+            device_id: chamber.device_id_clone(),
+            device_no: chamber.device_no_clone(),
+            msg_type: chamber.msg_type().map(MsgTypeEnum::code),
+            cmd_code: Some(chamber.cmd_code_clone()),
+            hex,
+            fields,
+            reply_hex,
+            reply_hexes,
+            reply_fields,
+            err_msg: None,
+        }
+    }
+
+    pub fn new_with_err_msg(hex: &str, err_msg: &str) -> Self {
+        Self {
+            hex: hex.into(),
+            err_msg: Some(err_msg.into()),
+            ..Default::default()
+        }
+    }
+
+    pub fn to_bytes(&self) -> ProtocolResult<Vec<u8>> {
+        _to_json_bytes(self)
+    }
+
+    pub fn from(data: &[u8]) -> ProtocolResult<Self> {
+        _from_json_bytes(data)
+    }
+
+    // Getter methods
+    pub fn success(&self) -> bool {
+        self.success
+    }
+
+    pub fn device_id(&self) -> Option<&str> {
+        self.device_id.as_deref()
+    }
+
+    pub fn device_no(&self) -> Option<&str> {
+        self.device_no.as_deref()
+    }
+
+    pub fn msg_type(&self) -> Option<&str> {
+        self.msg_type.as_deref()
+    }
+
+    pub fn cmd_code(&self) -> Option<&str> {
+        self.cmd_code.as_deref()
+    }
+
+    pub fn hex(&self) -> &str {
+        &self.hex
+    }
+
+    pub fn fields(&self) -> &[ReportField] {
+        &self.fields
+    }
+
+    pub fn reply_hex(&self) -> Option<&str> {
+        self.reply_hex.as_deref()
+    }
+
+    /// 全部应答帧的 hex，按发送顺序。只有一帧时为空，直接使用 `reply_hex`
+    pub fn reply_hexes(&self) -> &[String] {
+        &self.reply_hexes
+    }
+
+    pub fn reply_fields(&self) -> &[ReportField] {
+        &self.reply_fields
+    }
+
+    pub fn err_msg(&self) -> Option<&str> {
+        self.err_msg.as_deref()
+    }
+
+    // Setter methods
+    pub fn set_msg_type(&mut self, msg_type: &str) {
+        self.msg_type = Some(msg_type.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::reader::Reader;
+
+    #[test]
+    fn test_request_ref() {
+        let data = br#"{"deviceNo":"0001","msgtType":"report","hex":"68AA16","uri":"/a\/b"}"#;
+        let request = JniRequestRef::from(data).unwrap();
+        assert!(matches!(request.hex, Cow::Borrowed("68AA16")));
+        assert!(matches!(request.uri, Some(Cow::Owned(_))));
+        assert_eq!(request.msg_type(), Some("report"));
+        assert_eq!(request.hex_bytes().unwrap(), vec![0x68, 0xAA, 0x16]);
+        let owned = request.into_owned();
+        assert_eq!(owned.uri(), Some("/a/b"));
+        assert_eq!(owned.device_no(), Some("0001"));
+    }
+
+    #[test]
+    fn test_metrics() {
+        let err = ProtocolError::CrcError {
+            ori_crc: 1,
+            calc_crc: 2,
+        };
+        let mut response = JniResponse::new_with_error("0001", "A1", &err);
+        response.record_duration(Duration::from_millis(3));
+        response.record_duration(Duration::from_millis(5));
+        let metrics = response.metrics().unwrap();
+        assert_eq!(metrics.crc_valid, Some(false));
+        assert_eq!(metrics.duration_micros, Some(3_000));
+        let decoded = JniResponse::from(&response.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded.metrics(), response.metrics());
+
+        #[derive(Clone)]
+        struct Report;
+        impl Cmd for Report {
+            fn code(&self) -> String {
+                "01".into()
+            }
+            fn title(&self) -> String {
+                "上报".into()
+            }
+        }
+        // CRC 结果来自解码流程，与是否有名为 crc 的字段无关
+        let mut upstream = RawCapsule::<Report>::new_upstream(&[0x68, 0x01, 0x16]);
+        upstream.set_fields(vec![ReportField::new("crc", "crc", "0000".into())]);
+        let chamber = RawChamber::new(&upstream, &RawCapsule::new_downstream(Report, "0001", ""));
+        let metrics = JniResponse::upstream_response(&chamber).unwrap().metrics;
+        assert_eq!(metrics.unwrap().crc_valid, None);
+        upstream.set_fields(Vec::new());
+        upstream.set_crc_valid(Some(true));
+        let chamber = RawChamber::new(&upstream, &RawCapsule::new_downstream(Report, "0001", ""));
+        let metrics = JniResponse::upstream_response(&chamber).unwrap().metrics;
+        assert_eq!(metrics.unwrap().crc_valid, Some(true));
+    }
+
+    #[test]
+    fn test_fill_msg_type() {
+        #[derive(Clone)]
+        struct Heartbeat;
+        impl Cmd for Heartbeat {
+            fn code(&self) -> String {
+                "05".into()
+            }
+            fn title(&self) -> String {
+                "心跳".into()
+            }
+        }
+        let mut registry = CmdRegistry::new();
+        registry.register(Heartbeat).unwrap();
+        registry
+            .bind_msg_type("05", MsgTypeEnum::HeartBeat)
+            .unwrap();
+
+        let mut response = JniResponse::new_with_err_msg("0001", "05", "");
+        assert!(response.fill_msg_type(&registry));
+        assert_eq!(response.msg_type(), Some("heart_beat"));
+        assert!(!response.fill_msg_type(&registry));
+    }
+
+    #[test]
+    fn test_warnings() {
+        #[derive(Clone)]
+        struct Ack;
+        impl Cmd for Ack {
+            fn code(&self) -> String {
+                "A1".into()
+            }
+            fn title(&self) -> String {
+                "应答".into()
+            }
+            fn direction(&self) -> crate::DirectionEnum {
+                crate::DirectionEnum::Downstream
+            }
+        }
+
+        let mut capsule = RawCapsule::new_downstream(Ack, "0001", "");
+        capsule.add_warning(ProtocolWarning::unknown_enum_value("阀门状态", "07"));
+        let response = JniResponse::downstream_response(&capsule).unwrap();
+        assert!(response.success());
+        assert_eq!(
+            response.warnings()[0].code,
+            ProtocolWarning::UNKNOWN_ENUM_VALUE
+        );
+        assert_eq!(response.warnings()[0].field.as_deref(), Some("阀门状态"));
+        let decoded = JniResponse::from(&response.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded.warnings(), response.warnings());
+
+        let empty = JniResponse::new_with_err_msg("0001", "A1", "");
+        let json = String::from_utf8(empty.to_bytes().unwrap()).unwrap();
+        assert!(!json.contains("warnings"));
+    }
+
+    #[test]
+    fn test_numeric_value() {
+        use crate::{FieldConvertDecoder, FieldTranslator, FieldType};
+
+        let decoder = FieldConvertDecoder::new(
+            "累计用量",
+            FieldType::UnsignedU16(0.01),
+            Some(Symbol::CubicMeter),
+            false,
+        );
+        let field = decoder.translate(&[0x01, 0x77]).unwrap().to_report_field();
+        assert_eq!(field.value, "3.75 m³");
+        assert_eq!(field.numeric_value, Some(Decimal::new(375, 2)));
+        assert_eq!(field.unit.as_deref(), Some("m³"));
+        assert_eq!(field.symbol(), Some(Symbol::CubicMeter));
+        let json = serde_json::to_value(&field).unwrap();
+        assert_eq!(json["numericValue"], "3.75");
+
+        let ascii = FieldConvertDecoder::new("型号", FieldType::Ascii, None, false);
+        let field = ascii.translate(b"GM").unwrap().to_report_field();
+        assert!(field.numeric_value.is_none() && field.unit.is_none());
+        assert!(field.hex.is_none() && field.length.is_none());
+
+        let field = ascii.translate(b"GM").unwrap().to_report_field_with(true);
+        assert_eq!(field.hex.as_deref(), Some("474D"));
+        assert_eq!(field.length, Some(2));
+    }
+
+    #[test]
+    fn test_grouped_fields() {
+        let records: Vec<ReportField> = (1..=2)
+            .map(|i| {
+                ReportField::group(
+                    &format!("记录{}", i),
+                    &format!("jilu{}", i),
+                    vec![
+                        ReportField::new("时间", "shijian", format!("08:0{}", i)),
+                        ReportField::new("用量", "yongliang", format!("{}.5", i)),
+                    ],
+                )
+            })
+            .collect();
+        let history = ReportField::group("历史记录", "lishijilu", records);
+        assert!(history.is_group());
+        let values: Vec<&str> = history.leaves().iter().map(|f| f.value.as_str()).collect();
+        assert_eq!(values, ["08:01", "1.5", "08:02", "2.5"]);
+
+        let json = serde_json::to_value(&history).unwrap();
+        assert_eq!(json["children"][1]["children"][0]["value"], "08:02");
+        assert!(json["children"][1]["children"][0].get("children").is_none());
+        let restored: ReportField = serde_json::from_value(json).unwrap();
+        assert_eq!(restored, history);
+    }
+
+    #[test]
+    fn test_report_field_source() {
+        let bytes = [0x68, 0x12, 0x34, 0x16];
+        let mut reader = Reader::new(&bytes);
+        reader
+            .read_and_translate_head(1, |raw| Ok(Rawfield::new(raw, "帧头".into(), "68".into())))
+            .unwrap()
+            .read_and_translate_tail(1, |raw| Ok(Rawfield::new(raw, "帧尾".into(), "16".into())))
+            .unwrap()
+            .read_and_translate_remaining(|raw| {
+                Ok(Rawfield::new(raw, "数据".into(), "1234".into()))
+            })
+            .unwrap();
+        assert!(reader.to_report_fields().unwrap()[0].hex.is_none());
+        let fields = reader.to_report_fields_with(true).unwrap();
+        let ranges: Vec<_> = fields.iter().map(|f| f.byte_range().unwrap()).collect();
+        assert_eq!(ranges, vec![0..1, 3..4, 1..3]);
+        assert_eq!(fields[2].hex.as_deref(), Some("1234"));
+
+        let mut response = JniResponse::new_with_err_msg("0001", "A1", "");
+        response.set_rsp_jsons(fields);
+        response.clear_field_sources();
+        assert!(response.rsp_jsons()[0].byte_range().is_none());
+        let json = String::from_utf8(response.to_bytes().unwrap()).unwrap();
+        assert!(!json.contains("offset"));
+    }
+
+    #[test]
+    fn test_request_builder() {
+        let request = JniRequest::builder()
+            .device_no("0001")
+            .msg_type("report")
+            .hex("68 AA 16")
+            .param("interval", "60")
+            .build()
+            .unwrap();
+        assert_eq!(
+            request.param("interval"),
+            Some(&ParamValue::Text("60".into()))
+        );
+        let typed =
+            JniRequest::from(br#"{"cmdCode":"A1","params":{"n":60,"on":true,"s":"x"}}"#).unwrap();
+        assert_eq!(typed.param("n"), Some(&ParamValue::Int(60)));
+        // 超出 i64 的整数不会被当作浮点数而丢失精度
+        let big = JniRequest::from(br#"{"params":{"id":18446744073709551615}}"#).unwrap();
+        assert_eq!(big.param("id"), Some(&ParamValue::UInt(u64::MAX)));
+        assert_eq!(big.params_clone()["id"], u64::MAX.to_string());
+        assert_eq!(ParamValue::from(u64::MAX), ParamValue::UInt(u64::MAX));
+        assert_eq!(ParamValue::from(60u64), ParamValue::Int(60));
+        assert_eq!(typed.params_clone()["on"], "1");
+        assert_eq!(typed.params_clone()["s"], "x");
+        assert!(JniRequest::builder().build().is_err());
+        assert!(JniRequest::builder().hex("68A").build().is_err());
+        assert!(JniRequest::builder().cmd_code(" ").build().is_err());
+        assert!(
+            JniRequest::builder()
+                .cmd_code("A1")
+                .uri("/a b")
+                .build()
+                .is_err()
+        );
+
+        let legacy = JniRequest::from(br#"{"msgtType":"report","hex":"68"}"#).unwrap();
+        assert_eq!(legacy.msg_type(), Some("report"));
+    }
+
+    #[test]
+    fn test_chunk_round_trip() {
+        let mut response = JniResponse::new_with_err_msg("0001", "A1", "");
+        response.set_rsp_hex(&"AB".repeat(1000));
+        let mut chunks = response.to_chunks(256).unwrap();
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.total as usize == chunks.len()));
+        chunks.reverse();
+        let reassembled = JniResponseChunk::reassemble(chunks.clone()).unwrap();
+        assert_eq!(reassembled.rsp_hex(), response.rsp_hex());
+
+        chunks.pop();
+        assert!(JniResponseChunk::reassemble(chunks).is_err());
+        assert_eq!(
+            response.to_chunks(DEFAULT_CHUNK_THRESHOLD).unwrap().len(),
+            1
+        );
+    }
+
+    #[cfg(feature = "schema")]
+    #[test]
+    fn test_json_schemas() {
+        let schemas = json_schemas();
+        let (_, request) = schemas
+            .iter()
+            .find(|(name, _)| *name == "JniRequest")
+            .unwrap();
+        assert!(request["properties"]["deviceNo"].is_object());
+        assert!(request["properties"].get("device_no").is_none());
+    }
+
+    #[cfg(feature = "binary")]
+    #[test]
+    fn test_binary_round_trip() {
+        let mut response = JniResponse::new_with_err_msg("0001", "A1", "bad frame");
+        response.set_req_jsons(vec![ReportField::new("电压", "dianya", "3.6".into())]);
+        let bytes = response.to_bytes_binary().unwrap();
+        assert_eq!(WireFormat::detect(&bytes), WireFormat::Cbor);
+        let decoded = JniResponse::from_bytes_negotiated(&bytes).unwrap();
+        assert_eq!(decoded.req_jsons(), response.req_jsons());
+        assert_eq!(decoded.err_msg(), Some("bad frame"));
+
+        let json = response.to_bytes().unwrap();
+        assert!(JniResponse::from_bytes_binary(&json).is_err());
+        assert!(JniResponse::from_bytes_negotiated(&json).is_ok());
+    }
+
+    #[derive(Clone)]
+    struct Price;
+    impl Cmd for Price {
+        fn code(&self) -> String {
+            "A1".into()
+        }
+        fn title(&self) -> String {
+            "调价".into()
+        }
+    }
+
+    #[test]
+    fn test_jar_encode_request() {
+        let params = HashMap::from([("price".to_string(), ParamValue::Int(350))]);
+        let mut request = JarEncodeRequest::new(None, Some("0001".into()), "A1", params);
+        request.set_uri("/price");
+        let decoded = JarEncodeRequest::from(&request.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded.device_no(), Some("0001"));
+        assert_eq!((decoded.cmd_code(), decoded.uri()), ("A1", Some("/price")));
+        assert_eq!(decoded.param("price"), Some(&ParamValue::Int(350)));
+
+        let legacy = JniRequest {
+            device_no: Some("0001".into()),
+            msg_type: Some("device_param_setting".into()),
+            cmd_code: Some("A1".into()),
+            ..Default::default()
+        };
+        let converted = JarEncodeRequest::try_from(legacy.clone()).unwrap();
+        assert_eq!(converted.cmd_code(), "A1");
+        assert_eq!(converted.msg_type(), Some("device_param_setting"));
+        assert!(converted.params().is_empty());
+        let missing = JniRequest {
+            cmd_code: None,
+            ..legacy
+        };
+        assert!(matches!(
+            JarEncodeRequest::try_from(missing),
+            Err(ProtocolError::ValidationFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_jar_responses() {
+        let mut down = RawCapsule::new_downstream(Price, "0001", "D1");
+        down.set_bytes_and_generate_hex(&[0x68, 0xA1, 0x16])
+            .unwrap();
+        down.set_fields(vec![ReportField::new("单价", "dan_jia", "3.5".into())]);
+        let encoded = JarEncodeResponse::from_capsule(&down);
+        assert!(encoded.success());
+        assert_eq!(
+            (encoded.device_id(), encoded.cmd_code()),
+            (Some("D1"), Some("A1"))
+        );
+        assert_eq!(encoded.hex(), "68A116");
+        let restored = JarEncodeResponse::from(&encoded.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored.fields(), encoded.fields());
+        assert!(restored.err_msg().is_none());
+
+        let mut up = RawCapsule::<Price>::new_upstream(&[0x68, 0x01, 0x16]);
+        up.set_device_no("0001");
+        up.set_fields(vec![ReportField::new("电压", "dian_ya", "3.6".into())]);
+        let decoded = JarDecodeResponse::from_chamber(&RawChamber::new(&up, &down));
+        assert_eq!((decoded.hex(), decoded.cmd_code()), ("680116", Some("A1")));
+        assert_eq!(decoded.fields()[0].value, "3.6");
+        assert_eq!(decoded.reply_hex(), Some("68A116"));
+        assert_eq!(decoded.reply_fields()[0].code, "dan_jia");
+        let restored = JarDecodeResponse::from(&decoded.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored.reply_hex(), decoded.reply_hex());
+        assert_eq!(restored.fields(), decoded.fields());
+        assert!(decoded.reply_hexes().is_empty());
+
+        // 多帧应答按发送顺序全部带上
+        let mut push = RawCapsule::new_downstream(Price, "0001", "D1");
+        push.set_bytes_and_generate_hex(&[0x68, 0xA2, 0x16])
+            .unwrap();
+        let chamber = RawChamber::with_downstreams(&up, vec![down.clone(), push]);
+        let decoded = JarDecodeResponse::from_chamber(&chamber);
+        assert_eq!(decoded.reply_hex(), Some("68A116"));
+        assert_eq!(decoded.reply_hexes(), ["68A116", "68A216"]);
+        let restored = JarDecodeResponse::from(&decoded.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored.reply_hexes(), decoded.reply_hexes());
+
+        // 兼容旧版本拼错的 msgtType
+        let legacy = JarDecodeResponse::from(br#"{"success":false,"msgtType":"report"}"#).unwrap();
+        assert_eq!(legacy.msg_type(), Some("report"));
+        let failed = JarDecodeResponse::new_with_err_msg("68", "bad frame");
+        assert!(!failed.success() && failed.reply_hex().is_none());
+    }
+}
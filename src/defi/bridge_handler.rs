@@ -0,0 +1,167 @@
+use std::{collections::HashMap, sync::Arc, time::Instant};
+
+use async_trait::async_trait;
+
+use crate::defi::{
+    bridge::{JniRequest, JniResponse},
+    error::ProtocolError,
+};
+
+/// 异步的桥接处理器。每个协议实现一个，由 `BridgeDispatcher` 统一分发。
+#[async_trait]
+pub trait BridgeHandler: Send + Sync {
+    async fn handle(&self, request: JniRequest) -> JniResponse;
+}
+
+/// 桥接请求分发器。
+/// 路由顺序：先按 cmd_code 匹配，再按 msg_type 匹配，最后交给兜底处理器。
+/// 都没有匹配时返回失败的 JniResponse。
+#[derive(Default, Clone)]
+pub struct BridgeDispatcher {
+    by_cmd_code: HashMap<String, Arc<dyn BridgeHandler>>,
+    by_msg_type: HashMap<String, Arc<dyn BridgeHandler>>,
+    fallback: Option<Arc<dyn BridgeHandler>>,
+}
+
+impl BridgeDispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册处理某个指令码的处理器，重复注册会覆盖
+    pub fn register_cmd_code(
+        &mut self,
+        cmd_code: &str,
+        handler: Arc<dyn BridgeHandler>,
+    ) -> &mut Self {
+        self.by_cmd_code.insert(cmd_code.to_string(), handler);
+        self
+    }
+
+    /// 注册处理某种消息类型的处理器，重复注册会覆盖
+    pub fn register_msg_type(
+        &mut self,
+        msg_type: &str,
+        handler: Arc<dyn BridgeHandler>,
+    ) -> &mut Self {
+        self.by_msg_type.insert(msg_type.to_string(), handler);
+        self
+    }
+
+    /// 设置兜底处理器 (例如整个协议只有一个入口时)
+    pub fn set_fallback(&mut self, handler: Arc<dyn BridgeHandler>) -> &mut Self {
+        self.fallback = Some(handler);
+        self
+    }
+
+    // 查找请求对应的处理器
+    fn route(&self, request: &JniRequest) -> Option<Arc<dyn BridgeHandler>> {
+        request
+            .cmd_code()
+            .and_then(|code| self.by_cmd_code.get(code))
+            .or_else(|| {
+                request
+                    .msg_type()
+                    .and_then(|msg_type| self.by_msg_type.get(msg_type))
+            })
+            .or(self.fallback.as_ref())
+            .cloned()
+    }
+
+    /// 分发请求。处理器没有设置 trace_id 时，返回中沿用请求的 trace_id；
+    /// 同时记录处理耗时 (`JniMetrics::duration_micros`)
+    pub async fn dispatch(&self, request: JniRequest) -> JniResponse {
+        let trace_id = request.trace_id.clone();
+        let started = Instant::now();
+        let mut response = self._dispatch(request).await;
+        response.record_duration(started.elapsed());
+        response.inherit_trace_id(trace_id);
+        response
+    }
+
+    async fn _dispatch(&self, request: JniRequest) -> JniResponse {
+        match self.route(&request) {
+            #[cfg(feature = "tracing")]
+            Some(handler) => {
+                use tracing::Instrument;
+                let span = request.span();
+                handler.handle(request).instrument(span).await
+            }
+            #[cfg(not(feature = "tracing"))]
+            Some(handler) => handler.handle(request).await,
+            None => {
+                let err = ProtocolError::ValidationFailed(format!(
+                    "no handler for cmd_code {:?} / msg_type {:?}",
+                    request.cmd_code(),
+                    request.msg_type()
+                ));
+                JniResponse::new_with_error(
+                    request.device_no().unwrap_or_default(),
+                    request.cmd_code().unwrap_or_default(),
+                    &err,
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct EchoHandler(&'static str);
+
+    #[async_trait]
+    impl BridgeHandler for EchoHandler {
+        async fn handle(&self, request: JniRequest) -> JniResponse {
+            let mut response = JniResponse::new_with_err_msg(
+                request.device_no().unwrap_or_default(),
+                request.cmd_code().unwrap_or_default(),
+                "",
+            );
+            response.set_success(true);
+            response.set_rsp_hex(self.0);
+            response
+        }
+    }
+
+    fn request(cmd_code: Option<&str>, msg_type: Option<&str>) -> JniRequest {
+        JniRequest::new(
+            None,
+            Some("0001".into()),
+            msg_type.map(Into::into),
+            cmd_code.map(Into::into),
+            String::new(),
+            None,
+            None,
+        )
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_dispatch_routes() {
+        let mut dispatcher = BridgeDispatcher::new();
+        dispatcher
+            .register_cmd_code("A1", Arc::new(EchoHandler("cmd")))
+            .register_msg_type("report", Arc::new(EchoHandler("msg")));
+
+        let by_cmd = dispatcher
+            .dispatch(request(Some("A1"), Some("report")))
+            .await;
+        assert_eq!(by_cmd.rsp_hex(), "cmd");
+        let by_msg = dispatcher
+            .dispatch(request(Some("B2"), Some("report")))
+            .await;
+        assert_eq!(by_msg.rsp_hex(), "msg");
+        let missing = dispatcher.dispatch(request(Some("B2"), None)).await;
+        assert!(!missing.success());
+        assert_eq!(missing.error().unwrap().code, "VALIDATION_FAILED");
+
+        dispatcher.set_fallback(Arc::new(EchoHandler("fallback")));
+        let mut traced = request(None, None);
+        traced.set_trace_id("trace-1");
+        let fallback = dispatcher.dispatch(traced).await;
+        assert_eq!(fallback.rsp_hex(), "fallback");
+        assert_eq!(fallback.trace_id(), Some("trace-1"));
+        assert!(fallback.metrics().unwrap().duration_micros.is_some());
+    }
+}
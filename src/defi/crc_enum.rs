@@ -1,5 +1,13 @@
+use serde::{Deserialize, Serialize};
+
 use crate::defi::ProtocolResult;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
 
+/// CRC16 算法。serde 为 kebab-case，例如 "crc16-modbus"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "kebab-case")]
 pub enum CrcType {
     Crc16Ccitt,
     Crc16CcittFalse,
@@ -0,0 +1,20 @@
+use thiserror::Error;
+
+/// `AesCipher` 的错误
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum AesError {
+    #[error("Key must be 16 bytes for AES-128, but got {actual}")]
+    InvalidKeyLength { actual: usize },
+
+    #[error("IV must be 16 bytes, but got {actual}")]
+    InvalidIvLength { actual: usize },
+
+    #[error("Data length must be multiple of 16 bytes, but got {actual}")]
+    UnalignedData { actual: usize },
+
+    #[error("Data must be at least one block for CTS mode, but got {actual} bytes")]
+    DataTooShort { actual: usize },
+
+    #[error("Invalid padding")]
+    InvalidPadding,
+}
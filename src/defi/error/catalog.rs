@@ -0,0 +1,231 @@
+//! 错误信息目录：按运行时选择的语言渲染 `ProtocolError`。
+//!
+//! 默认英文，即 `thiserror` 的 Display；选择 `Locale::ZhCn` 后返回中文信息。
+//! 第三方库的错误 (`External`) 只翻译前缀，原始信息保持原文。
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use crate::defi::error::{
+    AesError, CommError, ErrorContext, HexDigestError, HexError, ProtocolError,
+};
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+
+static LOCALE: AtomicU8 = AtomicU8::new(0);
+
+/// 错误信息的语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    En,
+    ZhCn,
+}
+
+impl Locale {
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::ZhCn => "zh-CN",
+        }
+    }
+
+    /// 解析语言标签，支持 "en"、"en-US"、"zh"、"zh-CN"、"zh_CN" 等写法
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.split(['-', '_']).next()?.to_ascii_lowercase();
+        match primary.as_str() {
+            "en" => Some(Locale::En),
+            "zh" => Some(Locale::ZhCn),
+            _ => None,
+        }
+    }
+}
+
+/// 设置全局的错误信息语言 (影响 `ProtocolError::localized` 以及返回给平台的 `JniError::message`)
+pub fn set_locale(locale: Locale) {
+    LOCALE.store(locale as u8, Ordering::Relaxed);
+}
+
+pub fn locale() -> Locale {
+    match LOCALE.load(Ordering::Relaxed) {
+        1 => Locale::ZhCn,
+        _ => Locale::En,
+    }
+}
+
+pub(crate) fn render(error: &ProtocolError, locale: Locale) -> String {
+    match locale {
+        Locale::En => error.to_string(),
+        Locale::ZhCn => _zh(error),
+    }
+}
+
+fn _zh(error: &ProtocolError) -> String {
+    match error {
+        ProtocolError::HexDigestError(e) => _zh_digest(e),
+        ProtocolError::HexError(e) => _zh_hex(e),
+        ProtocolError::CommError(CommError::UnknownMsgType(msg_type)) => {
+            format!("未知的消息类型: {}", msg_type)
+        }
+        ProtocolError::AesError(e) => _zh_aes(e),
+        ProtocolError::CommonError(message) => format!("协议处理错误: {}", message),
+        ProtocolError::External(e) => format!("协议处理错误: {}", e),
+        ProtocolError::CrcError { ori_crc, calc_crc } => format!(
+            "CRC 校验失败，报文中的 CRC: {}，计算得到: {}",
+            ori_crc, calc_crc
+        ),
+        ProtocolError::CryptoError(message) => format!("AES 加解密失败: {}", message),
+        ProtocolError::InvalidKeyLength { actual } => format!(
+            "AES 密钥长度不合法，应为 16、24 或 32 字节，实际 {} 字节",
+            actual
+        ),
+        ProtocolError::UnsupportedMode(mode) => format!("不支持的模式: {}", mode),
+        ProtocolError::InputTooShort { needed, available } => format!(
+            "报文长度不足，至少需要 {} 字节，剩余 {} 字节",
+            needed, available
+        ),
+        ProtocolError::ValidationFailed(message) => format!("校验失败: {}", message),
+        ProtocolError::WithContext { context, source } => {
+            format!("{} ({})", _zh(source), _zh_context(context))
+        }
+    }
+}
+
+fn _zh_digest(error: &HexDigestError) -> String {
+    match error {
+        HexDigestError::CrcMismatch { expected, actual } => {
+            format!("CRC 校验失败，期望 {}，实际 {}", expected, actual)
+        }
+        HexDigestError::InvalidHead => "帧头不正确".into(),
+        HexDigestError::InvalidTail => "帧尾不正确".into(),
+        HexDigestError::UnknownCommandId(id) => format!("未知或不支持的数据标识: {}", id),
+        HexDigestError::CRCCalculateError => "CRC 计算失败".into(),
+        HexDigestError::LengthMismatch {
+            declared,
+            actual,
+            hint,
+        } => {
+            let mut message = format!("长度字段为 {}，但报文实际 {} 字节", declared, actual);
+            if let Some(hint) = hint {
+                message.push_str(&format!(" ({})", hint));
+            }
+            message
+        }
+        HexDigestError::TruncatedFrame {
+            declared,
+            available,
+        } => format!(
+            "报文被截断：长度字段为 {} 字节，实际只收到 {} 字节",
+            declared, available
+        ),
+        HexDigestError::UnknownCmdCode { code, nearest } => match nearest {
+            Some(nearest) => format!(
+                "未知的命令码 '{}'，最接近的已注册命令码为 '{}'",
+                code, nearest
+            ),
+            None => format!("未知的命令码 '{}'", code),
+        },
+    }
+}
+
+fn _zh_hex(error: &HexError) -> String {
+    match error {
+        HexError::NotHex(input) => format!("{} 不是合法的 hex 字符串", input),
+        HexError::InvalidFloatLength { expected, actual } => format!(
+            "浮点数的字节长度不正确，应为 {} 字节，实际 {} 字节",
+            expected, actual
+        ),
+        HexError::InvalidFloatLengthEither { actual } => format!(
+            "浮点数的字节长度不正确，应为 4 或 8 字节，实际 {} 字节",
+            actual
+        ),
+        HexError::HexParseError {
+            context, reason, ..
+        } => format!("解析 {} 的 hex 字符串失败: {}", context, reason),
+        HexError::HexLengthError {
+            context,
+            max_chars,
+            actual_chars,
+        } => format!(
+            "{} 的 hex 字符串过长，最多 {} 个字符，实际 {} 个",
+            context, max_chars, actual_chars
+        ),
+        HexError::BinaryLengthErrorNegative { bits } => {
+            format!("位数必须为正数，实际为 {}", bits)
+        }
+        HexError::BinaryParseError {
+            context, reason, ..
+        } => format!("解析 {} 的二进制字符串失败: {}", context, reason),
+        HexError::InvalidRange { start, end, reason } => format!(
+            "截取范围不合法，起始 {}，结束 {}，原因: {}",
+            start, end, reason
+        ),
+        HexError::NotAscii(input) => format!("不是合法的 ASCII (hex): {}", input),
+        HexError::NotBcd(input) => format!("不是合法的 BCD: {}", input),
+        HexError::NotMachineCode(input) => {
+            format!("不是合法的机器码 (Hex、BCD 或 ASCII-Hex): {}", input)
+        }
+        HexError::InvalidInput(input) => format!("输入不合法: {}", input),
+        HexError::PaddingError {
+            original_len,
+            target_len,
+        } => format!(
+            "填充失败：原始长度 ({} 字节) 超过目标长度 ({} 字节)",
+            original_len, target_len
+        ),
+    }
+}
+
+fn _zh_aes(error: &AesError) -> String {
+    match error {
+        AesError::InvalidKeyLength { actual } => {
+            format!("AES-128 密钥应为 16 字节，实际 {} 字节", actual)
+        }
+        AesError::InvalidIvLength { actual } => format!("IV 应为 16 字节，实际 {} 字节", actual),
+        AesError::UnalignedData { actual } => {
+            format!("数据长度应为 16 的倍数，实际 {} 字节", actual)
+        }
+        AesError::DataTooShort { actual } => {
+            format!("CTS 模式至少需要一个分组 (16 字节)，实际 {} 字节", actual)
+        }
+        AesError::InvalidPadding => "填充不合法".into(),
+    }
+}
+
+fn _zh_context(context: &ErrorContext) -> String {
+    let mut parts = Vec::new();
+    if let Some(device_no) = &context.device_no {
+        parts.push(format!("设备 {}", device_no));
+    }
+    if let Some(cmd_code) = &context.cmd_code {
+        parts.push(format!("命令 {}", cmd_code));
+    }
+    if let Some(field) = &context.field {
+        parts.push(format!("字段 {}", field));
+    }
+    if let Some(offset) = context.offset {
+        parts.push(format!("偏移 {}", offset));
+    }
+    parts.join("，")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_locales() {
+        let error = ProtocolError::InputTooShort {
+            needed: 4,
+            available: 2,
+        }
+        .with_context(Some(3), Some("电压"), Some("A1"));
+        assert_eq!(error.message_in(Locale::En), error.to_string());
+        assert_eq!(
+            error.message_in(Locale::ZhCn),
+            "报文长度不足，至少需要 4 字节，剩余 2 字节 (命令 A1，字段 电压，偏移 3)"
+        );
+        assert_eq!(Locale::from_tag("zh_CN"), Some(Locale::ZhCn));
+        assert_eq!(Locale::from_tag("en-US"), Some(Locale::En));
+        assert_eq!(Locale::from_tag("fr"), None);
+    }
+}
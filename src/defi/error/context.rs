@@ -0,0 +1,45 @@
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use core::fmt;
+
+/// 错误发生的位置：设备、命令、字段、字节偏移。由 Reader、解码器、编解码流程逐层补充
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErrorContext {
+    pub device_no: Option<String>,
+    pub cmd_code: Option<String>,
+    pub field: Option<String>,
+    pub offset: Option<usize>,
+}
+
+impl ErrorContext {
+    /// 用 `other` 补全尚未设置的信息 (已有的信息来自更内层，更精确，不覆盖)
+    pub fn merge(&mut self, other: ErrorContext) {
+        self.device_no = self.device_no.take().or(other.device_no);
+        self.cmd_code = self.cmd_code.take().or(other.cmd_code);
+        self.field = self.field.take().or(other.field);
+        self.offset = self.offset.or(other.offset);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == ErrorContext::default()
+    }
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(device_no) = &self.device_no {
+            parts.push(format!("device {}", device_no));
+        }
+        if let Some(cmd_code) = &self.cmd_code {
+            parts.push(format!("cmd {}", cmd_code));
+        }
+        if let Some(field) = &self.field {
+            parts.push(format!("field {}", field));
+        }
+        if let Some(offset) = self.offset {
+            parts.push(format!("offset {}", offset));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
@@ -1,3 +1,5 @@
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -16,4 +18,41 @@ pub enum HexDigestError {
 
     #[error("crc calculation error")]
     CRCCalculateError,
+
+    // 长度字段与实际帧长不一致 (且实际帧更长)。hint 为推测的原因，例如长度字段不含帧头帧尾
+    #[error(
+        "frame length field is {declared} but frame has {actual} bytes{}",
+        _hint(.hint)
+    )]
+    LengthMismatch {
+        declared: u64,
+        actual: usize,
+        hint: Option<String>,
+    },
+
+    // 长度字段声明的字节数多于实际收到的，报文被截断
+    #[error(
+        "frame is truncated: length field declares {declared} bytes but only {available} received"
+    )]
+    TruncatedFrame { declared: u64, available: usize },
+
+    // 未注册的命令码。nearest 为最接近的已注册命令码，便于排查固件调整了命令码的情况
+    #[error("unknown cmd code '{code}'{}", _nearest(.nearest))]
+    UnknownCmdCode {
+        code: String,
+        nearest: Option<String>,
+    },
+}
+
+fn _hint(hint: &Option<String>) -> String {
+    hint.as_ref()
+        .map(|hint| format!(" ({})", hint))
+        .unwrap_or_default()
+}
+
+fn _nearest(nearest: &Option<String>) -> String {
+    nearest
+        .as_ref()
+        .map(|code| format!(", nearest registered cmd code is '{}'", code))
+        .unwrap_or_default()
 }
@@ -1,5 +1,15 @@
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use core::num::ParseIntError;
+
 use thiserror::Error;
 
+/// `HexParseError` 保留的原始错误。hex 库只在 std 下为 `FromHexError` 实现 `Error`，no_std 下不保留
+#[cfg(feature = "std")]
+pub type HexSource = hex::FromHexError;
+#[cfg(not(feature = "std"))]
+pub type HexSource = core::convert::Infallible;
+
 #[derive(Error, Debug)]
 pub enum HexError {
     #[error("input {0} is not a valid hex string")]
@@ -19,6 +29,8 @@ pub enum HexError {
     HexParseError {
         context: &'static str,
         reason: String,
+        #[source]
+        source: Option<HexSource>,
     },
 
     #[error(
@@ -37,6 +49,8 @@ pub enum HexError {
     BinaryParseError {
         context: &'static str,
         reason: String,
+        #[source]
+        source: Option<ParseIntError>,
     },
 
     #[error("Invalid slice range. Start: {start}, End: {end}. Reason: {reason}")]
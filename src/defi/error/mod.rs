@@ -1,12 +1,46 @@
+//! 错误类型。所有对外接口统一返回 `ProtocolError`，按来源分为三层：
+//! - `HexError`：hex / BCD / ASCII 等格式转换 (`utils::hex_util`)；
+//! - `HexDigestError`：帧外壳 (帧头、帧尾、CRC、命令码) 的校验；
+//! - `CommError`：业务层，例如未知的消息类型；
+//! - `AesError`：`AesCipher` 加解密；
+//!
+//! 其余变体 (CRC、加解密、长度、校验规则) 直接挂在 `ProtocolError` 上。
+//! 子错误类型在本模块根部重新导出，调用方匹配时不必关心它定义在哪个文件。
+//! 第三方库的错误 (serde_json、prost 等) 通过 `ProtocolError::external` 原样保留，
+//! `core::error::Error::source` 可以一直追溯到最初的错误。
+//!
+//! 解码、编码流程会用 `with_context` 给错误附加设备、命令、字段、字节偏移 (`ErrorContext`)，
+//! 判断类别时 (`code`、`is_crc_error` 等) 以被包装的原始错误为准。
+//!
+//! 每个错误都有严重程度 (`Severity`)。宽松模式下，低于 `Fatal` 的错误可以降级为告警，
+//! 与处理结果一起放在 `ProtocolOutcome` 中返回。
+
+pub mod aes_error;
+pub mod catalog;
 pub mod comm_error;
+pub mod context;
 pub mod hex_digest_error;
 pub mod hex_error;
+pub mod severity;
+
+use core::error::Error as StdError;
 
 use thiserror::Error;
 
-use crate::defi::error::{
-    comm_error::CommError, hex_digest_error::HexDigestError, hex_error::HexError,
-};
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+
+pub use aes_error::AesError;
+pub use catalog::{Locale, locale, set_locale};
+pub use comm_error::CommError;
+pub use context::ErrorContext;
+pub use hex_digest_error::HexDigestError;
+pub use hex_error::HexError;
+pub use severity::{ProtocolOutcome, ProtocolWarning, Severity};
+
+/// 旧版本中 `HexDigestError` 的名称
+#[deprecated(note = "use HexDigestError")]
+pub type ProtocolDigestError = HexDigestError;
 
 #[derive(Error, Debug)]
 pub enum ProtocolError {
@@ -19,9 +53,16 @@ pub enum ProtocolError {
     #[error(transparent)]
     CommError(#[from] CommError),
 
+    #[error(transparent)]
+    AesError(#[from] AesError),
+
     #[error("protocol-core Error: {0}")]
     CommonError(String),
 
+    // 第三方库的错误，保留原始错误作为 source
+    #[error("protocol-core Error: {0}")]
+    External(#[source] Box<dyn StdError + Send + Sync>),
+
     #[error(
         "protocol-core crc compare error , crc in hex : {ori_crc} , calculated crc : {calc_crc}"
     )]
@@ -43,4 +84,245 @@ pub enum ProtocolError {
 
     #[error("Validation failed: {0}")]
     ValidationFailed(String),
+
+    #[error("{source} ({context})")]
+    WithContext {
+        context: ErrorContext,
+        #[source]
+        source: Box<ProtocolError>,
+    },
+}
+
+impl ProtocolError {
+    /// 错误类别代码，供跨语言调用方 (如 Java) 区分错误类型
+    pub fn code(&self) -> &'static str {
+        match self {
+            ProtocolError::HexDigestError(_) => "HEX_DIGEST_ERROR",
+            ProtocolError::HexError(_) => "HEX_ERROR",
+            ProtocolError::CommError(_) => "COMM_ERROR",
+            ProtocolError::AesError(AesError::InvalidKeyLength { .. }) => "INVALID_KEY_LENGTH",
+            ProtocolError::AesError(_) => "CRYPTO_ERROR",
+            ProtocolError::CommonError(_) | ProtocolError::External(_) => "COMMON_ERROR",
+            ProtocolError::CrcError { .. } => "CRC_ERROR",
+            ProtocolError::CryptoError(_) => "CRYPTO_ERROR",
+            ProtocolError::InvalidKeyLength { .. } => "INVALID_KEY_LENGTH",
+            ProtocolError::UnsupportedMode(_) => "UNSUPPORTED_MODE",
+            ProtocolError::InputTooShort { .. } => "INPUT_TOO_SHORT",
+            ProtocolError::ValidationFailed(_) => "VALIDATION_FAILED",
+            ProtocolError::WithContext { source, .. } => source.code(),
+        }
+    }
+
+    /// 稳定的数字错误码，与 `code` 一一对应，供 C / JNI / 平台直接按数值分支，不必解析错误信息。
+    /// 已分配的数值不会改变，新增类别只追加新值：
+    ///
+    /// | 数值 | code | 说明 |
+    /// |------|------|------|
+    /// | 1001 | HEX_ERROR | hex / BCD / ASCII 格式转换 |
+    /// | 1002 | HEX_DIGEST_ERROR | 帧头、帧尾、命令码校验 |
+    /// | 1003 | CRC_ERROR | CRC 校验失败 |
+    /// | 1004 | INPUT_TOO_SHORT | 报文长度不足 |
+    /// | 1005 | VALIDATION_FAILED | 校验规则、帧长度等不满足 |
+    /// | 2001 | COMM_ERROR | 业务层错误，例如未知的消息类型 |
+    /// | 3001 | CRYPTO_ERROR | 加解密失败 |
+    /// | 3002 | INVALID_KEY_LENGTH | 密钥长度不合法 |
+    /// | 3003 | UNSUPPORTED_MODE | 不支持的模式 |
+    /// | 9000 | COMMON_ERROR | 其他错误 |
+    pub fn numeric_code(&self) -> u32 {
+        match self {
+            ProtocolError::HexError(_) => 1001,
+            ProtocolError::HexDigestError(_) => 1002,
+            ProtocolError::CrcError { .. } => 1003,
+            ProtocolError::InputTooShort { .. } => 1004,
+            ProtocolError::ValidationFailed(_) => 1005,
+            ProtocolError::CommError(_) => 2001,
+            ProtocolError::AesError(AesError::InvalidKeyLength { .. }) => 3002,
+            ProtocolError::CryptoError(_) | ProtocolError::AesError(_) => 3001,
+            ProtocolError::InvalidKeyLength { .. } => 3002,
+            ProtocolError::UnsupportedMode(_) => 3003,
+            ProtocolError::CommonError(_) | ProtocolError::External(_) => 9000,
+            ProtocolError::WithContext { source, .. } => source.numeric_code(),
+        }
+    }
+
+    /// 按全局语言 (`set_locale`，默认英文) 渲染的错误信息
+    pub fn localized(&self) -> String {
+        catalog::render(self, catalog::locale())
+    }
+
+    /// 按指定语言渲染的错误信息，英文即 Display
+    pub fn message_in(&self, locale: Locale) -> String {
+        catalog::render(self, locale)
+    }
+
+    /// 包装第三方库的错误 (serde_json、prost、base64 等)，类别同 `CommonError`
+    pub fn external<E: StdError + Send + Sync + 'static>(error: E) -> Self {
+        ProtocolError::External(Box::new(error))
+    }
+
+    /// 附加出错位置。已经带有上下文时只补全缺少的信息，不会重复包装
+    pub fn with_context(
+        self,
+        offset: Option<usize>,
+        field_title: Option<&str>,
+        cmd_code: Option<&str>,
+    ) -> Self {
+        self.attach(ErrorContext {
+            offset,
+            field: field_title.map(str::to_string),
+            cmd_code: cmd_code.map(str::to_string),
+            ..Default::default()
+        })
+    }
+
+    /// 附加设备号，规则同 `with_context`
+    pub fn with_device(self, device_no: Option<&str>) -> Self {
+        self.attach(ErrorContext {
+            device_no: device_no.map(str::to_string),
+            ..Default::default()
+        })
+    }
+
+    fn attach(self, context: ErrorContext) -> Self {
+        if context.is_empty() {
+            return self;
+        }
+        match self {
+            ProtocolError::WithContext {
+                context: mut inner,
+                source,
+            } => {
+                inner.merge(context);
+                ProtocolError::WithContext {
+                    context: inner,
+                    source,
+                }
+            }
+            error => ProtocolError::WithContext {
+                context,
+                source: Box::new(error),
+            },
+        }
+    }
+
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            ProtocolError::WithContext { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// 去掉上下文后的原始错误
+    pub fn root(&self) -> &ProtocolError {
+        match self {
+            ProtocolError::WithContext { source, .. } => source.root(),
+            error => error,
+        }
+    }
+
+    /// 严重程度：帧外壳、CRC、长度不足及密钥/模式配置错误为 `Fatal`，其余为 `Error`
+    pub fn severity(&self) -> Severity {
+        match self.root() {
+            ProtocolError::HexDigestError(_)
+            | ProtocolError::CrcError { .. }
+            | ProtocolError::InputTooShort { .. }
+            | ProtocolError::InvalidKeyLength { .. }
+            | ProtocolError::AesError(AesError::InvalidKeyLength { .. })
+            | ProtocolError::UnsupportedMode(_) => Severity::Fatal,
+            _ => Severity::Error,
+        }
+    }
+
+    /// CRC 校验失败。`CrcError` 与 `HexDigestError::CrcMismatch` 都视为 CRC 错误
+    pub fn is_crc_error(&self) -> bool {
+        matches!(
+            self.root(),
+            ProtocolError::CrcError { .. }
+                | ProtocolError::HexDigestError(HexDigestError::CrcMismatch { .. })
+        )
+    }
+
+    /// 报文格式错误：hex 转换、帧外壳校验、CRC、长度不足
+    pub fn is_frame_error(&self) -> bool {
+        matches!(
+            self.root(),
+            ProtocolError::HexError(_)
+                | ProtocolError::HexDigestError(_)
+                | ProtocolError::CrcError { .. }
+                | ProtocolError::InputTooShort { .. }
+        )
+    }
+}
+
+// 流式连接 (TCP、串口) 的 IO 错误，tokio_util 的 Decoder / Encoder 要求错误类型能从它转换
+#[cfg(feature = "std")]
+impl From<std::io::Error> for ProtocolError {
+    fn from(error: std::io::Error) -> Self {
+        ProtocolError::external(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_categories() {
+        let crc = ProtocolError::from(HexDigestError::CrcMismatch {
+            expected: 1,
+            actual: 2,
+        });
+        assert!(crc.is_crc_error() && crc.is_frame_error());
+        assert!(
+            ProtocolError::CrcError {
+                ori_crc: 1,
+                calc_crc: 2
+            }
+            .is_crc_error()
+        );
+        let comm = ProtocolError::from(CommError::UnknownMsgType("x".into()));
+        assert!(!comm.is_crc_error() && !comm.is_frame_error());
+    }
+
+    #[test]
+    fn test_error_source_chain() {
+        let err = crate::hex_util::hex_to_bytes("ZZ").unwrap_err();
+        let source = err.source().expect("hex decode error is kept");
+        assert!(source.downcast_ref::<hex::FromHexError>().is_some());
+
+        let err = ProtocolError::from(AesError::InvalidKeyLength { actual: 8 });
+        assert_eq!(
+            (err.code(), err.numeric_code()),
+            ("INVALID_KEY_LENGTH", 3002)
+        );
+
+        let json = serde_json::from_str::<u8>("x").unwrap_err();
+        let err = ProtocolError::external(json).with_context(Some(0), None, None);
+        assert_eq!(err.code(), "COMMON_ERROR");
+        let source = err.source().and_then(StdError::source).unwrap();
+        assert!(source.downcast_ref::<serde_json::Error>().is_some());
+    }
+
+    #[test]
+    fn test_error_context() {
+        let error = ProtocolError::CrcError {
+            ori_crc: 1,
+            calc_crc: 2,
+        }
+        .with_context(Some(12), None, None)
+        .with_context(Some(0), Some("校验码"), Some("A1"))
+        .with_device(Some("0001"));
+        assert!(error.is_crc_error());
+        assert_eq!(error.code(), "CRC_ERROR");
+        assert_eq!(error.numeric_code(), 1003);
+        let context = error.context().unwrap();
+        assert_eq!(context.offset, Some(12));
+        assert_eq!(context.field.as_deref(), Some("校验码"));
+        assert!(matches!(error.root(), ProtocolError::CrcError { .. }));
+        assert!(
+            error
+                .to_string()
+                .ends_with("(device 0001, cmd A1, field 校验码, offset 12)")
+        );
+    }
 }
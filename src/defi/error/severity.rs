@@ -0,0 +1,165 @@
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::{ProtocolError, ProtocolResult};
+
+/// 错误的严重程度，按从轻到重排序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    // 不影响结果，随结果一起返回 (`ProtocolWarning`)
+    Warning,
+    // 单个字段或参数处理失败，宽松模式下可以降级为告警，跳过该字段继续处理
+    Error,
+    // 整帧不可信或配置错误 (CRC、帧头帧尾、长度不足、密钥等)，必须中止
+    Fatal,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+            Severity::Fatal => "fatal",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// 解码过程中发现的非致命异常 (未知枚举值、时间戳越界、填充不一致等)。
+/// 不影响 `success`，随 `JniResponse::warnings` 一起返回给平台
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[serde(rename_all = "camelCase")]
+pub struct ProtocolWarning {
+    // 告警类别代码，例如 UNKNOWN_ENUM_VALUE
+    pub code: String,
+    pub message: String,
+    // 触发告警的字段名称
+    #[serde(default)]
+    pub field: Option<String>,
+}
+
+impl ProtocolWarning {
+    pub const UNKNOWN_ENUM_VALUE: &'static str = "UNKNOWN_ENUM_VALUE";
+    pub const TIMESTAMP_OUT_OF_RANGE: &'static str = "TIMESTAMP_OUT_OF_RANGE";
+    pub const PADDING_MISMATCH: &'static str = "PADDING_MISMATCH";
+
+    pub fn new(code: &str, message: &str) -> Self {
+        Self {
+            code: code.to_string(),
+            message: message.to_string(),
+            field: None,
+        }
+    }
+
+    pub fn with_field(mut self, field: &str) -> Self {
+        self.field = Some(field.to_string());
+        self
+    }
+
+    /// 枚举字段出现未定义的取值
+    pub fn unknown_enum_value(field: &str, value: &str) -> Self {
+        Self::new(
+            Self::UNKNOWN_ENUM_VALUE,
+            &format!("unknown enum value '{}'", value),
+        )
+        .with_field(field)
+    }
+
+    /// 时间戳超出合理范围 (例如设备时钟未校准)
+    pub fn timestamp_out_of_range(field: &str, value: &str) -> Self {
+        Self::new(
+            Self::TIMESTAMP_OUT_OF_RANGE,
+            &format!("timestamp '{}' is out of range", value),
+        )
+        .with_field(field)
+    }
+
+    /// 填充字节与约定不一致
+    pub fn padding_mismatch(field: &str, hex: &str) -> Self {
+        Self::new(
+            Self::PADDING_MISMATCH,
+            &format!("unexpected padding '{}'", hex),
+        )
+        .with_field(field)
+    }
+}
+
+impl From<&ProtocolError> for ProtocolWarning {
+    fn from(err: &ProtocolError) -> Self {
+        let field = err.context().and_then(|context| context.field.clone());
+        Self {
+            code: err.code().to_string(),
+            message: err.localized(),
+            field,
+        }
+    }
+}
+
+/// 带告警的处理结果：`value` 为处理结果，`warnings` 为过程中的非致命问题 (包括宽松模式下降级的错误)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtocolOutcome<T> {
+    value: T,
+    warnings: Vec<ProtocolWarning>,
+}
+
+impl<T> ProtocolOutcome<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            warnings: Vec::new(),
+        }
+    }
+
+    pub fn with_warnings(value: T, warnings: Vec<ProtocolWarning>) -> Self {
+        Self { value, warnings }
+    }
+
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    pub fn warnings(&self) -> &[ProtocolWarning] {
+        &self.warnings
+    }
+
+    /// 没有任何告警
+    pub fn is_clean(&self) -> bool {
+        self.warnings.is_empty()
+    }
+
+    pub fn add_warning(&mut self, warning: ProtocolWarning) {
+        self.warnings.push(warning);
+    }
+
+    /// 宽松处理：成功时返回 `Some`；失败且严重程度低于 `Fatal` 时记录为告警并返回 `None`；`Fatal` 直接返回错误
+    pub fn absorb<U>(&mut self, result: ProtocolResult<U>) -> ProtocolResult<Option<U>> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if e.severity() < Severity::Fatal => {
+                self.warnings.push(ProtocolWarning::from(&e));
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> ProtocolOutcome<U> {
+        ProtocolOutcome {
+            value: f(self.value),
+            warnings: self.warnings,
+        }
+    }
+
+    pub fn into_value(self) -> T {
+        self.value
+    }
+
+    pub fn into_parts(self) -> (T, Vec<ProtocolWarning>) {
+        (self.value, self.warnings)
+    }
+}
@@ -0,0 +1,125 @@
+//! 基于 tonic 的 gRPC 协议服务 (`ProtocolService.Decode / Encode / BatchDecode`)。
+//! 服务定义见 `proto/bridge.proto`，服务端/客户端代码由 build.rs 生成。
+//!
+//! ```ignore
+//! let service = ProtocolGrpcService::new(decoder, encoder);
+//! tonic::transport::Server::builder()
+//!     .add_service(service.into_server())
+//!     .serve(addr)
+//!     .await?;
+//! ```
+
+use tonic::{Request, Response, Status};
+
+use crate::defi::{
+    bridge_handler::BridgeDispatcher,
+    proto::{BatchDecodeRequest, BatchDecodeResponse, JniRequest, JniResponse},
+};
+
+include!(concat!(
+    env!("OUT_DIR"),
+    "/protocol_core.bridge.ProtocolService.rs"
+));
+
+pub use protocol_service_client::ProtocolServiceClient;
+pub use protocol_service_server::{ProtocolService, ProtocolServiceServer};
+
+/// gRPC 服务实现。上行解码与下行编码分别交给各自的 `BridgeDispatcher` 路由。
+#[derive(Clone, Default)]
+pub struct ProtocolGrpcService {
+    decoder: BridgeDispatcher,
+    encoder: BridgeDispatcher,
+}
+
+impl ProtocolGrpcService {
+    pub fn new(decoder: BridgeDispatcher, encoder: BridgeDispatcher) -> Self {
+        Self { decoder, encoder }
+    }
+
+    /// 包装为可以直接注册到 tonic Server 的服务
+    pub fn into_server(self) -> ProtocolServiceServer<Self> {
+        ProtocolServiceServer::new(self)
+    }
+
+    async fn _decode(&self, request: JniRequest) -> JniResponse {
+        let response = self.decoder.dispatch(request.into()).await;
+        JniResponse::from(&response)
+    }
+}
+
+#[tonic::async_trait]
+impl ProtocolService for ProtocolGrpcService {
+    async fn decode(&self, request: Request<JniRequest>) -> Result<Response<JniResponse>, Status> {
+        Ok(Response::new(self._decode(request.into_inner()).await))
+    }
+
+    async fn encode(&self, request: Request<JniRequest>) -> Result<Response<JniResponse>, Status> {
+        let response = self.encoder.dispatch(request.into_inner().into()).await;
+        Ok(Response::new(JniResponse::from(&response)))
+    }
+
+    async fn batch_decode(
+        &self,
+        request: Request<BatchDecodeRequest>,
+    ) -> Result<Response<BatchDecodeResponse>, Status> {
+        let mut responses = Vec::new();
+        for request in request.into_inner().requests {
+            responses.push(self._decode(request).await);
+        }
+        Ok(Response::new(BatchDecodeResponse { responses }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::defi::bridge::{JniRequest as BridgeRequest, JniResponse as BridgeResponse};
+    use crate::defi::bridge_handler::BridgeHandler;
+
+    struct HexEcho;
+
+    #[async_trait::async_trait]
+    impl BridgeHandler for HexEcho {
+        async fn handle(&self, request: BridgeRequest) -> BridgeResponse {
+            let mut response = BridgeResponse::new_with_err_msg("", "", "");
+            response.set_success(true);
+            response.set_req_hex(request.hex());
+            response
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_batch_decode() {
+        let mut decoder = BridgeDispatcher::new();
+        decoder.set_fallback(Arc::new(HexEcho));
+        let service = ProtocolGrpcService::new(decoder, BridgeDispatcher::new());
+
+        let requests = ["68AA16", "68BB16"]
+            .iter()
+            .map(|hex| JniRequest {
+                hex: hex.to_string(),
+                ..Default::default()
+            })
+            .collect();
+        let response = service
+            .batch_decode(Request::new(BatchDecodeRequest { requests }))
+            .await
+            .unwrap()
+            .into_inner();
+        let hexes: Vec<&str> = response
+            .responses
+            .iter()
+            .map(|r| r.req_hex.as_str())
+            .collect();
+        assert_eq!(hexes, vec!["68AA16", "68BB16"]);
+
+        let encoded = service
+            .encode(Request::new(JniRequest::default()))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!encoded.success);
+    }
+}
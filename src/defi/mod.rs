@@ -1,5 +1,12 @@
 pub mod crc_enum;
 pub mod error;
+#[cfg(feature = "std")]
 pub mod bridge;
+#[cfg(feature = "async")]
+pub mod bridge_handler;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "proto")]
+pub mod proto;
 
 pub type ProtocolResult<T> = Result<T, error::ProtocolError>;
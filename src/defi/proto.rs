@@ -0,0 +1,379 @@
+//! 桥接类型的 protobuf 版本，与 `proto/bridge.proto` 保持一致。
+//! 使用 prost 派生 (不依赖 protoc)，修改字段时请同步更新 .proto 文件。
+
+use std::collections::HashMap;
+
+use prost::Message;
+
+use crate::defi::{
+    ProtocolResult,
+    bridge::{self, JniRequest as BridgeRequest, JniResponse as BridgeResponse},
+    error::ProtocolError,
+};
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ReportField {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub code: String,
+    #[prost(string, tag = "3")]
+    pub value: String,
+    #[prost(bool, tag = "4")]
+    pub alert: bool,
+    #[prost(string, optional, tag = "5")]
+    pub hex: Option<String>,
+    #[prost(uint64, optional, tag = "6")]
+    pub offset: Option<u64>,
+    #[prost(uint64, optional, tag = "7")]
+    pub length: Option<u64>,
+    // Decimal 的字符串形式
+    #[prost(string, optional, tag = "8")]
+    pub numeric_value: Option<String>,
+    #[prost(string, optional, tag = "9")]
+    pub unit: Option<String>,
+    #[prost(message, repeated, tag = "10")]
+    pub children: Vec<ReportField>,
+    #[prost(string, optional, tag = "11")]
+    pub alert_rule: Option<String>,
+    #[prost(uint32, optional, tag = "12")]
+    pub index: Option<u32>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct JniRequest {
+    #[prost(string, optional, tag = "1")]
+    pub device_id: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub device_no: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    pub msg_type: Option<String>,
+    #[prost(string, optional, tag = "4")]
+    pub cmd_code: Option<String>,
+    #[prost(string, tag = "5")]
+    pub hex: String,
+    #[prost(string, optional, tag = "6")]
+    pub uri: Option<String>,
+    #[prost(map = "string, string", tag = "7")]
+    pub params: HashMap<String, String>,
+    #[prost(string, optional, tag = "8")]
+    pub trace_id: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct JniError {
+    #[prost(string, tag = "1")]
+    pub code: String,
+    #[prost(string, tag = "2")]
+    pub message: String,
+    #[prost(string, optional, tag = "3")]
+    pub field: Option<String>,
+    #[prost(uint64, optional, tag = "4")]
+    pub offset: Option<u64>,
+    #[prost(uint32, tag = "5")]
+    pub numeric_code: u32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtocolWarning {
+    #[prost(string, tag = "1")]
+    pub code: String,
+    #[prost(string, tag = "2")]
+    pub message: String,
+    #[prost(string, optional, tag = "3")]
+    pub field: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct JniMetrics {
+    #[prost(uint64, optional, tag = "1")]
+    pub duration_micros: Option<u64>,
+    #[prost(uint64, optional, tag = "2")]
+    pub frame_len: Option<u64>,
+    #[prost(bool, optional, tag = "3")]
+    pub crc_valid: Option<bool>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct JniResponse {
+    #[prost(bool, tag = "1")]
+    pub success: bool,
+    #[prost(string, optional, tag = "2")]
+    pub device_id: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    pub device_no: Option<String>,
+    #[prost(string, optional, tag = "4")]
+    pub msg_type: Option<String>,
+    #[prost(string, optional, tag = "5")]
+    pub cmd_code: Option<String>,
+    #[prost(string, tag = "6")]
+    pub req_hex: String,
+    #[prost(string, tag = "7")]
+    pub rsp_hex: String,
+    #[prost(message, repeated, tag = "8")]
+    pub req_jsons: Vec<ReportField>,
+    #[prost(message, repeated, tag = "9")]
+    pub rsp_jsons: Vec<ReportField>,
+    #[prost(string, optional, tag = "10")]
+    pub err_msg: Option<String>,
+    #[prost(message, optional, tag = "11")]
+    pub error: Option<JniError>,
+    #[prost(string, optional, tag = "12")]
+    pub trace_id: Option<String>,
+    #[prost(message, optional, tag = "13")]
+    pub metrics: Option<JniMetrics>,
+    #[prost(message, repeated, tag = "14")]
+    pub warnings: Vec<ProtocolWarning>,
+    #[prost(string, repeated, tag = "15")]
+    pub rsp_hexes: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct BatchDecodeRequest {
+    #[prost(message, repeated, tag = "1")]
+    pub requests: Vec<JniRequest>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct BatchDecodeResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub responses: Vec<JniResponse>,
+}
+
+// --- 与桥接类型互相转换 ---
+
+impl From<&bridge::ReportField> for ReportField {
+    fn from(field: &bridge::ReportField) -> Self {
+        Self {
+            name: field.name.clone(),
+            code: field.code.clone(),
+            value: field.value.clone(),
+            alert: field.alert,
+            alert_rule: field.alert_rule.clone(),
+            index: field.index,
+            hex: field.hex.clone(),
+            offset: field.offset,
+            length: field.length,
+            numeric_value: field.numeric_value.map(|value| value.to_string()),
+            unit: field.unit.clone(),
+            children: field.children.iter().map(ReportField::from).collect(),
+        }
+    }
+}
+
+impl From<ReportField> for bridge::ReportField {
+    fn from(field: ReportField) -> Self {
+        Self {
+            name: field.name,
+            code: field.code,
+            value: field.value,
+            alert: field.alert,
+            alert_rule: field.alert_rule,
+            index: field.index,
+            hex: field.hex,
+            offset: field.offset,
+            length: field.length,
+            numeric_value: field.numeric_value.and_then(|value| value.parse().ok()),
+            unit: field.unit,
+            children: field.children.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<&bridge::JniError> for JniError {
+    fn from(error: &bridge::JniError) -> Self {
+        Self {
+            code: error.code.clone(),
+            message: error.message.clone(),
+            field: error.field.clone(),
+            offset: error.offset,
+            numeric_code: error.numeric_code,
+        }
+    }
+}
+
+impl From<JniError> for bridge::JniError {
+    fn from(error: JniError) -> Self {
+        Self {
+            code: error.code,
+            message: error.message,
+            field: error.field,
+            offset: error.offset,
+            numeric_code: error.numeric_code,
+        }
+    }
+}
+
+impl From<&bridge::ProtocolWarning> for ProtocolWarning {
+    fn from(warning: &bridge::ProtocolWarning) -> Self {
+        Self {
+            code: warning.code.clone(),
+            message: warning.message.clone(),
+            field: warning.field.clone(),
+        }
+    }
+}
+
+impl From<ProtocolWarning> for bridge::ProtocolWarning {
+    fn from(warning: ProtocolWarning) -> Self {
+        Self {
+            code: warning.code,
+            message: warning.message,
+            field: warning.field,
+        }
+    }
+}
+
+impl From<&bridge::JniMetrics> for JniMetrics {
+    fn from(metrics: &bridge::JniMetrics) -> Self {
+        Self {
+            duration_micros: metrics.duration_micros,
+            frame_len: metrics.frame_len,
+            crc_valid: metrics.crc_valid,
+        }
+    }
+}
+
+impl From<JniMetrics> for bridge::JniMetrics {
+    fn from(metrics: JniMetrics) -> Self {
+        Self {
+            duration_micros: metrics.duration_micros,
+            frame_len: metrics.frame_len,
+            crc_valid: metrics.crc_valid,
+        }
+    }
+}
+
+impl From<&BridgeRequest> for JniRequest {
+    fn from(request: &BridgeRequest) -> Self {
+        Self {
+            device_id: request.device_id.clone(),
+            device_no: request.device_no.clone(),
+            msg_type: request.msg_type.clone(),
+            cmd_code: request.cmd_code.clone(),
+            hex: request.hex.clone(),
+            uri: request.uri.clone(),
+            params: request.params_clone(),
+            trace_id: request.trace_id.clone(),
+        }
+    }
+}
+
+impl From<JniRequest> for BridgeRequest {
+    fn from(request: JniRequest) -> Self {
+        // protobuf 的 map 无法区分 "未设置" 与 "空"，空 map 视为未设置
+        let params = (!request.params.is_empty()).then_some(request.params);
+        let mut bridge_request = BridgeRequest::new(
+            request.device_id,
+            request.device_no,
+            request.msg_type,
+            request.cmd_code,
+            request.hex,
+            request.uri,
+            params,
+        );
+        bridge_request.trace_id = request.trace_id;
+        bridge_request
+    }
+}
+
+impl From<&BridgeResponse> for JniResponse {
+    fn from(response: &BridgeResponse) -> Self {
+        Self {
+            success: response.success,
+            device_id: response.device_id.clone(),
+            device_no: response.device_no.clone(),
+            msg_type: response.msg_type.clone(),
+            cmd_code: response.cmd_code.clone(),
+            req_hex: response.req_hex.clone(),
+            rsp_hex: response.rsp_hex.clone(),
+            rsp_hexes: response.rsp_hexes.clone(),
+            req_jsons: response.req_jsons.iter().map(ReportField::from).collect(),
+            rsp_jsons: response.rsp_jsons.iter().map(ReportField::from).collect(),
+            err_msg: response.err_msg.clone(),
+            error: response.error.as_ref().map(JniError::from),
+            trace_id: response.trace_id.clone(),
+            metrics: response.metrics.as_ref().map(JniMetrics::from),
+            warnings: response
+                .warnings
+                .iter()
+                .map(ProtocolWarning::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<JniResponse> for BridgeResponse {
+    fn from(response: JniResponse) -> Self {
+        Self {
+            success: response.success,
+            device_id: response.device_id,
+            device_no: response.device_no,
+            msg_type: response.msg_type,
+            cmd_code: response.cmd_code,
+            req_hex: response.req_hex,
+            rsp_hex: response.rsp_hex,
+            rsp_hexes: response.rsp_hexes,
+            req_jsons: response.req_jsons.into_iter().map(Into::into).collect(),
+            rsp_jsons: response.rsp_jsons.into_iter().map(Into::into).collect(),
+            err_msg: response.err_msg,
+            error: response.error.map(Into::into),
+            trace_id: response.trace_id,
+            metrics: response.metrics.map(Into::into),
+            warnings: response.warnings.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+// --- protobuf 编解码 ---
+
+impl BridgeRequest {
+    /// 编码为 protobuf 字节
+    pub fn to_bytes_proto(&self) -> Vec<u8> {
+        JniRequest::from(self).encode_to_vec()
+    }
+
+    /// 从 protobuf 字节解码
+    pub fn from_bytes_proto(data: &[u8]) -> ProtocolResult<Self> {
+        JniRequest::decode(data)
+            .map(Into::into)
+            .map_err(ProtocolError::external)
+    }
+}
+
+impl BridgeResponse {
+    /// 编码为 protobuf 字节
+    pub fn to_bytes_proto(&self) -> Vec<u8> {
+        JniResponse::from(self).encode_to_vec()
+    }
+
+    /// 从 protobuf 字节解码
+    pub fn from_bytes_proto(data: &[u8]) -> ProtocolResult<Self> {
+        JniResponse::decode(data)
+            .map(Into::into)
+            .map_err(ProtocolError::external)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JniError as BridgeError;
+
+    #[test]
+    fn test_proto_round_trip() {
+        let mut response = BridgeResponse::new_with_err_msg("0001", "A1", "");
+        response.set_rsp_jsons(vec![bridge::ReportField::new(
+            "电压",
+            "dianya",
+            "3.6".into(),
+        )]);
+        response.set_trace_id("trace-1");
+        response.set_error(BridgeError::new("CRC_ERROR", "crc mismatch").with_field("crc", 30));
+        let decoded = BridgeResponse::from_bytes_proto(&response.to_bytes_proto()).unwrap();
+        assert_eq!(decoded.rsp_jsons(), response.rsp_jsons());
+        assert_eq!(decoded.error(), response.error());
+        assert_eq!(decoded.device_no(), Some("0001"));
+        assert_eq!(decoded.trace_id(), Some("trace-1"));
+    }
+}
@@ -10,7 +10,9 @@
 
 use aes::Aes128;
 use aes::cipher::{BlockDecrypt, BlockEncrypt, KeyInit, generic_array::GenericArray};
-use rand::RngCore;
+
+use crate::core::metrics;
+pub use crate::defi::error::aes_error::AesError;
 
 /// AES操作模式枚举
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -48,9 +50,9 @@ impl AesCipher {
     ///
     /// # 返回
     /// 成功时返回AesCipher实例，失败时返回错误信息
-    pub fn new(key: &[u8], mode: AesMode) -> Result<Self, &'static str> {
+    pub fn new(key: &[u8], mode: AesMode) -> Result<Self, AesError> {
         if key.len() != 16 {
-            return Err("Key must be 16 bytes for AES-128");
+            return Err(AesError::InvalidKeyLength { actual: key.len() });
         }
 
         let key_array = GenericArray::from_slice(key);
@@ -72,12 +74,12 @@ impl AesCipher {
     ///
     /// # 返回
     /// 成功时返回加密后的数据，失败时返回错误信息
-    pub fn encrypt(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, &'static str> {
+    pub fn encrypt(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, AesError> {
         if data.is_empty() {
             return Ok(Vec::new());
         }
 
-        match self.mode {
+        let result = match self.mode {
             AesMode::ECB => self.encrypt_ecb(data),
             AesMode::CBC => self.encrypt_cbc(data, iv),
             AesMode::CFB => self.encrypt_cfb(data, iv),
@@ -85,7 +87,11 @@ impl AesCipher {
             AesMode::OFB => self.encrypt_ofb(data, iv),
             AesMode::CTS => self.encrypt_cts(data, iv),
             AesMode::NONE => self.encrypt_none(data),
+        };
+        if result.is_err() {
+            metrics::cipher_failed("encrypt", &format!("{:?}", self.mode));
         }
+        result
     }
 
     /// 解密数据
@@ -96,12 +102,12 @@ impl AesCipher {
     ///
     /// # 返回
     /// 成功时返回解密后的数据，失败时返回错误信息
-    pub fn decrypt(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, &'static str> {
+    pub fn decrypt(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, AesError> {
         if data.is_empty() {
             return Ok(Vec::new());
         }
 
-        match self.mode {
+        let result = match self.mode {
             AesMode::ECB => self.decrypt_ecb(data),
             AesMode::CBC => self.decrypt_cbc(data, iv),
             AesMode::CFB => self.decrypt_cfb(data, iv),
@@ -109,12 +115,16 @@ impl AesCipher {
             AesMode::OFB => self.decrypt_ofb(data, iv),
             AesMode::CTS => self.decrypt_cts(data, iv),
             AesMode::NONE => self.decrypt_none(data),
+        };
+        if result.is_err() {
+            metrics::cipher_failed("decrypt", &format!("{:?}", self.mode));
         }
+        result
     }
 
     // ECB模式加密
-    fn encrypt_ecb(&self, data: &[u8]) -> Result<Vec<u8>, &'static str> {
-        let padded_data = self.pkcs7_pad(data);
+    fn encrypt_ecb(&self, data: &[u8]) -> Result<Vec<u8>, AesError> {
+        let padded_data = pkcs7_pad(data);
         let mut result = Vec::with_capacity(padded_data.len());
 
         for chunk in padded_data.chunks(16) {
@@ -127,9 +137,9 @@ impl AesCipher {
     }
 
     // ECB模式解密
-    fn decrypt_ecb(&self, data: &[u8]) -> Result<Vec<u8>, &'static str> {
+    fn decrypt_ecb(&self, data: &[u8]) -> Result<Vec<u8>, AesError> {
         if !data.len().is_multiple_of(16) {
-            return Err("Data length must be multiple of 16 bytes");
+            return Err(AesError::UnalignedData { actual: data.len() });
         }
 
         let mut result = Vec::with_capacity(data.len());
@@ -140,16 +150,16 @@ impl AesCipher {
             result.extend_from_slice(&block);
         }
 
-        self.pkcs7_unpad(&result)
+        pkcs7_unpad(&result)
     }
 
     // CBC模式加密
-    fn encrypt_cbc(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, &'static str> {
+    fn encrypt_cbc(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, AesError> {
         if iv.len() != 16 {
-            return Err("IV must be 16 bytes");
+            return Err(AesError::InvalidIvLength { actual: iv.len() });
         }
 
-        let padded_data = self.pkcs7_pad(data);
+        let padded_data = pkcs7_pad(data);
         let mut result = Vec::with_capacity(padded_data.len());
         let mut prev_block = GenericArray::clone_from_slice(iv);
 
@@ -170,12 +180,12 @@ impl AesCipher {
     }
 
     // CBC模式解密
-    fn decrypt_cbc(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, &'static str> {
+    fn decrypt_cbc(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, AesError> {
         if iv.len() != 16 {
-            return Err("IV must be 16 bytes");
+            return Err(AesError::InvalidIvLength { actual: iv.len() });
         }
         if !data.len().is_multiple_of(16) {
-            return Err("Data length must be multiple of 16 bytes");
+            return Err(AesError::UnalignedData { actual: data.len() });
         }
 
         let mut result = Vec::with_capacity(data.len());
@@ -196,13 +206,13 @@ impl AesCipher {
             prev_block = current_block;
         }
 
-        self.pkcs7_unpad(&result)
+        pkcs7_unpad(&result)
     }
 
     // CFB模式加密
-    fn encrypt_cfb(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, &'static str> {
+    fn encrypt_cfb(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, AesError> {
         if iv.len() != 16 {
-            return Err("IV must be 16 bytes");
+            return Err(AesError::InvalidIvLength { actual: iv.len() });
         }
 
         let mut result = Vec::with_capacity(data.len());
@@ -232,9 +242,9 @@ impl AesCipher {
     }
 
     // CFB模式解密
-    fn decrypt_cfb(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, &'static str> {
+    fn decrypt_cfb(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, AesError> {
         if iv.len() != 16 {
-            return Err("IV must be 16 bytes");
+            return Err(AesError::InvalidIvLength { actual: iv.len() });
         }
 
         let mut result = Vec::with_capacity(data.len());
@@ -264,9 +274,9 @@ impl AesCipher {
     }
 
     // CTR模式加密
-    fn encrypt_ctr(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, &'static str> {
+    fn encrypt_ctr(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, AesError> {
         if iv.len() != 16 {
-            return Err("IV must be 16 bytes");
+            return Err(AesError::InvalidIvLength { actual: iv.len() });
         }
 
         let mut result = Vec::with_capacity(data.len());
@@ -288,15 +298,15 @@ impl AesCipher {
     }
 
     // CTR模式解密
-    fn decrypt_ctr(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, &'static str> {
+    fn decrypt_ctr(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, AesError> {
         // CTR模式加密解密相同
         self.encrypt_ctr(data, iv)
     }
 
     // OFB模式加密
-    fn encrypt_ofb(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, &'static str> {
+    fn encrypt_ofb(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, AesError> {
         if iv.len() != 16 {
-            return Err("IV must be 16 bytes");
+            return Err(AesError::InvalidIvLength { actual: iv.len() });
         }
 
         let mut result = Vec::with_capacity(data.len());
@@ -316,22 +326,22 @@ impl AesCipher {
     }
 
     // OFB模式解密
-    fn decrypt_ofb(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, &'static str> {
+    fn decrypt_ofb(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, AesError> {
         // OFB模式加密解密相同
         self.encrypt_ofb(data, iv)
     }
 
     // CTS模式加密
-    fn encrypt_cts(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, &'static str> {
+    fn encrypt_cts(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, AesError> {
         if iv.len() != 16 {
-            return Err("IV must be 16 bytes");
+            return Err(AesError::InvalidIvLength { actual: iv.len() });
         }
 
         let block_size = 16;
         let data_len = data.len();
 
         if data_len < block_size {
-            return Err("Data must be at least one block for CTS mode");
+            return Err(AesError::DataTooShort { actual: data.len() });
         }
 
         let full_blocks = data_len / block_size;
@@ -375,16 +385,16 @@ impl AesCipher {
     }
 
     // CTS模式解密
-    fn decrypt_cts(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, &'static str> {
+    fn decrypt_cts(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, AesError> {
         if iv.len() != 16 {
-            return Err("IV must be 16 bytes");
+            return Err(AesError::InvalidIvLength { actual: iv.len() });
         }
 
         let block_size = 16;
         let data_len = data.len();
 
         if data_len < block_size {
-            return Err("Data must be at least one block for CTS mode");
+            return Err(AesError::DataTooShort { actual: data.len() });
         }
 
         let full_blocks = data_len / block_size;
@@ -426,58 +436,64 @@ impl AesCipher {
     }
 
     // NONE模式 - 直接返回数据（无加密）
-    fn encrypt_none(&self, data: &[u8]) -> Result<Vec<u8>, &'static str> {
+    fn encrypt_none(&self, data: &[u8]) -> Result<Vec<u8>, AesError> {
         Ok(data.to_vec())
     }
 
     // NONE模式解密
-    fn decrypt_none(&self, data: &[u8]) -> Result<Vec<u8>, &'static str> {
+    fn decrypt_none(&self, data: &[u8]) -> Result<Vec<u8>, AesError> {
         Ok(data.to_vec())
     }
+}
 
-    // PKCS7填充
-    fn pkcs7_pad(&self, data: &[u8]) -> Vec<u8> {
-        let block_size = 16;
-        let padding_len = block_size - (data.len() % block_size);
-        let padding_byte = padding_len as u8;
+/// PKCS#7 填充到 16 字节的整数倍
+pub fn pkcs7_pad(data: &[u8]) -> Vec<u8> {
+    let block_size = 16;
+    let padding_len = block_size - (data.len() % block_size);
+    let padding_byte = padding_len as u8;
+
+    let mut padded = data.to_vec();
+    padded.resize(data.len() + padding_len, padding_byte);
+    padded
+}
 
-        let mut padded = data.to_vec();
-        padded.resize(data.len() + padding_len, padding_byte);
-        padded
+/// 去除 PKCS#7 填充。填充长度为 0、超过 16 或超过数据长度、填充字节不一致时报 `InvalidPadding`
+pub fn pkcs7_unpad(data: &[u8]) -> Result<Vec<u8>, AesError> {
+    if data.is_empty() {
+        return Ok(vec![]);
     }
 
-    // PKCS7去除填充
-    fn pkcs7_unpad(&self, data: &[u8]) -> Result<Vec<u8>, &'static str> {
-        if data.is_empty() {
-            return Ok(vec![]);
-        }
+    let padding_byte = data[data.len() - 1];
+    let padding_len = padding_byte as usize;
 
-        let padding_byte = data[data.len() - 1];
-        let padding_len = padding_byte as usize;
+    if padding_len == 0 || padding_len > 16 || padding_len > data.len() {
+        return Err(AesError::InvalidPadding);
+    }
 
-        if padding_len == 0 || padding_len > 16 {
-            return Err("Invalid padding");
+    // Verify padding bytes
+    for &byte in &data[data.len() - padding_len..] {
+        if byte != padding_byte {
+            return Err(AesError::InvalidPadding);
         }
-
-        // Verify padding bytes
-        for &byte in &data[data.len() - padding_len..] {
-            if byte != padding_byte {
-                return Err("Invalid padding");
-            }
-        }
-
-        Ok(data[..data.len() - padding_len].to_vec())
     }
+
+    Ok(data[..data.len() - padding_len].to_vec())
 }
 
 /// 生成随机的16字节初始化向量(IV)
 ///
 /// # 返回
-/// 16字节的随机IV数组
+/// 16字节的随机IV数组，来自操作系统随机源 (测试时可通过 `utils::seed_rand` 固定)。
+/// 随机源不可用时 panic，需要处理该错误时使用 `try_generate_iv`
 pub fn generate_iv() -> [u8; 16] {
+    try_generate_iv().expect("OS random source unavailable")
+}
+
+/// 同 `generate_iv`，随机源不可用时返回错误
+pub fn try_generate_iv() -> crate::ProtocolResult<[u8; 16]> {
     let mut iv = [0u8; 16];
-    rand::rng().fill_bytes(&mut iv);
-    iv
+    crate::utils::fill_rand_bytes(&mut iv)?;
+    Ok(iv)
 }
 
 /// 将字节数据转换为十六进制字符串
@@ -503,16 +519,29 @@ pub fn from_hex(hex_str: &str) -> Result<Vec<u8>, hex::FromHexError> {
 }
 
 /// 便捷函数：创建ECB模式的AES加密器
-pub fn new_ecb_cipher(key: &[u8]) -> Result<AesCipher, &'static str> {
+pub fn new_ecb_cipher(key: &[u8]) -> Result<AesCipher, AesError> {
     AesCipher::new(key, AesMode::ECB)
 }
 
 /// 便捷函数：创建CBC模式的AES加密器
-pub fn new_cbc_cipher(key: &[u8]) -> Result<AesCipher, &'static str> {
+pub fn new_cbc_cipher(key: &[u8]) -> Result<AesCipher, AesError> {
     AesCipher::new(key, AesMode::CBC)
 }
 
 /// 便捷函数：创建CTR模式的AES加密器
-pub fn new_ctr_cipher(key: &[u8]) -> Result<AesCipher, &'static str> {
+pub fn new_ctr_cipher(key: &[u8]) -> Result<AesCipher, AesError> {
     AesCipher::new(key, AesMode::CTR)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pkcs7_unpad() {
+        assert_eq!(pkcs7_unpad(&pkcs7_pad(b"abc")).unwrap(), b"abc");
+        // 填充长度超过数据长度
+        assert!(pkcs7_unpad(&[0x05]).is_err());
+        assert!(pkcs7_unpad(&[0x01, 0x00]).is_err());
+    }
+}
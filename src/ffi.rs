@@ -0,0 +1,228 @@
+//! C FFI 接口，供 C/C++ 采集程序直接嵌入本库。头文件见 `include/protocol_core.h` (由 cbindgen 生成)。
+//!
+//! 请求与返回都是 JSON 编码的 `JniRequest` / `JniResponse`。
+//! 所有权约定：
+//! - 入参 `request` 由调用方持有，本库只在调用期间读取；
+//! - 出参 `out` 中的缓冲区由本库分配，调用方使用完后必须调用 `protocol_buffer_free` 释放，且只能释放一次。
+//!
+//! 具体协议需要在启动时通过 `register_ffi_handlers` 注册上行解码/下行编码的处理函数。
+//! 以 C 动态库形式构建：`cargo rustc --release --features ffi --crate-type cdylib`
+
+use std::{panic, ptr, slice, time::Instant};
+
+use once_cell::sync::OnceCell;
+
+use crate::defi::{
+    bridge::{JniRequest, JniResponse},
+    error::ProtocolError,
+};
+
+/// 成功
+pub const PROTOCOL_OK: i32 = 0;
+/// 入参为空指针
+pub const PROTOCOL_ERR_NULL_POINTER: i32 = -1;
+/// 请求不是合法的 JniRequest JSON
+pub const PROTOCOL_ERR_INVALID_REQUEST: i32 = -2;
+/// 没有注册处理函数
+pub const PROTOCOL_ERR_NO_HANDLER: i32 = -3;
+/// 处理过程中发生 panic
+pub const PROTOCOL_ERR_PANIC: i32 = -4;
+/// 返回的 JniResponse 序列化失败
+pub const PROTOCOL_ERR_SERIALIZE: i32 = -5;
+
+/// 由本库分配、交给调用方的字节缓冲区
+#[repr(C)]
+pub struct ProtocolBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl ProtocolBuffer {
+    fn empty() -> Self {
+        Self {
+            data: ptr::null_mut(),
+            len: 0,
+        }
+    }
+
+    fn from_vec(bytes: Vec<u8>) -> Self {
+        let boxed = bytes.into_boxed_slice();
+        let len = boxed.len();
+        Self {
+            data: Box::into_raw(boxed) as *mut u8,
+            len,
+        }
+    }
+}
+
+/// 协议处理函数
+pub type FfiHandler = fn(JniRequest) -> JniResponse;
+
+struct FfiHandlers {
+    decode: FfiHandler,
+    encode: FfiHandler,
+}
+
+static FFI_HANDLERS: OnceCell<FfiHandlers> = OnceCell::new();
+
+/// 注册上行解码与下行编码的处理函数，只能注册一次
+pub fn register_ffi_handlers(decode: FfiHandler, encode: FfiHandler) -> crate::ProtocolResult<()> {
+    FFI_HANDLERS
+        .set(FfiHandlers { decode, encode })
+        .map_err(|_| ProtocolError::CommonError("ffi handlers already registered".into()))
+}
+
+// 已注册的 (解码, 编码) 处理函数，UniFFI 绑定也使用同一组
+#[cfg(feature = "uniffi")]
+pub(crate) fn registered_handlers() -> Option<(FfiHandler, FfiHandler)> {
+    FFI_HANDLERS
+        .get()
+        .map(|handlers| (handlers.decode, handlers.encode))
+}
+
+// 公共流程：解析请求 -> 调用处理函数 -> 写出返回。
+// 失败时 (空指针除外) 也会尽量写出一个带错误信息的 JniResponse。
+// 只要 out 非空，返回前一定会写入 (至少是空缓冲区)，调用方不会读到未初始化的内容。
+fn _call(
+    request: *const u8,
+    request_len: usize,
+    out: *mut ProtocolBuffer,
+    select: fn(&FfiHandlers) -> FfiHandler,
+) -> i32 {
+    if out.is_null() {
+        return PROTOCOL_ERR_NULL_POINTER;
+    }
+    // SAFETY: out 已检查非空，由调用方提供可写的 ProtocolBuffer
+    unsafe { out.write(ProtocolBuffer::empty()) };
+    if request.is_null() {
+        return PROTOCOL_ERR_NULL_POINTER;
+    }
+    // SAFETY: 调用方保证 request 指向 request_len 个可读字节，在调用期间有效
+    let data = unsafe { slice::from_raw_parts(request, request_len) };
+    let result = panic::catch_unwind(|| {
+        let request = match JniRequest::from(data) {
+            Ok(request) => request,
+            Err(e) => {
+                return (
+                    PROTOCOL_ERR_INVALID_REQUEST,
+                    JniResponse::new_with_error("", "", &e),
+                );
+            }
+        };
+        #[cfg(feature = "tracing")]
+        let _span = request.span().entered();
+        let trace_id = request.trace_id.clone();
+        let started = Instant::now();
+        let (code, mut response) = match FFI_HANDLERS.get() {
+            Some(handlers) => (PROTOCOL_OK, select(handlers)(request)),
+            None => {
+                let err = ProtocolError::CommonError("no ffi handler registered".into());
+                let response = JniResponse::new_with_error(
+                    request.device_no().unwrap_or_default(),
+                    request.cmd_code().unwrap_or_default(),
+                    &err,
+                );
+                (PROTOCOL_ERR_NO_HANDLER, response)
+            }
+        };
+        response.record_duration(started.elapsed());
+        response.inherit_trace_id(trace_id);
+        (code, response)
+    });
+    let (code, response) = match result {
+        Ok(result) => result,
+        Err(_) => return PROTOCOL_ERR_PANIC,
+    };
+    match response.to_bytes() {
+        Ok(bytes) => {
+            // SAFETY: 同上；之前写入的是空缓冲区，无需释放
+            unsafe { out.write(ProtocolBuffer::from_vec(bytes)) };
+            code
+        }
+        Err(_) => PROTOCOL_ERR_SERIALIZE,
+    }
+}
+
+/// 上行解码。`request` 为 JniRequest JSON，结果 (JniResponse JSON) 写入 `out`。
+///
+/// # Safety
+/// `request` 必须指向 `request_len` 个可读字节；`out` 必须指向可写的 `ProtocolBuffer`。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn protocol_decode(
+    request: *const u8,
+    request_len: usize,
+    out: *mut ProtocolBuffer,
+) -> i32 {
+    _call(request, request_len, out, |handlers| handlers.decode)
+}
+
+/// 下行编码。参数与返回值同 `protocol_decode`。
+///
+/// # Safety
+/// 同 `protocol_decode`。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn protocol_encode(
+    request: *const u8,
+    request_len: usize,
+    out: *mut ProtocolBuffer,
+) -> i32 {
+    _call(request, request_len, out, |handlers| handlers.encode)
+}
+
+/// 释放本库返回的缓冲区。传入空缓冲区是安全的。
+///
+/// # Safety
+/// `buffer` 必须来自 `protocol_decode` / `protocol_encode`，且未被释放过。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn protocol_buffer_free(buffer: ProtocolBuffer) {
+    if buffer.data.is_null() {
+        return;
+    }
+    // SAFETY: data/len 来自 ProtocolBuffer::from_vec 中的 Box<[u8]>
+    drop(unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(buffer.data, buffer.len)) });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn echo(request: JniRequest) -> JniResponse {
+        let mut response = JniResponse::new_with_err_msg(
+            request.device_no().unwrap_or_default(),
+            request.cmd_code().unwrap_or_default(),
+            "",
+        );
+        response.set_success(true);
+        response.set_rsp_hex(request.hex());
+        response
+    }
+
+    #[test]
+    fn test_ffi_round_trip() {
+        let _ = register_ffi_handlers(echo, echo);
+        let request = br#"{"deviceNo":"0001","hex":"68AA16"}"#;
+        let mut out = ProtocolBuffer::empty();
+        let code = unsafe { protocol_decode(request.as_ptr(), request.len(), &mut out) };
+        assert_eq!(code, PROTOCOL_OK);
+        let bytes = unsafe { slice::from_raw_parts(out.data, out.len) };
+        let response = JniResponse::from(bytes).unwrap();
+        assert_eq!(response.rsp_hex(), "68AA16");
+        unsafe { protocol_buffer_free(out) };
+
+        let mut out = ProtocolBuffer::empty();
+        let code = unsafe { protocol_encode(b"not json".as_ptr(), 8, &mut out) };
+        assert_eq!(code, PROTOCOL_ERR_INVALID_REQUEST);
+        assert!(!out.data.is_null());
+        unsafe { protocol_buffer_free(out) };
+
+        // 调用方未初始化 out 时，空指针请求也要写回空缓冲区
+        let mut out = ProtocolBuffer {
+            data: ptr::dangling_mut(),
+            len: 3,
+        };
+        let code = unsafe { protocol_decode(ptr::null(), 0, &mut out) };
+        assert_eq!(code, PROTOCOL_ERR_NULL_POINTER);
+        assert!(out.data.is_null());
+        assert_eq!(out.len, 0);
+    }
+}
@@ -1,24 +1,55 @@
+//! 关闭默认的 `std` feature 后为 `no_std + alloc`：只保留编解码核心 (`Reader` / `Writer` / `FieldType` /
+//! `hex_util` / `crc_util` / `math_util`)、字段定义 trait 与错误类型，嵌入式固件可以复用同一套字段定义。
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+// 让派生宏生成的 `::protocol_core::...` 路径在本 crate 内部同样可用
+extern crate self as protocol_core;
+
+mod collections;
 pub mod core;
 pub mod defi;
+#[cfg(feature = "std")]
 pub mod digester;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "uniffi")]
+pub mod mobile;
+#[cfg(feature = "test-support")]
+pub mod test_support;
 pub mod utils;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+// no_std 下补齐标准库 prelude 中的 alloc 类型
+#[cfg(not(feature = "std"))]
+mod prelude {
+    pub use alloc::{
+        boxed::Box,
+        format,
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+}
 
 pub use crate::core::{
-    DirectionEnum, MsgTypeEnum, Symbol,
-    cache::ProtocolCache,
+    DirectionEnum, MsgTypeEnum, RW, Symbol,
+    framing::{DEFAULT_MAX_FRAME_LEN, FrameScan, scan_frame},
     parts::{
+        param_descriptor::ParamDescriptor,
+        param_value::{EncodingInput, ParamValue},
         placeholder::PlaceHolder,
-        raw_capsule::RawCapsule,
-        raw_chamber::RawChamber,
         rawfield::Rawfield,
         traits::{
-            AutoDecoding, AutoDecodingParam, AutoEncoding, AutoEncodingParam, Cmd, ProtocolConfig,
-            Transport,
+            AutoDecoding, AutoDecodingParam, AutoEncoding, AutoEncodingParam, Cmd, DefaultProvider,
+            FieldCondition, PadSide, ProtocolConfig, RepeatSpec, ValidationRule,
         },
-        transport_carrier::TransportCarrier,
-        transport_pair::TransportPair,
     },
     reader::Reader,
+    tlv::{TlvFormat, TlvReader, TlvWriter},
     type_converter::{
         FieldCompareDecoder, FieldConvertDecoder, FieldEnumDecoder, FieldTranslator, FieldType,
         TryFromBytes,
@@ -27,15 +58,97 @@ pub use crate::core::{
 };
 pub use crate::defi::{
     ProtocolResult,
-    bridge::{
-        /* JarDecodeResponse, JarEncodeRequest, JarEncodeResponse, */ JniRequest, JniResponse,
-        ReportField,
-    },
     crc_enum::CrcType,
     error::{
-        ProtocolError, comm_error::CommError, hex_digest_error::HexDigestError, hex_error::HexError,
+        AesError, ErrorContext, Locale, ProtocolError, ProtocolOutcome, ProtocolWarning, Severity,
+        comm_error::CommError, hex_digest_error::HexDigestError, hex_error::HexError, set_locale,
     },
 };
-pub use crate::utils::{crc_util, generate_rand, hex_util, math_util, timestamp_util, to_pinyin};
+pub use crate::utils::{crc_util, hex_util, math_util};
 
+#[cfg(feature = "std")]
+pub use crate::core::codegen;
+#[cfg(feature = "std")]
+pub use crate::core::metrics;
+#[cfg(feature = "std")]
+pub use crate::core::metrics::{MetricsRecorder, set_recorder};
+#[cfg(feature = "std")]
+pub use crate::core::{
+    conformance::{
+        ConformanceReport, ConformanceSuite, ConformanceTarget, FieldDiff, TestVector, VectorResult,
+    },
+    parts::{
+        alert_rules::{AlertRule, AlertRules},
+        cipher_spec::{CipherSpec, IvStrategy},
+        cmd_registry::CmdRegistry,
+        raw_capsule::{DownstreamBuilder, RawCapsule},
+        raw_capsule_ref::RawCapsuleRef,
+        raw_chamber::RawChamber,
+        traits::Transport,
+        transport_carrier::{TransportCarrier, TransportCarrierBuilder, TransportField},
+        transport_pair::{PairInput, TransportCounter, TransportPair},
+    },
+    pipeline::{decode_upstream, decode_upstream_lenient, encode_downstream},
+    protocol_schema::{CmdSchema, EnvelopeSchema, FieldKind, FieldSchema, ProtocolSchema},
+    replay::{
+        CaptureOutcome, CaptureReader, CaptureRecord, CaptureWriter, ReplayMismatch, ReplayReport,
+        replay_capture,
+    },
+    simulator::{DeviceSimulator, ValueRange},
+    template::{FrameTemplate, TemplateSegment},
+};
+#[cfg(feature = "std")]
+pub use crate::defi::bridge::{
+    BridgeMessage, JarDecodeResponse, JarEncodeRequest, JarEncodeResponse, JniError, JniMetrics,
+    JniRequest, JniRequestRef, JniResponse, ReportField, WireFormat,
+};
+#[cfg(feature = "std")]
+pub use crate::utils::{
+    RandCharset, buffer_pool, buffer_pool::BufferPoolMetrics, fill_rand_bytes, generate_rand,
+    generate_rand_with_charset, generate_secure_bytes, generate_secure_rand, pinyin_util,
+    sequence_util, timestamp_util, to_pinyin,
+};
+// 固定随机种子只用于测试，生产构建中不提供
+#[cfg(any(test, feature = "test-support"))]
+pub use crate::utils::{clear_rand_seed, seed_rand};
+
+#[cfg(feature = "std")]
 pub use crate::digester::{aes_digester, md5_digester};
+
+// 派生宏生成的代码使用的类型，std 与 no_std 下路径一致
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::collections::HashMap;
+    pub use alloc::{string::String, vec, vec::Vec};
+}
+
+#[cfg(feature = "derive")]
+pub use protocol_core_derive::{AutoEncoding, AutoEncodingParam};
+
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
+
+#[cfg(feature = "cache")]
+pub use crate::core::cache::{
+    CacheEvictionPolicy, CacheMetrics, CacheSnapshotEntry, DeviceCache, DeviceCacheBuilder,
+    ProtocolCache,
+};
+#[cfg(feature = "cache")]
+pub use crate::core::dedup::{DedupKey, DedupWindow};
+#[cfg(feature = "rayon")]
+pub use crate::core::pipeline::decode_batch;
+#[cfg(feature = "cache")]
+pub use crate::core::pipeline::decode_upstream_dedup;
+#[cfg(feature = "cache")]
+pub use crate::core::session::{PendingCommand, SessionConfig, SessionEvent, SessionManager};
+
+#[cfg(feature = "async")]
+pub use crate::core::async_cache::AsyncDeviceCache;
+#[cfg(feature = "codec")]
+pub use crate::core::codec::ProtocolCodec;
+#[cfg(feature = "serial")]
+pub use crate::core::serial::{SerialFrameReader, silence_interval};
+#[cfg(feature = "async")]
+pub use crate::defi::bridge_handler::{BridgeDispatcher, BridgeHandler};
+#[cfg(feature = "grpc")]
+pub use crate::defi::grpc::ProtocolGrpcService;
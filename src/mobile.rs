@@ -0,0 +1,69 @@
+//! UniFFI 绑定，供 Android / iOS 调试 App 复用与服务端完全相同的解析逻辑。
+//!
+//! 桥接类型 (`JniRequest` / `JniResponse` / `ReportField` / `JniError`) 在开启 `uniffi` feature 时
+//! 直接派生为 UniFFI Record；处理函数与 C 接口共用，通过 `ffi::register_ffi_handlers` 注册。
+//! 生成绑定：先以 cdylib 构建本库，再执行
+//! `cargo run --features uniffi-cli --bin uniffi-bindgen generate --library <libprotocol_core.so> --language kotlin`
+
+use std::time::Instant;
+
+use rust_decimal::Decimal;
+
+use crate::{
+    defi::{
+        bridge::{JniRequest, JniResponse},
+        error::ProtocolError,
+    },
+    ffi::{FfiHandler, registered_handlers},
+};
+
+// ReportField::numeric_value 在 Kotlin / Swift 侧以字符串表示，避免精度损失
+uniffi::custom_type!(Decimal, String);
+
+impl crate::UniffiCustomTypeConverter for Decimal {
+    type Builtin = String;
+
+    fn into_custom(val: Self::Builtin) -> uniffi::Result<Self> {
+        Ok(val.parse()?)
+    }
+
+    fn from_custom(obj: Self) -> Self::Builtin {
+        obj.to_string()
+    }
+}
+
+fn _call(request: JniRequest, select: fn((FfiHandler, FfiHandler)) -> FfiHandler) -> JniResponse {
+    #[cfg(feature = "tracing")]
+    let _span = request.span().entered();
+    let trace_id = request.trace_id.clone();
+    let started = Instant::now();
+    let mut response = match registered_handlers() {
+        Some(handlers) => select(handlers)(request),
+        None => JniResponse::new_with_error(
+            request.device_no().unwrap_or_default(),
+            request.cmd_code().unwrap_or_default(),
+            &ProtocolError::CommonError("no handler registered".into()),
+        ),
+    };
+    response.record_duration(started.elapsed());
+    response.inherit_trace_id(trace_id);
+    response
+}
+
+/// 上行解码
+#[uniffi::export]
+pub fn decode(request: JniRequest) -> JniResponse {
+    _call(request, |(decode, _)| decode)
+}
+
+/// 下行编码
+#[uniffi::export]
+pub fn encode(request: JniRequest) -> JniResponse {
+    _call(request, |(_, encode)| encode)
+}
+
+/// 将报文字段标题转换为拼音 code (与 ReportField::code 规则一致)
+#[uniffi::export]
+pub fn field_code(title: String) -> String {
+    crate::utils::to_pinyin(&title)
+}
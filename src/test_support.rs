@@ -0,0 +1,272 @@
+//! 编解码对称性的属性测试 (proptest)。
+//!
+//! `field_value` 按 `FieldType` 生成随机的输入值；`assert_round_trip` 用 `AutoEncodingParam::to_bytes`
+//! 与 `Writer` 编码，再用 `FieldConvertDecoder` (`FieldTranslator`) 与 `Reader` 解码，断言解码结果与输入相等。
+//! 协议 crate 在 dev-dependencies 中开启 `test-support`，几行即可检查所有下行参数：
+//!
+//! ```ignore
+//! #[test]
+//! fn params_round_trip() {
+//!     protocol_core::test_support::check_round_trip(&MeterParams::Price).unwrap();
+//! }
+//! ```
+//!
+//! 数值字段按数值比较 (`"1.50"` 与 `"1.5"` 相等)，Hex 字段不区分大小写，其余按字符串比较。
+//! 整数字段的 `byte_length` 与类型宽度不一致 (例如 3 字节的 U32) 时，编码后无法按同一类型解码，会被报告为不对称。
+
+use core::str::FromStr;
+
+use proptest::collection::{SizeRange, vec};
+use proptest::prelude::*;
+use proptest::test_runner::{Config, TestError, TestRunner};
+use rust_decimal::Decimal;
+
+use crate::math_util::{self, DecimalRoundingMode};
+use crate::{
+    AutoEncoding, AutoEncodingParam, FieldConvertDecoder, FieldTranslator, FieldType, Reader,
+    ValidationRule, Writer,
+};
+
+// 生成输入时保留的小数位数 (Decimal 的上限)，保证输入恰好是 原始整数 * scale
+const INPUT_PRECISION: u32 = 28;
+// 变长 (byte_length = 0) 字段生成的最大字节数
+const MAX_VARIABLE_LEN: usize = 32;
+
+/// 按字段类型生成随机的输入值 (下行参数的字符串形式)。
+/// `byte_length` 为 0 时 Ascii / Hex 字段生成 1 ~ 32 字节，否则恰好 `byte_length` 字节 (不需要补齐)
+pub fn field_value(field_type: &FieldType, byte_length: usize) -> BoxedStrategy<String> {
+    match field_type {
+        FieldType::Empty => Just(String::new()).boxed(),
+        FieldType::StringOrBCD => vec(any::<u8>(), _size(byte_length))
+            .prop_map(hex::encode_upper)
+            .boxed(),
+        FieldType::Ascii => vec(0x20u8..=0x7E, _size(byte_length))
+            .prop_map(|bytes| bytes.into_iter().map(char::from).collect())
+            .boxed(),
+        FieldType::UnsignedU8(scale) => _int_value(0, u8::MAX as i128, *scale),
+        FieldType::UnsignedU16(scale) => _int_value(0, u16::MAX as i128, *scale),
+        FieldType::UnsignedU32(scale) => _int_value(0, u32::MAX as i128, *scale),
+        FieldType::UnsignedU64(scale) => _int_value(0, u64::MAX as i128, *scale),
+        FieldType::SignedI8(scale) => _int_value(i8::MIN as i128, i8::MAX as i128, *scale),
+        FieldType::SignedI16(scale) => _int_value(i16::MIN as i128, i16::MAX as i128, *scale),
+        FieldType::SignedI32(scale) => _int_value(i32::MIN as i128, i32::MAX as i128, *scale),
+        FieldType::SignedI64(scale) => _int_value(i64::MIN as i128, i64::MAX as i128, *scale),
+        // 只生成有限值，NaN / 无穷大没有下行的意义
+        FieldType::Float => {
+            (prop::num::f32::NORMAL | prop::num::f32::SUBNORMAL | prop::num::f32::ZERO)
+                .prop_map(|value| value.to_string())
+                .boxed()
+        }
+        FieldType::Double => {
+            (prop::num::f64::NORMAL | prop::num::f64::SUBNORMAL | prop::num::f64::ZERO)
+                .prop_map(|value| value.to_string())
+                .boxed()
+        }
+    }
+}
+
+/// 生成满足参数校验规则的输入值：有 `OneOf` 规则时从候选值中选取，其余规则过滤 `field_value` 的结果。
+/// `Pattern` 规则很难靠过滤满足，这类参数请自行提供策略并调用 `assert_round_trip`
+pub fn param_value<P: AutoEncodingParam + ?Sized>(param: &P) -> BoxedStrategy<String> {
+    let rules = param.rules();
+    let one_of = rules.iter().find_map(|rule| match rule {
+        ValidationRule::OneOf(values) if !values.is_empty() => Some(values.clone()),
+        _ => None,
+    });
+    let values = match one_of {
+        Some(values) => prop::sample::select(values).boxed(),
+        None => field_value(&param.field_type(), param.byte_length()),
+    };
+    values
+        .prop_filter("input violates validation rules", move |input| {
+            rules.iter().all(|rule| rule.check(input).unwrap_or(false))
+        })
+        .boxed()
+}
+
+/// 编码 `input` 后再解码，断言结果与 `input` 相等
+pub fn assert_round_trip<P: AutoEncodingParam + ?Sized>(
+    param: &P,
+    input: &str,
+) -> Result<(), TestCaseError> {
+    let code = param.code();
+    let title = param.title();
+    let field_type = param.field_type();
+    let bytes = param
+        .to_bytes(input)
+        .map_err(|e| TestCaseError::fail(format!("field '{}': encode '{}': {}", code, input, e)))?;
+
+    let mut writer = Writer::new();
+    writer
+        .write_bytes(&title, &bytes, input)
+        .map_err(|e| TestCaseError::fail(e.to_string()))?;
+    let buffer = writer
+        .buffer()
+        .map_err(|e| TestCaseError::fail(e.to_string()))?;
+
+    let decoder = FieldConvertDecoder::new(&title, field_type.clone(), None, param.swap());
+    let mut reader = Reader::new(buffer);
+    reader
+        .read_and_translate_remaining(|bytes| decoder.translate(bytes))
+        .map_err(|e| {
+            TestCaseError::fail(format!(
+                "field '{}': decode {}: {}",
+                code,
+                hex::encode_upper(&bytes),
+                e
+            ))
+        })?;
+    let decoded = reader.field_value(&title).unwrap_or_default();
+
+    prop_assert!(
+        _same_value(&field_type, input, decoded),
+        "field '{}' ({:?}): '{}' encoded as {} but decoded as '{}'",
+        code,
+        field_type,
+        input,
+        hex::encode_upper(&bytes),
+        decoded
+    );
+    Ok(())
+}
+
+/// 对单个参数运行属性测试，失败时返回 proptest 收缩后的最小输入
+pub fn check_param_round_trip<P: AutoEncodingParam + ?Sized>(
+    param: &P,
+) -> Result<(), TestError<String>> {
+    // 库内无法确定源文件位置，不持久化失败用例
+    let mut runner = TestRunner::new(Config {
+        failure_persistence: None,
+        ..Config::default()
+    });
+    runner.run(&param_value(param), |input| {
+        assert_round_trip(param, &input)
+    })
+}
+
+/// 对参数枚举的所有变体运行属性测试 (`FieldType::Empty` 的参数没有值，跳过)
+pub fn check_round_trip<P: AutoEncodingParam, E: AutoEncoding<P>>(
+    definition: &E,
+) -> Result<(), TestError<String>> {
+    for param in definition.variants() {
+        if param.field_type() != FieldType::Empty {
+            check_param_round_trip(&param)?;
+        }
+    }
+    Ok(())
+}
+
+fn _size(byte_length: usize) -> SizeRange {
+    if byte_length == 0 {
+        (1..=MAX_VARIABLE_LEN).into()
+    } else {
+        byte_length.into()
+    }
+}
+
+// 随机原始整数乘以 scale，即上行解码后应得到的值
+fn _int_value(min: i128, max: i128, scale: f64) -> BoxedStrategy<String> {
+    (min..=max)
+        .prop_map(move |raw| {
+            // scale 为 0 时无法生成，交给编码报错
+            math_util::scale_integer(raw, scale, INPUT_PRECISION, DecimalRoundingMode::HalfUp)
+                .map(|value| value.to_string())
+                .unwrap_or_else(|_| raw.to_string())
+        })
+        .boxed()
+}
+
+fn _same_value(field_type: &FieldType, input: &str, decoded: &str) -> bool {
+    match field_type {
+        FieldType::Empty => decoded.is_empty(),
+        FieldType::Ascii => input == decoded,
+        FieldType::StringOrBCD => input.eq_ignore_ascii_case(decoded),
+        // 单精度的极值超出 Decimal 的范围，按 f64 比较
+        FieldType::Float | FieldType::Double => {
+            matches!((input.parse::<f64>(), decoded.parse::<f64>()), (Ok(a), Ok(b)) if a == b)
+        }
+        _ => matches!(
+            (Decimal::from_str(input), Decimal::from_str(decoded)),
+            (Ok(a), Ok(b)) if a == b
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy)]
+    enum MeterParams {
+        Price,
+        Offset,
+        Total,
+        Ratio,
+        Energy,
+        Name,
+        Address,
+        Mode,
+        Address3,
+    }
+
+    impl AutoEncodingParam for MeterParams {
+        fn code(&self) -> String {
+            format!("{:?}", self)
+        }
+        fn title(&self) -> String {
+            self.code()
+        }
+        fn byte_length(&self) -> usize {
+            match self {
+                Self::Name => 8,
+                Self::Address => 6,
+                Self::Address3 => 3,
+                _ => 0,
+            }
+        }
+        fn field_type(&self) -> FieldType {
+            match self {
+                Self::Price => FieldType::UnsignedU16(0.01),
+                Self::Offset => FieldType::SignedI32(1.0),
+                Self::Total => FieldType::UnsignedU64(0.001),
+                Self::Ratio => FieldType::Float,
+                Self::Energy => FieldType::Double,
+                Self::Name => FieldType::Ascii,
+                Self::Address => FieldType::StringOrBCD,
+                Self::Mode => FieldType::UnsignedU8(1.0),
+                Self::Address3 => FieldType::UnsignedU32(1.0),
+            }
+        }
+        fn swap(&self) -> bool {
+            matches!(self, Self::Offset | Self::Address)
+        }
+        fn rules(&self) -> Vec<ValidationRule> {
+            match self {
+                Self::Price => vec![ValidationRule::Max(100.0)],
+                Self::Mode => vec![ValidationRule::OneOf(vec!["1".into(), "2".into()])],
+                _ => vec![],
+            }
+        }
+    }
+
+    struct Meter;
+
+    impl AutoEncoding<MeterParams> for Meter {
+        fn variants(&self) -> Vec<MeterParams> {
+            use MeterParams::*;
+            vec![Price, Offset, Total, Ratio, Energy, Name, Address, Mode]
+        }
+    }
+
+    #[test]
+    fn test_check_round_trip() {
+        check_round_trip(&Meter).unwrap();
+    }
+
+    #[test]
+    fn test_asymmetric_param() {
+        // 3 字节的 U32 编码后无法按 U32 解码
+        let error = check_param_round_trip(&MeterParams::Address3).unwrap_err();
+        assert!(error.to_string().contains("decode"));
+    }
+}
@@ -0,0 +1,181 @@
+//! 报文缓冲区 (`Vec<u8>`) 的复用池。
+//!
+//! `RawCapsule`、`Writer` 以及分帧 (`SerialFrameReader`、`ProtocolCodec`) 取出的帧都从池中获取缓冲区，
+//! 用完 (drop) 后归还，高吞吐时减少分配器的压力。每个线程各有一个池，取出、归还都不加锁，
+//! 并行解码 (`decode_batch`) 时线程之间不会争用；在其他线程 drop 的缓冲区归还到该线程的池。
+//! 池的容量和单个缓冲区的大小都有上限，超出上限的缓冲区直接释放，不会无限占用内存；
+//! 线程退出时它的池随之释放。命中率等指标是所有线程的合计，见 `metrics`。
+
+use std::{
+    cell::RefCell,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// 每个线程的池中最多保留的缓冲区个数
+pub const MAX_POOLED: usize = 256;
+/// 超过该容量 (字节) 的缓冲区不回收
+pub const MAX_RETAINED_CAPACITY: usize = 64 * 1024;
+
+static COUNTERS: PoolCounters = PoolCounters::new();
+
+thread_local! {
+    static POOL: RefCell<LocalPool> = const { RefCell::new(LocalPool(Vec::new())) };
+}
+
+// 线程的池，线程退出时从 pooled 计数中减去剩余的缓冲区
+struct LocalPool(Vec<Vec<u8>>);
+
+impl Drop for LocalPool {
+    fn drop(&mut self) {
+        COUNTERS
+            .pooled
+            .fetch_sub(self.0.len() as u64, Ordering::Relaxed);
+    }
+}
+
+/// 缓冲池指标快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BufferPoolMetrics {
+    /// 从池中取到缓冲区的次数
+    pub hits: u64,
+    /// 池为空、新分配的次数
+    pub misses: u64,
+    /// 归还到池中的次数
+    pub returned: u64,
+    /// 因池已满或容量过大而释放的次数
+    pub discarded: u64,
+    /// 当前所有线程的池中的缓冲区个数
+    pub pooled: u64,
+}
+
+impl BufferPoolMetrics {
+    /// 命中率 (0.0 ~ 1.0)，没有任何获取时返回 0
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+// (内部) 原子计数器
+struct PoolCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    returned: AtomicU64,
+    discarded: AtomicU64,
+    pooled: AtomicU64,
+}
+
+impl PoolCounters {
+    const fn new() -> Self {
+        Self {
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            returned: AtomicU64::new(0),
+            discarded: AtomicU64::new(0),
+            pooled: AtomicU64::new(0),
+        }
+    }
+}
+
+/// 取一个空的缓冲区，容量至少为 `capacity`
+pub(crate) fn take(capacity: usize) -> Vec<u8> {
+    // 线程退出过程中 (TLS 已销毁) 取不到池，按未命中处理
+    let pooled = POOL
+        .try_with(|pool| pool.borrow_mut().0.pop())
+        .ok()
+        .flatten();
+    match pooled {
+        Some(mut buffer) => {
+            COUNTERS.hits.fetch_add(1, Ordering::Relaxed);
+            COUNTERS.pooled.fetch_sub(1, Ordering::Relaxed);
+            buffer.reserve(capacity);
+            buffer
+        }
+        None => {
+            COUNTERS.misses.fetch_add(1, Ordering::Relaxed);
+            Vec::with_capacity(capacity)
+        }
+    }
+}
+
+/// 取一个缓冲区并复制 `bytes`，代替 `bytes.to_vec()`
+pub(crate) fn copy(bytes: &[u8]) -> Vec<u8> {
+    let mut buffer = take(bytes.len());
+    buffer.extend_from_slice(bytes);
+    buffer
+}
+
+/// 归还缓冲区。没有分配过内存的缓冲区直接忽略
+pub(crate) fn give(mut buffer: Vec<u8>) {
+    if buffer.capacity() == 0 {
+        return;
+    }
+    if buffer.capacity() <= MAX_RETAINED_CAPACITY {
+        buffer.clear();
+        let returned = POOL.try_with(|pool| {
+            let pool = &mut pool.borrow_mut().0;
+            if pool.len() < MAX_POOLED {
+                pool.push(buffer);
+                true
+            } else {
+                false
+            }
+        });
+        if returned == Ok(true) {
+            COUNTERS.returned.fetch_add(1, Ordering::Relaxed);
+            COUNTERS.pooled.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+    }
+    COUNTERS.discarded.fetch_add(1, Ordering::Relaxed);
+}
+
+/// 获取缓冲池指标快照
+pub fn metrics() -> BufferPoolMetrics {
+    BufferPoolMetrics {
+        hits: COUNTERS.hits.load(Ordering::Relaxed),
+        misses: COUNTERS.misses.load(Ordering::Relaxed),
+        returned: COUNTERS.returned.load(Ordering::Relaxed),
+        discarded: COUNTERS.discarded.load(Ordering::Relaxed),
+        pooled: COUNTERS.pooled.load(Ordering::Relaxed),
+    }
+}
+
+/// 清零缓冲池指标 (池中的缓冲区保留)
+pub fn reset_metrics() {
+    COUNTERS.hits.store(0, Ordering::Relaxed);
+    COUNTERS.misses.store(0, Ordering::Relaxed);
+    COUNTERS.returned.store(0, Ordering::Relaxed);
+    COUNTERS.discarded.store(0, Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_reuse() {
+        // 其他测试并行使用全局计数，只检查本测试造成的增量
+        let before = metrics();
+        let mut buffer = copy(&[0x68, 0x16]);
+        buffer.reserve(128);
+        let address = buffer.as_ptr();
+        give(buffer);
+        // 池是线程独占的，刚归还的缓冲区一定由本线程取回
+        let reused = take(4);
+        assert!(reused.is_empty() && reused.as_ptr() == address);
+        give(vec![0u8; MAX_RETAINED_CAPACITY + 1]);
+
+        let after = metrics();
+        assert!(after.hits + after.misses >= before.hits + before.misses + 2);
+        assert!(after.returned > before.returned);
+        assert!(after.discarded > before.discarded);
+    }
+}
@@ -1,5 +1,7 @@
 use rust_decimal::prelude::ToPrimitive;
 
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
 use crate::{
     defi::{
         ProtocolResult,
@@ -32,6 +34,24 @@ pub fn calculate_from_bytes_and_collect_hex_and_bytes(
     Ok((hex, crc_bytes.into()))
 }
 
+/// 按指定字节序比较报文中的 CRC 字节与计算值，另一种字节序视为不匹配
+pub fn compare_crc_ordered(crc_bytes: &[u8], crc: u16, little_endian: bool) -> ProtocolResult<()> {
+    let mut ordered = crc_bytes.to_vec();
+    if little_endian {
+        ordered.reverse();
+    }
+    let ori_crc = ordered.iter().fold(0u16, |acc, b| (acc << 8) | *b as u16);
+    if ordered.len() == 2 && ori_crc == crc {
+        Ok(())
+    } else {
+        Err(ProtocolError::CrcError {
+            ori_crc,
+            calc_crc: crc,
+        })
+    }
+}
+
+// 大端或小端都认为匹配
 pub fn compare_crc(crc1: &str, crc2: u16) -> ProtocolResult<()> {
     let crc1_u16 = hex_util::hex_to_u16(crc1)?;
     if crc1_u16 == crc2 {
@@ -2,7 +2,9 @@ use crate::defi::{
     ProtocolResult,
     error::{ProtocolError, hex_error::HexError},
 };
-use std::{fmt::LowerHex, mem::size_of}; // 引入 size_of
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use core::{fmt::LowerHex, mem::size_of}; // 引入 size_of
 
 // --- 核心转换 ---
 
@@ -14,6 +16,10 @@ pub fn hex_to_bytes(s: &str) -> ProtocolResult<Vec<u8>> {
         ProtocolError::HexError(HexError::HexParseError {
             context: "bytes",
             reason: e.to_string(),
+            #[cfg(feature = "std")]
+            source: Some(e),
+            #[cfg(not(feature = "std"))]
+            source: None,
         })
     })
 }
@@ -203,13 +209,13 @@ where
     let native_hex = format!("{:0width$x}", number, width = native_char_length).to_uppercase();
 
     match expected_char_length.cmp(&native_char_length) {
-        std::cmp::Ordering::Less => {
+        core::cmp::Ordering::Less => {
             // 截断
             let start_index = native_char_length - expected_char_length;
             Ok(native_hex[start_index..].to_string())
         }
-        std::cmp::Ordering::Equal => Ok(native_hex), // 长度相等
-        std::cmp::Ordering::Greater => {
+        core::cmp::Ordering::Equal => Ok(native_hex), // 长度相等
+        core::cmp::Ordering::Greater => {
             // 补位
             let padding_len = expected_char_length - native_char_length;
             // 使用 PartialOrd 和 Default 判断符号
@@ -346,13 +352,13 @@ fn _number_to_bits_internal(
     let native_len = native_width as usize;
 
     match expected_bit_length.cmp(&native_len) {
-        std::cmp::Ordering::Less => {
+        core::cmp::Ordering::Less => {
             // 截断
             let start_index = native_len - expected_bit_length;
             Ok(native_binary[start_index..].to_string())
         }
-        std::cmp::Ordering::Equal => Ok(native_binary), // 长度相等
-        std::cmp::Ordering::Greater => {
+        core::cmp::Ordering::Equal => Ok(native_binary), // 长度相等
+        core::cmp::Ordering::Greater => {
             // 补位 (零扩展)
             let padding_len = expected_bit_length - native_len;
             let mut padded_binary = String::with_capacity(expected_bit_length);
@@ -384,85 +390,53 @@ pub fn u16_to_binary_str(number: u16, expected_bit_length: usize) -> ProtocolRes
     _number_to_bits_internal(number as u64, 16, expected_bit_length)
 }
 
+fn _binary_parse_error(context: &'static str, e: core::num::ParseIntError) -> ProtocolError {
+    ProtocolError::HexError(HexError::BinaryParseError {
+        context,
+        reason: e.to_string(),
+        source: Some(e),
+    })
+}
+
 /// binary-string -> i64
 pub fn binary_str_to_i64(binary_str: &str) -> ProtocolResult<i64> {
     u64::from_str_radix(binary_str, 2)
         .map(|u| u as i64) // 按位重解释
-        .map_err(|e| {
-            ProtocolError::HexError(HexError::BinaryParseError {
-                context: "i64",
-                reason: e.to_string(),
-            })
-        })
+        .map_err(|e| _binary_parse_error("i64", e))
 }
 /// binary-string -> u64
 pub fn binary_str_to_u64(binary_str: &str) -> ProtocolResult<u64> {
-    u64::from_str_radix(binary_str, 2).map_err(|e| {
-        ProtocolError::HexError(HexError::BinaryParseError {
-            context: "u64",
-            reason: e.to_string(),
-        })
-    })
+    u64::from_str_radix(binary_str, 2).map_err(|e| _binary_parse_error("u64", e))
 }
 /// binary-string -> i32
 pub fn binary_str_to_i32(binary_str: &str) -> ProtocolResult<i32> {
     u32::from_str_radix(binary_str, 2)
         .map(|u| u as i32)
-        .map_err(|e| {
-            ProtocolError::HexError(HexError::BinaryParseError {
-                context: "i32",
-                reason: e.to_string(),
-            })
-        })
+        .map_err(|e| _binary_parse_error("i32", e))
 }
 /// binary-string -> u32
 pub fn binary_str_to_u32(binary_str: &str) -> ProtocolResult<u32> {
-    u32::from_str_radix(binary_str, 2).map_err(|e| {
-        ProtocolError::HexError(HexError::BinaryParseError {
-            context: "u32",
-            reason: e.to_string(),
-        })
-    })
+    u32::from_str_radix(binary_str, 2).map_err(|e| _binary_parse_error("u32", e))
 }
 /// binary-string -> i16
 pub fn binary_str_to_i16(binary_str: &str) -> ProtocolResult<i16> {
     u16::from_str_radix(binary_str, 2)
         .map(|u| u as i16)
-        .map_err(|e| {
-            ProtocolError::HexError(HexError::BinaryParseError {
-                context: "i16",
-                reason: e.to_string(),
-            })
-        })
+        .map_err(|e| _binary_parse_error("i16", e))
 }
 /// binary-string -> u16
 pub fn binary_str_to_u16(binary_str: &str) -> ProtocolResult<u16> {
-    u16::from_str_radix(binary_str, 2).map_err(|e| {
-        ProtocolError::HexError(HexError::BinaryParseError {
-            context: "u16",
-            reason: e.to_string(),
-        })
-    })
+    u16::from_str_radix(binary_str, 2).map_err(|e| _binary_parse_error("u16", e))
 }
 /// binary-string -> i8
 pub fn binary_str_to_i8(binary_str: &str) -> ProtocolResult<i8> {
     u8::from_str_radix(binary_str, 2)
         .map(|u| u as i8)
-        .map_err(|e| {
-            ProtocolError::HexError(HexError::BinaryParseError {
-                context: "i8",
-                reason: e.to_string(),
-            })
-        })
+        .map_err(|e| _binary_parse_error("i8", e))
 }
 /// binary-string -> u8
 pub fn binary_str_to_u8(binary_str: &str) -> ProtocolResult<u8> {
-    u8::from_str_radix(binary_str, 2).map_err(|e| {
-        ProtocolError::HexError(HexError::BinaryParseError {
-            context: "u8",
-            reason: e.to_string(),
-        })
-    })
+    u8::from_str_radix(binary_str, 2).map_err(|e| _binary_parse_error("u8", e))
 }
 
 /// binary-string -> Vec<bool>
@@ -478,6 +452,7 @@ pub fn binary_str_to_bits(binary_str: &str) -> ProtocolResult<Vec<bool>> {
                     "Invalid character '{}' found in binary string",
                     invalid_char
                 ),
+                source: None,
             })),
         })
         .collect() // 收集 Result<bool, ProtocolError> 到 Result<Vec<bool>, ProtocolError>
@@ -561,7 +536,11 @@ pub fn replace_bytes(
     } else {
         (total_length_i64 + end_byte_pos).max(0) as usize
     };
-    if final_start > final_end { /* ... 错误处理 ... */ }
+    if final_start > final_end {
+        return Err(ProtocolError::CommonError(
+            "fn: replace_bytes has invalid input params".into(),
+        ));
+    }
 
     let mut result_vec = ori_bytes.to_vec();
     result_vec.splice(final_start..final_end, replace_bytes.iter().copied());
@@ -589,7 +568,11 @@ pub fn pad_bytes_to_block_size(
 ) -> ProtocolResult<Vec<u8>> {
     // ... (保持您之前的实现)
     let origin_length = data.len();
-    if block_size == 0 { /* ... 错误处理 ... */ }
+    if block_size == 0 {
+        return Err(ProtocolError::HexError(HexError::InvalidInput(
+            "Block size must be positive".into(),
+        )));
+    }
     let short_by = if origin_length == block_size {
         0
     } else if origin_length < block_size {
@@ -760,10 +743,12 @@ pub fn ascii_to_string(ascii_hex_str: &str) -> ProtocolResult<String> {
     if v.is_empty() {
         return Ok(String::new());
     }
-    ensure_is_ascii_hex(&v)?;
-    let bytes = hex::decode(&v).unwrap(); // 安全，已检查
-    // from_utf8 在这里也是安全的，因为我们保证了是ASCII
-    Ok(String::from_utf8(bytes).unwrap())
+    // v 已经清理过，直接解码 (再次清理会剥掉 "0x0x41" 中的第二个前缀，导致检查与解码不一致)
+    let bytes = hex::decode(&v)
+        .ok()
+        .filter(|bytes| bytes.is_ascii())
+        .ok_or_else(|| ProtocolError::HexError(HexError::NotAscii(ascii_hex_str.into())))?;
+    Ok(bytes.into_iter().map(char::from).collect())
 }
 
 /// String -> ASCII Hex
@@ -798,3 +783,17 @@ fn _clean_and_pad_hex_str(hex: &str) -> String {
         format!("0{}", cleaned)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // fuzz 发现的 panic
+    #[test]
+    fn test_malformed_inputs() {
+        assert!(ascii_to_string("0x0x41").is_err());
+        assert_eq!(ascii_to_string("0x4142").unwrap(), "AB");
+        assert!(replace_bytes(&[0x01], 1, -1, &[0x02]).is_err());
+        assert!(pad_bytes_to_block_size(&[0x01], 0, None).is_err());
+    }
+}
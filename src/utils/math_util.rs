@@ -1,12 +1,25 @@
+use crate::collections::HashMap;
+use crate::core::Symbol;
 use crate::defi::{ProtocolResult, error::ProtocolError};
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
 use rust_decimal::RoundingStrategy;
 use rust_decimal::prelude::*;
 
+/// 默认的缩放精度 (小数位数)
+pub const DEFAULT_PRECISION: u32 = 6;
+
 /// 模仿 Java 的 RoundingMode，提供给外部调用者使用
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum DecimalRoundingMode {
     /// (HALF_UP) 四舍五入
+    #[default]
     HalfUp,
+    /// (HALF_DOWN) 五舍六入
+    HalfDown,
+    /// (HALF_EVEN) 银行家舍入
+    HalfEven,
     /// (DOWN) 直接截断
     Down,
     /// (UP) 远离零
@@ -22,6 +35,8 @@ impl DecimalRoundingMode {
     fn to_strategy(self) -> RoundingStrategy {
         match self {
             DecimalRoundingMode::HalfUp => RoundingStrategy::MidpointAwayFromZero,
+            DecimalRoundingMode::HalfDown => RoundingStrategy::MidpointTowardZero,
+            DecimalRoundingMode::HalfEven => RoundingStrategy::MidpointNearestEven,
             DecimalRoundingMode::Down => RoundingStrategy::ToZero,
             DecimalRoundingMode::Up => RoundingStrategy::AwayFromZero,
             DecimalRoundingMode::Ceiling => RoundingStrategy::ToPositiveInfinity,
@@ -124,3 +139,509 @@ pub fn divide(
     let final_result = result.round_dp_with_strategy(scale, rounding_mode.to_strategy());
     Ok(decimal_to_f64(final_result))
 }
+
+/// 按倍率缩放 (value * scale)，并按指定精度和舍入模式取舍
+///
+/// # Arguments
+/// * `value` - 原始值
+/// * `scale` - 缩放倍率 (例如 0.01)
+/// * `precision` - 小数位数
+/// * `rounding_mode` - 舍入模式
+pub fn scale(
+    value: f64,
+    scale: f64,
+    precision: u32,
+    rounding_mode: DecimalRoundingMode,
+) -> ProtocolResult<f64> {
+    if scale == 0.0 {
+        return Err(ProtocolError::ValidationFailed(
+            "Scale factor cannot be zero.".to_string(),
+        ));
+    }
+    multiply(precision, rounding_mode, &[value, scale])
+}
+
+/// 整数高精度缩放 (value * scale)，全程使用 Decimal，不经过 f64
+///
+/// 用于 u64/i64 等累计量：`value as f64` 在超过 2^53 时会静默丢失精度。
+///
+/// # Arguments
+/// * `value` - 原始整数值
+/// * `scale` - 缩放倍率 (例如 0.001)
+/// * `precision` - 小数位数
+/// * `rounding_mode` - 舍入模式
+pub fn scale_integer(
+    value: i128,
+    scale: f64,
+    precision: u32,
+    rounding_mode: DecimalRoundingMode,
+) -> ProtocolResult<Decimal> {
+    if scale == 0.0 {
+        return Err(ProtocolError::ValidationFailed(
+            "Scale factor cannot be zero.".to_string(),
+        ));
+    }
+    let d_value = Decimal::from_i128(value).ok_or_else(|| {
+        ProtocolError::CommonError(format!("Integer {} is out of Decimal range", value))
+    })?;
+    let result = d_value
+        .checked_mul(f64_to_decimal(scale)?)
+        .ok_or_else(|| ProtocolError::CommonError("Decimal multiplication overflow".into()))?;
+    Ok(result
+        .round_dp_with_strategy(precision, rounding_mode.to_strategy())
+        .normalize())
+}
+
+/// 整数高精度反缩放 (input / scale)，结果向零截断为整数
+///
+/// `scale_integer` 的逆操作，用于下行编码 u64/i64 字段。
+pub fn unscale_to_integer(input: &str, scale: f64) -> ProtocolResult<i128> {
+    unscale_to_integer_with(input, scale, DecimalRoundingMode::Down)
+}
+
+/// 同 `unscale_to_integer`，但按 `rounding_mode` 舍入为整数
+///
+/// 例如 scale = 0.1 时，`"12.55"` 按 HalfUp 得到 126，按 Down 得到 125。
+pub fn unscale_to_integer_with(
+    input: &str,
+    scale: f64,
+    rounding_mode: DecimalRoundingMode,
+) -> ProtocolResult<i128> {
+    if scale == 0.0 {
+        return Err(ProtocolError::ValidationFailed(
+            "Scale factor cannot be zero.".to_string(),
+        ));
+    }
+    let trimmed = input.trim();
+    let d_input = Decimal::from_str(trimmed)
+        .or_else(|_| Decimal::from_scientific(trimmed))
+        .map_err(|_| {
+            ProtocolError::ValidationFailed(format!("Failed to parse input '{}' as Decimal", input))
+        })?;
+    let result = d_input
+        .checked_div(f64_to_decimal(scale)?)
+        .ok_or_else(|| ProtocolError::CommonError("Decimal division overflow".into()))?;
+    result
+        .round_dp_with_strategy(0, rounding_mode.to_strategy())
+        .to_i128()
+        .ok_or_else(|| {
+            ProtocolError::CommonError(format!("Decimal {} cannot be converted to integer", result))
+        })
+}
+
+/// 计算计数器增量，自动处理计数器翻转 (rollover)
+///
+/// 例如 6 位 BCD 累计量在 999999 之后回到 0：
+/// `counter_delta(999990, 5, 999999)` = 15。
+///
+/// # Arguments
+/// * `prev` - 上一次的读数
+/// * `current` - 本次读数
+/// * `max_value` - 计数器可表示的最大值 (包含)，翻转后从 0 重新开始
+pub fn counter_delta(prev: u64, current: u64, max_value: u64) -> ProtocolResult<u64> {
+    if prev > max_value || current > max_value {
+        return Err(ProtocolError::ValidationFailed(format!(
+            "Counter reading out of range. prev: {}, current: {}, max: {}",
+            prev, current, max_value
+        )));
+    }
+    if current >= prev {
+        Ok(current - prev)
+    } else {
+        // 翻转：prev -> max_value -> 0 -> current
+        Ok((max_value - prev) + current + 1)
+    }
+}
+
+// --- 百分比 / 比率 ---
+
+/// 高精度比率 (numerator / denominator)，返回 Decimal
+///
+/// # Arguments
+/// * `numerator` - 分子
+/// * `denominator` - 分母
+/// * `scale` - 小数位数
+/// * `rounding_mode` - 舍入模式
+pub fn ratio(
+    numerator: f64,
+    denominator: f64,
+    scale: u32,
+    rounding_mode: DecimalRoundingMode,
+) -> ProtocolResult<Decimal> {
+    let d_numerator = f64_to_decimal(numerator)?;
+    let d_denominator = f64_to_decimal(denominator)?;
+    if d_denominator.is_zero() {
+        return Err(ProtocolError::CommonError("Division by zero".into()));
+    }
+    let result = d_numerator
+        .checked_div(d_denominator)
+        .ok_or_else(|| ProtocolError::CommonError("Decimal division overflow".into()))?;
+    Ok(result
+        .round_dp_with_strategy(scale, rounding_mode.to_strategy())
+        .normalize())
+}
+
+/// 计算 value 在 [min, max] 区间内所处的百分比，结果限制在 0~100 之间
+///
+/// 例如电池电量 (按毫伏区间换算)、阀门开度 (按 ADC 原始值换算)：
+/// `percentage_in_range(3300.0, 3000.0, 3600.0, 1, HalfUp)` = 50。
+///
+/// # Arguments
+/// * `value` - 当前值
+/// * `min` - 对应 0% 的值
+/// * `max` - 对应 100% 的值 (可以小于 min，表示反向量程)
+/// * `scale` - 小数位数
+/// * `rounding_mode` - 舍入模式
+pub fn percentage_in_range(
+    value: f64,
+    min: f64,
+    max: f64,
+    scale: u32,
+    rounding_mode: DecimalRoundingMode,
+) -> ProtocolResult<Decimal> {
+    let d_value = f64_to_decimal(value)?;
+    let d_min = f64_to_decimal(min)?;
+    let d_max = f64_to_decimal(max)?;
+    let subtraction_overflow = || ProtocolError::CommonError("Decimal subtraction overflow".into());
+    let span = d_max.checked_sub(d_min).ok_or_else(subtraction_overflow)?;
+    if span.is_zero() {
+        return Err(ProtocolError::ValidationFailed(format!(
+            "Percentage range is empty. min: {}, max: {}",
+            min, max
+        )));
+    }
+    let result = d_value
+        .checked_sub(d_min)
+        .ok_or_else(subtraction_overflow)?
+        .checked_mul(Decimal::ONE_HUNDRED)
+        .and_then(|v| v.checked_div(span))
+        .ok_or_else(|| ProtocolError::CommonError("Decimal percentage overflow".into()))?;
+    Ok(result
+        .clamp(Decimal::ZERO, Decimal::ONE_HUNDRED)
+        .round_dp_with_strategy(scale, rounding_mode.to_strategy())
+        .normalize())
+}
+
+/// 计算 part 占 total 的百分比 (不做区间限制，可超过 100)
+pub fn percentage(
+    part: f64,
+    total: f64,
+    scale: u32,
+    rounding_mode: DecimalRoundingMode,
+) -> ProtocolResult<Decimal> {
+    let d_part = f64_to_decimal(part)?;
+    let d_total = f64_to_decimal(total)?;
+    if d_total.is_zero() {
+        return Err(ProtocolError::CommonError("Division by zero".into()));
+    }
+    let result = d_part
+        .checked_mul(Decimal::ONE_HUNDRED)
+        .and_then(|v| v.checked_div(d_total))
+        .ok_or_else(|| ProtocolError::CommonError("Decimal percentage overflow".into()))?;
+    Ok(result
+        .round_dp_with_strategy(scale, rounding_mode.to_strategy())
+        .normalize())
+}
+
+/// 将百分比格式化为 ReportField 使用的字符串 (例如 "85.5 %")
+pub fn format_percentage(percent: &Decimal) -> String {
+    format!("{} {}", percent.normalize(), Symbol::Percent.tag())
+}
+
+// --- 表达式求值 ---
+
+// 括号、一元正负号的最大嵌套层数，防止配置中的畸形表达式 (如 "((((..." ) 耗尽栈空间
+const MAX_EXPR_DEPTH: usize = 64;
+
+/// 计算一个简单的四则运算表达式 (例如 `"(x-4000)*0.01 + y"`)
+///
+/// 支持 `+ - * /`、括号、一元负号、数字字面量以及变量 (字母/数字/下划线组成)。
+/// 计算过程全程使用 Decimal，结果最后再转换为 f64。
+/// 常用于把标定公式放到配置中，由解码器在运行时代入原始值计算。
+///
+/// # Arguments
+/// * `expr` - 表达式
+/// * `vars` - 变量表，表达式中出现的变量必须在此定义
+pub fn eval(expr: &str, vars: &HashMap<String, f64>) -> ProtocolResult<f64> {
+    let mut decimal_vars = HashMap::with_capacity(vars.len());
+    for (name, value) in vars {
+        decimal_vars.insert(name.as_str(), f64_to_decimal(*value)?);
+    }
+    let result = eval_decimal(expr, &decimal_vars)?;
+    Ok(decimal_to_f64(result))
+}
+
+/// 同 `eval`，但变量与结果均为 Decimal，避免 f64 往返带来的精度损失
+pub fn eval_decimal(expr: &str, vars: &HashMap<&str, Decimal>) -> ProtocolResult<Decimal> {
+    let mut parser = ExprParser {
+        chars: expr.chars().collect(),
+        pos: 0,
+        depth: 0,
+        vars,
+    };
+    let value = parser.parse_expr()?;
+    parser.skip_whitespace();
+    if parser.pos < parser.chars.len() {
+        return Err(parser.error("unexpected trailing input"));
+    }
+    Ok(value)
+}
+
+/// (内部) 递归下降解析器
+///
+/// expr   := term (('+' | '-') term)*
+/// term   := factor (('*' | '/') factor)*
+/// factor := ('-' | '+') factor | number | ident | '(' expr ')'
+struct ExprParser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    depth: usize, // 当前 factor 的嵌套层数
+    vars: &'a HashMap<&'a str, Decimal>,
+}
+
+impl ExprParser<'_> {
+    fn error(&self, reason: &str) -> ProtocolError {
+        let expr: String = self.chars.iter().collect();
+        ProtocolError::ValidationFailed(format!(
+            "Invalid expression '{}' at position {}: {}",
+            expr, self.pos, reason
+        ))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.chars.len() && self.chars[self.pos].is_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.get(self.pos).copied()
+    }
+
+    fn parse_expr(&mut self) -> ProtocolResult<Decimal> {
+        let mut value = self.parse_term()?;
+        while let Some(op) = self.peek() {
+            if op != '+' && op != '-' {
+                break;
+            }
+            self.pos += 1;
+            let rhs = self.parse_term()?;
+            value = if op == '+' {
+                value
+                    .checked_add(rhs)
+                    .ok_or_else(|| ProtocolError::CommonError("Decimal addition overflow".into()))?
+            } else {
+                value.checked_sub(rhs).ok_or_else(|| {
+                    ProtocolError::CommonError("Decimal subtraction overflow".into())
+                })?
+            };
+        }
+        Ok(value)
+    }
+
+    fn parse_term(&mut self) -> ProtocolResult<Decimal> {
+        let mut value = self.parse_factor()?;
+        while let Some(op) = self.peek() {
+            if op != '*' && op != '/' {
+                break;
+            }
+            self.pos += 1;
+            let rhs = self.parse_factor()?;
+            value = if op == '*' {
+                value.checked_mul(rhs).ok_or_else(|| {
+                    ProtocolError::CommonError("Decimal multiplication overflow".into())
+                })?
+            } else {
+                if rhs.is_zero() {
+                    return Err(ProtocolError::CommonError("Division by zero".into()));
+                }
+                value
+                    .checked_div(rhs)
+                    .ok_or_else(|| ProtocolError::CommonError("Decimal division overflow".into()))?
+            };
+        }
+        Ok(value)
+    }
+
+    fn parse_factor(&mut self) -> ProtocolResult<Decimal> {
+        if self.depth >= MAX_EXPR_DEPTH {
+            return Err(self.error("expression is nested too deeply"));
+        }
+        self.depth += 1;
+        let value = self.parse_factor_inner();
+        self.depth -= 1;
+        value
+    }
+
+    fn parse_factor_inner(&mut self) -> ProtocolResult<Decimal> {
+        match self.peek() {
+            Some('-') => {
+                self.pos += 1;
+                Ok(-self.parse_factor()?)
+            }
+            Some('+') => {
+                self.pos += 1;
+                self.parse_factor()
+            }
+            Some('(') => {
+                self.pos += 1;
+                let value = self.parse_expr()?;
+                if self.peek() != Some(')') {
+                    return Err(self.error("expected ')'"));
+                }
+                self.pos += 1;
+                Ok(value)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let start = self.pos;
+                while self.pos < self.chars.len()
+                    && (self.chars[self.pos].is_ascii_digit() || self.chars[self.pos] == '.')
+                {
+                    self.pos += 1;
+                }
+                let literal: String = self.chars[start..self.pos].iter().collect();
+                Decimal::from_str(&literal).map_err(|_| self.error("invalid number"))
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let start = self.pos;
+                while self.pos < self.chars.len()
+                    && (self.chars[self.pos].is_alphanumeric() || self.chars[self.pos] == '_')
+                {
+                    self.pos += 1;
+                }
+                let name: String = self.chars[start..self.pos].iter().collect();
+                self.vars.get(name.as_str()).copied().ok_or_else(|| {
+                    ProtocolError::ValidationFailed(format!("Undefined variable '{}'", name))
+                })
+            }
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end of expression")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scale_integer_keeps_precision_above_2_pow_53() {
+        let value: i128 = 9_007_199_254_740_993; // 2^53 + 1
+        let scaled = scale_integer(value, 0.001, 3, DecimalRoundingMode::HalfUp).unwrap();
+        assert_eq!(scaled.to_string(), "9007199254740.993");
+        assert_eq!(
+            unscale_to_integer("9007199254740.993", 0.001).unwrap(),
+            value
+        );
+    }
+
+    #[test]
+    fn test_unscale_with_rounding_mode() {
+        use crate::FieldType;
+
+        assert_eq!(
+            unscale_to_integer_with("12.55", 0.1, DecimalRoundingMode::HalfUp).unwrap(),
+            126
+        );
+        assert_eq!(
+            unscale_to_integer_with("12.55", 0.1, DecimalRoundingMode::Down).unwrap(),
+            125
+        );
+        let ft = FieldType::UnsignedU16(0.1);
+        assert_eq!(ft.encode("12.5").unwrap(), vec![0x00, 0x7D]);
+        assert_eq!(
+            ft.encode_with("0.29", DecimalRoundingMode::Ceiling)
+                .unwrap(),
+            vec![0x00, 0x03]
+        );
+        assert!(ft.encode("6553.6").is_err());
+        assert!(ft.encode("-1").is_err());
+    }
+
+    #[test]
+    fn test_encode_out_of_range() {
+        use crate::FieldType;
+
+        // 各宽度的整数超出范围时都报同样的错误，而不是饱和或截断
+        for (ft, input) in [
+            (FieldType::UnsignedU8(1.0), "256"),
+            (FieldType::UnsignedU16(1.0), "65536"),
+            (FieldType::UnsignedU32(1.0), "4294967296"),
+            (FieldType::UnsignedU64(1.0), "18446744073709551616"),
+            (FieldType::SignedI8(1.0), "-129"),
+            (FieldType::SignedI16(0.1), "3276.8"),
+            (FieldType::SignedI32(1.0), "2147483648"),
+            (FieldType::SignedI64(1.0), "-9223372036854775809"),
+        ] {
+            let error = ft.encode(input).unwrap_err();
+            assert!(error.to_string().contains("out of range"), "{:?}", ft);
+        }
+        assert_eq!(
+            FieldType::UnsignedU8(1.0).encode("255").unwrap(),
+            vec![0xFF]
+        );
+    }
+
+    #[test]
+    fn test_counter_delta_rollover() {
+        assert_eq!(counter_delta(100, 150, 999_999).unwrap(), 50);
+        assert_eq!(counter_delta(999_990, 5, 999_999).unwrap(), 15);
+        assert_eq!(counter_delta(u64::MAX, 0, u64::MAX).unwrap(), 1);
+        assert!(counter_delta(1_000_000, 5, 999_999).is_err());
+    }
+
+    #[test]
+    fn test_percentage_in_range() {
+        let level =
+            percentage_in_range(3300.0, 3000.0, 3600.0, 1, DecimalRoundingMode::HalfUp).unwrap();
+        assert_eq!(format_percentage(&level), "50 %");
+        let clamped =
+            percentage_in_range(3700.0, 3000.0, 3600.0, 1, DecimalRoundingMode::HalfUp).unwrap();
+        assert_eq!(clamped, Decimal::ONE_HUNDRED);
+        let third = percentage(1.0, 3.0, 2, DecimalRoundingMode::HalfUp).unwrap();
+        assert_eq!(third.to_string(), "33.33");
+        // 接近 Decimal 上限的量程，相减溢出时报错而不是 panic
+        let error =
+            percentage_in_range(0.0, -7e28, 7e28, 1, DecimalRoundingMode::HalfUp).unwrap_err();
+        assert!(error.to_string().contains("overflow"));
+        assert!(percentage_in_range(7e28, -7e28, 0.0, 1, DecimalRoundingMode::HalfUp).is_err());
+    }
+
+    #[test]
+    fn test_eval_formula() {
+        let mut vars = HashMap::new();
+        vars.insert("x".to_string(), 4250.0);
+        vars.insert("y".to_string(), 1.5);
+        let result = eval("(x-4000)*0.01 + y", &vars).unwrap();
+        assert_eq!(result, 4.0);
+    }
+
+    #[test]
+    fn test_eval_precedence_and_unary() {
+        let vars = HashMap::new();
+        assert_eq!(eval("1 + 2 * 3", &vars).unwrap(), 7.0);
+        assert_eq!(eval("-(1 + 2) * 3", &vars).unwrap(), -9.0);
+        assert_eq!(eval("0.1 + 0.2", &vars).unwrap(), 0.3);
+    }
+
+    #[test]
+    fn test_eval_errors() {
+        let vars = HashMap::new();
+        assert!(eval("z + 1", &vars).is_err());
+        assert!(eval("1 / 0", &vars).is_err());
+        assert!(eval("(1 + 2", &vars).is_err());
+        assert!(eval("1 + 2)", &vars).is_err());
+    }
+
+    #[test]
+    fn test_eval_depth_limit() {
+        let vars = HashMap::new();
+        let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(eval(&nested(MAX_EXPR_DEPTH - 1), &vars).unwrap(), 1.0);
+        assert!(eval(&nested(MAX_EXPR_DEPTH), &vars).is_err());
+        assert!(eval(&"(".repeat(100_000), &vars).is_err());
+        let error = eval(&format!("{}1", "-".repeat(100_000)), &vars).unwrap_err();
+        assert!(error.to_string().contains("nested too deeply"));
+    }
+}
@@ -0,0 +1,360 @@
+#[cfg(feature = "cache")]
+use moka::sync::Cache;
+use once_cell::sync::Lazy;
+use pinyin::{ToPinyin, ToPinyinMulti};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+// 词组最大长度 (字符数)，用于最长匹配
+const MAX_PHRASE_LEN: usize = 8;
+
+// 内置多音字词典：仅收录协议字段标题中常见、且默认读音错误的词组
+static PHRASE_DICT: Lazy<HashMap<&'static str, &'static [&'static str]>> = Lazy::new(|| {
+    let entries: &[(&str, &[&str])] = &[
+        // 重
+        ("重量", &["zhong", "liang"]),
+        ("重置", &["chong", "zhi"]),
+        ("重启", &["chong", "qi"]),
+        ("重新", &["chong", "xin"]),
+        ("重发", &["chong", "fa"]),
+        ("重试", &["chong", "shi"]),
+        ("重复", &["chong", "fu"]),
+        ("重连", &["chong", "lian"]),
+        ("重传", &["chong", "chuan"]),
+        // 长
+        ("长度", &["chang", "du"]),
+        ("时长", &["shi", "chang"]),
+        ("长时间", &["chang", "shi", "jian"]),
+        ("长期", &["chang", "qi"]),
+        ("长连接", &["chang", "lian", "jie"]),
+        ("波长", &["bo", "chang"]),
+        ("增长", &["zeng", "zhang"]),
+        ("厂长", &["chang", "zhang"]),
+        // 行
+        ("行程", &["xing", "cheng"]),
+        ("运行", &["yun", "xing"]),
+        ("执行", &["zhi", "xing"]),
+        ("上行", &["shang", "xing"]),
+        ("下行", &["xia", "xing"]),
+        ("银行", &["yin", "hang"]),
+        ("行业", &["hang", "ye"]),
+        // 调
+        ("调价", &["tiao", "jia"]),
+        ("调节", &["tiao", "jie"]),
+        ("调试", &["tiao", "shi"]),
+        ("调整", &["tiao", "zheng"]),
+        ("调压", &["tiao", "ya"]),
+        ("调度", &["diao", "du"]),
+        // 其他
+        ("还款", &["huan", "kuan"]),
+        ("归还", &["gui", "huan"]),
+        ("传感器", &["chuan", "gan", "qi"]),
+        ("单价", &["dan", "jia"]),
+    ];
+    entries.iter().copied().collect()
+});
+
+// 用户自定义覆盖表，优先级高于内置词典
+static OVERRIDE_DICT: Lazy<RwLock<HashMap<String, Vec<String>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+// 转换结果缓存。字段标题数量有限但调用极其频繁 (每个 ReportField 一次)。
+// 每种选项一个缓存，以 &str 查找，命中时不分配 String
+#[cfg(feature = "cache")]
+static PINYIN_CACHE: Lazy<[Cache<String, String>; PINYIN_CACHE_SLOTS]> =
+    Lazy::new(|| core::array::from_fn(|_| Cache::builder().max_capacity(2_048).build()));
+
+// 自定义读音的版本号，每次修改时加一。计算期间版本变化的结果不写回缓存
+#[cfg(feature = "cache")]
+static PINYIN_GENERATION: RwLock<u64> = RwLock::new(0);
+
+// 分隔符 2 种 × 风格 4 种
+#[cfg(feature = "cache")]
+const PINYIN_CACHE_SLOTS: usize = 8;
+
+/// 注册一个自定义读音 (词组或单字)，优先级高于内置多音字词典
+///
+/// 例如 `register_pinyin_override("累计", &["lei", "ji"])`。
+/// 用于保证生成的 `ReportField::code` 稳定且正确。
+pub fn register_pinyin_override(word: &str, syllables: &[&str]) {
+    if let Ok(mut dict) = OVERRIDE_DICT.write() {
+        dict.insert(
+            word.to_string(),
+            syllables.iter().map(|s| s.to_string()).collect(),
+        );
+    }
+    _invalidate_cache();
+}
+
+/// 移除一个自定义读音
+pub fn remove_pinyin_override(word: &str) {
+    if let Ok(mut dict) = OVERRIDE_DICT.write() {
+        dict.remove(word);
+    }
+    _invalidate_cache();
+}
+
+/// 清空所有自定义读音
+pub fn clear_pinyin_overrides() {
+    if let Ok(mut dict) = OVERRIDE_DICT.write() {
+        dict.clear();
+    }
+    _invalidate_cache();
+}
+
+/// 预先计算一批标题的拼音 (默认选项)，避免首次上报时的转换开销
+pub fn preload_pinyin(titles: &[&str]) {
+    for title in titles {
+        to_pinyin(title);
+    }
+}
+
+/// (内部) 在 chars 开头做最长匹配，返回 (匹配的字符数, 读音)
+fn _match_phrase(chars: &[char]) -> Option<(usize, Vec<String>)> {
+    let overrides = OVERRIDE_DICT.read().ok();
+    let max_len = chars.len().min(MAX_PHRASE_LEN);
+    for len in (1..=max_len).rev() {
+        let word: String = chars[..len].iter().collect();
+        if let Some(syllables) = overrides.as_ref().and_then(|d| d.get(&word)) {
+            return Some((len, syllables.clone()));
+        }
+        if len >= 2
+            && let Some(syllables) = PHRASE_DICT.get(word.as_str())
+        {
+            return Some((len, syllables.iter().map(|s| s.to_string()).collect()));
+        }
+    }
+    None
+}
+
+/// 拼音之间的分隔符
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PinyinSeparator {
+    /// 无分隔 (例如 `liuliang`)
+    None,
+    /// 下划线分隔 (例如 `liu_liang`)
+    #[default]
+    Underscore,
+}
+
+impl PinyinSeparator {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PinyinSeparator::None => "",
+            PinyinSeparator::Underscore => "_",
+        }
+    }
+}
+
+/// 拼音输出风格
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PinyinStyle {
+    /// 无声调 (例如 `liu`)
+    #[default]
+    Plain,
+    /// 仅首字母 (例如 `l`)
+    FirstLetter,
+    /// 声调符号 (例如 `liú`)
+    Tone,
+    /// 声调数字在末尾 (例如 `liu2`)
+    ToneNumEnd,
+}
+
+/// `to_pinyin_with` 的选项。默认与 `to_pinyin` 的输出一致 (下划线 + 无声调)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct PinyinOptions {
+    pub separator: PinyinSeparator,
+    pub style: PinyinStyle,
+}
+
+impl PinyinOptions {
+    pub fn new(separator: PinyinSeparator, style: PinyinStyle) -> Self {
+        Self { separator, style }
+    }
+
+    /// 首字母缩写 (例如 "流量" -> `ll`)
+    pub fn abbreviation() -> Self {
+        Self::new(PinyinSeparator::None, PinyinStyle::FirstLetter)
+    }
+
+    // 在 PINYIN_CACHE 中的下标
+    #[cfg(feature = "cache")]
+    fn _cache_slot(&self) -> usize {
+        self.separator as usize * 4 + self.style as usize
+    }
+}
+
+/// (内部) 按风格输出单个汉字的拼音
+fn _styled(pinyin: pinyin::Pinyin, style: PinyinStyle) -> String {
+    match style {
+        PinyinStyle::Plain => pinyin.plain().to_string(),
+        PinyinStyle::FirstLetter => pinyin.first_letter().to_string(),
+        PinyinStyle::Tone => pinyin.with_tone().to_string(),
+        PinyinStyle::ToneNumEnd => pinyin.with_tone_num_end().to_string(),
+    }
+}
+
+/// (内部) 按风格输出词典中的读音。
+/// 词典只记录无声调读音，需要声调时在该字的多音读音中反查。
+fn _styled_syllable(ch: Option<char>, syllable: &str, style: PinyinStyle) -> String {
+    match style {
+        PinyinStyle::Plain => syllable.to_string(),
+        PinyinStyle::FirstLetter => syllable.chars().take(1).collect(),
+        PinyinStyle::Tone | PinyinStyle::ToneNumEnd => ch
+            .and_then(|c| c.to_pinyin_multi())
+            .and_then(|multi| multi.into_iter().find(|p| p.plain() == syllable))
+            .map(|p| _styled(p, style))
+            .unwrap_or_else(|| syllable.to_string()),
+    }
+}
+
+pub fn to_pinyin(s: &str) -> String {
+    to_pinyin_with(s, &PinyinOptions::default())
+}
+
+/// 按指定的分隔符和风格将文字转换为拼音
+///
+/// 不同下游系统期望的 code 不同，例如 `liuliang`、`liu_liang` 或 `ll`。
+/// 结果会被缓存，修改自定义读音时缓存自动失效。
+#[cfg(feature = "cache")]
+pub fn to_pinyin_with(s: &str, options: &PinyinOptions) -> String {
+    _cached(s, options, || _to_pinyin_uncached(s, options))
+}
+
+#[cfg(feature = "cache")]
+fn _cached(s: &str, options: &PinyinOptions, compute: impl FnOnce() -> String) -> String {
+    let cache = &PINYIN_CACHE[options._cache_slot()];
+    if let Some(hit) = cache.get(s) {
+        return hit;
+    }
+    let generation = PINYIN_GENERATION.read().map(|g| *g).ok();
+    let value = compute();
+    // 持有读锁写入，与 `_invalidate_cache` 的加一、清空互斥
+    if let Ok(current) = PINYIN_GENERATION.read()
+        && Some(*current) == generation
+    {
+        cache.insert(s.to_string(), value.clone());
+    }
+    value
+}
+
+/// 按指定的分隔符和风格将文字转换为拼音 (未开启 `cache` feature 时不缓存)
+#[cfg(not(feature = "cache"))]
+pub fn to_pinyin_with(s: &str, options: &PinyinOptions) -> String {
+    _to_pinyin_uncached(s, options)
+}
+
+// 自定义读音变化后，缓存的结果失效
+fn _invalidate_cache() {
+    #[cfg(feature = "cache")]
+    if let Ok(mut generation) = PINYIN_GENERATION.write() {
+        *generation += 1;
+        for cache in PINYIN_CACHE.iter() {
+            cache.invalidate_all();
+        }
+    }
+}
+
+fn _to_pinyin_uncached(s: &str, options: &PinyinOptions) -> String {
+    let mut result: Vec<String> = Vec::new();
+    let mut non_chinese_buffer = String::new();
+
+    let chars: Vec<char> = s.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        // 1. 多音字词组 (自定义覆盖 > 内置词典)
+        if let Some((len, syllables)) = _match_phrase(&chars[i..]) {
+            if !non_chinese_buffer.is_empty() {
+                result.push(non_chinese_buffer.clone());
+                non_chinese_buffer.clear();
+            }
+            // 读音与字一一对应时才能反查声调
+            let aligned = syllables.len() == len;
+            for (idx, syllable) in syllables.iter().enumerate() {
+                let ch = if aligned { Some(chars[i + idx]) } else { None };
+                result.push(_styled_syllable(ch, syllable, options.style));
+            }
+            i += len;
+            continue;
+        }
+
+        let original_char = chars[i];
+        i += 1;
+
+        match original_char.to_pinyin() {
+            Some(pinyin) => {
+                if !non_chinese_buffer.is_empty() {
+                    result.push(non_chinese_buffer.clone());
+                    non_chinese_buffer.clear();
+                }
+                result.push(_styled(pinyin, options.style));
+            }
+            None => {
+                // 2. 非中文字符
+                if original_char.is_alphanumeric() {
+                    non_chinese_buffer.push(original_char);
+                } else {
+                    // 2b. 如果是空格、标点等
+                    // 检查缓冲区，如果里面有 "gemini"，先将其推入结果
+                    if !non_chinese_buffer.is_empty() {
+                        result.push(non_chinese_buffer.clone());
+                        non_chinese_buffer.clear();
+                    }
+                    // (我们忽略这个空格或标点符号本身)
+                }
+            }
+        }
+    }
+    if !non_chinese_buffer.is_empty() {
+        result.push(non_chinese_buffer);
+    }
+
+    result.join(options.separator.as_str()).trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_pinyin_polyphones() {
+        assert_eq!(to_pinyin("长度"), "chang_du");
+        assert_eq!(to_pinyin("重量"), "zhong_liang");
+        assert_eq!(to_pinyin("运行时长"), "yun_xing_shi_chang");
+        assert_eq!(to_pinyin("调价 v2"), "tiao_jia_v2");
+    }
+
+    #[test]
+    fn test_to_pinyin_styles() {
+        let none = PinyinOptions::new(PinyinSeparator::None, PinyinStyle::Plain);
+        assert_eq!(to_pinyin_with("流量", &none), "liuliang");
+        assert_eq!(to_pinyin_with("流量", &PinyinOptions::abbreviation()), "ll");
+        let tone = PinyinOptions::new(PinyinSeparator::Underscore, PinyinStyle::ToneNumEnd);
+        assert_eq!(to_pinyin_with("长度", &tone), "chang2_du4");
+    }
+
+    #[test]
+    fn test_to_pinyin_override() {
+        register_pinyin_override("阀门状态", &["fa", "men", "zt"]);
+        assert_eq!(to_pinyin("阀门状态"), "fa_men_zt");
+        remove_pinyin_override("阀门状态");
+        assert_eq!(to_pinyin("阀门状态"), "fa_men_zhuang_tai");
+    }
+
+    #[cfg(feature = "cache")]
+    #[test]
+    fn test_stale_result_not_cached() {
+        let options = PinyinOptions::default();
+        // 计算期间注册自定义读音，模拟并发的缓存失效 (标题不能与其他测试重复，否则直接命中缓存)
+        let stale = _cached("阀门累计开启", &options, || {
+            let value = _to_pinyin_uncached("阀门累计开启", &options);
+            register_pinyin_override("阀门累计开启", &["fm", "lj", "kq"]);
+            value
+        });
+        assert_eq!(stale, "fa_men_lei_ji_kai_qi");
+        assert_eq!(to_pinyin("阀门累计开启"), "fm_lj_kq");
+        remove_pinyin_override("阀门累计开启");
+        assert_eq!(to_pinyin("阀门累计开启"), "fa_men_lei_ji_kai_qi");
+    }
+}
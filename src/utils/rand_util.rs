@@ -0,0 +1,223 @@
+//! 随机字符串与随机字节。依赖线程本地随机源与操作系统随机源，需要 `std`。
+
+use rand::rngs::OsRng;
+use rand::{Rng, RngCore, TryRngCore};
+#[cfg(any(test, feature = "test-support"))]
+use {
+    rand::{SeedableRng, rngs::StdRng},
+    std::cell::RefCell,
+};
+
+use crate::defi::{ProtocolResult, error::ProtocolError};
+
+// 定义字符集：大写字母(A-Z) + 小写字母(a-z) + 数字(0-9)
+const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+const DIGITS_CHARSET: &[u8] = b"0123456789";
+const HEX_UPPER_CHARSET: &[u8] = b"0123456789ABCDEF";
+const HEX_LOWER_CHARSET: &[u8] = b"0123456789abcdef";
+
+/// 随机字符串使用的字符集
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RandCharset {
+    /// 大小写字母 + 数字
+    #[default]
+    Alphanumeric,
+    /// 纯数字 (0-9)
+    Digits,
+    /// 大写 Hex (0-9, A-F)
+    HexUpper,
+    /// 小写 Hex (0-9, a-f)
+    HexLower,
+}
+
+impl RandCharset {
+    fn chars(&self) -> &'static [u8] {
+        match self {
+            RandCharset::Alphanumeric => CHARSET,
+            RandCharset::Digits => DIGITS_CHARSET,
+            RandCharset::HexUpper => HEX_UPPER_CHARSET,
+            RandCharset::HexLower => HEX_LOWER_CHARSET,
+        }
+    }
+}
+
+/// (内部) 使用给定的随机源，从字符集中取 len 个字符
+pub(crate) fn rand_string_from<R: Rng + ?Sized>(
+    rng: &mut R,
+    len: usize,
+    charset: RandCharset,
+) -> String {
+    let chars = charset.chars();
+    std::iter::repeat_with(|| {
+        let idx = rng.random_range(0..chars.len());
+        chars[idx] as char
+    })
+    .take(len)
+    .collect()
+}
+
+pub fn generate_rand(len: usize) -> String {
+    generate_rand_with_charset(len, RandCharset::Alphanumeric)
+}
+
+/// 生成指定字符集的随机字符串 (线程本地随机源，受 `seed_rand` 影响)
+pub fn generate_rand_with_charset(len: usize, charset: RandCharset) -> String {
+    with_rng(|rng| rand_string_from(rng, len, charset))
+}
+
+// --- 可复现的随机模式 (仅测试与 `test-support`) ---
+
+#[cfg(any(test, feature = "test-support"))]
+thread_local! {
+    // 当前线程的固定种子随机源。为 None 时使用系统线程随机源。
+    static SEEDED_RNG: RefCell<Option<StdRng>> = const { RefCell::new(None) };
+}
+
+/// 为当前线程设置随机种子，使 `generate_rand`、`generate_iv` 等输出可逐字节复现
+///
+/// 只在测试与 `test-support` feature 下提供，生产构建中 IV 始终来自操作系统随机源。
+/// `generate_secure_*` 不受影响。
+#[cfg(any(test, feature = "test-support"))]
+pub fn seed_rand(seed: u64) {
+    SEEDED_RNG.with(|cell| *cell.borrow_mut() = Some(StdRng::seed_from_u64(seed)));
+}
+
+/// 清除当前线程的随机种子，恢复为系统随机源
+#[cfg(any(test, feature = "test-support"))]
+pub fn clear_rand_seed() {
+    SEEDED_RNG.with(|cell| *cell.borrow_mut() = None);
+}
+
+/// 用操作系统随机源填充 buf，随机源不可用时返回错误。
+/// 测试与 `test-support` 下设置了 `seed_rand` 时改用固定种子的随机源
+pub fn fill_rand_bytes(buf: &mut [u8]) -> ProtocolResult<()> {
+    #[cfg(any(test, feature = "test-support"))]
+    if SEEDED_RNG
+        .with(|cell| {
+            cell.borrow_mut()
+                .as_mut()
+                .map(|seeded| seeded.fill_bytes(buf))
+        })
+        .is_some()
+    {
+        return Ok(());
+    }
+    _try_fill(&mut OsRng, buf)
+}
+
+/// (内部) 选择当前线程应使用的随机源
+fn with_rng<T>(f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+    #[cfg(any(test, feature = "test-support"))]
+    return SEEDED_RNG.with(|cell| match cell.borrow_mut().as_mut() {
+        Some(seeded) => f(seeded),
+        None => f(&mut rand::rng()),
+    });
+    #[cfg(not(any(test, feature = "test-support")))]
+    f(&mut rand::rng())
+}
+
+/// 使用操作系统 CSPRNG 生成随机字符串，适用于放入下行帧的挑战随机数 (nonce)。
+/// 随机源不可用时返回错误，不会 panic
+pub fn generate_secure_rand(len: usize, charset: RandCharset) -> ProtocolResult<String> {
+    _secure_string_from(&mut OsRng, len, charset)
+}
+
+/// 使用操作系统 CSPRNG 生成随机字节
+pub fn generate_secure_bytes(len: usize) -> ProtocolResult<Vec<u8>> {
+    let mut bytes = vec![0u8; len];
+    _try_fill(&mut OsRng, &mut bytes)?;
+    Ok(bytes)
+}
+
+// 每次从随机源取的字节数
+const SECURE_CHUNK: usize = 64;
+
+/// (内部) 按块取随机字节并做拒绝采样：丢弃落在字符集整数倍之外的字节，保证每个字符等概率
+fn _secure_string_from<R: TryRngCore + ?Sized>(
+    rng: &mut R,
+    len: usize,
+    charset: RandCharset,
+) -> ProtocolResult<String> {
+    let chars = charset.chars();
+    let zone = 256 - 256 % chars.len();
+    let mut result = String::with_capacity(len);
+    let mut buf = [0u8; SECURE_CHUNK];
+    // 字符集均为 ASCII，字节数即字符数
+    while result.len() < len {
+        _try_fill(rng, &mut buf)?;
+        let accepted = buf.iter().filter(|b| (**b as usize) < zone);
+        for b in accepted.take(len - result.len()) {
+            result.push(chars[*b as usize % chars.len()] as char);
+        }
+    }
+    Ok(result)
+}
+
+fn _try_fill<R: TryRngCore + ?Sized>(rng: &mut R, buf: &mut [u8]) -> ProtocolResult<()> {
+    rng.try_fill_bytes(buf)
+        .map_err(|e| ProtocolError::CommonError(format!("OS random source unavailable: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded_rand_is_reproducible() {
+        seed_rand(42);
+        let first = (generate_rand(16), crate::aes_digester::generate_iv());
+        seed_rand(42);
+        let second = (generate_rand(16), crate::aes_digester::generate_iv());
+        clear_rand_seed();
+        assert_ne!(first.1, crate::aes_digester::generate_iv());
+        clear_rand_seed();
+        assert_eq!(first, second);
+    }
+
+    // 前 n 次取值成功，之后失败的随机源
+    struct FlakyRng(usize);
+
+    impl TryRngCore for FlakyRng {
+        type Error = std::io::Error;
+
+        fn try_next_u32(&mut self) -> Result<u32, Self::Error> {
+            let mut buf = [0u8; 4];
+            self.try_fill_bytes(&mut buf)?;
+            Ok(u32::from_be_bytes(buf))
+        }
+
+        fn try_next_u64(&mut self) -> Result<u64, Self::Error> {
+            let mut buf = [0u8; 8];
+            self.try_fill_bytes(&mut buf)?;
+            Ok(u64::from_be_bytes(buf))
+        }
+
+        fn try_fill_bytes(&mut self, dst: &mut [u8]) -> Result<(), Self::Error> {
+            if self.0 == 0 {
+                return Err(std::io::Error::other("entropy exhausted"));
+            }
+            self.0 -= 1;
+            // 0xFF 超出 Digits 的采样区间，应被丢弃
+            for (i, b) in dst.iter_mut().enumerate() {
+                *b = if i % 2 == 0 { 0xFF } else { i as u8 };
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_secure_rand_reports_rng_failure() {
+        let digits = _secure_string_from(&mut FlakyRng(1), 20, RandCharset::Digits).unwrap();
+        assert_eq!(digits.len(), 20);
+        assert!(digits.bytes().all(|b| b.is_ascii_digit()));
+        // 第一块只有 32 个可用字节，取 40 个字符时需要第二块，随机源失败时报错而不是 panic
+        let error = _secure_string_from(&mut FlakyRng(1), 40, RandCharset::Digits).unwrap_err();
+        assert!(error.to_string().contains("unavailable"));
+        assert_eq!(
+            generate_secure_rand(8, RandCharset::HexUpper)
+                .unwrap()
+                .len(),
+            8
+        );
+    }
+}
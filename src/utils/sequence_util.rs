@@ -0,0 +1,160 @@
+#[cfg(feature = "cache")]
+use moka::sync::Cache;
+#[cfg(feature = "cache")]
+use once_cell::sync::Lazy;
+#[cfg(feature = "cache")]
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{defi::ProtocolResult, utils::hex_util};
+
+/// 线程安全的单调序列号生成器，超过 `max_value` 后回绕到 0。
+///
+/// 可在多线程 (如 JNI 桥接层) 中安全调用，替代各协议实现里自行维护的计数器。
+#[derive(Debug)]
+pub struct SequenceGenerator {
+    counter: AtomicU64,
+    max_value: u64,
+}
+
+impl SequenceGenerator {
+    /// 创建一个从 0 开始、最大值为 `max_value` (包含) 的生成器
+    pub const fn new(max_value: u64) -> Self {
+        Self::new_with_start(0, max_value)
+    }
+
+    /// 创建一个从 `start` 开始的生成器 (首次 `next` 返回 `start + 1`)
+    pub const fn new_with_start(start: u64, max_value: u64) -> Self {
+        Self {
+            counter: AtomicU64::new(start),
+            max_value,
+        }
+    }
+
+    /// 获取下一个序列号 (原子操作)
+    pub fn next(&self) -> u64 {
+        let max_value = self.max_value;
+        let prev = self
+            .counter
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |cur| {
+                Some(Self::step(cur, max_value))
+            })
+            // 闭包始终返回 Some，不会失败
+            .unwrap_or_else(|cur| cur);
+        Self::step(prev, max_value)
+    }
+
+    /// 获取下一个序列号，并编码为指定字节长度的 Hex 字符串
+    pub fn next_hex(&self, byte_length: usize) -> ProtocolResult<String> {
+        hex_util::u64_to_hex(self.next(), byte_length)
+    }
+
+    /// 当前序列号 (不递增)
+    pub fn current(&self) -> u64 {
+        self.counter.load(Ordering::Acquire)
+    }
+
+    /// 重置当前序列号 (例如设备重新注册后与设备端同步)
+    pub fn reset(&self, value: u64) {
+        self.counter
+            .store(value.min(self.max_value), Ordering::Release);
+    }
+
+    pub fn max_value(&self) -> u64 {
+        self.max_value
+    }
+
+    fn step(cur: u64, max_value: u64) -> u64 {
+        if cur >= max_value { 0 } else { cur + 1 }
+    }
+}
+
+// --- 全局 / 按设备的序列号 ---
+
+// 全局序列号，按 2 字节序号回绕
+static GLOBAL_SEQUENCE: SequenceGenerator = SequenceGenerator::new(u16::MAX as u64);
+
+// 按设备划分的序列号。长时间不用的设备会被淘汰，避免无限增长。
+#[cfg(feature = "cache")]
+static DEVICE_SEQUENCES: Lazy<Cache<String, Arc<SequenceGenerator>>> = Lazy::new(|| {
+    Cache::builder()
+        .max_capacity(100_000)
+        .time_to_idle(std::time::Duration::from_secs(24 * 60 * 60))
+        .build()
+});
+
+/// 获取下一个全局序列号 (0..=0xFFFF 回绕)
+pub fn next_global_sequence() -> u64 {
+    GLOBAL_SEQUENCE.next()
+}
+
+/// 获取指定设备的序列号生成器，不存在时按 `max_value` 创建。
+///
+/// 生成器已存在时 `max_value` 被忽略，沿用创建时的上限 (可通过 `SequenceGenerator::max_value` 查看)；
+/// 需要更换上限时先调用 `remove_device_sequence`。
+#[cfg(feature = "cache")]
+pub fn device_sequence(unique: &str, max_value: u64) -> Arc<SequenceGenerator> {
+    DEVICE_SEQUENCES.get_with(unique.to_string(), || {
+        Arc::new(SequenceGenerator::new(max_value))
+    })
+}
+
+/// 获取指定设备的下一个序列号，`max_value` 的规则同 `device_sequence`
+#[cfg(feature = "cache")]
+pub fn next_device_sequence(unique: &str, max_value: u64) -> u64 {
+    device_sequence(unique, max_value).next()
+}
+
+/// 移除指定设备的序列号生成器
+#[cfg(feature = "cache")]
+pub fn remove_device_sequence(unique: &str) {
+    DEVICE_SEQUENCES.invalidate(unique);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_sequence_wraps_at_max() {
+        let seq = SequenceGenerator::new_with_start(254, 255);
+        assert_eq!(seq.next(), 255);
+        assert_eq!(seq.next(), 0);
+        assert_eq!(seq.next(), 1);
+        assert_eq!(seq.next_hex(1).unwrap(), "02");
+    }
+
+    #[test]
+    fn test_sequence_is_unique_across_threads() {
+        let seq = Arc::new(SequenceGenerator::new(u64::MAX));
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let seq = Arc::clone(&seq);
+                std::thread::spawn(move || (0..1000).map(|_| seq.next()).collect::<Vec<_>>())
+            })
+            .collect();
+        let mut all: Vec<u64> = handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect();
+        all.sort_unstable();
+        all.dedup();
+        assert_eq!(all.len(), 4000);
+    }
+
+    #[cfg(feature = "cache")]
+    #[test]
+    fn test_device_sequence_keeps_first_max_value() {
+        let unique = "test_device_sequence_keeps_first_max_value";
+        let seq = device_sequence(unique, 1);
+        assert_eq!(seq.next(), 1);
+        // 已存在的生成器沿用创建时的上限
+        assert_eq!(device_sequence(unique, 255).max_value(), 1);
+        assert_eq!(next_device_sequence(unique, 255), 0);
+
+        remove_device_sequence(unique);
+        assert_eq!(device_sequence(unique, 255).max_value(), 255);
+        remove_device_sequence(unique);
+    }
+}
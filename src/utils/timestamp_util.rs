@@ -9,6 +9,7 @@ use crate::{
 };
 
 /// 定义了 BCD 时间戳的格式化类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TimestampType {
     Year,                   //yyyy
     YearMonth,              //yyyy-MM
@@ -273,3 +274,112 @@ fn convert_to_hour_min_sec(timestamp: &str) -> String {
         timestamp.to_string()
     }
 }
+
+// --- 时长 (Duration) 转换 ---
+
+const SECS_PER_MINUTE: u64 = 60;
+const SECS_PER_HOUR: u64 = 60 * SECS_PER_MINUTE;
+const SECS_PER_DAY: u64 = 24 * SECS_PER_HOUR;
+
+/// 将秒数转换为可读时长 (例如 `274329` -> "3d 04:12:09")
+///
+/// 不足一天时省略天数部分 (例如 `3661` -> "01:01:01")。
+/// 常用于电池运行时长、阀门开启时长等计数字段。
+pub fn seconds_to_duration(seconds: u64) -> String {
+    let days = seconds / SECS_PER_DAY;
+    let hours = (seconds % SECS_PER_DAY) / SECS_PER_HOUR;
+    let minutes = (seconds % SECS_PER_HOUR) / SECS_PER_MINUTE;
+    let secs = seconds % SECS_PER_MINUTE;
+    if days > 0 {
+        format!("{}d {:02}:{:02}:{:02}", days, hours, minutes, secs)
+    } else {
+        format!("{:02}:{:02}:{:02}", hours, minutes, secs)
+    }
+}
+
+/// 将分钟数转换为可读时长 (例如 `4572` -> "3d 04:12:00")
+pub fn minutes_to_duration(minutes: u64) -> ProtocolResult<String> {
+    let seconds = minutes.checked_mul(SECS_PER_MINUTE).ok_or_else(|| {
+        ProtocolError::ValidationFailed(format!("Duration of {} minutes overflows u64", minutes))
+    })?;
+    Ok(seconds_to_duration(seconds))
+}
+
+/// 将可读时长解析回秒数 (`seconds_to_duration` 的逆操作)
+///
+/// 支持 "3d 04:12:09"、"04:12:09" 两种格式，时分秒必须是冒号分隔的三段数字，
+/// 小时 0 ~ 23、分钟与秒 0 ~ 59 (超过一天的部分用天数表示)。
+pub fn duration_to_seconds(duration: &str) -> ProtocolResult<u64> {
+    let invalid = || HexError::InvalidInput(format!("Invalid duration string: {}", duration));
+    let trimmed = duration.trim();
+
+    // 1. 拆分天数部分 (可选)
+    let (days, clock) = match trimmed.split_once('d') {
+        Some((d, rest)) => {
+            let days: u64 = d.trim().parse().map_err(|_| invalid())?;
+            (days, rest.trim())
+        }
+        None => (0, trimmed),
+    };
+
+    // 2. 解析 HH:MM:SS
+    let parts: Vec<&str> = clock.split(':').collect();
+    if parts.len() != 3 {
+        return Err(ProtocolError::HexError(invalid()));
+    }
+    let mut nums = [0u64; 3];
+    for (slot, part) in nums.iter_mut().zip(parts.iter()) {
+        *slot = part.parse().map_err(|_| invalid())?;
+    }
+    let [hours, minutes, secs] = nums;
+    if hours >= 24 || minutes >= 60 || secs >= 60 {
+        return Err(ProtocolError::HexError(invalid()));
+    }
+
+    days.checked_mul(SECS_PER_DAY)
+        .and_then(|d| d.checked_add(hours.checked_mul(SECS_PER_HOUR)?))
+        .and_then(|t| t.checked_add(minutes * SECS_PER_MINUTE + secs))
+        .ok_or_else(|| {
+            ProtocolError::ValidationFailed(format!("Duration {} overflows u64", duration))
+        })
+}
+
+/// 将可读时长解析回分钟数 (秒数部分向下取整)
+pub fn duration_to_minutes(duration: &str) -> ProtocolResult<u64> {
+    Ok(duration_to_seconds(duration)? / SECS_PER_MINUTE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duration_round_trip() {
+        assert_eq!(seconds_to_duration(274_329), "3d 04:12:09");
+        assert_eq!(seconds_to_duration(3661), "01:01:01");
+        assert_eq!(minutes_to_duration(4572).unwrap(), "3d 04:12:00");
+        assert_eq!(duration_to_seconds("3d 04:12:09").unwrap(), 274_329);
+        assert_eq!(duration_to_seconds(" 01:01:01 ").unwrap(), 3661);
+        assert_eq!(duration_to_minutes("3d 04:12:59").unwrap(), 4572);
+        assert_eq!(duration_to_seconds("0d 23:59:59").unwrap(), 86_399);
+    }
+
+    #[test]
+    fn test_invalid_duration() {
+        for input in [
+            "3d 25:00:00",
+            "24:00:00",
+            "00:60:00",
+            "00:00:60",
+            "04:12",
+            "1:2:3:4",
+            "xd 00:00:00",
+            "aa:00:00",
+            "",
+        ] {
+            assert!(duration_to_seconds(input).is_err(), "{}", input);
+        }
+        assert!(duration_to_seconds(&format!("{}d 00:00:00", u64::MAX)).is_err());
+        assert!(minutes_to_duration(u64::MAX).is_err());
+    }
+}
@@ -0,0 +1,117 @@
+//! 浏览器端报文调试工具使用的 wasm-bindgen 接口。
+//!
+//! 技术支持在页面上粘贴一段 hex 报文和字段布局，即可看到解析出的 ReportField。
+//! 构建：`cargo build --target wasm32-unknown-unknown --no-default-features --features wasm`
+
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    core::{parts::rawfield::Rawfield, reader::Reader, type_converter::FieldType},
+    defi::{ProtocolResult, bridge::ReportField, error::ProtocolError},
+    utils::hex_util,
+};
+
+/// 字段布局中的一项，例如 `{"title": "电压", "length": 2, "type": "u16", "scale": 0.01}`
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldLayout {
+    pub title: String,
+    pub length: usize,
+    // bcd / hex / ascii / u8 / u16 / u32 / u64 / i8 / i16 / i32 / i64 / float / double
+    #[serde(rename = "type")]
+    pub field_type: String,
+    // 缩小倍数，仅对整数类型有效
+    #[serde(default)]
+    pub scale: Option<f64>,
+    // 是否为小端
+    #[serde(default)]
+    pub little_endian: bool,
+}
+
+impl FieldLayout {
+    fn to_field_type(&self) -> ProtocolResult<FieldType> {
+        let scale = self.scale.unwrap_or(1.0);
+        let field_type = match self.field_type.to_ascii_lowercase().as_str() {
+            "bcd" | "hex" | "string" => FieldType::StringOrBCD,
+            "ascii" => FieldType::Ascii,
+            "u8" => FieldType::UnsignedU8(scale),
+            "u16" => FieldType::UnsignedU16(scale),
+            "u32" => FieldType::UnsignedU32(scale),
+            "u64" => FieldType::UnsignedU64(scale),
+            "i8" => FieldType::SignedI8(scale),
+            "i16" => FieldType::SignedI16(scale),
+            "i32" => FieldType::SignedI32(scale),
+            "i64" => FieldType::SignedI64(scale),
+            "float" => FieldType::Float,
+            "double" => FieldType::Double,
+            other => {
+                return Err(ProtocolError::ValidationFailed(format!(
+                    "unknown field type '{}' for '{}'",
+                    other, self.title
+                )));
+            }
+        };
+        Ok(field_type)
+    }
+}
+
+/// 按字段布局依次解析 hex 报文，返回解析出的字段。
+/// 布局之外多余的字节会作为 "remaining" 字段返回。
+pub fn decode_with_layout(hex: &str, layout: &[FieldLayout]) -> ProtocolResult<Vec<ReportField>> {
+    let bytes = hex_util::hex_to_bytes(hex)?;
+    let mut reader = Reader::new(&bytes);
+    for field in layout {
+        let field_type = field.to_field_type()?;
+        let translate = |raw: &[u8]| {
+            let value = if field.little_endian {
+                let mut le = raw.to_vec();
+                le.reverse();
+                field_type.decode(&le)?
+            } else {
+                field_type.decode(raw)?
+            };
+            Ok(Rawfield::new(raw, field.title.clone(), value))
+        };
+        reader.read_and_translate_head(field.length, translate)?;
+    }
+    if reader.remaining_len() > 0 {
+        reader.read_and_translate_remaining(|raw| {
+            Ok(Rawfield::new(
+                raw,
+                "remaining".into(),
+                hex_util::bytes_to_hex(raw)?,
+            ))
+        })?;
+    }
+    reader.to_report_fields()
+}
+
+/// 浏览器入口：`layout_json` 为 `FieldLayout` 数组的 JSON，返回 ReportField 数组的 JSON
+#[wasm_bindgen(js_name = decodeHexFields)]
+pub fn decode_hex_fields(hex: &str, layout_json: &str) -> Result<String, JsError> {
+    let layout: Vec<FieldLayout> = serde_json::from_str(layout_json)?;
+    let fields = decode_with_layout(hex, &layout).map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(serde_json::to_string(&fields)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_with_layout() {
+        let layout: Vec<FieldLayout> = serde_json::from_str(
+            r#"[
+                {"title": "设备号", "length": 2, "type": "bcd"},
+                {"title": "电压", "length": 2, "type": "u16", "scale": 0.01},
+                {"title": "温度", "length": 1, "type": "i8", "littleEndian": false}
+            ]"#,
+        )
+        .unwrap();
+        let fields = decode_with_layout("1234015EFF16", &layout).unwrap();
+        let values: Vec<&str> = fields.iter().map(|f| f.value.as_str()).collect();
+        assert_eq!(values, vec!["1234", "3.5", "-1", "16"]);
+        assert_eq!(fields[3].name, "remaining");
+    }
+}
@@ -0,0 +1,309 @@
+// @generated by protocol_core::codegen from schema "vendor-a", do not edit.
+
+/// vendor-a 的帧外壳
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Envelope;
+
+impl ::protocol_core::ProtocolConfig for Envelope {
+    fn head_tag(&self) -> String {
+        "68".to_string()
+    }
+
+    fn tail_tag(&self) -> String {
+        "16".to_string()
+    }
+
+    fn crc_mode(&self) -> ::protocol_core::CrcType {
+        ::protocol_core::CrcType::Crc16Modbus
+    }
+
+    fn crc_index(&self) -> (u8, u8) {
+        (0, 0)
+    }
+
+    fn length_index(&self) -> (u8, u8) {
+        (1, 2)
+    }
+
+    fn max_frame_len(&self) -> usize {
+        0
+    }
+
+    fn crc_little_endian(&self) -> bool {
+        false
+    }
+}
+
+/// 协议中的所有命令
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Cmd {
+    /// 数据上报 (01)
+    ShuJuShangBao,
+    /// 阀门控制 (A1)
+    ValveControl,
+}
+
+impl Cmd {
+    pub const ALL: [Cmd; 2] = [Cmd::ShuJuShangBao, Cmd::ValveControl];
+
+    /// 按命令码与方向查找，`DirectionEnum::Both` 表示不限方向
+    pub fn from_code(code: &str, direction: ::protocol_core::DirectionEnum) -> Option<Self> {
+        Self::ALL.into_iter().find(|cmd| {
+            let d = ::protocol_core::Cmd::direction(cmd);
+            ::protocol_core::Cmd::code(cmd).eq_ignore_ascii_case(code)
+                && match direction {
+                    ::protocol_core::DirectionEnum::Upstream => d.is_upstream(),
+                    ::protocol_core::DirectionEnum::Downstream => d.is_downstream(),
+                    ::protocol_core::DirectionEnum::Both => true,
+                }
+        })
+    }
+}
+
+impl ::protocol_core::Cmd for Cmd {
+    fn code(&self) -> String {
+        match self {
+            Cmd::ShuJuShangBao => "01".to_string(),
+            Cmd::ValveControl => "A1".to_string(),
+        }
+    }
+
+    fn title(&self) -> String {
+        match self {
+            Cmd::ShuJuShangBao => "数据上报".to_string(),
+            Cmd::ValveControl => "阀门控制".to_string(),
+        }
+    }
+
+    fn direction(&self) -> ::protocol_core::DirectionEnum {
+        match self {
+            Cmd::ShuJuShangBao => ::protocol_core::DirectionEnum::Upstream,
+            Cmd::ValveControl => ::protocol_core::DirectionEnum::Downstream,
+        }
+    }
+
+    fn rw(&self) -> Option<::protocol_core::RW> {
+        None
+    }
+
+    fn msg_type(&self) -> Option<::protocol_core::MsgTypeEnum> {
+        None
+    }
+
+}
+
+/// 数据上报 (01) 的字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShuJuShangBaoField {
+    /// 命令码
+    CmdCode,
+    /// 电压
+    DianYa,
+}
+
+impl ShuJuShangBaoField {
+    pub const ALL: [ShuJuShangBaoField; 2] = [ShuJuShangBaoField::CmdCode, ShuJuShangBaoField::DianYa];
+}
+
+impl ::protocol_core::AutoDecodingParam<String> for ShuJuShangBaoField {
+    fn byte_length(&self) -> usize {
+        match self {
+            ShuJuShangBaoField::CmdCode => 1,
+            ShuJuShangBaoField::DianYa => 2,
+        }
+    }
+
+    fn title(&self) -> String {
+        match self {
+            ShuJuShangBaoField::CmdCode => "命令码".to_string(),
+            ShuJuShangBaoField::DianYa => "电压".to_string(),
+        }
+    }
+
+    fn swap(&self) -> bool {
+        false
+    }
+
+    fn cmd_code(&self) -> String {
+        "01".to_string()
+    }
+
+    fn symbol(&self) -> Option<::protocol_core::Symbol> {
+        match self {
+            ShuJuShangBaoField::CmdCode => None,
+            ShuJuShangBaoField::DianYa => Some(::protocol_core::Symbol::from_tag("V")),
+        }
+    }
+
+    fn precision(&self) -> u32 {
+        6
+    }
+
+    fn field_type(&self) -> ::protocol_core::FieldType {
+        match self {
+            ShuJuShangBaoField::CmdCode => ::protocol_core::FieldType::StringOrBCD,
+            ShuJuShangBaoField::DianYa => ::protocol_core::FieldType::UnsignedU16(0.1),
+        }
+    }
+
+    fn compare_target(&self) -> Vec<u8> {
+        match self {
+            ShuJuShangBaoField::CmdCode => vec![0x01],
+            ShuJuShangBaoField::DianYa => vec![],
+        }
+    }
+
+    fn enum_values(&self) -> Vec<(String, String)> {
+        vec![]
+    }
+
+    fn condition(&self) -> Option<::protocol_core::FieldCondition> {
+        None
+    }
+
+}
+
+impl ::protocol_core::AutoDecoding<ShuJuShangBaoField, String> for ShuJuShangBaoField {
+    fn variants(&self) -> Vec<ShuJuShangBaoField> {
+        ShuJuShangBaoField::ALL.to_vec()
+    }
+}
+
+/// 阀门控制 (A1) 的字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValveControlField {
+    /// 命令码
+    CmdCode,
+    /// 阀门
+    FaMen,
+}
+
+impl ValveControlField {
+    pub const ALL: [ValveControlField; 2] = [ValveControlField::CmdCode, ValveControlField::FaMen];
+}
+
+impl ::protocol_core::AutoEncodingParam for ValveControlField {
+    fn code(&self) -> String {
+        match self {
+            ValveControlField::CmdCode => "cmd_code".to_string(),
+            ValveControlField::FaMen => "valve".to_string(),
+        }
+    }
+
+    fn title(&self) -> String {
+        match self {
+            ValveControlField::CmdCode => "命令码".to_string(),
+            ValveControlField::FaMen => "阀门".to_string(),
+        }
+    }
+
+    fn byte_length(&self) -> usize {
+        1
+    }
+
+    fn cmd_code(&self) -> String {
+        "A1".to_string()
+    }
+
+    fn field_type(&self) -> ::protocol_core::FieldType {
+        match self {
+            ValveControlField::CmdCode => ::protocol_core::FieldType::StringOrBCD,
+            ValveControlField::FaMen => ::protocol_core::FieldType::UnsignedU8(1.0),
+        }
+    }
+
+    fn default_value(&self) -> String {
+        "".to_string()
+    }
+
+    fn default_hex(&self) -> String {
+        match self {
+            ValveControlField::CmdCode => "A1".to_string(),
+            ValveControlField::FaMen => "".to_string(),
+        }
+    }
+
+    fn swap(&self) -> bool {
+        false
+    }
+
+    fn required(&self) -> bool {
+        true
+    }
+
+    fn condition(&self) -> Option<::protocol_core::FieldCondition> {
+        None
+    }
+
+    fn rules(&self) -> Vec<::protocol_core::ValidationRule> {
+        vec![]
+    }
+
+}
+
+impl ::protocol_core::AutoEncoding<ValveControlField> for ValveControlField {
+    fn variants(&self) -> Vec<ValveControlField> {
+        ValveControlField::ALL.to_vec()
+    }
+}
+
+/// 上行解码：按帧中的命令码选择字段定义
+pub fn decode(
+    bytes: &[u8],
+) -> ::protocol_core::ProtocolResult<::protocol_core::RawCapsule<Cmd>> {
+    ::protocol_core::ProtocolConfig::validate_envelope(&Envelope, bytes)?;
+    let raw = bytes.get(2..3).ok_or(::protocol_core::ProtocolError::InputTooShort {
+        needed: 3,
+        available: bytes.len(),
+    })?;
+    let code = ::protocol_core::hex_util::bytes_to_hex(raw)?;
+    let (cmd, mut capsule) = match code.as_str() {
+        "01" => (Cmd::ShuJuShangBao, ::protocol_core::decode_upstream::<Cmd, ShuJuShangBaoField, ShuJuShangBaoField, String>(&Envelope, &ShuJuShangBaoField::ALL[0], bytes)?),
+        _ => {
+            return Err(::protocol_core::HexDigestError::UnknownCmdCode {
+                code,
+                nearest: None,
+            }
+            .into());
+        }
+    };
+    capsule.set_cmd(cmd);
+    Ok(capsule)
+}
+
+/// 下行编码：参数名为字段的 code，取值表中的文字会换成对应的值，固定内容与默认值可以省略
+pub fn encode(
+    params: &::std::collections::HashMap<String, String>,
+    capsule: &mut ::protocol_core::RawCapsule<Cmd>,
+) -> ::protocol_core::ProtocolResult<usize> {
+    let cmd = capsule.cmd().copied().ok_or_else(|| {
+        ::protocol_core::ProtocolError::ValidationFailed("capsule has no cmd to encode".into())
+    })?;
+    let mut params = params.clone();
+    match cmd {
+        Cmd::ValveControl => {
+            _prepare_params(&mut params, &["cmd_code"], &[("valve", "开", "1")]);
+            ::protocol_core::encode_downstream(&Envelope, &ValveControlField::ALL[0], &params, capsule)
+        }
+        _ => Err(::protocol_core::ProtocolError::ValidationFailed(format!(
+            "cmd {:?} is not a downstream cmd",
+            cmd
+        ))),
+    }
+}
+
+// 固定内容与默认值放入空输入，取值表中的文字换成值
+fn _prepare_params(
+    params: &mut ::std::collections::HashMap<String, String>,
+    constants: &[&str],
+    labels: &[(&str, &str, &str)],
+) {
+    for code in constants {
+        params.entry(code.to_string()).or_default();
+    }
+    for (code, label, value) in labels {
+        if params.get(*code).map(String::as_str) == Some(*label) {
+            params.insert(code.to_string(), value.to_string());
+        }
+    }
+}